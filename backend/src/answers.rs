@@ -0,0 +1,200 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::models::EmbeddingId;
+
+/// JSON schema the assistant is asked to answer with
+pub fn answer_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "answer": { "type": "string" },
+            "cited_chunk_ids": { "type": "array", "items": { "type": "integer" } },
+            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+            "not_covered_by_rules": { "type": "boolean" }
+        },
+        "required": ["answer", "cited_chunk_ids", "confidence", "not_covered_by_rules"]
+    })
+}
+
+/// Prompt section instructing the model to produce a structured answer
+pub fn answer_format_instructions() -> String {
+    format!(
+        "Response Format:
+Respond with a single JSON object and nothing else, matching this JSON schema:
+{}
+- \"answer\": your answer for the players, in plain prose
+- \"cited_chunk_ids\": the ids of the [chunk N] passages your answer relies on
+- \"confidence\": how sure you are that the answer is correct, from 0 to 1
+- \"not_covered_by_rules\": true when the provided rules do not answer the question",
+        answer_schema()
+    )
+}
+
+/// An assistant answer after parsing and validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredAnswer {
+    pub answer: String,
+    pub cited_chunk_ids: Vec<EmbeddingId>,
+    pub confidence: Option<f32>,
+    pub not_covered_by_rules: bool,
+    /// False when the model ignored the requested format and we fell back to free text
+    pub structured: bool,
+}
+
+#[derive(Deserialize)]
+struct RawAnswer {
+    answer: String,
+    #[serde(default, alias = "citations", alias = "cited_chunks")]
+    cited_chunk_ids: Vec<serde_json::Value>,
+    #[serde(default)]
+    confidence: Option<serde_json::Value>,
+    #[serde(default, alias = "not_covered")]
+    not_covered_by_rules: bool,
+}
+
+/// Parse a model response, keeping only citations of chunks that were actually provided
+pub fn parse_answer(raw: &str, available_ids: &[EmbeddingId]) -> StructuredAnswer {
+    if let Some(parsed) = extract_json_object(raw)
+        .and_then(|json| serde_json::from_str::<RawAnswer>(json).ok())
+        .filter(|parsed| !parsed.answer.trim().is_empty())
+    {
+        let mut cited_chunk_ids = Vec::new();
+        for value in &parsed.cited_chunk_ids {
+            let id = value
+                .as_i64()
+                .or_else(|| value.as_str().and_then(parse_chunk_reference));
+            match id {
+                Some(id) if available_ids.contains(&id) => {
+                    if !cited_chunk_ids.contains(&id) {
+                        cited_chunk_ids.push(id);
+                    }
+                }
+                _ => tracing::debug!("Dropping citation of unknown chunk {}", value),
+            }
+        }
+
+        return StructuredAnswer {
+            answer: parsed.answer.trim().to_string(),
+            cited_chunk_ids,
+            confidence: parsed.confidence.as_ref().and_then(parse_confidence),
+            not_covered_by_rules: parsed.not_covered_by_rules,
+            structured: true,
+        };
+    }
+
+    // Non-compliant model: keep the text and recover any inline [chunk N] references
+    let reference_pattern = Regex::new(r"\[chunk (\d+)\]").unwrap();
+    let mut cited_chunk_ids = Vec::new();
+    for capture in reference_pattern.captures_iter(raw) {
+        if let Ok(id) = capture[1].parse::<EmbeddingId>()
+            && available_ids.contains(&id)
+            && !cited_chunk_ids.contains(&id)
+        {
+            cited_chunk_ids.push(id);
+        }
+    }
+
+    StructuredAnswer {
+        answer: raw.trim().to_string(),
+        cited_chunk_ids,
+        confidence: None,
+        not_covered_by_rules: false,
+        structured: false,
+    }
+}
+
+/// Find the JSON object in a response, tolerating code fences and surrounding prose
+pub fn extract_json_object(raw: &str) -> Option<&str> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    (start < end).then(|| &raw[start..=end])
+}
+
+/// Accept "12", "chunk 12" and "[chunk 12]" as chunk references
+pub fn parse_chunk_reference(reference: &str) -> Option<EmbeddingId> {
+    reference
+        .trim_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .ok()
+}
+
+/// Normalize confidence to 0..=1, accepting percentages and high/medium/low labels
+pub fn parse_confidence(value: &serde_json::Value) -> Option<f32> {
+    let confidence = match value {
+        serde_json::Value::Number(n) => n.as_f64()? as f32,
+        serde_json::Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "high" => 0.9,
+            "medium" => 0.6,
+            "low" => 0.3,
+            other => other.trim_end_matches('%').parse::<f32>().ok()?,
+        },
+        _ => return None,
+    };
+
+    let confidence = if confidence > 1.0 {
+        confidence / 100.0
+    } else {
+        confidence
+    };
+
+    (0.0..=1.0).contains(&confidence).then_some(confidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compliant_answer() {
+        let raw = r#"{"answer": "Move the robber to any hex.", "cited_chunk_ids": [4, 7], "confidence": 0.85, "not_covered_by_rules": false}"#;
+        let parsed = parse_answer(raw, &[4, 7, 9]);
+
+        assert!(parsed.structured);
+        assert_eq!(parsed.answer, "Move the robber to any hex.");
+        assert_eq!(parsed.cited_chunk_ids, vec![4, 7]);
+        assert_eq!(parsed.confidence, Some(0.85));
+        assert!(!parsed.not_covered_by_rules);
+    }
+
+    #[test]
+    fn test_parse_fenced_answer_drops_unknown_citations() {
+        let raw = "Here you go:\n```json\n{\"answer\": \"No.\", \"cited_chunk_ids\": [\"chunk 3\", 99, 3], \"confidence\": \"high\", \"not_covered_by_rules\": true}\n```";
+        let parsed = parse_answer(raw, &[3]);
+
+        assert!(parsed.structured);
+        assert_eq!(parsed.cited_chunk_ids, vec![3]);
+        assert_eq!(parsed.confidence, Some(0.9));
+        assert!(parsed.not_covered_by_rules);
+    }
+
+    #[test]
+    fn test_parse_percentage_confidence() {
+        let raw = r#"{"answer": "Yes.", "cited_chunk_ids": [], "confidence": 75}"#;
+        assert_eq!(parse_answer(raw, &[]).confidence, Some(0.75));
+
+        let raw = r#"{"answer": "Yes.", "cited_chunk_ids": [], "confidence": -2}"#;
+        assert_eq!(parse_answer(raw, &[]).confidence, None);
+    }
+
+    #[test]
+    fn test_fallback_for_free_text() {
+        let raw = "You may trade with the bank at 4:1 [chunk 12]. See also [chunk 40].";
+        let parsed = parse_answer(raw, &[12, 13]);
+
+        assert!(!parsed.structured);
+        assert_eq!(parsed.answer, raw);
+        assert_eq!(parsed.cited_chunk_ids, vec![12]);
+        assert_eq!(parsed.confidence, None);
+    }
+
+    #[test]
+    fn test_fallback_for_empty_answer_field() {
+        let raw = r#"{"answer": "  ", "cited_chunk_ids": [1]}"#;
+        let parsed = parse_answer(raw, &[1]);
+
+        assert!(!parsed.structured);
+        assert!(parsed.cited_chunk_ids.is_empty());
+    }
+}
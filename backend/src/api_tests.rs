@@ -0,0 +1,1996 @@
+//! End-to-end tests that drive the HTTP API in-process against the mock providers
+
+use dropshot::{
+    ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HandlerTaskMode, HttpServer,
+    HttpServerStarter,
+};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+use crate::{
+    AppState, Provider, create_api_description,
+    llm::{LLMClient, MockReply},
+};
+
+const RULES_PDF: &[u8] = include_bytes!("../tests/fixtures/rules.pdf");
+const RULES_GOLDEN: &str = include_str!("../tests/fixtures/rules_golden.json");
+const BGG_RANKS: &str = include_str!("../tests/fixtures/bgg_ranks.csv");
+const BGG_THING: &str = include_str!("../tests/fixtures/bgg_thing.xml");
+
+/// A running server backed by a throwaway database and uploads directory
+struct TestServer {
+    server: HttpServer<AppState>,
+    client: reqwest::Client,
+    base_url: String,
+    _dir: TempDir,
+}
+
+impl TestServer {
+    fn start() -> Self {
+        Self::start_with(|app_state| app_state)
+    }
+
+    fn start_with(configure: impl FnOnce(AppState) -> AppState) -> Self {
+        let dir = TempDir::new().unwrap();
+        let app_state = configure(
+            AppState::new(dir.path().join("atlas.db"), Provider::Mock)
+                .unwrap()
+                .with_uploads_dir(dir.path().join("uploads")),
+        );
+
+        let config = ConfigDropshot {
+            bind_address: "127.0.0.1:0".parse().unwrap(),
+            default_request_body_max_bytes: 10 * 1024 * 1024,
+            default_handler_task_mode: HandlerTaskMode::Detached,
+            log_headers: Default::default(),
+        };
+        let log = ConfigLogging::StderrTerminal {
+            level: ConfigLoggingLevel::Warn,
+        }
+        .to_logger("api-tests")
+        .unwrap();
+
+        let server =
+            HttpServerStarter::new(&config, create_api_description().unwrap(), app_state, &log)
+                .unwrap()
+                .start();
+        let base_url = format!("http://{}", server.local_addr());
+
+        Self {
+            server,
+            client: reqwest::Client::new(),
+            base_url,
+            _dir: dir,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Value {
+        let response = self
+            .client
+            .post(self.url(path))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert!(
+            response.status().is_success(),
+            "POST {} failed: {:?}",
+            path,
+            response
+        );
+        response.json().await.unwrap()
+    }
+
+    async fn get(&self, path: &str) -> Value {
+        let response = self.client.get(self.url(path)).send().await.unwrap();
+        assert!(
+            response.status().is_success(),
+            "GET {} failed: {:?}",
+            path,
+            response
+        );
+        response.json().await.unwrap()
+    }
+
+    /// Rows a table still holds for a game, read straight from the database
+    fn rows_for_game(&self, table: &str, game_id: i64) -> i64 {
+        let sql = format!("SELECT COUNT(*) FROM {} WHERE game_id = ?", table);
+        self.server
+            .app_private()
+            .db()
+            .with_connection(|conn| conn.query_row(&sql, [game_id], |row| row.get(0)))
+            .unwrap()
+    }
+
+    async fn stop(self) {
+        self.server.close().await.unwrap();
+    }
+}
+
+/// Create a game and upload the fixture rulebook for it
+async fn game_with_rules(server: &TestServer) -> i64 {
+    let game = server
+        .post(
+            "/api/games",
+            json!({
+                "name": "Harbor Traders",
+                "min_players": 3,
+                "max_players": 4,
+            }),
+        )
+        .await;
+    let game_id = game["id"].as_i64().unwrap();
+    upload_rules(server, game_id).await;
+
+    game_id
+}
+
+async fn upload_rules(server: &TestServer, game_id: i64) {
+    let response = server
+        .client
+        .post(server.url(&format!("/api/games/{}/rules-upload", game_id)))
+        .body(RULES_PDF)
+        .send()
+        .await
+        .unwrap();
+    assert!(
+        response.status().is_success(),
+        "upload failed: {:?}",
+        response
+    );
+    let upload: Value = response.json().await.unwrap();
+    assert!(upload["chunks_processed"].as_u64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_upload_and_search_rules() {
+    let server = TestServer::start();
+    let game_id = game_with_rules(&server).await;
+
+    let info = server
+        .get(&format!("/api/games/{}/rules-info", game_id))
+        .await;
+    assert!(info["has_rules_pdf"].as_bool().unwrap());
+
+    let search = server
+        .get(&format!(
+            "/api/chat/search-rules?game_id={}&query=robber&limit=3",
+            game_id
+        ))
+        .await;
+    let results = search["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(
+        results[0]["chunk_text"]
+            .as_str()
+            .unwrap()
+            .to_lowercase()
+            .contains("robber")
+    );
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_chat_answers_from_uploaded_rules() {
+    let server = TestServer::start();
+    let game_id = game_with_rules(&server).await;
+
+    let session = server
+        .post(
+            "/api/chat/sessions",
+            json!({ "game_id": game_id, "title": "Robber questions" }),
+        )
+        .await;
+    let session_id = session["id"].as_i64().unwrap();
+
+    let question = "What happens to the robber when a seven is rolled?";
+    let chat = server
+        .post(
+            "/api/chat/message",
+            json!({ "session_id": session_id, "message": question }),
+        )
+        .await;
+
+    // The mock model cites every chunk it was shown
+    assert!(chat["structured"].as_bool().unwrap());
+    assert!(!chat["cached"].as_bool().unwrap());
+    assert!(!chat["not_covered_by_rules"].as_bool().unwrap());
+    let sources = chat["context_sources"].as_array().unwrap();
+    assert!(!sources.is_empty());
+    assert!(
+        sources
+            .iter()
+            .all(|source| source["cited"].as_bool().unwrap())
+    );
+    assert!(
+        chat["message"]["content"]
+            .as_str()
+            .unwrap()
+            .contains(question)
+    );
+    assert_eq!(chat["grounding"]["method"], "llm_judge");
+    assert!(
+        chat["grounding"]["unsupported_sentences"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+
+    // The same opening question in a new session is answered from the cache
+    let session = server
+        .post("/api/chat/sessions", json!({ "game_id": game_id }))
+        .await;
+    let repeat = server
+        .post(
+            "/api/chat/message",
+            json!({ "session_id": session["id"], "message": question }),
+        )
+        .await;
+    assert!(repeat["cached"].as_bool().unwrap());
+    assert_eq!(repeat["message"]["content"], chat["message"]["content"]);
+
+    let history = server
+        .get(&format!("/api/chat/sessions/{}", session_id))
+        .await;
+    assert_eq!(history["messages"].as_array().unwrap().len(), 2);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_chat_answers_from_faq_first() {
+    let server = TestServer::start();
+    let game_id = game_with_rules(&server).await;
+
+    let search = server
+        .get(&format!(
+            "/api/chat/search-rules?game_id={}&query=robber&limit=1",
+            game_id
+        ))
+        .await;
+    let chunk_id = search["results"][0]["chunk_id"].clone();
+
+    let entry = server
+        .post(
+            "/api/faq",
+            json!({
+                "game_id": game_id,
+                "question": "Where can the robber be moved?",
+                "answer": "To any other hex.",
+                "cited_chunks": [chunk_id],
+            }),
+        )
+        .await;
+    assert!(entry["edited"].as_bool().unwrap());
+
+    let session = server
+        .post("/api/chat/sessions", json!({ "game_id": game_id }))
+        .await;
+    let chat = server
+        .post(
+            "/api/chat/message",
+            json!({
+                "session_id": session["id"],
+                "message": "where can the robber be moved",
+            }),
+        )
+        .await;
+
+    assert_eq!(chat["faq_match"]["entry_id"], entry["id"]);
+    assert_eq!(chat["message"]["content"], "To any other hex.");
+    assert_eq!(chat["context_sources"][0]["embedding_id"], chunk_id);
+
+    let faq = server.get(&format!("/api/faq?game_id={}", game_id)).await;
+    assert_eq!(faq.as_array().unwrap().len(), 1);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_new_rulebook_retires_faq_answers_citing_the_old_one() {
+    // The generated entry cites every chunk, so it survives whichever batch it lands in
+    let server = TestServer::start_with(|app_state| {
+        app_state.with_llm(LLMClient::scripted(vec![MockReply::Text(
+            json!({ "faq": [{
+                "question": "What happens when a seven is rolled?",
+                "answer": "The robber moves.",
+                "cited_chunk_ids": (1..=200).collect::<Vec<i64>>(),
+            }]})
+            .to_string(),
+        )]))
+    });
+    let game_id = game_with_rules(&server).await;
+
+    let search = server
+        .get(&format!(
+            "/api/chat/search-rules?game_id={}&query=robber&limit=1",
+            game_id
+        ))
+        .await;
+    let edited = server
+        .post(
+            "/api/faq",
+            json!({
+                "game_id": game_id,
+                "question": "Where can the robber be moved?",
+                "answer": "To any other hex.",
+                "cited_chunks": [search["results"][0]["chunk_id"]],
+            }),
+        )
+        .await;
+    let generated = server
+        .post(
+            &format!("/api/games/{}/faq", game_id),
+            json!({ "max_questions": 1 }),
+        )
+        .await;
+    assert_eq!(generated["entries"].as_array().unwrap().len(), 2);
+
+    let session = server
+        .post("/api/chat/sessions", json!({ "game_id": game_id }))
+        .await;
+    let ask = |message: &'static str| {
+        let server = &server;
+        let session_id = session["id"].clone();
+        async move {
+            server
+                .post(
+                    "/api/chat/message",
+                    json!({ "session_id": session_id, "message": message }),
+                )
+                .await
+        }
+    };
+    let chat = ask("What happens when a seven is rolled?").await;
+    assert_eq!(chat["message"]["content"], "The robber moves.");
+
+    // A new rulebook retires the generated entry and keeps the edited one
+    upload_rules(&server, game_id).await;
+    let faq = server.get(&format!("/api/faq?game_id={}", game_id)).await;
+    assert_eq!(faq.as_array().unwrap().len(), 1);
+    assert_eq!(faq[0]["id"], edited["id"]);
+
+    // Once the chunks the edited entry cites are gone, chat searches the rules instead
+    let response = server
+        .client
+        .delete(server.url(&format!("/api/games/{}/rules", game_id)))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    upload_rules(&server, game_id).await;
+    for question in [
+        "What happens when a seven is rolled?",
+        "Where can the robber be moved?",
+    ] {
+        let chat = ask(question).await;
+        assert!(chat["faq_match"].is_null(), "{}", question);
+        assert!(!chat["context_sources"].as_array().unwrap().is_empty());
+    }
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_evaluation_run_scores_and_diffs_golden_questions() {
+    let server = TestServer::start();
+    let game_id = game_with_rules(&server).await;
+
+    let golden: Value = serde_json::from_str(RULES_GOLDEN).unwrap();
+    let questions = server
+        .post(
+            "/api/eval/questions",
+            json!({ "game_id": game_id, "questions": golden }),
+        )
+        .await;
+    assert_eq!(questions.as_array().unwrap().len(), 4);
+
+    // The fixture rulebook is small enough that the top 5 chunks cover every keyword
+    let first = server
+        .post(
+            "/api/eval/runs",
+            json!({ "game_id": game_id, "k": 5, "generate_answers": true }),
+        )
+        .await;
+    let summary = &first["run"]["summary"];
+    assert_eq!(summary["questions"], 4);
+    assert_eq!(summary["keyword_recall"], 1.0);
+    assert!(summary["mrr"].as_f64().unwrap() > 0.0);
+    assert!(summary["recall_at_k"].is_null());
+    assert!(summary["answer_match_rate"].is_number());
+    assert!(first["diff"].is_null());
+
+    let second = server
+        .post("/api/eval/runs", json!({ "game_id": game_id, "k": 5 }))
+        .await;
+    assert_eq!(second["diff"]["baseline_run_id"], first["run"]["id"]);
+    assert_eq!(second["diff"]["keyword_recall_delta"], 0.0);
+    assert!(second["diff"]["answer_match_rate_delta"].is_null());
+
+    let runs = server
+        .get(&format!("/api/eval/runs?game_id={}", game_id))
+        .await;
+    assert_eq!(runs[0]["id"], second["run"]["id"]);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_game_library_filters_and_sorts() {
+    let server = TestServer::start();
+
+    let names = |page: &Value| -> Vec<String> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|game| game["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // The seeded library fits six players only in Twilight Imperium
+    let page = server.get("/api/games?players=6").await;
+    assert_eq!(names(&page), vec!["Twilight Imperium: Fourth Edition"]);
+    assert_eq!(page["total"], 1);
+
+    let page = server.get("/api/games?q=galax").await;
+    assert_eq!(names(&page), vec!["Twilight Imperium: Fourth Edition"]);
+
+    let page = server
+        .get("/api/games?max_play_time=70&sort=complexity")
+        .await;
+    assert_eq!(names(&page), vec!["Wingspan", "Pandemic Legacy: Season 1"]);
+
+    let page = server.get("/api/games?min_year=2019&sort=year").await;
+    assert_eq!(names(&page), vec!["Ark Nova", "Dune: Imperium", "Wingspan"]);
+
+    let page = server
+        .get("/api/games?publisher=fantasy%20flight%20games&name=rebel")
+        .await;
+    assert_eq!(names(&page), vec!["Star Wars: Rebellion"]);
+
+    // Renaming a game keeps the full-text index in sync
+    let game = server
+        .post("/api/games", json!({ "name": "Harbor Traders" }))
+        .await;
+    let response = server
+        .client
+        .put(server.url(&format!("/api/games/{}", game["id"])))
+        .json(&json!({ "name": "Harbour Merchants" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(server.get("/api/games?q=harbor").await["total"], 0);
+    assert_eq!(
+        names(&server.get("/api/games?q=merchant").await),
+        vec!["Harbour Merchants"]
+    );
+
+    let response = server
+        .client
+        .get(server.url("/api/games?min_complexity=4&max_complexity=2"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.stop().await;
+}
+
+/// Serve one XML API2 response, standing in for BoardGameGeek
+async fn bgg_stand_in(body: &'static str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/xmlapi2", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = [0u8; 4096];
+        let read = stream.read(&mut request).await.unwrap();
+        assert!(
+            String::from_utf8_lossy(&request[..read]).starts_with("GET /xmlapi2/thing?id=13,926")
+        );
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    });
+    base_url
+}
+
+#[tokio::test]
+async fn test_bgg_import_creates_and_updates_games() {
+    let server = TestServer::start();
+
+    // Ranks update the seeded Brass by BGG id and create CATAN; the expansion
+    // and anything past the limit are skipped
+    let response = server
+        .client
+        .post(server.url("/api/import/bgg/ranks?limit=2"))
+        .body(BGG_RANKS)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success(), "{:?}", response);
+    let summary: Value = response.json().await.unwrap();
+    assert_eq!(summary["created"], 1);
+    assert_eq!(summary["updated"], 1);
+    assert_eq!(summary["skipped"], 2);
+    assert_eq!(server.get("/api/games?q=brass").await["total"], 1);
+
+    let catan_id = summary["game_ids"][1].as_i64().unwrap();
+    let catan = server.get(&format!("/api/games/{}", catan_id)).await;
+    assert_eq!(catan["bgg_id"], 13);
+    assert_eq!(catan["min_players"], Value::Null);
+
+    // A thing document fills in the details
+    let summary = server
+        .post("/api/import/bgg/things", json!({ "xml": BGG_THING }))
+        .await;
+    assert_eq!(summary["updated"], 1);
+    assert_eq!(summary["skipped"], 1);
+    assert_eq!(summary["game_ids"], json!([catan_id]));
+
+    let catan = server.get(&format!("/api/games/{}", catan_id)).await;
+    assert_eq!(catan["publisher"], "KOSMOS");
+    assert_eq!(catan["min_players"], 3);
+    assert_eq!(catan["play_time_minutes"], 120);
+    assert_eq!(catan["complexity_rating"], 2.2958);
+
+    // Mechanics and categories become tags
+    let tags = server.get(&format!("/api/games/{}/tags", catan_id)).await;
+    let tags: Vec<(&str, &str)> = tags
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tag| {
+            (
+                tag["tag_type"].as_str().unwrap(),
+                tag["name"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert!(tags.contains(&("mechanic", "Trading")));
+    assert!(tags.contains(&("category", "Economic")));
+    let page = server
+        .get("/api/games?tags=dice%20rolling,negotiation")
+        .await;
+    assert_eq!(page["items"][0]["id"], catan_id);
+
+    // The import indexes the new description, so lookups find it straight away
+    let found = server.get("/api/library/search?q=catan&limit=1").await;
+    assert_eq!(found[0]["game_id"], catan_id);
+
+    let response = server
+        .client
+        .post(server.url("/api/import/bgg/things"))
+        .json(&json!({ "xml": "<html>" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.stop().await;
+
+    // Fetching by id goes to the configured base URL
+    let base_url = bgg_stand_in(BGG_THING).await;
+    let server = TestServer::start_with(|app_state| app_state.with_bgg_base_url(base_url));
+    let summary = server
+        .post(
+            "/api/import/bgg/things",
+            json!({ "bgg_ids": [13, 926], "include_expansions": true }),
+        )
+        .await;
+    assert_eq!(summary["created"], 2);
+    assert_eq!(summary["skipped"], 0);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_backup_restores_library_on_another_server() {
+    let source = TestServer::start();
+    let game_id = game_with_rules(&source).await;
+    source
+        .post(
+            "/api/house-rules",
+            json!({
+                "game_id": game_id,
+                "title": "Free parking",
+                "description": "Taxes go to the middle of the table.",
+            }),
+        )
+        .await;
+    let session = source
+        .post(
+            "/api/chat/sessions",
+            json!({ "game_id": game_id, "title": "Robber questions" }),
+        )
+        .await;
+    source
+        .post(
+            "/api/chat/message",
+            json!({ "session_id": session["id"], "message": "Where does the robber start?" }),
+        )
+        .await;
+    let expansion = source
+        .post(
+            "/api/games",
+            json!({ "name": "Harbor Traders: Storm Season", "base_game_id": game_id }),
+        )
+        .await;
+    source
+        .post(
+            "/api/plays",
+            json!({
+                "game_id": game_id,
+                "played_at": "2026-03-14T19:30:00Z",
+                "location": "Game night",
+                "expansion_ids": [expansion["id"]],
+                "chat_session_ids": [session["id"]],
+                "players": [
+                    { "name": "Ada", "score": 12, "winner": true, "team": "Harbor" },
+                    { "name": "Grace", "score": 9, "team": "Harbor" },
+                    { "name": "Linus", "score": 7 },
+                ],
+            }),
+        )
+        .await;
+    let players = source.get("/api/players").await;
+    let player_id = |name: &str| {
+        players
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|player| player["name"] == name)
+            .unwrap()["id"]
+            .clone()
+    };
+    source
+        .post(
+            "/api/leagues",
+            json!({
+                "name": "Spring Season",
+                "starts_at": "2026-03-01T00:00:00Z",
+                "player_ids": [player_id("Ada"), player_id("Grace")],
+                "game_ids": [game_id],
+            }),
+        )
+        .await;
+
+    let archive = source.get("/api/backup").await;
+    assert_eq!(archive["format_version"], 1);
+    assert_eq!(
+        archive["leagues"][0]["player_names"],
+        json!(["Ada", "Grace"])
+    );
+    let archived = archive["games"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|archived| archived["game"]["id"] == game_id)
+        .unwrap();
+    assert!(archived["rules_document"]["content"].is_string());
+    assert!(!archived["chunks"].as_array().unwrap().is_empty());
+    source.stop().await;
+
+    let target = TestServer::start_with(|app_state| {
+        app_state.with_llm(LLMClient::scripted(vec![MockReply::Text(
+            json!({ "faq": [{
+                "question": "Where does the robber start?",
+                "answer": "In the desert.",
+                "cited_chunk_ids": (1..=200).collect::<Vec<i64>>(),
+            }]})
+            .to_string(),
+        )]))
+    });
+    let restore = |conflict: &'static str| {
+        let target = &target;
+        let archive = &archive;
+        async move {
+            target
+                .post(
+                    &format!("/api/backup/restore?conflict={}", conflict),
+                    archive.clone(),
+                )
+                .await
+        }
+    };
+
+    // The seeded games are already there and match by BGG id
+    let summary = restore("skip").await;
+    assert_eq!(summary["created"], 2);
+    assert_eq!(summary["skipped"], 10);
+    assert_eq!(summary["reembedded_chunks"], 0);
+
+    let game_ids = summary["game_ids"].as_array().unwrap();
+    let restored_id = game_ids[game_ids.len() - 2].clone();
+    let restored_expansion_id = game_ids[game_ids.len() - 1].clone();
+    let info = target
+        .get(&format!("/api/games/{}/rules-info", restored_id))
+        .await;
+    assert!(info["has_rules_pdf"].as_bool().unwrap());
+    let search = target
+        .get(&format!(
+            "/api/chat/search-rules?game_id={}&query=robber&limit=3",
+            restored_id
+        ))
+        .await;
+    assert!(!search["results"].as_array().unwrap().is_empty());
+    let rules = target
+        .get(&format!("/api/house-rules?game_id={}", restored_id))
+        .await;
+    assert_eq!(rules["items"][0]["title"], "Free parking");
+    let sessions = target
+        .get(&format!(
+            "/api/chat/sessions?game_id={}&page=1&limit=10",
+            restored_id
+        ))
+        .await;
+    assert_eq!(sessions["total"], 1);
+
+    // Plays keep their players, expansions and consulted sessions
+    let plays_url = format!("/api/plays?game_id={}", restored_id);
+    let plays = target.get(&plays_url).await;
+    assert_eq!(plays["total"], 1);
+    let play = &plays["items"][0];
+    assert_eq!(play["played_at"], "2026-03-14T19:30:00Z");
+    assert_eq!(play["location"], "Game night");
+    assert_eq!(play["expansion_ids"], json!([restored_expansion_id]));
+    assert_eq!(
+        play["chat_session_ids"],
+        json!([sessions["items"][0]["id"]])
+    );
+    let players = |play: &Value| {
+        play["players"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|player| {
+                (
+                    player["name"].clone(),
+                    player["score"].clone(),
+                    player["winner"].clone(),
+                    player["team"].clone(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        players(play),
+        vec![
+            (json!("Ada"), json!(12), json!(true), json!("Harbor")),
+            (json!("Grace"), json!(9), json!(false), json!("Harbor")),
+            (json!("Linus"), json!(7), json!(false), Value::Null),
+        ]
+    );
+
+    // Leagues keep their members and games
+    let league_members = || {
+        let target = &target;
+        async move {
+            let leagues = target.get("/api/leagues").await;
+            assert_eq!(leagues.as_array().unwrap().len(), 1);
+            let league = leagues[0].clone();
+            let players = target.get("/api/players").await;
+            let mut names: Vec<String> = league["player_ids"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|id| {
+                    let player = players
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .find(|player| &player["id"] == id)
+                        .unwrap();
+                    player["name"].as_str().unwrap().to_string()
+                })
+                .collect();
+            names.sort();
+            (league, names)
+        }
+    };
+    let (league, names) = league_members().await;
+    assert_eq!(league["name"], "Spring Season");
+    assert_eq!(league["starts_at"], "2026-03-01T00:00:00Z");
+    assert_eq!(league["game_ids"], json!([restored_id]));
+    assert_eq!(names, vec!["Ada", "Grace"]);
+
+    // Merging again adds nothing new; overwriting replaces rather than duplicates
+    let summary = restore("merge").await;
+    assert_eq!(summary["merged"], 12);
+    let sessions = target
+        .get(&format!(
+            "/api/chat/sessions?game_id={}&page=1&limit=10",
+            restored_id
+        ))
+        .await;
+    assert_eq!(sessions["total"], 1);
+    assert_eq!(target.get(&plays_url).await["total"], 1);
+    let (league, names) = league_members().await;
+    assert_eq!(league["game_ids"], json!([restored_id]));
+    assert_eq!(names, vec!["Ada", "Grace"]);
+
+    // Overwriting replaces the chunks a generated FAQ cites, so the FAQ goes too
+    let faq = target
+        .post(
+            &format!("/api/games/{}/faq", restored_id),
+            json!({ "max_questions": 1 }),
+        )
+        .await;
+    assert_eq!(faq["generated"], 1);
+
+    let summary = restore("overwrite").await;
+    assert_eq!(summary["overwritten"], 12);
+    let faq = target
+        .get(&format!("/api/faq?game_id={}", restored_id))
+        .await;
+    assert_eq!(faq, json!([]));
+    let game_ids = summary["game_ids"].as_array().unwrap();
+    assert_eq!(game_ids[game_ids.len() - 2], restored_id);
+    let rules = target
+        .get(&format!("/api/house-rules?game_id={}", restored_id))
+        .await;
+    assert_eq!(rules["total"], 1);
+    let plays = target.get(&plays_url).await;
+    assert_eq!(plays["total"], 1);
+    let play = &plays["items"][0];
+    assert_eq!(play["expansion_ids"], json!([restored_expansion_id]));
+    let sessions = target
+        .get(&format!(
+            "/api/chat/sessions?game_id={}&page=1&limit=10",
+            restored_id
+        ))
+        .await;
+    assert_eq!(
+        play["chat_session_ids"],
+        json!([sessions["items"][0]["id"]])
+    );
+    assert_eq!(players(play).len(), 3);
+    assert_eq!(
+        target.get("/api/players").await.as_array().unwrap().len(),
+        3
+    );
+    let (league, names) = league_members().await;
+    assert_eq!(league["game_ids"], json!([restored_id]));
+    assert_eq!(names, vec!["Ada", "Grace"]);
+
+    let mut future = archive.clone();
+    future["format_version"] = json!(99);
+    let response = target
+        .client
+        .post(target.url("/api/backup/restore"))
+        .json(&future)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    target.stop().await;
+}
+
+#[tokio::test]
+async fn test_tags_classify_and_filter_games() {
+    let server = TestServer::start();
+
+    let tag_id = |tag: &Value| tag["id"].as_i64().unwrap();
+    let worker_placement = server
+        .post(
+            "/api/tags",
+            json!({ "name": "Worker Placement", "tag_type": "mechanic" }),
+        )
+        .await;
+    let economic = server
+        .post(
+            "/api/tags",
+            json!({ "name": "Economic", "tag_type": "category" }),
+        )
+        .await;
+
+    // Names are unique within a type, ignoring case
+    let response = server
+        .client
+        .post(server.url("/api/tags"))
+        .json(&json!({ "name": "worker placement", "tag_type": "mechanic" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let page = server.get("/api/games?name=a&limit=50").await;
+    let game_id = |name: &str| {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|game| game["name"] == name)
+            .unwrap()["id"]
+            .as_i64()
+            .unwrap()
+    };
+    let brass = game_id("Brass: Birmingham");
+    let ark_nova = game_id("Ark Nova");
+
+    let tagged = |game_id: i64, tags: Vec<i64>| {
+        let server = &server;
+        async move {
+            let response = server
+                .client
+                .put(server.url(&format!("/api/games/{}/tags", game_id)))
+                .json(&json!({ "tag_ids": tags }))
+                .send()
+                .await
+                .unwrap();
+            assert!(response.status().is_success(), "{:?}", response);
+            response.json::<Value>().await.unwrap()
+        }
+    };
+    let tags = tagged(brass, vec![tag_id(&worker_placement), tag_id(&economic)]).await;
+    assert_eq!(tags.as_array().unwrap().len(), 2);
+    tagged(ark_nova, vec![tag_id(&economic)]).await;
+
+    let names = |page: &Value| -> Vec<String> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|game| game["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let page = server.get("/api/games?tags=economic").await;
+    assert_eq!(names(&page), vec!["Ark Nova", "Brass: Birmingham"]);
+    assert_eq!(page["items"][1]["tags"][0]["name"], "Economic");
+    let page = server
+        .get("/api/games?tags=Economic,%20worker%20placement")
+        .await;
+    assert_eq!(names(&page), vec!["Brass: Birmingham"]);
+
+    let tags = server.get("/api/tags?tag_type=category").await;
+    assert_eq!(tags.as_array().unwrap().len(), 1);
+    assert_eq!(tags[0]["game_count"], 2);
+
+    // Deleting a tag takes it off every game
+    let response = server
+        .client
+        .delete(server.url(&format!("/api/tags/{}", tag_id(&economic))))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let tags = server.get(&format!("/api/games/{}/tags", brass)).await;
+    assert_eq!(tags[0]["name"], "Worker Placement");
+    assert_eq!(tags.as_array().unwrap().len(), 1);
+
+    let response = server
+        .client
+        .put(server.url(&format!("/api/games/{}/tags", brass)))
+        .json(&json!({ "tag_ids": [tag_id(&economic)] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    // Deleted games no longer count towards their tags
+    let prototype = server
+        .post("/api/games", json!({ "name": "Worker Prototype" }))
+        .await;
+    let prototype_id = prototype["id"].as_i64().unwrap();
+    tagged(prototype_id, vec![tag_id(&worker_placement)]).await;
+    let tag_path = format!("/api/tags/{}", tag_id(&worker_placement));
+    let game_count = server.get(&tag_path).await["game_count"].as_i64().unwrap();
+    server
+        .client
+        .delete(server.url(&format!("/api/games/{}", prototype_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(server.get(&tag_path).await["game_count"], game_count - 1);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_comparison_session_survives_deleting_a_compared_game() {
+    let server = TestServer::start();
+    let game_id = game_with_rules(&server).await;
+    let other = server
+        .post("/api/games", json!({ "name": "Lighthouse Keepers" }))
+        .await;
+    let other_id = other["id"].as_i64().unwrap();
+    upload_rules(&server, other_id).await;
+
+    let session = server
+        .post(
+            "/api/chat/sessions",
+            json!({ "game_id": game_id, "compare_game_ids": [other_id] }),
+        )
+        .await;
+    assert_eq!(session["compare_game_ids"], json!([other_id]));
+
+    server
+        .client
+        .delete(server.url(&format!("/api/games/{}", other_id)))
+        .send()
+        .await
+        .unwrap();
+    let history = server
+        .get(&format!("/api/chat/sessions/{}", session["id"]))
+        .await;
+    assert_eq!(history["session"]["compare_game_ids"], json!([]));
+
+    // The session carries on with the game that is left
+    let chat = server
+        .post(
+            "/api/chat/message",
+            json!({
+                "session_id": session["id"],
+                "message": "What happens when a seven is rolled?",
+            }),
+        )
+        .await;
+    assert!(
+        chat["context_sources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|source| source["game_id"] == game_id)
+    );
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_deleting_a_game_leaves_nothing_behind() {
+    // The quick-reference bullet cites every chunk, so it keeps whichever passages are retrieved
+    let server = TestServer::start_with(|app_state| {
+        app_state.with_llm(LLMClient::scripted(vec![MockReply::Text(
+            json!({ "sections": [{
+                "title": "Setup",
+                "bullets": [{
+                    "text": "Place the robber on the desert.",
+                    "cited_chunk_ids": (1..=200).collect::<Vec<i64>>(),
+                }],
+            }]})
+            .to_string(),
+        )]))
+    });
+    let game_id = game_with_rules(&server).await;
+
+    server
+        .get(&format!("/api/games/{}/quick-reference", game_id))
+        .await;
+    assert_eq!(server.rows_for_game("quick_references", game_id), 1);
+
+    server
+        .post(
+            "/api/prompt-templates",
+            json!({
+                "game_id": game_id,
+                "name": "Harbor master",
+                "template": "Answer as the harbor master of {{game_name}}.\n{{rules_context}}",
+            }),
+        )
+        .await;
+
+    let session = server
+        .post("/api/chat/sessions", json!({ "game_id": game_id }))
+        .await;
+    server
+        .post(
+            "/api/chat/message",
+            json!({
+                "session_id": session["id"],
+                "message": "What happens when a seven is rolled?",
+            }),
+        )
+        .await;
+    assert_eq!(server.rows_for_game("vec_answer_cache", game_id), 1);
+
+    server
+        .post(
+            "/api/faq",
+            json!({
+                "game_id": game_id,
+                "question": "Where can the robber be moved?",
+                "answer": "To any other hex.",
+                "cited_chunks": [],
+            }),
+        )
+        .await;
+    assert_eq!(server.rows_for_game("vec_faq_entries", game_id), 1);
+
+    let golden: Value = serde_json::from_str(RULES_GOLDEN).unwrap();
+    server
+        .post(
+            "/api/eval/questions",
+            json!({ "game_id": game_id, "questions": golden }),
+        )
+        .await;
+    server
+        .post("/api/eval/runs", json!({ "game_id": game_id, "k": 5 }))
+        .await;
+    assert_eq!(server.rows_for_game("eval_runs", game_id), 1);
+
+    let response = server
+        .client
+        .delete(server.url(&format!("/api/games/{}", game_id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+
+    assert_eq!(server.rows_for_game("prompt_templates", game_id), 0);
+    assert_eq!(server.rows_for_game("answer_cache", game_id), 0);
+    assert_eq!(server.rows_for_game("vec_answer_cache", game_id), 0);
+    assert_eq!(server.rows_for_game("faq_entries", game_id), 0);
+    assert_eq!(server.rows_for_game("vec_faq_entries", game_id), 0);
+    assert_eq!(server.rows_for_game("quick_references", game_id), 0);
+    assert_eq!(server.rows_for_game("eval_questions", game_id), 0);
+    assert_eq!(server.rows_for_game("eval_runs", game_id), 0);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_expansion_rules_amend_base_game() {
+    let server = TestServer::start();
+    let base_id = game_with_rules(&server).await;
+
+    let expansion = server
+        .post(
+            "/api/games",
+            json!({ "name": "Harbor Traders: Storm Season", "base_game_id": base_id }),
+        )
+        .await;
+    let expansion_id = expansion["id"].as_i64().unwrap();
+    assert_eq!(expansion["base_game_id"], base_id);
+    upload_rules(&server, expansion_id).await;
+    let unrelated = server
+        .post("/api/games", json!({ "name": "Lighthouse Keepers" }))
+        .await;
+    let unrelated_id = unrelated["id"].as_i64().unwrap();
+
+    let expansions = server
+        .get(&format!("/api/games/{}/expansions", base_id))
+        .await;
+    assert_eq!(expansions.as_array().unwrap().len(), 1);
+    assert_eq!(expansions[0]["id"], expansion_id);
+
+    // Expansions cannot have expansions, and only a base game's own expansions can be in play
+    let status = |path: &'static str, body: Value| {
+        let server = &server;
+        async move {
+            server
+                .client
+                .post(server.url(path))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+    let nested = json!({ "name": "Storm Season Promo", "base_game_id": expansion_id });
+    assert_eq!(status("/api/games", nested).await, 400);
+
+    // A game can be made an expansion and then a standalone game again
+    let put = |body: Value| {
+        let server = &server;
+        async move {
+            server
+                .client
+                .put(server.url(&format!("/api/games/{}", unrelated_id)))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let linked: Value = put(json!({ "base_game_id": base_id }))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(linked["base_game_id"], base_id);
+    let both = json!({ "base_game_id": base_id, "clear_base_game": true });
+    assert_eq!(put(both).await.status(), 400);
+    let unlinked: Value = put(json!({ "name": "Lighthouse Keepers", "clear_base_game": true }))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(unlinked["base_game_id"].is_null());
+    let expansions = server
+        .get(&format!("/api/games/{}/expansions", base_id))
+        .await;
+    assert_eq!(expansions.as_array().unwrap().len(), 1);
+    let foreign = json!({ "game_id": base_id, "expansion_ids": [unrelated_id] });
+    assert_eq!(status("/api/chat/sessions", foreign).await, 400);
+
+    let session = server
+        .post(
+            "/api/chat/sessions",
+            json!({ "game_id": base_id, "expansion_ids": [expansion_id] }),
+        )
+        .await;
+    assert_eq!(session["expansion_ids"], json!([expansion_id]));
+
+    // Both rulebooks match equally well, so the expansion's passages come first
+    let chat = server
+        .post(
+            "/api/chat/message",
+            json!({
+                "session_id": session["id"],
+                "message": "What happens to the robber when a seven is rolled?",
+            }),
+        )
+        .await;
+    let sources = chat["context_sources"].as_array().unwrap();
+    assert_eq!(sources[0]["game_id"], expansion_id);
+    assert!(sources.iter().any(|source| source["game_id"] == base_id));
+
+    // Deleting the expansion takes it out of play, and the session carries on
+    server
+        .client
+        .delete(server.url(&format!("/api/games/{}", expansion_id)))
+        .send()
+        .await
+        .unwrap();
+    let history = server
+        .get(&format!("/api/chat/sessions/{}", session["id"]))
+        .await;
+    assert_eq!(history["session"]["expansion_ids"], json!([]));
+    let chat = server
+        .post(
+            "/api/chat/message",
+            json!({
+                "session_id": session["id"],
+                "message": "Where does the robber start the game?",
+            }),
+        )
+        .await;
+    assert_eq!(chat["cached"], false);
+    assert!(
+        chat["context_sources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|source| source["game_id"] == base_id)
+    );
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_plays_log_players_scores_and_sessions() {
+    let server = TestServer::start();
+
+    let game = server
+        .post("/api/games", json!({ "name": "Harbor Traders" }))
+        .await;
+    let game_id = game["id"].as_i64().unwrap();
+    let expansion = server
+        .post(
+            "/api/games",
+            json!({ "name": "Harbor Traders: Storm Season", "base_game_id": game_id }),
+        )
+        .await;
+    let expansion_id = expansion["id"].as_i64().unwrap();
+    let session = server
+        .post("/api/chat/sessions", json!({ "game_id": game_id }))
+        .await;
+    let session_id = session["id"].as_i64().unwrap();
+
+    let play = server
+        .post(
+            "/api/plays",
+            json!({
+                "game_id": game_id,
+                "played_at": "2026-03-14T19:30:00Z",
+                "duration_minutes": 95,
+                "location": "Kitchen table",
+                "expansion_ids": [expansion_id],
+                "players": [
+                    { "name": "Ada", "score": 41, "winner": true },
+                    { "name": "Grace", "score": 37 },
+                ],
+                "chat_session_ids": [session_id],
+            }),
+        )
+        .await;
+    let play_id = play["id"].as_i64().unwrap();
+    assert_eq!(play["game_name"], "Harbor Traders");
+    assert_eq!(play["expansion_ids"], json!([expansion_id]));
+    assert_eq!(play["chat_session_ids"], json!([session_id]));
+    assert_eq!(play["players"][0]["name"], "Ada");
+    assert_eq!(play["players"][0]["winner"], true);
+    assert_eq!(play["players"][1]["score"], 37);
+
+    // Players are matched by name ignoring case, and the game remembers its latest play
+    server
+        .post(
+            "/api/plays",
+            json!({
+                "game_id": game_id,
+                "played_at": "2026-02-01T18:00:00Z",
+                "players": [{ "name": "ada" }, { "name": "Linus", "winner": true }],
+            }),
+        )
+        .await;
+    let game = server.get(&format!("/api/games/{}", game_id)).await;
+    assert_eq!(game["last_played_at"], "2026-03-14T19:30:00Z");
+
+    let page = server.get("/api/plays?player=ADA&limit=1").await;
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["total_pages"], 2);
+    assert_eq!(page["items"][0]["id"], play_id);
+    let page = server.get("/api/plays?player=Grace").await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(
+        page["items"][0]["players"][0]["player_id"],
+        play["players"][0]["player_id"]
+    );
+
+    let send = |method: reqwest::Method, path: String, body: Value| {
+        let server = &server;
+        async move {
+            server
+                .client
+                .request(method, server.url(&path))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let duplicate = json!({
+        "game_id": game_id,
+        "players": [{ "name": "Ada" }, { "name": " ada " }],
+    });
+    let response = send(reqwest::Method::POST, "/api/plays".to_string(), duplicate).await;
+    assert_eq!(response.status(), 400);
+    let not_an_expansion = json!({
+        "game_id": expansion_id,
+        "expansion_ids": [game_id],
+        "players": [{ "name": "Ada" }],
+    });
+    let response = send(
+        reqwest::Method::POST,
+        "/api/plays".to_string(),
+        not_an_expansion,
+    )
+    .await;
+    assert_eq!(response.status(), 400);
+
+    let response = send(
+        reqwest::Method::PUT,
+        format!("/api/plays/{}", play_id),
+        json!({ "notes": "Storm hit twice", "chat_session_ids": [] }),
+    )
+    .await;
+    assert!(response.status().is_success());
+    let play: Value = response.json().await.unwrap();
+    assert_eq!(play["notes"], "Storm hit twice");
+    assert_eq!(play["location"], "Kitchen table");
+    assert_eq!(play["chat_session_ids"], json!([]));
+    assert_eq!(play["players"].as_array().unwrap().len(), 2);
+
+    let response = send(
+        reqwest::Method::DELETE,
+        format!("/api/plays/{}", play_id),
+        Value::Null,
+    )
+    .await;
+    assert!(response.status().is_success());
+    let game = server.get(&format!("/api/games/{}", game_id)).await;
+    assert_eq!(game["last_played_at"], "2026-02-01T18:00:00Z");
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_player_and_play_statistics() {
+    let server = TestServer::start();
+
+    let new_game = |name: &'static str| {
+        let server = &server;
+        async move {
+            server.post("/api/games", json!({ "name": name })).await["id"]
+                .as_i64()
+                .unwrap()
+        }
+    };
+    let harbor = new_game("Harbor Traders").await;
+    let lighthouse = new_game("Lighthouse Keepers").await;
+    let orchard = new_game("Orchard Rush").await;
+
+    // Harbor Traders three times, Lighthouse Keepers twice, Orchard Rush once
+    let plays = [
+        (harbor, "2026-01-05T19:00:00Z", 40, 35, true),
+        (harbor, "2026-02-05T19:00:00Z", 52, 60, false),
+        (harbor, "2026-03-05T19:00:00Z", 47, 30, true),
+        (lighthouse, "2026-02-10T19:00:00Z", 12, 15, false),
+        (lighthouse, "2026-03-10T19:00:00Z", 18, 11, true),
+        (orchard, "2026-03-20T19:00:00Z", 7, 9, false),
+    ];
+    for (game_id, played_at, ada_score, grace_score, ada_won) in plays {
+        server
+            .post(
+                "/api/plays",
+                json!({
+                    "game_id": game_id,
+                    "played_at": played_at,
+                    "duration_minutes": 60,
+                    "players": [
+                        { "name": "Ada", "score": ada_score, "winner": ada_won },
+                        { "name": "Grace", "score": grace_score, "winner": !ada_won },
+                    ],
+                }),
+            )
+            .await;
+    }
+
+    let summary = server.get("/api/stats").await;
+    assert_eq!(summary["total_plays"], 6);
+    assert_eq!(summary["games_played"], 3);
+    assert_eq!(summary["players"], 2);
+    assert_eq!(summary["total_minutes"], 360);
+    assert_eq!(summary["h_index"], 2);
+    let march = server
+        .get("/api/stats?from=2026-03-01T00:00:00Z&to=2026-03-31T23:59:59Z")
+        .await;
+    assert_eq!(march["total_plays"], 3);
+    assert_eq!(march["h_index"], 1);
+
+    let most_played = server.get("/api/stats/most-played?limit=2").await;
+    assert_eq!(most_played[0]["game_id"], harbor);
+    assert_eq!(most_played[0]["plays"], 3);
+    assert_eq!(most_played[1]["game_id"], lighthouse);
+    assert_eq!(most_played.as_array().unwrap().len(), 2);
+
+    // The seeded catalog has never been played
+    let shelf = server.get("/api/stats/shelf-of-shame").await;
+    let shelf = shelf.as_array().unwrap();
+    assert_eq!(shelf.len(), 10);
+    assert!(shelf.iter().all(|game| game["game_id"] != harbor));
+
+    let stats = server.get(&format!("/api/games/{}/stats", harbor)).await;
+    assert_eq!(stats["plays"], 3);
+    assert_eq!(stats["best_score"], 60);
+    assert_eq!(stats["average_duration_minutes"], 60.0);
+    assert_eq!(stats["players"][0]["name"], "Ada");
+    assert_eq!(stats["players"][0]["wins"], 2);
+    assert_eq!(stats["players"][0]["average_score"], 46.333333333333336);
+
+    let players = server.get("/api/players").await;
+    let ada = players
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|player| player["name"] == "Ada")
+        .unwrap();
+    assert_eq!(ada["play_count"], 6);
+    assert_eq!(ada["win_count"], 3);
+    let stats = server
+        .get(&format!("/api/players/{}/stats", ada["id"]))
+        .await;
+    assert_eq!(stats["win_rate"], 0.5);
+    assert_eq!(stats["h_index"], 2);
+    assert_eq!(stats["games"][0]["game_id"], harbor);
+    assert_eq!(stats["games"][0]["best_score"], 52);
+    assert_eq!(stats["games"][2]["win_rate"], 0.0);
+
+    let response = server
+        .client
+        .post(server.url("/api/players"))
+        .json(&json!({ "name": "ada" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_league_standings_and_rating_history() {
+    let server = TestServer::start();
+
+    let harbor = server
+        .post("/api/games", json!({ "name": "Harbor Traders" }))
+        .await["id"]
+        .as_i64()
+        .unwrap();
+    let orchard = server
+        .post("/api/games", json!({ "name": "Orchard Rush" }))
+        .await["id"]
+        .as_i64()
+        .unwrap();
+
+    // A team game, then a free-for-all with a guest who is not in the league
+    server
+        .post(
+            "/api/plays",
+            json!({
+                "game_id": harbor,
+                "played_at": "2026-04-01T19:00:00Z",
+                "players": [
+                    { "name": "Ada", "winner": true, "team": "Red" },
+                    { "name": "Grace", "team": "Red" },
+                    { "name": "Linus", "team": "Blue" },
+                    { "name": "Margaret", "team": "Blue" },
+                ],
+            }),
+        )
+        .await;
+    server
+        .post(
+            "/api/plays",
+            json!({
+                "game_id": harbor,
+                "played_at": "2026-04-08T19:00:00Z",
+                "players": [
+                    { "name": "Ada", "score": 31, "winner": true },
+                    { "name": "Linus", "score": 20 },
+                    { "name": "Grace", "score": 10 },
+                    { "name": "Guest", "score": 40 },
+                ],
+            }),
+        )
+        .await;
+
+    let players = server.get("/api/players").await;
+    let player_id = |name: &str| {
+        players
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|player| player["name"] == name)
+            .unwrap()["id"]
+            .as_i64()
+            .unwrap()
+    };
+    let ada = player_id("Ada");
+
+    let league = server
+        .post(
+            "/api/leagues",
+            json!({
+                "name": "Spring Season",
+                "starts_at": "2026-03-01T00:00:00Z",
+                "ends_at": "2026-05-31T23:59:59Z",
+                "player_ids": [ada, player_id("Grace"), player_id("Linus"), player_id("Margaret")],
+                "game_ids": [harbor],
+            }),
+        )
+        .await;
+    let league_id = league["id"].as_i64().unwrap();
+    assert_eq!(league["player_ids"].as_array().unwrap().len(), 4);
+    assert_eq!(league["game_ids"], json!([harbor]));
+
+    let standings = server
+        .get(&format!("/api/leagues/{}/standings", league_id))
+        .await;
+    let names: Vec<&str> = standings
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|standing| standing["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Ada", "Grace", "Linus", "Margaret"]);
+    assert_eq!(standings[0]["rank"], 1);
+    assert_eq!(standings[0]["plays"], 2);
+    assert_eq!(standings[0]["wins"], 2);
+    assert_eq!(standings[3]["rating"], 1484.0);
+
+    let history = server
+        .get(&format!(
+            "/api/leagues/{}/ratings/history?player_id={}",
+            league_id, ada
+        ))
+        .await;
+    assert_eq!(history.as_array().unwrap().len(), 2);
+    assert_eq!(history[0]["rating_before"], 1500.0);
+    assert_eq!(history[0]["rating_after"], 1516.0);
+    assert_eq!(history[1]["rating_before"], 1516.0);
+
+    // Orchard Rush is not one of the league's games
+    let response = server
+        .client
+        .get(server.url(&format!(
+            "/api/leagues/{}/standings?game_id={}",
+            league_id, orchard
+        )))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = server
+        .client
+        .post(server.url("/api/leagues"))
+        .json(&json!({ "name": "spring season" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    // Moving the season past both plays leaves nobody rated
+    let league = server
+        .client
+        .put(server.url(&format!("/api/leagues/{}", league_id)))
+        .json(&json!({ "starts_at": "2026-05-01T00:00:00Z" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(league.status(), 200);
+    let standings = server
+        .get(&format!("/api/leagues/{}/standings", league_id))
+        .await;
+    assert_eq!(standings, json!([]));
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_collection_status_and_loans() {
+    let server = TestServer::start();
+
+    let game_id = server
+        .post("/api/games", json!({ "name": "Harbor Traders" }))
+        .await["id"]
+        .as_i64()
+        .unwrap();
+    let collection_url = format!("/api/games/{}/collection", game_id);
+    let put = |path: String, body: Value| {
+        let server = &server;
+        async move {
+            server
+                .client
+                .put(server.url(&path))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Games start out owned
+    let collection = server.get(&collection_url).await;
+    assert_eq!(collection["status"], "owned");
+    assert_eq!(collection["updated_at"], Value::Null);
+
+    let response = put(
+        collection_url.clone(),
+        json!({ "status": "owned", "wishlist_priority": 2 }),
+    )
+    .await;
+    assert_eq!(response.status(), 400);
+
+    let response = put(
+        collection_url.clone(),
+        json!({ "status": "wishlist", "wishlist_priority": 2, "shelf_location": "Hall closet" }),
+    )
+    .await;
+    assert_eq!(response.status(), 200);
+    let wishlist = server.get("/api/games?collection_status=wishlist").await;
+    assert_eq!(wishlist["total"], 1);
+    assert_eq!(wishlist["items"][0]["id"], game_id);
+    let shelf = server.get("/api/stats/shelf-of-shame").await;
+    assert!(
+        shelf
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|game| game["game_id"] != game_id)
+    );
+
+    // Only owned games can be lent out
+    let lend = |body: Value| {
+        let server = &server;
+        async move {
+            server
+                .client
+                .post(server.url("/api/loans"))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let loan_request = json!({
+        "game_id": game_id,
+        "borrower": "Grace",
+        "loaned_at": "2026-01-10T18:00:00Z",
+        "due_at": "2026-01-24T18:00:00Z",
+    });
+    assert_eq!(lend(loan_request.clone()).await.status(), 400);
+
+    let collection: Value = put(
+        collection_url.clone(),
+        json!({ "status": "owned", "acquisition_price": 39.5 }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    assert_eq!(collection["wishlist_priority"], Value::Null);
+    assert_eq!(collection["shelf_location"], "Hall closet");
+
+    let loan: Value = lend(loan_request.clone()).await.json().await.unwrap();
+    assert_eq!(loan["overdue"], true);
+    assert_eq!(lend(loan_request).await.status(), 400);
+    let collection = server.get(&collection_url).await;
+    assert_eq!(collection["current_loan"]["borrower"], "Grace");
+
+    let overdue = server.get("/api/loans?overdue=true").await;
+    assert_eq!(overdue.as_array().unwrap().len(), 1);
+    assert_eq!(overdue[0]["id"], loan["id"]);
+
+    let returned = server
+        .post(
+            &format!("/api/loans/{}/return", loan["id"]),
+            json!({ "returned_at": "2026-01-30T12:00:00Z" }),
+        )
+        .await;
+    assert_eq!(returned["overdue"], false);
+    assert_eq!(server.get("/api/loans?overdue=true").await, json!([]));
+    let response = server
+        .client
+        .post(server.url(&format!("/api/loans/{}/return", loan["id"])))
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    let collection = server.get(&collection_url).await;
+    assert_eq!(collection["current_loan"], Value::Null);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_recommendations_fit_the_table_and_favor_liked_games() {
+    let server = TestServer::start();
+
+    let new_game = |body: Value| {
+        let server = &server;
+        async move {
+            server.post("/api/games", body).await["id"]
+                .as_i64()
+                .unwrap()
+        }
+    };
+    let harbor = new_game(json!({
+        "name": "Harbor Traders",
+        "description": "Merchants trade spices and silk between harbor ports.",
+        "min_players": 2, "max_players": 5, "play_time_minutes": 60, "complexity_rating": 2.5,
+    }))
+    .await;
+    let caravans = new_game(json!({
+        "name": "Spice Caravans",
+        "description": "Merchants trade spices and silk along desert caravan routes.",
+        "min_players": 3, "max_players": 5, "play_time_minutes": 75, "complexity_rating": 2.8,
+    }))
+    .await;
+    let orchard = new_game(json!({
+        "name": "Orchard Rush",
+        "description": "Pick apples quickly before the frost arrives.",
+        "min_players": 2, "max_players": 6, "play_time_minutes": 30, "complexity_rating": 1.2,
+    }))
+    .await;
+    let siege = new_game(json!({
+        "name": "Long Siege",
+        "min_players": 2, "max_players": 6, "play_time_minutes": 240,
+    }))
+    .await;
+    let wishlisted = new_game(json!({
+        "name": "Cloud Regatta",
+        "min_players": 2, "max_players": 6, "play_time_minutes": 45,
+    }))
+    .await;
+    server
+        .client
+        .put(server.url(&format!("/api/games/{}/collection", wishlisted)))
+        .json(&json!({ "status": "wishlist" }))
+        .send()
+        .await
+        .unwrap();
+    let lent_out = new_game(json!({
+        "name": "Lantern Festival",
+        "min_players": 2, "max_players": 6, "play_time_minutes": 45,
+    }))
+    .await;
+    server
+        .post(
+            "/api/loans",
+            json!({ "game_id": lent_out, "borrower": "Linus" }),
+        )
+        .await;
+
+    // The group has been playing Harbor Traders
+    server
+        .post(
+            "/api/plays",
+            json!({
+                "game_id": harbor,
+                "players": [{ "name": "Ada", "winner": true }, { "name": "Grace" }],
+            }),
+        )
+        .await;
+
+    let response = server
+        .post(
+            "/api/recommendations",
+            json!({
+                "players": 5,
+                "max_play_time": 90,
+                "player_names": ["ada", "Grace"],
+                "limit": 20,
+                "explain": true,
+            }),
+        )
+        .await;
+    assert_eq!(response["liked_game_ids"], json!([harbor]));
+
+    let picks = response["recommendations"].as_array().unwrap();
+    let position = |game_id: i64| picks.iter().position(|pick| pick["game_id"] == game_id);
+    assert!(position(siege).is_none());
+    assert!(position(wishlisted).is_none());
+    assert!(position(lent_out).is_none());
+    assert!(position(caravans).unwrap() < position(orchard).unwrap());
+    // Just played, so it ranks below the similar game it is liked for
+    assert!(position(caravans).unwrap() < position(harbor).unwrap());
+    let caravan_pick = &picks[position(caravans).unwrap()];
+    assert_eq!(caravan_pick["similar_to"], harbor);
+    // The offline model gives no usable justification, which leaves picks unexplained
+    assert_eq!(caravan_pick["justification"], Value::Null);
+    assert!(
+        picks
+            .iter()
+            .all(|pick| pick["max_players"].as_i64().unwrap() >= 5)
+    );
+
+    let response = server
+        .client
+        .post(server.url("/api/recommendations"))
+        .json(&json!({ "players": 4, "liked_game_ids": [999_999] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn test_similar_games_and_library_search() {
+    let server = TestServer::start();
+
+    let new_game = |body: Value| {
+        let server = &server;
+        async move {
+            server.post("/api/games", body).await["id"]
+                .as_i64()
+                .unwrap()
+        }
+    };
+    let crypt = new_game(json!({
+        "name": "Crypt Raiders",
+        "description": "Cooperative dungeon crawler where heroes explore crypts across a branching campaign.",
+    }))
+    .await;
+    let tomb = new_game(json!({
+        "name": "Tomb Wardens",
+        "description": "Heroes explore crypts in a cooperative dungeon crawler campaign against monsters.",
+    }))
+    .await;
+    let orchard = new_game(json!({
+        "name": "Orchard Rush",
+        "description": "Pick apples quickly before the frost arrives.",
+    }))
+    .await;
+    let blank = new_game(json!({ "name": "Untitled Prototype" })).await;
+
+    // A game with tags but no description is indexed by its tags
+    let goblins = new_game(json!({ "name": "Goblin Depths" })).await;
+    let tag = server
+        .post(
+            "/api/tags",
+            json!({ "name": "Cooperative Dungeon Crawler", "tag_type": "category" }),
+        )
+        .await;
+    server
+        .client
+        .put(server.url(&format!("/api/games/{}/tags", goblins)))
+        .json(&json!({ "tag_ids": [tag["id"]] }))
+        .send()
+        .await
+        .unwrap();
+
+    let similar = server
+        .get(&format!("/api/games/{}/similar?limit=3", crypt))
+        .await;
+    let similar = similar.as_array().unwrap();
+    assert_eq!(similar.len(), 3);
+    assert_eq!(similar[0]["game_id"], tomb);
+    assert!(similar.iter().all(|game| game["game_id"] != crypt));
+    let similarities: Vec<f64> = similar
+        .iter()
+        .map(|game| game["similarity"].as_f64().unwrap())
+        .collect();
+    assert!(similarities.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let found = server
+        .get("/api/library/search?q=cooperative%20dungeon%20crawler%20with%20campaign&limit=2")
+        .await;
+    let found: Vec<i64> = found
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|game| game["game_id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(found.len(), 2);
+    assert!(
+        found
+            .iter()
+            .all(|game_id| [crypt, tomb, goblins].contains(game_id))
+    );
+    let found = server.get("/api/library/search?q=goblin%20depths").await;
+    assert_eq!(found[0]["game_id"], goblins);
+
+    // An edited description is embedded again when it is saved
+    server
+        .client
+        .put(server.url(&format!("/api/games/{}", orchard)))
+        .json(&json!({
+            "description": "Cooperative dungeon crawler campaign where heroes explore crypts."
+        }))
+        .send()
+        .await
+        .unwrap();
+    let similar = server
+        .get(&format!("/api/games/{}/similar?limit=1", orchard))
+        .await;
+    assert!([crypt, tomb].contains(&similar[0]["game_id"].as_i64().unwrap()));
+
+    // Deleted games leave the index
+    server
+        .client
+        .delete(server.url(&format!("/api/games/{}", tomb)))
+        .send()
+        .await
+        .unwrap();
+    let similar = server
+        .get(&format!("/api/games/{}/similar?limit=50", crypt))
+        .await;
+    assert!(
+        similar
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|game| game["game_id"] != tomb)
+    );
+
+    for (path, status) in [
+        (format!("/api/games/{}/similar", blank), 400),
+        ("/api/games/999999/similar".to_string(), 404),
+        (format!("/api/games/{}/similar?limit=0", crypt), 400),
+        ("/api/library/search?q=%20".to_string(), 400),
+    ] {
+        let response = server.client.get(server.url(&path)).send().await.unwrap();
+        assert_eq!(response.status(), status, "GET {}", path);
+    }
+
+    server.stop().await;
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::{
+    AppState,
+    db::{self, Database},
+    models::{ArchivedDocument, ArchivedGame, ArchivedLeague, Game, LibraryArchive, LoanListQuery},
+    pdf,
+};
+
+/// Bump whenever the archive layout changes in a way older builds cannot read
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+pub fn check_format_version(format_version: u32) -> Result<()> {
+    if format_version == 0 || format_version > ARCHIVE_FORMAT_VERSION {
+        bail!(
+            "Unsupported archive format version {}; this server reads versions 1 to {}",
+            format_version,
+            ARCHIVE_FORMAT_VERSION
+        );
+    }
+    Ok(())
+}
+
+pub fn encode_document(path: &Path) -> Result<ArchivedDocument> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(ArchivedDocument {
+        file_name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "rules.pdf".to_string()),
+        content: STANDARD.encode(bytes),
+    })
+}
+
+/// Decode an archived rulebook, checking that it is still a PDF
+pub fn decode_document(document: &ArchivedDocument) -> Result<Vec<u8>> {
+    let bytes = STANDARD
+        .decode(document.content.trim())
+        .with_context(|| format!("{} is not valid base64", document.file_name))?;
+    pdf::validate_pdf_file(&bytes).with_context(|| document.file_name.clone())?;
+    Ok(bytes)
+}
+
+async fn archive_game(db: &Database, game: Game) -> Result<ArchivedGame> {
+    let tags = db::tags::list_game_tags(db, game.id)
+        .await?
+        .unwrap_or_default();
+    let house_rules = db::house_rules::list_house_rules_by_game(db, game.id, false).await?;
+    let chunks = db::backup::list_game_chunks(db, game.id).await?;
+    let plays = db::backup::list_game_plays(db, game.id).await?;
+    let collection = db::collection::get_game_collection(db, game.id).await?;
+    let loans = db::collection::list_loans(
+        db,
+        &LoanListQuery {
+            game_id: Some(game.id),
+            active: None,
+            overdue: false,
+            borrower: None,
+        },
+    )
+    .await?;
+
+    let mut chat_sessions = Vec::new();
+    for session_id in db::backup::list_chat_session_ids(db, game.id).await? {
+        if let Some(history) = db::chat::get_chat_history(db, session_id).await? {
+            chat_sessions.push(history);
+        }
+    }
+
+    // A missing upload only loses the original file; its text and chunks are still archived
+    let rules_document = match &game.rules_pdf_path {
+        Some(path) => match encode_document(Path::new(path)) {
+            Ok(document) => Some(document),
+            Err(e) => {
+                tracing::warn!(
+                    "Leaving the rulebook of game {} out of the archive: {:#}",
+                    game.id,
+                    e
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(ArchivedGame {
+        game,
+        tags,
+        house_rules,
+        rules_document,
+        chunks,
+        chat_sessions,
+        plays,
+        collection,
+        loans,
+    })
+}
+
+/// Gather the whole library into an archive
+pub async fn export_library(app_state: &AppState) -> Result<LibraryArchive> {
+    let db = app_state.db();
+
+    let mut games = Vec::new();
+    for game in db::games::list_all_games(&db).await? {
+        games.push(archive_game(&db, game).await?);
+    }
+
+    let player_names: HashMap<_, _> = db::players::list_players(&db)
+        .await?
+        .into_iter()
+        .map(|player| (player.id, player.name))
+        .collect();
+    let leagues = db::leagues::list_leagues(&db)
+        .await?
+        .into_iter()
+        .map(|league| ArchivedLeague {
+            player_names: league
+                .player_ids
+                .iter()
+                .filter_map(|id| player_names.get(id).cloned())
+                .collect(),
+            league,
+        })
+        .collect();
+
+    Ok(LibraryArchive {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        exported_at: chrono::Utc::now(),
+        embedding_model: app_state.embedder().get_model().to_string(),
+        games,
+        leagues,
+    })
+}
+
+/// Embed an archive's chunks again with the current model, when it used another one.
+/// Returns how many chunks were re-embedded
+pub async fn reembed_chunks(app_state: &AppState, archive: &mut LibraryArchive) -> Result<usize> {
+    if archive.embedding_model == app_state.embedder().get_model() {
+        return Ok(0);
+    }
+
+    let mut reembedded = 0;
+    for archived in &mut archive.games {
+        if archived.chunks.is_empty() {
+            continue;
+        }
+        let texts: Vec<String> = archived
+            .chunks
+            .iter()
+            .map(|chunk| chunk.chunk_text.clone())
+            .collect();
+        let embeddings = app_state.embedder().generate_embeddings(&texts).await?;
+        for (chunk, embedding) in archived.chunks.iter_mut().zip(embeddings) {
+            chunk.embedding = embedding;
+        }
+        reembedded += texts.len();
+    }
+
+    archive.embedding_model = app_state.embedder().get_model().to_string();
+    Ok(reembedded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_format_version() {
+        assert!(check_format_version(ARCHIVE_FORMAT_VERSION).is_ok());
+        assert!(check_format_version(0).is_err());
+        assert!(check_format_version(ARCHIVE_FORMAT_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_document_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("game_1_rules.pdf");
+        std::fs::write(&path, b"%PDF-1.4 harbor traders").unwrap();
+
+        let document = encode_document(&path).unwrap();
+        assert_eq!(document.file_name, "game_1_rules.pdf");
+        assert_eq!(
+            decode_document(&document).unwrap(),
+            b"%PDF-1.4 harbor traders"
+        );
+
+        let not_a_pdf = ArchivedDocument {
+            file_name: "notes.txt".to_string(),
+            content: STANDARD.encode(b"just some notes"),
+        };
+        assert!(decode_document(&not_a_pdf).is_err());
+        let corrupt = ArchivedDocument {
+            file_name: "rules.pdf".to_string(),
+            content: "%%%".to_string(),
+        };
+        assert!(decode_document(&corrupt).is_err());
+    }
+}
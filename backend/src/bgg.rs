@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+
+use crate::models::{BggRank, BggThing};
+
+pub const DEFAULT_BGG_BASE_URL: &str = "https://boardgamegeek.com/xmlapi2";
+
+/// Most ids the XML API2 accepts in one `thing` request
+pub const THING_BATCH_SIZE: usize = 20;
+
+#[derive(Deserialize)]
+struct RankRow {
+    id: i32,
+    name: String,
+    #[serde(default, deserialize_with = "csv::invalid_option")]
+    yearpublished: Option<i32>,
+    #[serde(default, deserialize_with = "csv::invalid_option")]
+    rank: Option<i32>,
+    #[serde(default, deserialize_with = "csv::invalid_option")]
+    is_expansion: Option<i32>,
+}
+
+/// Parse the BGG ranks CSV, ignoring the per-genre rank columns.
+/// A year or rank of 0 means BGG has none
+pub fn parse_ranks_csv(data: &[u8]) -> Result<Vec<BggRank>> {
+    let mut reader = csv::Reader::from_reader(data);
+
+    let mut ranks = Vec::new();
+    for (line, row) in reader.deserialize::<RankRow>().enumerate() {
+        // Line 1 is the header
+        let row = row.with_context(|| format!("Invalid ranks CSV row on line {}", line + 2))?;
+        ranks.push(BggRank {
+            bgg_id: row.id,
+            name: row.name.trim().to_string(),
+            year_published: row.yearpublished.filter(|&year| year != 0),
+            rank: row.rank.filter(|&rank| rank > 0),
+            is_expansion: row.is_expansion.unwrap_or(0) != 0,
+        });
+    }
+    Ok(ranks)
+}
+
+/// Parse an XML API2 `thing` document into the games it describes
+pub fn parse_things_xml(xml: &str) -> Result<Vec<BggThing>> {
+    let document = roxmltree::Document::parse(xml).context("Invalid BGG XML")?;
+    let items = document.root_element();
+    if !items.has_tag_name("items") {
+        bail!(
+            "Expected an <items> document, found <{}>",
+            items.tag_name().name()
+        );
+    }
+
+    items
+        .children()
+        .filter(|node| node.has_tag_name("item"))
+        .map(parse_thing)
+        .collect()
+}
+
+fn parse_thing(item: roxmltree::Node) -> Result<BggThing> {
+    let bgg_id: i32 = item
+        .attribute("id")
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| anyhow!("BGG item without a numeric id"))?;
+
+    let name = item
+        .children()
+        .find(|node| node.has_tag_name("name") && node.attribute("type") == Some("primary"))
+        .and_then(|node| node.attribute("value"))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow!("BGG item {} has no primary name", bgg_id))?
+        .to_string();
+
+    let value = |tag: &str| {
+        item.children()
+            .find(|node| node.has_tag_name(tag))
+            .and_then(|node| node.attribute("value"))
+    };
+    // BGG reports unknown numbers as 0
+    let positive = |tag: &str| {
+        value(tag)
+            .and_then(|v| v.trim().parse::<i32>().ok())
+            .filter(|&n| n > 0)
+    };
+    let links = |link_type: &str| -> Vec<String> {
+        item.children()
+            .filter(|node| node.has_tag_name("link") && node.attribute("type") == Some(link_type))
+            .filter_map(|node| node.attribute("value"))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect()
+    };
+
+    let weight = item
+        .descendants()
+        .find(|node| node.has_tag_name("averageweight"))
+        .and_then(|node| node.attribute("value"))
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|weight| (1.0..=5.0).contains(weight));
+
+    Ok(BggThing {
+        bgg_id,
+        name,
+        description: item
+            .children()
+            .find(|node| node.has_tag_name("description"))
+            .and_then(|node| node.text())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string),
+        publisher: links("boardgamepublisher").into_iter().next(),
+        year_published: positive("yearpublished"),
+        min_players: positive("minplayers"),
+        max_players: positive("maxplayers"),
+        play_time_minutes: positive("playingtime").or_else(|| positive("maxplaytime")),
+        weight,
+        mechanics: links("boardgamemechanic"),
+        categories: links("boardgamecategory"),
+        is_expansion: item.attribute("type") == Some("boardgameexpansion"),
+    })
+}
+
+/// Fetches `thing` documents from BGG or any server speaking its XML API2
+pub struct BggClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl BggClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn thing_url(&self, bgg_ids: &[i32]) -> String {
+        let ids: Vec<String> = bgg_ids.iter().map(i32::to_string).collect();
+        format!("{}/thing?id={}&stats=1", self.base_url, ids.join(","))
+    }
+
+    /// Fetch the `thing` document for up to `THING_BATCH_SIZE` ids
+    pub async fn fetch_things(&self, bgg_ids: &[i32]) -> Result<String> {
+        let url = self.thing_url(bgg_ids);
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?;
+
+        // BGG answers 202 while it prepares a document; the caller can try again later
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            bail!("BGG queued the request for {}; try again shortly", url);
+        }
+        if !response.status().is_success() {
+            bail!("{} returned {}", url, response.status());
+        }
+        response
+            .text()
+            .await
+            .with_context(|| format!("Failed to read {}", url))
+    }
+}
+
+impl Default for BggClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_BGG_BASE_URL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THING_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<items termsofuse="https://boardgamegeek.com/xmlapi/termsofuse">
+  <item type="boardgame" id="13">
+    <name type="alternate" sortindex="1" value="Die Siedler von Catan" />
+    <name type="primary" sortindex="1" value="CATAN" />
+    <description>Trade, build &amp; settle.&#10;</description>
+    <yearpublished value="1995" />
+    <minplayers value="3" />
+    <maxplayers value="4" />
+    <playingtime value="0" />
+    <maxplaytime value="120" />
+    <link type="boardgamecategory" id="1026" value="Negotiation" />
+    <link type="boardgamemechanic" id="2072" value="Dice Rolling" />
+    <link type="boardgamemechanic" id="2004" value="Set Collection" />
+    <link type="boardgamepublisher" id="37" value="KOSMOS" />
+    <statistics page="1"><ratings><averageweight value="2.2917" /></ratings></statistics>
+  </item>
+  <item type="boardgameexpansion" id="926">
+    <name type="primary" sortindex="1" value="CATAN: Seafarers" />
+    <statistics page="1"><ratings><averageweight value="0" /></ratings></statistics>
+  </item>
+</items>"#;
+
+    #[test]
+    fn test_parse_things_xml() {
+        let things = parse_things_xml(THING_XML).unwrap();
+
+        assert_eq!(
+            things[0],
+            BggThing {
+                bgg_id: 13,
+                name: "CATAN".to_string(),
+                description: Some("Trade, build & settle.".to_string()),
+                publisher: Some("KOSMOS".to_string()),
+                year_published: Some(1995),
+                min_players: Some(3),
+                max_players: Some(4),
+                play_time_minutes: Some(120),
+                weight: Some(2.2917),
+                mechanics: vec!["Dice Rolling".to_string(), "Set Collection".to_string()],
+                categories: vec!["Negotiation".to_string()],
+                is_expansion: false,
+            }
+        );
+        assert!(things[1].is_expansion);
+        assert_eq!(things[1].weight, None);
+        assert!(parse_things_xml("<html></html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_ranks_csv() {
+        let csv = "id,name,yearpublished,rank,bayesaverage,average,usersrated,is_expansion,abstracts_rank\n\
+                   224517,Brass: Birmingham,2018,1,8.4,8.6,48000,0,\n\
+                   926,CATAN: Seafarers,1997,0,6.5,7.0,20000,1,\n";
+        let ranks = parse_ranks_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(
+            ranks,
+            vec![
+                BggRank {
+                    bgg_id: 224517,
+                    name: "Brass: Birmingham".to_string(),
+                    year_published: Some(2018),
+                    rank: Some(1),
+                    is_expansion: false,
+                },
+                BggRank {
+                    bgg_id: 926,
+                    name: "CATAN: Seafarers".to_string(),
+                    year_published: Some(1997),
+                    rank: None,
+                    is_expansion: true,
+                },
+            ]
+        );
+        assert!(parse_ranks_csv(b"id,name\nnot-a-number,Oops\n").is_err());
+    }
+
+    #[test]
+    fn test_thing_url() {
+        let client = BggClient::new("http://localhost:9000/xmlapi2/");
+        assert_eq!(
+            client.thing_url(&[13, 926]),
+            "http://localhost:9000/xmlapi2/thing?id=13,926&stats=1"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::models::{EmbeddingSearchResult, GameId};
+
+/// Most games one comparison session may cover, the primary game included
+pub const MAX_COMPARED_GAMES: usize = 4;
+
+/// Rule chunks retrieved for a comparison question across all games
+pub const COMPARISON_CHUNK_LIMIT: usize = 8;
+
+/// Fewest chunks retrieved per game, so no game is crowded out of the comparison
+const MIN_CHUNKS_PER_GAME: usize = 2;
+
+/// Chunks to retrieve from each game so every game gets an equal share
+pub fn per_game_quota(games: usize) -> usize {
+    if games == 0 {
+        return 0;
+    }
+    COMPARISON_CHUNK_LIMIT
+        .div_ceil(games)
+        .max(MIN_CHUNKS_PER_GAME)
+}
+
+/// Merge per-game results round-robin by rank.
+///
+/// The prompt budget drops chunks from the end of the list, so interleaving
+/// makes every game lose its weakest matches before any game loses its best.
+pub fn interleave(per_game: Vec<Vec<EmbeddingSearchResult>>) -> Vec<EmbeddingSearchResult> {
+    let mut iters: Vec<_> = per_game.into_iter().map(Vec::into_iter).collect();
+    let mut merged = Vec::new();
+
+    loop {
+        let before = merged.len();
+        for iter in iters.iter_mut() {
+            if let Some(result) = iter.next() {
+                merged.push(result);
+            }
+        }
+        if merged.len() == before {
+            return merged;
+        }
+    }
+}
+
+/// Check the games for a new comparison session: no duplicates and not too many
+pub fn validate_compared_games(game_id: GameId, compare_game_ids: &[GameId]) -> Result<(), String> {
+    let mut seen = vec![game_id];
+    for id in compare_game_ids {
+        if seen.contains(id) {
+            return Err(format!("Game {} is listed more than once", id));
+        }
+        seen.push(*id);
+    }
+
+    if seen.len() > MAX_COMPARED_GAMES {
+        return Err(format!(
+            "A session can compare at most {} games",
+            MAX_COMPARED_GAMES
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EmbeddingSourceType;
+
+    fn result(id: i64, game_id: GameId) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id,
+            chunk_text: format!("chunk {}", id),
+            similarity_score: 0.5,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_quota_is_balanced_with_a_floor() {
+        assert_eq!(per_game_quota(1), 8);
+        assert_eq!(per_game_quota(2), 4);
+        assert_eq!(per_game_quota(3), 3);
+        assert_eq!(per_game_quota(4), 2);
+        assert_eq!(per_game_quota(0), 0);
+    }
+
+    #[test]
+    fn test_interleave_alternates_games_by_rank() {
+        let merged = interleave(vec![
+            vec![result(1, 1), result(2, 1), result(3, 1)],
+            vec![result(10, 2)],
+            vec![result(20, 3), result(21, 3)],
+        ]);
+
+        let ids: Vec<i64> = merged.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![1, 10, 20, 2, 21, 3]);
+    }
+
+    #[test]
+    fn test_validate_compared_games() {
+        assert!(validate_compared_games(1, &[2, 3]).is_ok());
+        assert!(validate_compared_games(1, &[2, 1]).is_err());
+        assert!(validate_compared_games(1, &[2, 2]).is_err());
+        assert!(validate_compared_games(1, &[2, 3, 4, 5]).is_err());
+    }
+}
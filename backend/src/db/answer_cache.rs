@@ -0,0 +1,197 @@
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{CachedAnswer, CachedAnswerId, CreateCachedAnswerRequest, GameId};
+
+const CACHED_ANSWER_COLUMNS: &str = "id, game_id, question, answer, context_sources, cited_chunks, confidence, not_covered_by_rules, structured, source_fingerprint, hit_count, created_at, last_hit_at";
+
+fn json_column<T: serde::de::DeserializeOwned>(
+    row: &Row,
+    index: usize,
+    column: &str,
+) -> SqliteResult<T> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|_| {
+        rusqlite::Error::InvalidColumnType(index, column.to_string(), rusqlite::types::Type::Text)
+    })
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> SqliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))
+}
+
+fn cached_answer_from_row(row: &Row) -> SqliteResult<CachedAnswer> {
+    let last_hit_at: Option<String> = row.get(12)?;
+
+    Ok(CachedAnswer {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        question: row.get(2)?,
+        answer: row.get(3)?,
+        context_sources: json_column(row, 4, "context_sources")?,
+        cited_chunks: row
+            .get::<_, Option<String>>(5)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        confidence: row.get(6)?,
+        not_covered_by_rules: row.get::<_, Option<bool>>(7)?.unwrap_or(false),
+        structured: row.get(8)?,
+        source_fingerprint: row.get(9)?,
+        hit_count: row.get(10)?,
+        created_at: parse_datetime(row, "created_at")?,
+        last_hit_at: match last_hit_at {
+            Some(_) => Some(parse_datetime(row, "last_hit_at")?),
+            None => None,
+        },
+    })
+}
+
+fn delete_cached_answers_sync(conn: &Connection, ids: &[CachedAnswerId]) -> SqliteResult<u64> {
+    let mut delete_vec = conn.prepare("DELETE FROM vec_answer_cache WHERE rowid = ?")?;
+    let mut delete_row = conn.prepare("DELETE FROM answer_cache WHERE id = ?")?;
+
+    let mut removed = 0;
+    for id in ids {
+        delete_vec.execute(params![id])?;
+        removed += delete_row.execute(params![id])? as u64;
+    }
+    Ok(removed)
+}
+
+/// Nearest cached questions for a game with their cosine similarity, most similar first
+pub async fn find_similar_answers(
+    db: &Database,
+    game_id: GameId,
+    question_embedding: &[f32],
+    limit: u32,
+) -> SqliteResult<Vec<(CachedAnswer, f32)>> {
+    db.with_connection(|conn| {
+        let query_json = serde_json::to_string(question_embedding)
+            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))?;
+
+        let mut vec_stmt = conn.prepare(
+            r#"
+            SELECT rowid, distance
+            FROM vec_answer_cache
+            WHERE question_vector MATCH ?1 AND game_id = ?2 AND k = ?3
+            ORDER BY distance
+            "#,
+        )?;
+        let neighbors: Vec<(CachedAnswerId, f32)> = vec_stmt
+            .query_map(params![query_json, game_id, limit], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut answer_stmt = conn.prepare(&format!(
+            "SELECT {} FROM answer_cache WHERE id = ?",
+            CACHED_ANSWER_COLUMNS
+        ))?;
+
+        let mut results = Vec::new();
+        for (id, distance) in neighbors {
+            if let Some(answer) = answer_stmt
+                .query_row(params![id], cached_answer_from_row)
+                .optional()?
+            {
+                results.push((answer, 1.0 - distance));
+            }
+        }
+
+        Ok(results)
+    })
+}
+
+pub async fn store_cached_answer(
+    db: &Database,
+    request: CreateCachedAnswerRequest,
+) -> SqliteResult<CachedAnswerId> {
+    db.with_transaction(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let context_sources_json = to_json(&request.context_sources)?;
+        let cited_chunks_json = to_json(&request.cited_chunks)?;
+        let embedding_json = to_json(&request.question_embedding)?;
+
+        conn.execute(
+            r#"
+            INSERT INTO answer_cache (
+                game_id, question, answer, context_sources, cited_chunks, confidence,
+                not_covered_by_rules, structured, source_fingerprint, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                request.game_id,
+                request.question,
+                request.answer,
+                context_sources_json,
+                cited_chunks_json,
+                request.confidence,
+                request.not_covered_by_rules,
+                request.structured,
+                request.source_fingerprint,
+                now_str
+            ],
+        )?;
+
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "INSERT INTO vec_answer_cache (rowid, game_id, question_vector) VALUES (?, ?, ?)",
+            params![id, request.game_id, embedding_json],
+        )?;
+
+        Ok(id)
+    })
+}
+
+pub async fn record_answer_cache_hit(db: &Database, id: CachedAnswerId) -> SqliteResult<()> {
+    db.with_connection(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        conn.execute(
+            "UPDATE answer_cache SET hit_count = hit_count + 1, last_hit_at = ? WHERE id = ?",
+            params![now_str, id],
+        )?;
+        Ok(())
+    })
+}
+
+pub async fn delete_cached_answer(db: &Database, id: CachedAnswerId) -> SqliteResult<bool> {
+    db.with_transaction(|conn| Ok(delete_cached_answers_sync(conn, &[id])? > 0))
+}
+
+/// Drop cached answers and their vectors inside an open transaction
+pub fn invalidate_answer_cache_sync(
+    conn: &Connection,
+    game_id: Option<GameId>,
+) -> SqliteResult<u64> {
+    let mut stmt = conn.prepare("SELECT id FROM answer_cache WHERE ?1 IS NULL OR game_id = ?1")?;
+    let ids: Vec<CachedAnswerId> = stmt
+        .query_map(params![game_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    delete_cached_answers_sync(conn, &ids)
+}
+
+/// Drop every cached answer for a game, or for all games
+pub async fn invalidate_answer_cache(db: &Database, game_id: Option<GameId>) -> SqliteResult<u64> {
+    db.with_transaction(|conn| invalidate_answer_cache_sync(conn, game_id))
+}
+
+/// Number of cached answers and their total hits, for a game or for all games
+pub async fn get_answer_cache_totals(
+    db: &Database,
+    game_id: Option<GameId>,
+) -> SqliteResult<(u64, u64)> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(hit_count), 0)
+            FROM answer_cache
+            WHERE ?1 IS NULL OR game_id = ?1
+            "#,
+            params![game_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    })
+}
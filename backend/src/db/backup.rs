@@ -0,0 +1,783 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params};
+
+use super::{
+    Database,
+    collection::delete_game_collection_sync,
+    faq::delete_generated_faq_sync,
+    format_datetime, parse_datetime,
+    plays::{delete_plays_of_game_sync, player_id_sync, plays_in_order_sync},
+    tags::add_game_tag_sync,
+};
+use crate::models::{
+    ArchivedGame, ArchivedLeague, ChatSessionId, ConflictStrategy, Embedding, EmbeddingId,
+    EmbeddingSourceType, Game, GameId, HouseRuleId, LeagueId, Play, PlayId, RestoreSummary,
+};
+
+fn to_json<T: serde::Serialize>(value: &T) -> SqliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))
+}
+
+/// A game's chunks with their vectors, in document order
+pub async fn list_game_chunks(db: &Database, game_id: GameId) -> SqliteResult<Vec<Embedding>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT e.id, e.game_id, e.chunk_text, vec_to_json(v.embedding_vector), e.chunk_index,
+                   e.source_type, e.source_id, e.metadata, e.created_at
+            FROM embeddings e
+            JOIN vec_embeddings v ON e.id = v.rowid
+            WHERE e.game_id = ?
+            ORDER BY e.source_type ASC, e.chunk_index ASC, e.id ASC
+            "#,
+        )?;
+
+        stmt.query_map(params![game_id], |row| {
+            let embedding_json: String = row.get(3)?;
+            let source_type: String = row.get(5)?;
+
+            Ok(Embedding {
+                id: row.get(0)?,
+                game_id: row.get(1)?,
+                chunk_text: row.get(2)?,
+                embedding: serde_json::from_str(&embedding_json).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        3,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?,
+                chunk_index: row.get(4)?,
+                source_type: EmbeddingSourceType::from_str(&source_type)
+                    .unwrap_or(EmbeddingSourceType::RulesPdf),
+                source_id: row.get(6)?,
+                metadata: row.get(7)?,
+                created_at: parse_datetime(row, "created_at")?,
+            })
+        })?
+        .collect()
+    })
+}
+
+/// Ids of the chat sessions whose primary game is `game_id`, oldest first
+pub async fn list_chat_session_ids(
+    db: &Database,
+    game_id: GameId,
+) -> SqliteResult<Vec<ChatSessionId>> {
+    db.with_connection(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT id FROM chat_sessions WHERE game_id = ? ORDER BY id")?;
+        stmt.query_map(params![game_id], |row| row.get(0))?
+            .collect()
+    })
+}
+
+/// A game's plays, oldest first
+pub async fn list_game_plays(db: &Database, game_id: GameId) -> SqliteResult<Vec<Play>> {
+    db.with_connection(|conn| plays_in_order_sync(conn, &[game_id], None, None))
+}
+
+/// The library game an archived one corresponds to: same BGG id,
+/// or same name when neither has a BGG id
+fn find_existing_game_sync(conn: &Connection, game: &Game) -> SqliteResult<Option<GameId>> {
+    match game.bgg_id {
+        Some(bgg_id) => conn
+            .query_row(
+                "SELECT id FROM games WHERE bgg_id = ?",
+                params![bgg_id],
+                |row| row.get(0),
+            )
+            .optional(),
+        None => conn
+            .query_row(
+                r#"
+                SELECT id FROM games
+                WHERE bgg_id IS NULL AND name = ? COLLATE NOCASE
+                ORDER BY id LIMIT 1
+                "#,
+                params![game.name],
+                |row| row.get(0),
+            )
+            .optional(),
+    }
+}
+
+fn insert_game_sync(conn: &Connection, game: &Game) -> SqliteResult<GameId> {
+    conn.execute(
+        r#"
+        INSERT INTO games (
+            name, description, publisher, year_published,
+            min_players, max_players, play_time_minutes, complexity_rating,
+            bgg_id, rules_text, created_at, updated_at, last_played_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        params![
+            game.name,
+            game.description,
+            game.publisher,
+            game.year_published,
+            game.min_players,
+            game.max_players,
+            game.play_time_minutes,
+            game.complexity_rating,
+            game.bgg_id,
+            game.rules_text,
+            format_datetime(game.created_at),
+            format_datetime(game.updated_at),
+            game.last_played_at.map(format_datetime)
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replace a game's details and drop everything attached to it
+fn overwrite_game_sync(conn: &Connection, game_id: GameId, game: &Game) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM vec_embeddings WHERE rowid IN (SELECT id FROM embeddings WHERE game_id = ?)",
+        params![game_id],
+    )?;
+    conn.execute("DELETE FROM embeddings WHERE game_id = ?", params![game_id])?;
+    // Generated FAQ answers cite the chunks just dropped
+    delete_generated_faq_sync(conn, game_id)?;
+    conn.execute("DELETE FROM game_tags WHERE game_id = ?", params![game_id])?;
+    delete_plays_of_game_sync(conn, game_id)?;
+    delete_game_collection_sync(conn, game_id)?;
+    conn.execute(
+        "DELETE FROM house_rules WHERE game_id = ?",
+        params![game_id],
+    )?;
+    conn.execute(
+        r#"
+        DELETE FROM chat_messages
+        WHERE session_id IN (SELECT id FROM chat_sessions WHERE game_id = ?)
+        "#,
+        params![game_id],
+    )?;
+    conn.execute(
+        r#"
+        DELETE FROM chat_session_games
+        WHERE session_id IN (SELECT id FROM chat_sessions WHERE game_id = ?)
+        "#,
+        params![game_id],
+    )?;
+    conn.execute(
+        r#"
+        DELETE FROM chat_session_expansions
+        WHERE session_id IN (SELECT id FROM chat_sessions WHERE game_id = ?)
+        "#,
+        params![game_id],
+    )?;
+    conn.execute(
+        "DELETE FROM chat_sessions WHERE game_id = ?",
+        params![game_id],
+    )?;
+
+    conn.execute(
+        r#"
+        UPDATE games SET
+            name = ?, description = ?, publisher = ?, year_published = ?,
+            min_players = ?, max_players = ?, play_time_minutes = ?, complexity_rating = ?,
+            rules_pdf_path = NULL, rules_text = ?, last_played_at = ?, base_game_id = NULL
+        WHERE id = ?
+        "#,
+        params![
+            game.name,
+            game.description,
+            game.publisher,
+            game.year_published,
+            game.min_players,
+            game.max_players,
+            game.play_time_minutes,
+            game.complexity_rating,
+            game.rules_text,
+            game.last_played_at.map(format_datetime),
+            game_id
+        ],
+    )?;
+    Ok(())
+}
+
+/// Fill in the details a game is missing. Returns whether it should take the
+/// archived rulebook, which it does only if it has none of its own
+fn merge_game_sync(conn: &Connection, game_id: GameId, game: &Game) -> SqliteResult<bool> {
+    let has_rules: bool = conn.query_row(
+        r#"
+        SELECT rules_text IS NOT NULL
+            OR EXISTS(SELECT 1 FROM embeddings WHERE game_id = games.id AND source_type = 'rules_pdf')
+        FROM games WHERE id = ?
+        "#,
+        params![game_id],
+        |row| row.get(0),
+    )?;
+
+    conn.execute(
+        r#"
+        UPDATE games SET
+            description = COALESCE(description, ?),
+            publisher = COALESCE(publisher, ?),
+            year_published = COALESCE(year_published, ?),
+            min_players = COALESCE(min_players, ?),
+            max_players = COALESCE(max_players, ?),
+            play_time_minutes = COALESCE(play_time_minutes, ?),
+            complexity_rating = COALESCE(complexity_rating, ?),
+            rules_text = COALESCE(rules_text, ?),
+            last_played_at = MAX(COALESCE(last_played_at, ?), COALESCE(?, last_played_at))
+        WHERE id = ?
+        "#,
+        params![
+            game.description,
+            game.publisher,
+            game.year_published,
+            game.min_players,
+            game.max_players,
+            game.play_time_minutes,
+            game.complexity_rating,
+            game.rules_text,
+            game.last_played_at.map(format_datetime),
+            game.last_played_at.map(format_datetime),
+            game_id
+        ],
+    )?;
+    Ok(!has_rules)
+}
+
+/// Links between restored rows and archived ids, made once every game and chat session
+/// has its library id
+#[derive(Default)]
+struct GameLinks {
+    /// Restored games and the archived id of their base game
+    base_games: Vec<(GameId, GameId)>,
+    /// Restored comparison sessions and the archived ids of the games they compare
+    compared_games: Vec<(ChatSessionId, Vec<GameId>)>,
+    /// Restored sessions and the archived ids of their expansions in play
+    expansions: Vec<(ChatSessionId, Vec<GameId>)>,
+    /// Library ids of the archived chat sessions restored or already in the library
+    session_ids: HashMap<ChatSessionId, ChatSessionId>,
+    /// Restored plays and the archived ids of the expansions they used
+    play_expansions: Vec<(PlayId, Vec<GameId>)>,
+    /// Restored plays and the archived ids of the chat sessions they consulted
+    play_sessions: Vec<(PlayId, Vec<ChatSessionId>)>,
+}
+
+/// Restore the archived tags, house rules, chunks, chat sessions, plays, collection details
+/// and loans onto a library game.
+/// When merging, house rules whose title the game already uses, and chat sessions
+/// and plays it already has, are left out
+fn restore_game_contents_sync(
+    conn: &Connection,
+    game_id: GameId,
+    archived: &ArchivedGame,
+    take_rules: bool,
+    merging: bool,
+    links: &mut GameLinks,
+) -> SqliteResult<()> {
+    for tag in &archived.tags {
+        add_game_tag_sync(conn, game_id, tag.tag_type, &tag.name)?;
+    }
+
+    let mut house_rule_ids: HashMap<HouseRuleId, HouseRuleId> = HashMap::new();
+    for rule in &archived.house_rules {
+        if merging {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM house_rules WHERE game_id = ? AND title = ? COLLATE NOCASE)",
+                params![game_id, rule.title],
+                |row| row.get(0),
+            )?;
+            if exists {
+                continue;
+            }
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO house_rules (
+                game_id, title, description, category, is_active, analysis, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                game_id,
+                rule.title,
+                rule.description,
+                rule.category,
+                rule.is_active,
+                rule.analysis.as_ref().map(to_json).transpose()?,
+                format_datetime(rule.created_at),
+                format_datetime(rule.updated_at)
+            ],
+        )?;
+        house_rule_ids.insert(rule.id, conn.last_insert_rowid());
+    }
+
+    let mut chunk_ids: HashMap<EmbeddingId, EmbeddingId> = HashMap::new();
+    let mut insert_chunk = conn.prepare(
+        r#"
+        INSERT INTO embeddings (
+            game_id, chunk_text, chunk_index, source_type, source_id, metadata, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )?;
+    let mut insert_vector =
+        conn.prepare("INSERT INTO vec_embeddings (rowid, embedding_vector) VALUES (?, ?)")?;
+    for chunk in &archived.chunks {
+        let source_id = match chunk.source_type {
+            EmbeddingSourceType::RulesPdf if take_rules => None,
+            EmbeddingSourceType::RulesPdf => continue,
+            EmbeddingSourceType::HouseRule => {
+                match chunk.source_id.and_then(|id| house_rule_ids.get(&id)) {
+                    Some(&house_rule_id) => Some(house_rule_id),
+                    None => continue,
+                }
+            }
+        };
+
+        insert_chunk.execute(params![
+            game_id,
+            chunk.chunk_text,
+            chunk.chunk_index,
+            chunk.source_type.as_str(),
+            source_id,
+            chunk.metadata,
+            format_datetime(chunk.created_at)
+        ])?;
+        let chunk_id = conn.last_insert_rowid();
+        insert_vector.execute(params![chunk_id, to_json(&chunk.embedding)?])?;
+        chunk_ids.insert(chunk.id, chunk_id);
+    }
+
+    // Citations to chunks that were not restored are dropped
+    let remap_chunks = |ids: &Option<Vec<EmbeddingId>>| -> SqliteResult<Option<String>> {
+        ids.as_ref()
+            .map(|ids| {
+                let ids: Vec<EmbeddingId> = ids
+                    .iter()
+                    .filter_map(|id| chunk_ids.get(id).copied())
+                    .collect();
+                to_json(&ids)
+            })
+            .transpose()
+    };
+
+    for history in &archived.chat_sessions {
+        let session = &history.session;
+        if merging {
+            let existing: Option<ChatSessionId> = conn
+                .query_row(
+                    r#"
+                    SELECT id FROM chat_sessions
+                    WHERE game_id = ? AND title IS ? AND created_at = ?
+                    ORDER BY id LIMIT 1
+                    "#,
+                    params![game_id, session.title, format_datetime(session.created_at)],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(session_id) = existing {
+                links.session_ids.insert(session.id, session_id);
+                continue;
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO chat_sessions (game_id, title, created_at, updated_at) VALUES (?, ?, ?, ?)",
+            params![
+                game_id,
+                session.title,
+                format_datetime(session.created_at),
+                format_datetime(session.updated_at)
+            ],
+        )?;
+        let session_id = conn.last_insert_rowid();
+        links.session_ids.insert(session.id, session_id);
+
+        for message in &history.messages {
+            conn.execute(
+                r#"
+                INSERT INTO chat_messages (
+                    session_id, role, content, context_chunks, tool_trace,
+                    cited_chunks, confidence, not_covered_by_rules, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    session_id,
+                    message.role.as_str(),
+                    message.content,
+                    remap_chunks(&message.context_chunks)?,
+                    message.tool_trace.as_ref().map(to_json).transpose()?,
+                    remap_chunks(&message.cited_chunks)?,
+                    message.confidence,
+                    message.not_covered_by_rules,
+                    format_datetime(message.created_at)
+                ],
+            )?;
+        }
+
+        // Adding messages bumps the session's updated_at; put the archived one back
+        conn.execute(
+            "UPDATE chat_sessions SET updated_at = ? WHERE id = ?",
+            params![format_datetime(session.updated_at), session_id],
+        )?;
+
+        if !session.compare_game_ids.is_empty() {
+            links
+                .compared_games
+                .push((session_id, session.compare_game_ids.clone()));
+        }
+        if !session.expansion_ids.is_empty() {
+            links
+                .expansions
+                .push((session_id, session.expansion_ids.clone()));
+        }
+    }
+
+    for play in &archived.plays {
+        let played_at = format_datetime(play.played_at);
+        if merging {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM plays WHERE game_id = ? AND played_at = ?)",
+                params![game_id, played_at],
+                |row| row.get(0),
+            )?;
+            if exists {
+                continue;
+            }
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO plays (
+                game_id, played_at, duration_minutes, location, notes, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                game_id,
+                played_at,
+                play.duration_minutes,
+                play.location,
+                play.notes,
+                format_datetime(play.created_at),
+                format_datetime(play.updated_at)
+            ],
+        )?;
+        let play_id = conn.last_insert_rowid();
+
+        // Players are matched by name, so they join the library's own players
+        for (position, player) in play.players.iter().enumerate() {
+            conn.execute(
+                r#"
+                INSERT OR IGNORE INTO play_players (
+                    play_id, player_id, position, score, is_winner, team
+                ) VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    play_id,
+                    player_id_sync(conn, &player.name)?,
+                    position as i64,
+                    player.score,
+                    player.winner,
+                    player.team
+                ],
+            )?;
+        }
+
+        if !play.expansion_ids.is_empty() {
+            links
+                .play_expansions
+                .push((play_id, play.expansion_ids.clone()));
+        }
+        if !play.chat_session_ids.is_empty() {
+            links
+                .play_sessions
+                .push((play_id, play.chat_session_ids.clone()));
+        }
+    }
+
+    // When merging, collection details the game already has are kept
+    if let Some(collection) = &archived.collection {
+        conn.execute(
+            &format!(
+                r#"
+                INSERT OR {} INTO game_collection (
+                    game_id, status, wishlist_priority, acquired_at, acquisition_price,
+                    shelf_location, rating, notes, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, COALESCE(?, CURRENT_TIMESTAMP))
+                "#,
+                if merging { "IGNORE" } else { "REPLACE" }
+            ),
+            params![
+                game_id,
+                collection.status.as_str(),
+                collection.wishlist_priority,
+                collection.acquired_at.map(format_datetime),
+                collection.acquisition_price,
+                collection.shelf_location,
+                collection.rating,
+                collection.notes,
+                collection.updated_at.map(format_datetime)
+            ],
+        )?;
+    }
+
+    for loan in &archived.loans {
+        let loaned_at = format_datetime(loan.loaned_at);
+        if merging {
+            let exists: bool = conn.query_row(
+                r#"
+                SELECT EXISTS(
+                    SELECT 1 FROM game_loans
+                    WHERE game_id = ? AND borrower = ? COLLATE NOCASE AND loaned_at = ?
+                )
+                "#,
+                params![game_id, loan.borrower, loaned_at],
+                |row| row.get(0),
+            )?;
+            if exists {
+                continue;
+            }
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO game_loans (game_id, borrower, loaned_at, due_at, returned_at, notes)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                game_id,
+                loan.borrower,
+                loaned_at,
+                loan.due_at.map(format_datetime),
+                loan.returned_at.map(format_datetime),
+                loan.notes
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Restore an archived league, matched with a library league by name. Members join
+/// by name, and its games are the library games the archived ones were restored to.
+/// When merging, a league that already counts every game keeps doing so
+fn restore_league_sync(
+    conn: &Connection,
+    archived: &ArchivedLeague,
+    game_ids: &HashMap<GameId, GameId>,
+    conflict: ConflictStrategy,
+) -> SqliteResult<()> {
+    let league = &archived.league;
+    let existing: Option<LeagueId> = conn
+        .query_row(
+            "SELECT id FROM leagues WHERE name = ?",
+            params![league.name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let starts_at = league.starts_at.map(format_datetime);
+    let ends_at = league.ends_at.map(format_datetime);
+
+    let (league_id, take_games) = match (existing, conflict) {
+        (None, _) => {
+            conn.execute(
+                r#"
+                INSERT INTO leagues (name, description, starts_at, ends_at, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+                params![
+                    league.name,
+                    league.description,
+                    starts_at,
+                    ends_at,
+                    format_datetime(league.created_at),
+                    format_datetime(league.updated_at)
+                ],
+            )?;
+            (conn.last_insert_rowid(), true)
+        }
+        (Some(_), ConflictStrategy::Skip) => return Ok(()),
+        (Some(league_id), ConflictStrategy::Overwrite) => {
+            conn.execute(
+                "DELETE FROM league_players WHERE league_id = ?",
+                params![league_id],
+            )?;
+            conn.execute(
+                "DELETE FROM league_games WHERE league_id = ?",
+                params![league_id],
+            )?;
+            conn.execute(
+                r#"
+                UPDATE leagues SET description = ?, starts_at = ?, ends_at = ?, updated_at = ?
+                WHERE id = ?
+                "#,
+                params![
+                    league.description,
+                    starts_at,
+                    ends_at,
+                    format_datetime(league.updated_at),
+                    league_id
+                ],
+            )?;
+            (league_id, true)
+        }
+        (Some(league_id), ConflictStrategy::Merge) => {
+            conn.execute(
+                r#"
+                UPDATE leagues SET
+                    description = COALESCE(description, ?),
+                    starts_at = COALESCE(starts_at, ?),
+                    ends_at = COALESCE(ends_at, ?)
+                WHERE id = ?
+                "#,
+                params![league.description, starts_at, ends_at, league_id],
+            )?;
+            let has_games: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM league_games WHERE league_id = ?)",
+                params![league_id],
+                |row| row.get(0),
+            )?;
+            (league_id, has_games)
+        }
+    };
+
+    for name in &archived.player_names {
+        conn.execute(
+            "INSERT OR IGNORE INTO league_players (league_id, player_id) VALUES (?, ?)",
+            params![league_id, player_id_sync(conn, name)?],
+        )?;
+    }
+    if take_games {
+        for game_id in league.game_ids.iter().filter_map(|id| game_ids.get(id)) {
+            conn.execute(
+                "INSERT OR IGNORE INTO league_games (league_id, game_id) VALUES (?, ?)",
+                params![league_id, game_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Restore archived games and leagues in one transaction, resolving conflicts with
+/// the library's as asked. Also returns the archive positions of the games
+/// whose rulebook document was taken and still has to be written to disk
+pub async fn restore_library(
+    db: &Database,
+    games: &[ArchivedGame],
+    leagues: &[ArchivedLeague],
+    conflict: ConflictStrategy,
+) -> SqliteResult<(RestoreSummary, Vec<usize>)> {
+    db.with_transaction(|conn| {
+        let mut summary = RestoreSummary::default();
+        let mut documents = Vec::new();
+        let mut game_ids: HashMap<GameId, GameId> = HashMap::new();
+        let mut links = GameLinks::default();
+
+        for (position, archived) in games.iter().enumerate() {
+            let existing = find_existing_game_sync(conn, &archived.game)?;
+            let (game_id, take_rules) = match (existing, conflict) {
+                (None, _) => {
+                    summary.created += 1;
+                    (insert_game_sync(conn, &archived.game)?, true)
+                }
+                (Some(game_id), ConflictStrategy::Skip) => {
+                    summary.skipped += 1;
+                    game_ids.insert(archived.game.id, game_id);
+                    summary.game_ids.push(game_id);
+                    continue;
+                }
+                (Some(game_id), ConflictStrategy::Overwrite) => {
+                    summary.overwritten += 1;
+                    overwrite_game_sync(conn, game_id, &archived.game)?;
+                    (game_id, true)
+                }
+                (Some(game_id), ConflictStrategy::Merge) => {
+                    summary.merged += 1;
+                    (game_id, merge_game_sync(conn, game_id, &archived.game)?)
+                }
+            };
+
+            restore_game_contents_sync(
+                conn,
+                game_id,
+                archived,
+                take_rules,
+                existing.is_some() && conflict == ConflictStrategy::Merge,
+                &mut links,
+            )?;
+            if let Some(base_game_id) = archived.game.base_game_id {
+                links.base_games.push((game_id, base_game_id));
+            }
+            if take_rules && archived.rules_document.is_some() {
+                documents.push(position);
+            }
+            game_ids.insert(archived.game.id, game_id);
+            summary.game_ids.push(game_id);
+        }
+
+        // Games and sessions can point at games later in the archive, so link them last.
+        // Merged games keep a base game they already have
+        for (game_id, base_game_id) in links.base_games {
+            if let Some(base_game_id) = game_ids.get(&base_game_id) {
+                conn.execute(
+                    "UPDATE games SET base_game_id = COALESCE(base_game_id, ?) WHERE id = ?",
+                    params![base_game_id, game_id],
+                )?;
+            }
+        }
+        for (session_id, compare_game_ids) in links.compared_games {
+            let compare_game_ids = compare_game_ids
+                .iter()
+                .filter_map(|id| game_ids.get(id));
+            for (position, game_id) in compare_game_ids.enumerate() {
+                conn.execute(
+                    "INSERT OR IGNORE INTO chat_session_games (session_id, game_id, position) VALUES (?, ?, ?)",
+                    params![session_id, game_id, position as i64],
+                )?;
+            }
+        }
+        for (session_id, expansion_ids) in links.expansions {
+            let expansion_ids = expansion_ids.iter().filter_map(|id| game_ids.get(id));
+            for (position, game_id) in expansion_ids.enumerate() {
+                conn.execute(
+                    "INSERT OR IGNORE INTO chat_session_expansions (session_id, game_id, position) VALUES (?, ?, ?)",
+                    params![session_id, game_id, position as i64],
+                )?;
+            }
+        }
+
+        for (play_id, expansion_ids) in links.play_expansions {
+            let expansion_ids = expansion_ids.iter().filter_map(|id| game_ids.get(id));
+            for (position, game_id) in expansion_ids.enumerate() {
+                conn.execute(
+                    "INSERT OR IGNORE INTO play_expansions (play_id, game_id, position) VALUES (?, ?, ?)",
+                    params![play_id, game_id, position as i64],
+                )?;
+            }
+        }
+        // Sessions of skipped games were not restored, so links to them are dropped
+        for (play_id, session_ids) in links.play_sessions {
+            for session_id in session_ids.iter().filter_map(|id| links.session_ids.get(id)) {
+                conn.execute(
+                    "INSERT OR IGNORE INTO play_chat_sessions (play_id, session_id) VALUES (?, ?)",
+                    params![play_id, session_id],
+                )?;
+            }
+        }
+
+        for archived in leagues {
+            restore_league_sync(conn, archived, &game_ids, conflict)?;
+        }
+
+        Ok((summary, documents))
+    })
+}
+
+/// Point a restored game at its rulebook file
+pub async fn set_rules_pdf_path(db: &Database, game_id: GameId, path: &str) -> SqliteResult<()> {
+    db.with_connection(|conn| {
+        conn.execute(
+            "UPDATE games SET rules_pdf_path = ? WHERE id = ?",
+            params![path, game_id],
+        )?;
+        Ok(())
+    })
+}
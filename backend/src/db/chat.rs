@@ -2,17 +2,34 @@ use rusqlite::{params, Result as SqliteResult};
 use chrono::Utc;
 use crate::models::{
     ChatSession, ChatSessionId, ChatMessage, GameId, 
-    CreateChatSessionRequest, ChatHistory, ChatSessionSummary, PaginatedResponse
+    CreateChatSessionRequest, ChatHistory, ChatSessionSummary, PaginatedResponse, ToolInvocation,
+    CreateChatMessageRequest
 };
 use super::{Database, parse_datetime, PaginationInfo};
 
+// Session columns, with the compared games and expansions as JSON arrays in list order
+const SESSION_SELECT: &str = r#"
+    SELECT
+        cs.id, cs.game_id, cs.title, cs.created_at, cs.updated_at,
+        (SELECT json_group_array(csg.game_id ORDER BY csg.position)
+         FROM chat_session_games csg WHERE csg.session_id = cs.id) AS compare_game_ids,
+        (SELECT json_group_array(cse.game_id ORDER BY cse.position)
+         FROM chat_session_expansions cse WHERE cse.session_id = cs.id) AS expansion_ids
+    FROM chat_sessions cs
+    WHERE cs.id = ?
+"#;
+
 pub async fn list_chat_sessions(db: &Database, game_id: GameId, page: u32, limit: u32) -> SqliteResult<PaginatedResponse<ChatSessionSummary>> {
     let pagination = PaginationInfo::new(page, limit);
     
     db.with_connection(|conn| {
-        // Get total count for the specific game
+        // Get total count for the specific game, including comparisons it takes part in
         let total: u32 = conn.query_row(
-            "SELECT COUNT(*) FROM chat_sessions WHERE game_id = ?",
+            r#"
+            SELECT COUNT(*) FROM chat_sessions
+            WHERE game_id = ?1
+               OR id IN (SELECT session_id FROM chat_session_games WHERE game_id = ?1)
+            "#,
             params![game_id],
             |row| row.get(0)
         )?;
@@ -23,13 +40,18 @@ pub async fn list_chat_sessions(db: &Database, game_id: GameId, page: u32, limit
             SELECT 
                 cs.id, cs.game_id, cs.title, cs.created_at,
                 COUNT(cm.id) as message_count,
-                MAX(cm.created_at) as last_message_at
+                MAX(cm.created_at) as last_message_at,
+                (SELECT json_group_array(csg.game_id ORDER BY csg.position)
+                 FROM chat_session_games csg WHERE csg.session_id = cs.id) AS compare_game_ids,
+                (SELECT json_group_array(cse.game_id ORDER BY cse.position)
+                 FROM chat_session_expansions cse WHERE cse.session_id = cs.id) AS expansion_ids
             FROM chat_sessions cs
             LEFT JOIN chat_messages cm ON cs.id = cm.session_id
-            WHERE cs.game_id = ?
+            WHERE cs.game_id = ?1
+               OR cs.id IN (SELECT session_id FROM chat_session_games WHERE game_id = ?1)
             GROUP BY cs.id, cs.game_id, cs.title, cs.created_at
             ORDER BY COALESCE(MAX(cm.created_at), cs.created_at) DESC
-            LIMIT ? OFFSET ?
+            LIMIT ?2 OFFSET ?3
             "#
         )?;
 
@@ -49,6 +71,8 @@ pub async fn list_chat_sessions(db: &Database, game_id: GameId, page: u32, limit
             Ok(ChatSessionSummary {
                 id: row.get(0)?,
                 game_id: row.get(1)?,
+                compare_game_ids: game_ids_from_json(row.get(6)?),
+                expansion_ids: game_ids_from_json(row.get(7)?),
                 title: row.get(2)?,
                 message_count,
                 last_message_at,
@@ -66,19 +90,9 @@ pub async fn list_chat_sessions(db: &Database, game_id: GameId, page: u32, limit
 pub async fn get_chat_history(db: &Database, session_id: ChatSessionId) -> SqliteResult<Option<ChatHistory>> {
     db.with_connection(|conn| {
         // First get the session
-        let mut session_stmt = conn.prepare(
-            "SELECT id, game_id, title, created_at, updated_at FROM chat_sessions WHERE id = ?"
-        )?;
+        let mut session_stmt = conn.prepare(SESSION_SELECT)?;
 
-        let session_result = session_stmt.query_row(params![session_id], |row| {
-            Ok(ChatSession {
-                id: row.get(0)?,
-                game_id: row.get(1)?,
-                title: row.get(2)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        });
+        let session_result = session_stmt.query_row(params![session_id], session_from_row);
 
         let session = match session_result {
             Ok(session) => session,
@@ -89,32 +103,14 @@ pub async fn get_chat_history(db: &Database, session_id: ChatSessionId) -> Sqlit
         // Get messages for the session
         let mut messages_stmt = conn.prepare(
             r#"
-            SELECT id, session_id, role, content, context_chunks, created_at
+            SELECT id, session_id, role, content, context_chunks, tool_trace, cited_chunks, confidence, not_covered_by_rules, created_at
             FROM chat_messages 
             WHERE session_id = ?
             ORDER BY created_at ASC
             "#
         )?;
 
-        let message_iter = messages_stmt.query_map(params![session_id], |row| {
-            let role_str: String = row.get(2)?;
-            let role = crate::models::MessageRole::from_str(&role_str)
-                .unwrap_or(crate::models::MessageRole::User);
-            
-            let context_chunks: Option<String> = row.get(4)?;
-            let context_chunks = context_chunks.and_then(|s| {
-                serde_json::from_str::<Vec<i64>>(&s).ok()
-            });
-
-            Ok(ChatMessage {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                role,
-                content: row.get(3)?,
-                context_chunks,
-                created_at: parse_datetime(row, "created_at")?,
-            })
-        })?;
+        let message_iter = messages_stmt.query_map(params![session_id], message_from_row)?;
 
         let messages: Result<Vec<ChatMessage>, _> = message_iter.collect();
         let messages = messages?;
@@ -128,18 +124,20 @@ pub async fn create_chat_session(db: &Database, request: CreateChatSessionReques
         let now = Utc::now();
         let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
 
-        // First verify the game exists
-        let game_exists: bool = conn.query_row(
-            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
-            params![request.game_id],
-            |row| row.get(0)
-        )?;
-
-        if !game_exists {
-            return Err(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
-                Some("Game does not exist".to_string())
-            ));
+        // First verify the game and any compared games exist
+        for game_id in std::iter::once(&request.game_id).chain(&request.compare_game_ids) {
+            let game_exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+                params![game_id],
+                |row| row.get(0)
+            )?;
+
+            if !game_exists {
+                return Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some("Game does not exist".to_string())
+                ));
+            }
         }
 
         conn.execute(
@@ -152,72 +150,87 @@ pub async fn create_chat_session(db: &Database, request: CreateChatSessionReques
 
         let session_id = conn.last_insert_rowid();
 
+        for (position, game_id) in request.compare_game_ids.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO chat_session_games (session_id, game_id, position) VALUES (?, ?, ?)",
+                params![session_id, game_id, position as i64]
+            )?;
+        }
+
+        // Only expansions of the session's game can be in play
+        for (position, game_id) in request.expansion_ids.iter().enumerate() {
+            let is_expansion: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM games WHERE id = ? AND base_game_id = ?)",
+                params![game_id, request.game_id],
+                |row| row.get(0)
+            )?;
+
+            if !is_expansion {
+                return Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some(format!("Game {} is not an expansion of game {}", game_id, request.game_id))
+                ));
+            }
+
+            conn.execute(
+                "INSERT INTO chat_session_expansions (session_id, game_id, position) VALUES (?, ?, ?)",
+                params![session_id, game_id, position as i64]
+            )?;
+        }
+
         // Fetch the created session
-        let mut stmt = conn.prepare(
-            "SELECT id, game_id, title, created_at, updated_at FROM chat_sessions WHERE id = ?"
-        )?;
+        let mut stmt = conn.prepare(SESSION_SELECT)?;
 
-        stmt.query_row(params![session_id], |row| {
-            Ok(ChatSession {
-                id: row.get(0)?,
-                game_id: row.get(1)?,
-                title: row.get(2)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        })
+        stmt.query_row(params![session_id], session_from_row)
     })
 }
 
 pub async fn add_message_to_session(
-    db: &Database, 
-    session_id: ChatSessionId, 
-    role: crate::models::MessageRole, 
-    content: String,
-    context_chunks: Option<Vec<i64>>
+    db: &Database,
+    request: CreateChatMessageRequest
 ) -> SqliteResult<ChatMessage> {
     db.with_transaction(|conn| {
         let now = Utc::now();
         let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
 
-        let context_chunks_json = context_chunks.map(|chunks| {
+        let context_chunks_json = request.context_chunks.map(|chunks| {
+            serde_json::to_string(&chunks).unwrap_or_else(|_| "[]".to_string())
+        });
+        let tool_trace_json = request.tool_trace.map(|trace| {
+            serde_json::to_string(&trace).unwrap_or_else(|_| "[]".to_string())
+        });
+        let cited_chunks_json = request.cited_chunks.map(|chunks| {
             serde_json::to_string(&chunks).unwrap_or_else(|_| "[]".to_string())
         });
 
         conn.execute(
             r#"
-            INSERT INTO chat_messages (session_id, role, content, context_chunks, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO chat_messages (
+                session_id, role, content, context_chunks, tool_trace,
+                cited_chunks, confidence, not_covered_by_rules, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
-            params![session_id, role.as_str(), content, context_chunks_json, now_str]
+            params![
+                request.session_id,
+                request.role.as_str(),
+                request.content,
+                context_chunks_json,
+                tool_trace_json,
+                cited_chunks_json,
+                request.confidence,
+                request.not_covered_by_rules,
+                now_str
+            ]
         )?;
 
         let message_id = conn.last_insert_rowid();
 
         // Fetch the created message
         let mut stmt = conn.prepare(
-            "SELECT id, session_id, role, content, context_chunks, created_at FROM chat_messages WHERE id = ?"
+            "SELECT id, session_id, role, content, context_chunks, tool_trace, cited_chunks, confidence, not_covered_by_rules, created_at FROM chat_messages WHERE id = ?"
         )?;
 
-        stmt.query_row(params![message_id], |row| {
-            let role_str: String = row.get(2)?;
-            let role = crate::models::MessageRole::from_str(&role_str)
-                .unwrap_or(crate::models::MessageRole::User);
-            
-            let context_chunks: Option<String> = row.get(4)?;
-            let context_chunks = context_chunks.and_then(|s| {
-                serde_json::from_str::<Vec<i64>>(&s).ok()
-            });
-
-            Ok(ChatMessage {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                role,
-                content: row.get(3)?,
-                context_chunks,
-                created_at: parse_datetime(row, "created_at")?,
-            })
-        })
+        stmt.query_row(params![message_id], message_from_row)
     })
 }
 
@@ -236,7 +249,7 @@ pub async fn get_session_messages(db: &Database, session_id: ChatSessionId, limi
         let query = if let Some(limit) = limit {
             format!(
                 r#"
-                SELECT id, session_id, role, content, context_chunks, created_at
+                SELECT id, session_id, role, content, context_chunks, tool_trace, cited_chunks, confidence, not_covered_by_rules, created_at
                 FROM chat_messages 
                 WHERE session_id = ?
                 ORDER BY created_at DESC
@@ -246,7 +259,7 @@ pub async fn get_session_messages(db: &Database, session_id: ChatSessionId, limi
             )
         } else {
             r#"
-            SELECT id, session_id, role, content, context_chunks, created_at
+            SELECT id, session_id, role, content, context_chunks, tool_trace, cited_chunks, confidence, not_covered_by_rules, created_at
             FROM chat_messages 
             WHERE session_id = ?
             ORDER BY created_at ASC
@@ -255,27 +268,61 @@ pub async fn get_session_messages(db: &Database, session_id: ChatSessionId, limi
 
         let mut stmt = conn.prepare(&query)?;
 
-        let message_iter = stmt.query_map(params![session_id], |row| {
-            let role_str: String = row.get(2)?;
-            let role = crate::models::MessageRole::from_str(&role_str)
-                .unwrap_or(crate::models::MessageRole::User);
-            
-            let context_chunks: Option<String> = row.get(4)?;
-            let context_chunks = context_chunks.and_then(|s| {
-                serde_json::from_str::<Vec<i64>>(&s).ok()
-            });
-
-            Ok(ChatMessage {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                role,
-                content: row.get(3)?,
-                context_chunks,
-                created_at: parse_datetime(row, "created_at")?,
-            })
-        })?;
+        let message_iter = stmt.query_map(params![session_id], message_from_row)?;
 
         let messages: Result<Vec<ChatMessage>, _> = message_iter.collect();
         messages
     })
-}
\ No newline at end of file
+}
+
+// Helper function to map a chat_sessions row selected with SESSION_SELECT
+fn session_from_row(row: &rusqlite::Row) -> SqliteResult<ChatSession> {
+    Ok(ChatSession {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        compare_game_ids: game_ids_from_json(row.get(5)?),
+        expansion_ids: game_ids_from_json(row.get(6)?),
+        title: row.get(2)?,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+    })
+}
+
+fn game_ids_from_json(json: Option<String>) -> Vec<GameId> {
+    json.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+// Helper function to map a chat_messages row selected with the standard column list
+fn message_from_row(row: &rusqlite::Row) -> SqliteResult<ChatMessage> {
+    let role_str: String = row.get(2)?;
+    let role = crate::models::MessageRole::from_str(&role_str)
+        .unwrap_or(crate::models::MessageRole::User);
+
+    let context_chunks: Option<String> = row.get(4)?;
+    let context_chunks = context_chunks.and_then(|s| {
+        serde_json::from_str::<Vec<i64>>(&s).ok()
+    });
+
+    let tool_trace: Option<String> = row.get(5)?;
+    let tool_trace = tool_trace.and_then(|s| {
+        serde_json::from_str::<Vec<ToolInvocation>>(&s).ok()
+    });
+
+    let cited_chunks: Option<String> = row.get(6)?;
+    let cited_chunks = cited_chunks.and_then(|s| {
+        serde_json::from_str::<Vec<i64>>(&s).ok()
+    });
+
+    Ok(ChatMessage {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        role,
+        content: row.get(3)?,
+        context_chunks,
+        tool_trace,
+        cited_chunks,
+        confidence: row.get(7)?,
+        not_covered_by_rules: row.get(8)?,
+        created_at: parse_datetime(row, "created_at")?,
+    })
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, format_datetime, parse_datetime};
+use crate::models::{
+    CollectionStatus, CreateLoanRequest, GameCollection, GameId, Loan, LoanId, LoanListQuery,
+    UpdateGameCollectionRequest,
+};
+
+const LOAN_COLUMNS: &str = r#"
+    l.id, l.game_id, g.name, l.borrower, l.loaned_at, l.due_at, l.returned_at, l.notes,
+    (l.returned_at IS NULL AND l.due_at IS NOT NULL AND l.due_at < CURRENT_TIMESTAMP) AS overdue
+"#;
+
+/// Status of the game aliased `g`; games without collection details are owned
+pub const COLLECTION_STATUS: &str =
+    "COALESCE((SELECT gc.status FROM game_collection gc WHERE gc.game_id = g.id), 'owned')";
+
+fn optional_datetime(row: &Row, column: &str) -> SqliteResult<Option<DateTime<Utc>>> {
+    let value: Option<String> = row.get(column)?;
+    match value {
+        Some(_) => Ok(Some(parse_datetime(row, column)?)),
+        None => Ok(None),
+    }
+}
+
+fn status_from_row(row: &Row, index: usize) -> SqliteResult<CollectionStatus> {
+    let status: Option<String> = row.get(index)?;
+    match status {
+        Some(status) => CollectionStatus::from_str(&status).ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(
+                index,
+                "status".to_string(),
+                rusqlite::types::Type::Text,
+            )
+        }),
+        None => Ok(CollectionStatus::Owned),
+    }
+}
+
+fn loan_from_row(row: &Row) -> SqliteResult<Loan> {
+    Ok(Loan {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        game_name: row.get(2)?,
+        borrower: row.get(3)?,
+        loaned_at: parse_datetime(row, "loaned_at")?,
+        due_at: optional_datetime(row, "due_at")?,
+        returned_at: optional_datetime(row, "returned_at")?,
+        notes: row.get(7)?,
+        overdue: row.get(8)?,
+    })
+}
+
+fn invalid_reference<T>(message: String) -> SqliteResult<T> {
+    Err(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+        Some(message),
+    ))
+}
+
+fn get_loan_sync(conn: &Connection, loan_id: LoanId) -> SqliteResult<Option<Loan>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM game_loans l JOIN games g ON g.id = l.game_id WHERE l.id = ?",
+            LOAN_COLUMNS
+        ),
+        params![loan_id],
+        loan_from_row,
+    )
+    .optional()
+}
+
+fn active_loan_sync(conn: &Connection, game_id: GameId) -> SqliteResult<Option<Loan>> {
+    conn.query_row(
+        &format!(
+            r#"
+            SELECT {} FROM game_loans l JOIN games g ON g.id = l.game_id
+            WHERE l.game_id = ? AND l.returned_at IS NULL
+            ORDER BY l.loaned_at DESC LIMIT 1
+            "#,
+            LOAN_COLUMNS
+        ),
+        params![game_id],
+        loan_from_row,
+    )
+    .optional()
+}
+
+/// A game's collection details, or `None` if the game does not exist
+pub fn get_game_collection_sync(
+    conn: &Connection,
+    game_id: GameId,
+) -> SqliteResult<Option<GameCollection>> {
+    let collection = conn
+        .query_row(
+            r#"
+            SELECT g.id, gc.status, gc.wishlist_priority, gc.acquired_at, gc.acquisition_price,
+                   gc.shelf_location, gc.notes, gc.updated_at, gc.rating
+            FROM games g LEFT JOIN game_collection gc ON gc.game_id = g.id
+            WHERE g.id = ?
+            "#,
+            params![game_id],
+            |row| {
+                Ok(GameCollection {
+                    game_id: row.get(0)?,
+                    status: status_from_row(row, 1)?,
+                    wishlist_priority: row.get(2)?,
+                    acquired_at: optional_datetime(row, "acquired_at")?,
+                    acquisition_price: row.get(4)?,
+                    shelf_location: row.get(5)?,
+                    rating: row.get(8)?,
+                    notes: row.get(6)?,
+                    current_loan: None,
+                    updated_at: optional_datetime(row, "updated_at")?,
+                })
+            },
+        )
+        .optional()?;
+
+    match collection {
+        Some(mut collection) => {
+            collection.current_loan = active_loan_sync(conn, game_id)?;
+            Ok(Some(collection))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Personal ratings of every rated game
+pub async fn game_ratings(db: &Database) -> SqliteResult<HashMap<GameId, f64>> {
+    db.with_connection(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT game_id, rating FROM game_collection WHERE rating IS NOT NULL")?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    })
+}
+
+/// Drop a game's collection details and loans
+pub fn delete_game_collection_sync(conn: &Connection, game_id: GameId) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM game_collection WHERE game_id = ?",
+        params![game_id],
+    )?;
+    conn.execute("DELETE FROM game_loans WHERE game_id = ?", params![game_id])?;
+    Ok(())
+}
+
+pub async fn get_game_collection(
+    db: &Database,
+    game_id: GameId,
+) -> SqliteResult<Option<GameCollection>> {
+    db.with_connection(|conn| get_game_collection_sync(conn, game_id))
+}
+
+pub async fn update_game_collection(
+    db: &Database,
+    game_id: GameId,
+    request: UpdateGameCollectionRequest,
+) -> SqliteResult<Option<GameCollection>> {
+    db.with_transaction(|conn| {
+        let game_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if !game_exists {
+            return Ok(None);
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO game_collection (game_id) VALUES (?)",
+            params![game_id],
+        )?;
+        conn.execute(
+            r#"
+            UPDATE game_collection SET
+                status = COALESCE(?, status),
+                wishlist_priority = COALESCE(?, wishlist_priority),
+                acquired_at = COALESCE(?, acquired_at),
+                acquisition_price = COALESCE(?, acquisition_price),
+                shelf_location = COALESCE(?, shelf_location),
+                rating = COALESCE(?, rating),
+                notes = COALESCE(?, notes),
+                updated_at = ?
+            WHERE game_id = ?
+            "#,
+            params![
+                request.status.map(|status| status.as_str()),
+                request.wishlist_priority,
+                request.acquired_at.map(format_datetime),
+                request.acquisition_price,
+                request.shelf_location,
+                request.rating,
+                request.notes,
+                format_datetime(Utc::now()),
+                game_id
+            ],
+        )?;
+        conn.execute(
+            "UPDATE game_collection SET wishlist_priority = NULL WHERE game_id = ? AND status != 'wishlist'",
+            params![game_id],
+        )?;
+
+        get_game_collection_sync(conn, game_id)
+    })
+}
+
+pub async fn list_loans(db: &Database, query: &LoanListQuery) -> SqliteResult<Vec<Loan>> {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params_vec: Vec<&dyn rusqlite::ToSql> = Vec::new();
+
+    if let Some(game_id) = &query.game_id {
+        conditions.push("l.game_id = ?");
+        params_vec.push(game_id);
+    }
+    match query.active {
+        Some(true) => conditions.push("l.returned_at IS NULL"),
+        Some(false) => conditions.push("l.returned_at IS NOT NULL"),
+        None => {}
+    }
+    if query.overdue {
+        conditions.push("l.returned_at IS NULL AND l.due_at < CURRENT_TIMESTAMP");
+    }
+    let borrower = query.borrower.as_deref().map(str::trim);
+    if let Some(borrower) = &borrower {
+        conditions.push("l.borrower = ? COLLATE NOCASE");
+        params_vec.push(borrower);
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    // Overdue loans are most urgent when they have been due the longest
+    let order_by = if query.overdue {
+        "l.due_at ASC, l.id ASC"
+    } else {
+        "l.loaned_at DESC, l.id DESC"
+    };
+
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT {} FROM game_loans l JOIN games g ON g.id = l.game_id
+            {}
+            ORDER BY {}
+            "#,
+            LOAN_COLUMNS, where_clause, order_by
+        ))?;
+        stmt.query_map(params_vec.as_slice(), loan_from_row)?
+            .collect()
+    })
+}
+
+pub async fn get_loan(db: &Database, loan_id: LoanId) -> SqliteResult<Option<Loan>> {
+    db.with_connection(|conn| get_loan_sync(conn, loan_id))
+}
+
+/// Lend out a game; it must be owned and not already on loan
+pub async fn create_loan(db: &Database, request: CreateLoanRequest) -> SqliteResult<Loan> {
+    db.with_transaction(|conn| {
+        let Some(collection) = get_game_collection_sync(conn, request.game_id)? else {
+            return invalid_reference(format!("Game {} does not exist", request.game_id));
+        };
+        if !collection.status.is_owned() {
+            return invalid_reference(format!(
+                "Game {} is not owned, so it cannot be lent out",
+                request.game_id
+            ));
+        }
+        if let Some(loan) = collection.current_loan {
+            return invalid_reference(format!(
+                "Game {} is already on loan to {}",
+                request.game_id, loan.borrower
+            ));
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO game_loans (game_id, borrower, loaned_at, due_at, notes)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            params![
+                request.game_id,
+                request.borrower.trim(),
+                format_datetime(request.loaned_at.unwrap_or_else(Utc::now)),
+                request.due_at.map(format_datetime),
+                request.notes
+            ],
+        )?;
+        get_loan_sync(conn, conn.last_insert_rowid())?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+/// Mark a loan as returned, or `None` if the loan does not exist
+pub async fn return_loan(
+    db: &Database,
+    loan_id: LoanId,
+    returned_at: Option<DateTime<Utc>>,
+) -> SqliteResult<Option<Loan>> {
+    db.with_transaction(|conn| {
+        let Some(loan) = get_loan_sync(conn, loan_id)? else {
+            return Ok(None);
+        };
+        if loan.returned_at.is_some() {
+            return invalid_reference(format!("Loan {} has already been returned", loan_id));
+        }
+        let returned_at = returned_at.unwrap_or_else(Utc::now);
+        if returned_at < loan.loaned_at {
+            return invalid_reference(format!(
+                "Loan {} cannot be returned before it was made",
+                loan_id
+            ));
+        }
+
+        conn.execute(
+            "UPDATE game_loans SET returned_at = ? WHERE id = ?",
+            params![format_datetime(returned_at), loan_id],
+        )?;
+        get_loan_sync(conn, loan_id)
+    })
+}
+
+pub async fn delete_loan(db: &Database, loan_id: LoanId) -> SqliteResult<bool> {
+    db.with_connection(|conn| {
+        let rows_affected =
+            conn.execute("DELETE FROM game_loans WHERE id = ?", params![loan_id])?;
+        Ok(rows_affected > 0)
+    })
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as SqliteResult, params};
+
+use super::Database;
+
+/// Persistent totals for the embedding cache
+pub struct CacheTotals {
+    pub entries: u64,
+    pub lifetime_hits: u64,
+    pub oldest_entry: Option<String>,
+}
+
+/// Look up cached vectors by text hash, bumping usage stats for every hit
+pub async fn get_cached_embeddings(
+    db: &Database,
+    model: &str,
+    text_hashes: &[String],
+) -> SqliteResult<HashMap<String, Vec<f32>>> {
+    db.with_transaction(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut found = HashMap::new();
+
+        let mut select_stmt = conn
+            .prepare("SELECT embedding FROM embedding_cache WHERE text_hash = ? AND model = ?")?;
+        let mut touch_stmt = conn.prepare(
+            r#"
+            UPDATE embedding_cache
+            SET hit_count = hit_count + 1, last_used_at = ?
+            WHERE text_hash = ? AND model = ?
+            "#,
+        )?;
+
+        for text_hash in text_hashes {
+            if found.contains_key(text_hash) {
+                continue;
+            }
+
+            let embedding_json: Option<String> = select_stmt
+                .query_row(params![text_hash, model], |row| row.get(0))
+                .optional()?;
+
+            if let Some(embedding_json) = embedding_json {
+                let embedding: Vec<f32> = serde_json::from_str(&embedding_json).map_err(|_| {
+                    rusqlite::Error::InvalidColumnType(
+                        0,
+                        "embedding".to_string(),
+                        rusqlite::types::Type::Text,
+                    )
+                })?;
+                touch_stmt.execute(params![now_str, text_hash, model])?;
+                found.insert(text_hash.clone(), embedding);
+            }
+        }
+
+        Ok(found)
+    })
+}
+
+/// Store freshly generated vectors, replacing any existing entry for the same key
+pub async fn store_cached_embeddings(
+    db: &Database,
+    model: &str,
+    entries: &[(String, Vec<f32>)],
+) -> SqliteResult<()> {
+    db.with_transaction(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut stmt = conn.prepare(
+            r#"
+            INSERT OR REPLACE INTO embedding_cache (
+                text_hash, model, embedding, dimensions, hit_count, created_at, last_used_at
+            ) VALUES (?, ?, ?, ?, 0, ?, ?)
+            "#,
+        )?;
+
+        for (text_hash, embedding) in entries {
+            let embedding_json = serde_json::to_string(embedding)
+                .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))?;
+            stmt.execute(params![
+                text_hash,
+                model,
+                embedding_json,
+                embedding.len() as i64,
+                now_str,
+                now_str
+            ])?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Evict least-recently-used entries until at most `max_entries` remain
+pub async fn evict_embedding_cache(db: &Database, max_entries: u64) -> SqliteResult<u64> {
+    db.with_transaction(|conn| {
+        let entries: u64 =
+            conn.query_row("SELECT COUNT(*) FROM embedding_cache", [], |row| row.get(0))?;
+
+        if entries <= max_entries {
+            return Ok(0);
+        }
+
+        let excess = entries - max_entries;
+        let rows_affected = conn.execute(
+            r#"
+            DELETE FROM embedding_cache
+            WHERE rowid IN (
+                SELECT rowid FROM embedding_cache
+                ORDER BY last_used_at ASC, hit_count ASC
+                LIMIT ?
+            )
+            "#,
+            params![excess as i64],
+        )?;
+
+        Ok(rows_affected as u64)
+    })
+}
+
+/// Remove cached entries, optionally only those produced by one model
+pub async fn clear_embedding_cache(db: &Database, model: Option<&str>) -> SqliteResult<u64> {
+    db.with_connection(|conn| {
+        let rows_affected = if let Some(model) = model {
+            conn.execute(
+                "DELETE FROM embedding_cache WHERE model = ?",
+                params![model],
+            )?
+        } else {
+            conn.execute("DELETE FROM embedding_cache", [])?
+        };
+        Ok(rows_affected as u64)
+    })
+}
+
+pub async fn get_cache_totals(db: &Database) -> SqliteResult<CacheTotals> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(hit_count), 0), MIN(created_at)
+            FROM embedding_cache
+            "#,
+            [],
+            |row| {
+                Ok(CacheTotals {
+                    entries: row.get(0)?,
+                    lifetime_hits: row.get(1)?,
+                    oldest_entry: row.get(2)?,
+                })
+            },
+        )
+    })
+}
@@ -4,7 +4,7 @@ use serde_json;
 
 use crate::models::{
     CreateEmbeddingRequest, Embedding, EmbeddingId, EmbeddingSearchResult, EmbeddingSourceType,
-    GameId, HouseRuleId, SimilaritySearchRequest,
+    GameId, HouseRuleId, RuleChunk, SimilaritySearchRequest,
 };
 
 use super::{Database, parse_datetime};
@@ -235,6 +235,7 @@ pub async fn similarity_search(
 
                     results.push(EmbeddingSearchResult {
                         id: *id,
+                        game_id: request.game_id,
                         chunk_text: chunk_text.clone(),
                         similarity_score: similarity_score as f32,
                         source_type,
@@ -330,6 +331,97 @@ pub async fn get_embedding_by_id(
     })
 }
 
+/// Get the chunks surrounding `embedding_id` in document order (including the chunk itself)
+pub async fn get_chunk_neighbors(
+    db: &Database,
+    embedding_id: EmbeddingId,
+    window: u32,
+) -> SqliteResult<Vec<RuleChunk>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT n.id, n.game_id, n.chunk_text, n.chunk_index, n.source_type, n.source_id, n.metadata
+            FROM embeddings e
+            JOIN embeddings n
+                ON n.game_id = e.game_id
+                AND n.source_type = e.source_type
+                AND n.source_id IS e.source_id
+                AND n.chunk_index BETWEEN e.chunk_index - ?2 AND e.chunk_index + ?2
+            WHERE e.id = ?1
+            ORDER BY n.chunk_index ASC
+            "#,
+        )?;
+
+        let chunk_iter = stmt.query_map(params![embedding_id, window], |row| {
+            let source_type_str: String = row.get(4)?;
+            let source_type = EmbeddingSourceType::from_str(&source_type_str)
+                .unwrap_or(EmbeddingSourceType::RulesPdf);
+
+            Ok(RuleChunk {
+                id: row.get(0)?,
+                game_id: row.get(1)?,
+                chunk_text: row.get(2)?,
+                chunk_index: row.get(3)?,
+                source_type,
+                source_id: row.get(5)?,
+                metadata: row.get(6)?,
+            })
+        })?;
+
+        chunk_iter.collect()
+    })
+}
+
+/// Get a game's chunks of one source type in document order
+pub async fn list_chunks_for_game(
+    db: &Database,
+    game_id: GameId,
+    source_type: EmbeddingSourceType,
+) -> SqliteResult<Vec<RuleChunk>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, game_id, chunk_text, chunk_index, source_type, source_id, metadata
+            FROM embeddings
+            WHERE game_id = ? AND source_type = ?
+            ORDER BY chunk_index ASC, id ASC
+            "#,
+        )?;
+
+        let chunk_iter = stmt.query_map(params![game_id, source_type.as_str()], |row| {
+            Ok(RuleChunk {
+                id: row.get(0)?,
+                game_id: row.get(1)?,
+                chunk_text: row.get(2)?,
+                chunk_index: row.get(3)?,
+                source_type: source_type.clone(),
+                source_id: row.get(5)?,
+                metadata: row.get(6)?,
+            })
+        })?;
+
+        chunk_iter.collect()
+    })
+}
+
+/// Get the current text of the given chunks; ids that no longer exist are omitted
+pub async fn get_chunk_texts(
+    db: &Database,
+    embedding_ids: &[EmbeddingId],
+) -> SqliteResult<Vec<(EmbeddingId, String)>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare("SELECT id, chunk_text FROM embeddings WHERE id = ?")?;
+        let mut chunks = Vec::new();
+        for embedding_id in embedding_ids {
+            let mut rows = stmt.query(params![embedding_id])?;
+            if let Some(row) = rows.next()? {
+                chunks.push((row.get(0)?, row.get(1)?));
+            }
+        }
+        Ok(chunks)
+    })
+}
+
 // Batch operations for efficiency
 pub async fn create_embeddings_batch(
     db: &Database,
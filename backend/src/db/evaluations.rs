@@ -0,0 +1,192 @@
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{
+    EvalQuestion, EvalQuestionId, EvalRun, EvalRunId, GameId, GoldenQuestion, NewEvalRun,
+    QueryStrategy,
+};
+
+const EVAL_QUESTION_COLUMNS: &str =
+    "id, game_id, question, expected_chunk_ids, expected_keywords, created_at";
+
+const EVAL_RUN_COLUMNS: &str = "id, game_id, strategy, k, generate_answers, embedding_model, \
+                                llm_model, summary, results, created_at";
+
+fn to_json<T: serde::Serialize>(value: &T) -> SqliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(row: &Row, index: usize) -> SqliteResult<T> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn eval_question_from_row(row: &Row) -> SqliteResult<EvalQuestion> {
+    Ok(EvalQuestion {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        question: row.get(2)?,
+        expected_chunk_ids: from_json(row, 3)?,
+        expected_keywords: from_json(row, 4)?,
+        created_at: parse_datetime(row, "created_at")?,
+    })
+}
+
+fn eval_run_from_row(row: &Row) -> SqliteResult<EvalRun> {
+    let strategy: String = row.get(2)?;
+
+    Ok(EvalRun {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        strategy: QueryStrategy::from_str(&strategy).unwrap_or_default(),
+        k: row.get(3)?,
+        generate_answers: row.get(4)?,
+        embedding_model: row.get(5)?,
+        llm_model: row.get(6)?,
+        summary: from_json(row, 7)?,
+        results: from_json(row, 8)?,
+        created_at: parse_datetime(row, "created_at")?,
+    })
+}
+
+fn list_eval_questions_sync(conn: &Connection, game_id: GameId) -> SqliteResult<Vec<EvalQuestion>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM eval_questions WHERE game_id = ? ORDER BY id ASC",
+        EVAL_QUESTION_COLUMNS
+    ))?;
+    stmt.query_map(params![game_id], eval_question_from_row)?
+        .collect()
+}
+
+fn get_eval_run_sync(conn: &Connection, id: EvalRunId) -> SqliteResult<Option<EvalRun>> {
+    conn.query_row(
+        &format!("SELECT {} FROM eval_runs WHERE id = ?", EVAL_RUN_COLUMNS),
+        params![id],
+        eval_run_from_row,
+    )
+    .optional()
+}
+
+pub async fn list_eval_questions(
+    db: &Database,
+    game_id: GameId,
+) -> SqliteResult<Vec<EvalQuestion>> {
+    db.with_connection(|conn| list_eval_questions_sync(conn, game_id))
+}
+
+/// Add golden questions to a game, optionally replacing its current set.
+/// Returns the game's whole golden set
+pub async fn import_eval_questions(
+    db: &Database,
+    game_id: GameId,
+    questions: &[GoldenQuestion],
+    replace: bool,
+) -> SqliteResult<Vec<EvalQuestion>> {
+    db.with_transaction(|conn| {
+        let game_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if !game_exists {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                Some("Game does not exist".to_string()),
+            ));
+        }
+
+        if replace {
+            conn.execute(
+                "DELETE FROM eval_questions WHERE game_id = ?",
+                params![game_id],
+            )?;
+        }
+
+        let mut insert = conn.prepare(
+            r#"
+            INSERT INTO eval_questions (game_id, question, expected_chunk_ids, expected_keywords)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )?;
+        for question in questions {
+            insert.execute(params![
+                game_id,
+                question.question.trim(),
+                to_json(&question.expected_chunk_ids)?,
+                to_json(&question.expected_keywords)?
+            ])?;
+        }
+
+        list_eval_questions_sync(conn, game_id)
+    })
+}
+
+pub async fn delete_eval_question(db: &Database, id: EvalQuestionId) -> SqliteResult<bool> {
+    db.with_connection(|conn| {
+        let rows_affected = conn.execute("DELETE FROM eval_questions WHERE id = ?", params![id])?;
+        Ok(rows_affected > 0)
+    })
+}
+
+pub async fn create_eval_run(db: &Database, run: NewEvalRun) -> SqliteResult<EvalRun> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            r#"
+            INSERT INTO eval_runs (
+                game_id, strategy, k, generate_answers, embedding_model, llm_model, summary, results
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                run.game_id,
+                run.strategy.as_str(),
+                run.k,
+                run.generate_answers,
+                run.embedding_model,
+                run.llm_model,
+                to_json(&run.summary)?,
+                to_json(&run.results)?
+            ],
+        )?;
+
+        get_eval_run_sync(conn, conn.last_insert_rowid())?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+pub async fn get_eval_run(db: &Database, id: EvalRunId) -> SqliteResult<Option<EvalRun>> {
+    db.with_connection(|conn| get_eval_run_sync(conn, id))
+}
+
+/// The game's most recent run before `before_id`
+pub async fn get_previous_eval_run(
+    db: &Database,
+    game_id: GameId,
+    before_id: EvalRunId,
+) -> SqliteResult<Option<EvalRun>> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM eval_runs WHERE game_id = ? AND id < ? ORDER BY id DESC LIMIT 1",
+                EVAL_RUN_COLUMNS
+            ),
+            params![game_id, before_id],
+            eval_run_from_row,
+        )
+        .optional()
+    })
+}
+
+/// A game's runs, newest first
+pub async fn list_eval_runs(db: &Database, game_id: GameId) -> SqliteResult<Vec<EvalRun>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM eval_runs WHERE game_id = ? ORDER BY id DESC",
+            EVAL_RUN_COLUMNS
+        ))?;
+        stmt.query_map(params![game_id], eval_run_from_row)?
+            .collect()
+    })
+}
@@ -0,0 +1,258 @@
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{FaqEntry, FaqEntryId, GameId, NewFaqEntry, UpdateFaqEntryRequest};
+
+const FAQ_ENTRY_COLUMNS: &str =
+    "id, game_id, question, answer, cited_chunks, edited, position, created_at, updated_at";
+
+fn to_json<T: serde::Serialize>(value: &T) -> SqliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))
+}
+
+fn faq_entry_from_row(row: &Row) -> SqliteResult<FaqEntry> {
+    let cited_chunks: String = row.get(4)?;
+
+    Ok(FaqEntry {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        question: row.get(2)?,
+        answer: row.get(3)?,
+        cited_chunks: serde_json::from_str(&cited_chunks).unwrap_or_default(),
+        edited: row.get(5)?,
+        position: row.get(6)?,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+    })
+}
+
+fn get_faq_entry_sync(conn: &Connection, id: FaqEntryId) -> SqliteResult<Option<FaqEntry>> {
+    conn.query_row(
+        &format!("SELECT {} FROM faq_entries WHERE id = ?", FAQ_ENTRY_COLUMNS),
+        params![id],
+        faq_entry_from_row,
+    )
+    .optional()
+}
+
+fn list_faq_entries_sync(conn: &Connection, game_id: GameId) -> SqliteResult<Vec<FaqEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM faq_entries WHERE game_id = ? ORDER BY position ASC, id ASC",
+        FAQ_ENTRY_COLUMNS
+    ))?;
+    stmt.query_map(params![game_id], faq_entry_from_row)?
+        .collect()
+}
+
+fn insert_faq_entry_sync(
+    conn: &Connection,
+    entry: &NewFaqEntry,
+    position: i32,
+    now_str: &str,
+) -> SqliteResult<FaqEntryId> {
+    conn.execute(
+        r#"
+        INSERT INTO faq_entries (
+            game_id, question, answer, cited_chunks, edited, position, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        params![
+            entry.game_id,
+            entry.question,
+            entry.answer,
+            to_json(&entry.cited_chunks)?,
+            entry.edited,
+            position,
+            now_str,
+            now_str
+        ],
+    )?;
+
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "INSERT INTO vec_faq_entries (rowid, game_id, question_vector) VALUES (?, ?, ?)",
+        params![id, entry.game_id, to_json(&entry.question_embedding)?],
+    )?;
+    Ok(id)
+}
+
+fn delete_faq_entries_sync(conn: &Connection, ids: &[FaqEntryId]) -> SqliteResult<u64> {
+    let mut delete_vec = conn.prepare("DELETE FROM vec_faq_entries WHERE rowid = ?")?;
+    let mut delete_row = conn.prepare("DELETE FROM faq_entries WHERE id = ?")?;
+
+    let mut removed = 0;
+    for id in ids {
+        delete_vec.execute(params![id])?;
+        removed += delete_row.execute(params![id])? as u64;
+    }
+    Ok(removed)
+}
+
+/// Drop a game's generated, unedited entries. Returns how many were removed
+pub fn delete_generated_faq_sync(conn: &Connection, game_id: GameId) -> SqliteResult<u64> {
+    let mut stmt =
+        conn.prepare("SELECT id FROM faq_entries WHERE game_id = ? AND edited = FALSE")?;
+    let generated: Vec<FaqEntryId> = stmt
+        .query_map(params![game_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    delete_faq_entries_sync(conn, &generated)
+}
+
+/// Drop every entry of a game's FAQ, edited or not
+pub fn delete_game_faq_sync(conn: &Connection, game_id: GameId) -> SqliteResult<u64> {
+    let mut stmt = conn.prepare("SELECT id FROM faq_entries WHERE game_id = ?")?;
+    let entries: Vec<FaqEntryId> = stmt
+        .query_map(params![game_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    delete_faq_entries_sync(conn, &entries)
+}
+
+fn ensure_game_exists(conn: &Connection, game_id: GameId) -> SqliteResult<()> {
+    let game_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+        params![game_id],
+        |row| row.get(0),
+    )?;
+
+    if !game_exists {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+            Some("Game does not exist".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn list_faq_entries(db: &Database, game_id: GameId) -> SqliteResult<Vec<FaqEntry>> {
+    db.with_connection(|conn| list_faq_entries_sync(conn, game_id))
+}
+
+pub async fn get_faq_entry(db: &Database, id: FaqEntryId) -> SqliteResult<Option<FaqEntry>> {
+    db.with_connection(|conn| get_faq_entry_sync(conn, id))
+}
+
+/// Add one entry at the end of its game's FAQ
+pub async fn create_faq_entry(db: &Database, entry: NewFaqEntry) -> SqliteResult<FaqEntry> {
+    db.with_transaction(|conn| {
+        ensure_game_exists(conn, entry.game_id)?;
+
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let position: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM faq_entries WHERE game_id = ?",
+            params![entry.game_id],
+            |row| row.get(0),
+        )?;
+
+        let id = insert_faq_entry_sync(conn, &entry, position, &now_str)?;
+        get_faq_entry_sync(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+/// Swap a game's generated, unedited entries for freshly generated ones.
+/// Returns the game's whole FAQ and how many entries were replaced
+pub async fn replace_generated_faq(
+    db: &Database,
+    game_id: GameId,
+    entries: Vec<NewFaqEntry>,
+) -> SqliteResult<(Vec<FaqEntry>, u64)> {
+    db.with_transaction(|conn| {
+        ensure_game_exists(conn, game_id)?;
+        let replaced = delete_generated_faq_sync(conn, game_id)?;
+
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let first_position: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM faq_entries WHERE game_id = ?",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        for (offset, entry) in entries.iter().enumerate() {
+            insert_faq_entry_sync(conn, entry, first_position + offset as i32, &now_str)?;
+        }
+
+        Ok((list_faq_entries_sync(conn, game_id)?, replaced))
+    })
+}
+
+pub async fn delete_generated_faq(db: &Database, game_id: GameId) -> SqliteResult<u64> {
+    db.with_transaction(|conn| delete_generated_faq_sync(conn, game_id))
+}
+
+/// Apply an edit and mark the entry as edited; a new question needs its new vector
+pub async fn update_faq_entry(
+    db: &Database,
+    id: FaqEntryId,
+    request: UpdateFaqEntryRequest,
+    question_embedding: Option<Vec<f32>>,
+) -> SqliteResult<Option<FaqEntry>> {
+    db.with_transaction(|conn| {
+        let Some(existing) = get_faq_entry_sync(conn, id)? else {
+            return Ok(None);
+        };
+
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        conn.execute(
+            r#"
+            UPDATE faq_entries
+            SET question = ?, answer = ?, cited_chunks = ?, edited = TRUE, updated_at = ?
+            WHERE id = ?
+            "#,
+            params![
+                request.question.unwrap_or(existing.question),
+                request.answer.unwrap_or(existing.answer),
+                to_json(&request.cited_chunks.unwrap_or(existing.cited_chunks))?,
+                now_str,
+                id
+            ],
+        )?;
+
+        if let Some(question_embedding) = question_embedding {
+            conn.execute(
+                "UPDATE vec_faq_entries SET question_vector = ? WHERE rowid = ?",
+                params![to_json(&question_embedding)?, id],
+            )?;
+        }
+
+        get_faq_entry_sync(conn, id)
+    })
+}
+
+pub async fn delete_faq_entry(db: &Database, id: FaqEntryId) -> SqliteResult<bool> {
+    db.with_transaction(|conn| Ok(delete_faq_entries_sync(conn, &[id])? > 0))
+}
+
+/// Nearest FAQ questions for a game with their cosine similarity, most similar first
+pub async fn find_similar_faq_entries(
+    db: &Database,
+    game_id: GameId,
+    question_embedding: &[f32],
+    limit: u32,
+) -> SqliteResult<Vec<(FaqEntry, f32)>> {
+    db.with_connection(|conn| {
+        let query_json = to_json(&question_embedding)?;
+
+        let mut vec_stmt = conn.prepare(
+            r#"
+            SELECT rowid, distance
+            FROM vec_faq_entries
+            WHERE question_vector MATCH ?1 AND game_id = ?2 AND k = ?3
+            ORDER BY distance
+            "#,
+        )?;
+        let neighbors: Vec<(FaqEntryId, f32)> = vec_stmt
+            .query_map(params![query_json, game_id, limit], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut results = Vec::new();
+        for (id, distance) in neighbors {
+            if let Some(entry) = get_faq_entry_sync(conn, id)? {
+                results.push((entry, 1.0 - distance));
+            }
+        }
+
+        Ok(results)
+    })
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{Connection, Result as SqliteResult, params};
+
+use super::{Database, format_datetime};
+use crate::models::{GameDescriptionSource, GameId};
+
+fn to_json<T: serde::Serialize>(value: &T) -> SqliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))
+}
+
+/// Drop a game's description vector
+pub fn delete_description_embedding_sync(conn: &Connection, game_id: GameId) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM vec_game_descriptions WHERE rowid = ?",
+        params![game_id],
+    )?;
+    conn.execute(
+        "DELETE FROM game_description_embeddings WHERE game_id = ?",
+        params![game_id],
+    )?;
+    Ok(())
+}
+
+/// Every game's name, description and tags
+pub async fn list_description_sources(db: &Database) -> SqliteResult<Vec<GameDescriptionSource>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT g.id, g.name, g.description,
+                   (SELECT json_group_array(name) FROM (
+                        SELECT t.name FROM game_tags gt JOIN tags t ON t.id = gt.tag_id
+                        WHERE gt.game_id = g.id
+                        ORDER BY t.name COLLATE NOCASE
+                   )) AS tags
+            FROM games g
+            ORDER BY g.id ASC
+            "#,
+        )?;
+        stmt.query_map([], |row| {
+            let tags: String = row.get(3)?;
+            Ok(GameDescriptionSource {
+                game_id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
+            })
+        })?
+        .collect()
+    })
+}
+
+/// Hash of the text behind each stored description vector
+pub async fn description_hashes(db: &Database) -> SqliteResult<HashMap<GameId, String>> {
+    db.with_connection(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT game_id, text_hash FROM game_description_embeddings")?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    })
+}
+
+/// Store description vectors, replacing any the games already had
+pub async fn store_description_embeddings(
+    db: &Database,
+    embeddings: &[(GameId, String, Vec<f32>)],
+) -> SqliteResult<()> {
+    db.with_transaction(|conn| {
+        let now_str = format_datetime(Utc::now());
+        for (game_id, text_hash, embedding) in embeddings {
+            delete_description_embedding_sync(conn, *game_id)?;
+            conn.execute(
+                "INSERT INTO vec_game_descriptions (rowid, description_vector) VALUES (?, ?)",
+                params![game_id, to_json(embedding)?],
+            )?;
+            conn.execute(
+                r#"
+                INSERT INTO game_description_embeddings (game_id, text_hash, updated_at)
+                VALUES (?, ?, ?)
+                "#,
+                params![game_id, text_hash, now_str],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+pub async fn delete_description_embeddings(db: &Database, game_ids: &[GameId]) -> SqliteResult<()> {
+    db.with_transaction(|conn| {
+        for game_id in game_ids {
+            delete_description_embedding_sync(conn, *game_id)?;
+        }
+        Ok(())
+    })
+}
+
+/// Stored description vectors of the given games; games without one are left out
+pub async fn get_description_embeddings(
+    db: &Database,
+    game_ids: &[GameId],
+) -> SqliteResult<HashMap<GameId, Vec<f32>>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT rowid, vec_to_json(description_vector) FROM vec_game_descriptions
+            WHERE rowid IN (SELECT value FROM json_each(?))
+            "#,
+        )?;
+        stmt.query_map(params![to_json(&game_ids)?], |row| {
+            let embedding_json: String = row.get(1)?;
+            let embedding = serde_json::from_str(&embedding_json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    1,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?;
+            Ok((row.get(0)?, embedding))
+        })?
+        .collect()
+    })
+}
+
+/// Nearest games by description vector with their cosine similarity, most similar first
+pub async fn find_similar_games(
+    db: &Database,
+    embedding: &[f32],
+    limit: u32,
+) -> SqliteResult<Vec<(GameId, f64)>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT rowid, distance
+            FROM vec_game_descriptions
+            WHERE description_vector MATCH ?1 AND k = ?2
+            ORDER BY distance
+            "#,
+        )?;
+        stmt.query_map(params![to_json(&embedding)?, limit], |row| {
+            let distance: f64 = row.get(1)?;
+            Ok((row.get(0)?, 1.0 - distance))
+        })?
+        .collect()
+    })
+}
@@ -1,40 +1,220 @@
-use super::{Database, PaginationInfo, parse_datetime};
+use super::{
+    Database, PaginationInfo,
+    answer_cache::invalidate_answer_cache_sync,
+    collection::{COLLECTION_STATUS, delete_game_collection_sync},
+    faq::delete_game_faq_sync,
+    game_descriptions::delete_description_embedding_sync,
+    parse_datetime,
+    plays::delete_game_plays_sync,
+    tags::set_game_tags_sync,
+};
 use crate::models::{
-    CreateGameRequest, Game, GameId, GameSummary, PaginatedResponse, RulesInfoResponse,
-    UpdateGameRequest,
+    BggImportSummary, BggRank, BggThing, CreateGameRequest, Game, GameId, GameListQuery, GameSort,
+    GameSummary, PaginatedResponse, RulesInfoResponse, SortOrder, TagType, UpdateGameRequest,
 };
 use chrono::Utc;
-use rusqlite::{Result as SqliteResult, params};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+fn game_from_row(row: &Row) -> SqliteResult<Game> {
+    let last_played_at: Option<String> = row.get(14)?;
+
+    Ok(Game {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        publisher: row.get(3)?,
+        year_published: row.get(4)?,
+        min_players: row.get(5)?,
+        max_players: row.get(6)?,
+        play_time_minutes: row.get(7)?,
+        complexity_rating: row.get(8)?,
+        bgg_id: row.get(9)?,
+        rules_pdf_path: row.get(10)?,
+        rules_text: row.get(11)?,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+        last_played_at: match last_played_at {
+            Some(_) => Some(parse_datetime(row, "last_played_at")?),
+            None => None,
+        },
+        base_game_id: row.get(15)?,
+    })
+}
+
+/// Quote each word for an FTS5 MATCH so user input cannot break the query syntax;
+/// the last word matches as a prefix so results show up while typing
+fn fts_match_expression(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
+/// Escape LIKE wildcards so a name filter matches literally
+fn like_substring(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
 
 pub async fn list_games(
     db: &Database,
-    page: u32,
-    limit: u32,
+    query: &GameListQuery,
 ) -> SqliteResult<PaginatedResponse<GameSummary>> {
-    let pagination = PaginationInfo::new(page, limit);
+    let pagination = PaginationInfo::new(query.page, query.limit);
+
+    // Build the filters, each bound to its own parameters
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params_vec: Vec<&dyn rusqlite::ToSql> = Vec::new();
+
+    let name_pattern = query.name.as_deref().map(like_substring);
+    if let Some(pattern) = &name_pattern {
+        conditions.push("g.name LIKE ? ESCAPE '\\'");
+        params_vec.push(pattern);
+    }
+    let fts_expression = query.q.as_deref().and_then(fts_match_expression);
+    if let Some(expression) = &fts_expression {
+        conditions.push("g.id IN (SELECT rowid FROM games_fts WHERE games_fts MATCH ?)");
+        params_vec.push(expression);
+    }
+    if let Some(players) = &query.players {
+        conditions.push("g.min_players <= ? AND g.max_players >= ?");
+        params_vec.push(players);
+        params_vec.push(players);
+    }
+    if let Some(max_play_time) = &query.max_play_time {
+        conditions.push("g.play_time_minutes <= ?");
+        params_vec.push(max_play_time);
+    }
+    if let Some(min_complexity) = &query.min_complexity {
+        conditions.push("g.complexity_rating >= ?");
+        params_vec.push(min_complexity);
+    }
+    if let Some(max_complexity) = &query.max_complexity {
+        conditions.push("g.complexity_rating <= ?");
+        params_vec.push(max_complexity);
+    }
+    if let Some(min_year) = &query.min_year {
+        conditions.push("g.year_published >= ?");
+        params_vec.push(min_year);
+    }
+    if let Some(max_year) = &query.max_year {
+        conditions.push("g.year_published <= ?");
+        params_vec.push(max_year);
+    }
+    if let Some(publisher) = &query.publisher {
+        conditions.push("g.publisher = ? COLLATE NOCASE");
+        params_vec.push(publisher);
+    }
+    match query.has_rules {
+        Some(true) => conditions.push("g.rules_pdf_path IS NOT NULL"),
+        Some(false) => conditions.push("g.rules_pdf_path IS NULL"),
+        None => {}
+    }
+    let active_house_rules =
+        "EXISTS (SELECT 1 FROM house_rules hr WHERE hr.game_id = g.id AND hr.is_active = TRUE)";
+    let no_active_house_rules = format!("NOT {}", active_house_rules);
+    match query.has_house_rules {
+        Some(true) => conditions.push(active_house_rules),
+        Some(false) => conditions.push(&no_active_house_rules),
+        None => {}
+    }
+
+    let collection_status = query.collection_status.map(|status| status.as_str());
+    let in_collection_status = format!("{} = ?", COLLECTION_STATUS);
+    if let Some(status) = &collection_status {
+        conditions.push(&in_collection_status);
+        params_vec.push(status);
+    }
+
+    let tag_names: Vec<&str> = query
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    for name in &tag_names {
+        conditions.push(
+            "EXISTS (SELECT 1 FROM game_tags gt JOIN tags t ON t.id = gt.tag_id \
+             WHERE gt.game_id = g.id AND t.name = ? COLLATE NOCASE)",
+        );
+        params_vec.push(name);
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let direction = match query.order.unwrap_or(query.sort.default_order()) {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+    // Games missing the sort value go last either way; name and id break ties
+    let order_by = match query.sort {
+        GameSort::Name => format!("g.name COLLATE NOCASE {}, g.id", direction),
+        GameSort::Year => format!(
+            "g.year_published {} NULLS LAST, g.name COLLATE NOCASE",
+            direction
+        ),
+        GameSort::Complexity => format!(
+            "g.complexity_rating {} NULLS LAST, g.name COLLATE NOCASE",
+            direction
+        ),
+        GameSort::RecentlyUpdated => format!("g.updated_at {}, g.id {}", direction, direction),
+        GameSort::RecentlyPlayed => format!(
+            "g.last_played_at {} NULLS LAST, g.name COLLATE NOCASE",
+            direction
+        ),
+    };
 
     db.with_connection(|conn| {
-        // Get total count
-        let total: u32 = conn.query_row("SELECT COUNT(*) FROM games", [], |row| row.get(0))?;
+        // Get total count of matching games
+        let total: u32 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM games g {}", where_clause),
+            params_vec.as_slice(),
+            |row| row.get(0),
+        )?;
 
         // Get games with house rules count
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
             SELECT
                 g.id, g.name, g.publisher, g.year_published,
-                g.min_players, g.max_players, g.complexity_rating,
-                g.rules_pdf_path,
-                COUNT(hr.id) as house_rules_count
+                g.min_players, g.max_players, g.play_time_minutes, g.complexity_rating,
+                g.rules_pdf_path, g.updated_at, g.last_played_at,
+                (SELECT COUNT(*) FROM house_rules hr
+                 WHERE hr.game_id = g.id AND hr.is_active = TRUE) as house_rules_count,
+                (SELECT json_group_array(
+                            json_object('id', t.id, 'name', t.name, 'tag_type', t.tag_type)
+                            ORDER BY t.tag_type, t.name COLLATE NOCASE)
+                 FROM game_tags gt JOIN tags t ON t.id = gt.tag_id
+                 WHERE gt.game_id = g.id) as tags,
+                g.base_game_id
             FROM games g
-            LEFT JOIN house_rules hr ON g.id = hr.game_id AND hr.is_active = TRUE
-            GROUP BY g.id, g.name, g.publisher, g.year_published,
-                     g.min_players, g.max_players, g.complexity_rating, g.rules_pdf_path
-            ORDER BY g.name ASC
+            {}
+            ORDER BY {}
             LIMIT ? OFFSET ?
             "#,
-        )?;
+            where_clause, order_by
+        ))?;
 
-        let game_iter = stmt.query_map(params![pagination.limit, pagination.offset], |row| {
+        let mut page_params = params_vec.clone();
+        page_params.push(&pagination.limit);
+        page_params.push(&pagination.offset);
+
+        let game_iter = stmt.query_map(page_params.as_slice(), |row| {
+            let last_played_at: Option<String> = row.get(10)?;
+            let tags: String = row.get(12)?;
             Ok(GameSummary {
                 id: row.get(0)?,
                 name: row.get(1)?,
@@ -42,16 +222,35 @@ pub async fn list_games(
                 year_published: row.get(3)?,
                 min_players: row.get(4)?,
                 max_players: row.get(5)?,
-                complexity_rating: row.get(6)?,
-                has_rules_pdf: row.get::<_, Option<String>>(7)?.is_some(),
-                house_rules_count: row.get(8)?,
+                play_time_minutes: row.get(6)?,
+                complexity_rating: row.get(7)?,
+                has_rules_pdf: row.get::<_, Option<String>>(8)?.is_some(),
+                house_rules_count: row.get(11)?,
+                updated_at: parse_datetime(row, "updated_at")?,
+                last_played_at: match last_played_at {
+                    Some(_) => Some(parse_datetime(row, "last_played_at")?),
+                    None => None,
+                },
+                base_game_id: row.get(13)?,
+                tags: serde_json::from_str(&tags).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        12,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?,
             })
         })?;
 
         let games: Result<Vec<GameSummary>, _> = game_iter.collect();
         let games = games?;
 
-        Ok(PaginatedResponse::new(games, total, page, limit))
+        Ok(PaginatedResponse::new(
+            games,
+            total,
+            query.page,
+            query.limit,
+        ))
     })
 }
 
@@ -61,29 +260,13 @@ pub async fn get_game(db: &Database, game_id: GameId) -> SqliteResult<Option<Gam
             r#"
             SELECT id, name, description, publisher, year_published,
                    min_players, max_players, play_time_minutes, complexity_rating,
-                   bgg_id, rules_pdf_path, rules_text, created_at, updated_at
+                   bgg_id, rules_pdf_path, rules_text, created_at, updated_at, last_played_at,
+                   base_game_id
             FROM games WHERE id = ?
             "#,
         )?;
 
-        let result = stmt.query_row(params![game_id], |row| {
-            Ok(Game {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                publisher: row.get(3)?,
-                year_published: row.get(4)?,
-                min_players: row.get(5)?,
-                max_players: row.get(6)?,
-                play_time_minutes: row.get(7)?,
-                complexity_rating: row.get(8)?,
-                bgg_id: row.get(9)?,
-                rules_pdf_path: row.get(10)?,
-                rules_text: row.get(11)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        });
+        let result = stmt.query_row(params![game_id], game_from_row);
 
         match result {
             Ok(game) => Ok(Some(game)),
@@ -93,8 +276,141 @@ pub async fn get_game(db: &Database, game_id: GameId) -> SqliteResult<Option<Gam
     })
 }
 
+/// Every game in the library, oldest first
+pub async fn list_all_games(db: &Database) -> SqliteResult<Vec<Game>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, name, description, publisher, year_published,
+                   min_players, max_players, play_time_minutes, complexity_rating,
+                   bgg_id, rules_pdf_path, rules_text, created_at, updated_at, last_played_at,
+                   base_game_id
+            FROM games ORDER BY id ASC
+            "#,
+        )?;
+        stmt.query_map([], game_from_row)?.collect()
+    })
+}
+
+/// Owned base games on the shelf that suit the table: the player count, and the play
+/// time and complexity limits when given. Games missing a value that is filtered on,
+/// and games out on loan, are left out
+pub async fn list_playable_games(
+    db: &Database,
+    players: i32,
+    max_play_time: Option<i32>,
+    min_complexity: Option<f64>,
+    max_complexity: Option<f64>,
+) -> SqliteResult<Vec<Game>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT g.id, g.name, g.description, g.publisher, g.year_published,
+                   g.min_players, g.max_players, g.play_time_minutes, g.complexity_rating,
+                   g.bgg_id, g.rules_pdf_path, g.rules_text, g.created_at, g.updated_at,
+                   g.last_played_at, g.base_game_id
+            FROM games g
+            WHERE g.base_game_id IS NULL
+              AND {} IN ('owned', 'for_trade')
+              AND NOT EXISTS(
+                  SELECT 1 FROM game_loans l WHERE l.game_id = g.id AND l.returned_at IS NULL
+              )
+              AND g.min_players <= ?1 AND g.max_players >= ?1
+              AND (?2 IS NULL OR g.play_time_minutes <= ?2)
+              AND (?3 IS NULL OR g.complexity_rating >= ?3)
+              AND (?4 IS NULL OR g.complexity_rating <= ?4)
+            ORDER BY g.name COLLATE NOCASE
+            "#,
+            COLLECTION_STATUS
+        ))?;
+        stmt.query_map(
+            params![players, max_play_time, min_complexity, max_complexity],
+            game_from_row,
+        )?
+        .collect()
+    })
+}
+
+/// A base game's expansions sorted by name, or `None` if the game does not exist
+pub async fn list_expansions(db: &Database, game_id: GameId) -> SqliteResult<Option<Vec<Game>>> {
+    db.with_connection(|conn| {
+        let game_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if !game_exists {
+            return Ok(None);
+        }
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, name, description, publisher, year_published,
+                   min_players, max_players, play_time_minutes, complexity_rating,
+                   bgg_id, rules_pdf_path, rules_text, created_at, updated_at, last_played_at,
+                   base_game_id
+            FROM games WHERE base_game_id = ? ORDER BY name COLLATE NOCASE, id
+            "#,
+        )?;
+        stmt.query_map(params![game_id], game_from_row)?
+            .collect::<SqliteResult<Vec<Game>>>()
+            .map(Some)
+    })
+}
+
+/// Check that a game may become an expansion of `base_game_id`: the base exists and
+/// is not an expansion itself, and the game (`None` while creating it) has no expansions
+fn check_base_game_sync(
+    conn: &Connection,
+    game_id: Option<GameId>,
+    base_game_id: GameId,
+) -> SqliteResult<()> {
+    let invalid = |message: String| {
+        Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+            Some(message),
+        ))
+    };
+
+    if game_id == Some(base_game_id) {
+        return invalid("Base game cannot be the game itself".to_string());
+    }
+    let base_of_base: Option<Option<GameId>> = conn
+        .query_row(
+            "SELECT base_game_id FROM games WHERE id = ?",
+            params![base_game_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match base_of_base {
+        None => return invalid(format!("Base game {} does not exist", base_game_id)),
+        Some(Some(_)) => {
+            return invalid(format!("Base game {} is itself an expansion", base_game_id));
+        }
+        Some(None) => {}
+    }
+    if let Some(game_id) = game_id {
+        let has_expansions: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE base_game_id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if has_expansions {
+            return invalid(format!(
+                "Base game cannot be set on game {} because it has expansions of its own",
+                game_id
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub async fn create_game(db: &Database, request: CreateGameRequest) -> SqliteResult<Game> {
     db.with_transaction(|conn| {
+        if let Some(base_game_id) = request.base_game_id {
+            check_base_game_sync(conn, None, base_game_id)?;
+        }
+
         let now = Utc::now();
         let now_str = now.format("%Y-%m-%d %H:%M:%S").to_string();
 
@@ -103,8 +419,8 @@ pub async fn create_game(db: &Database, request: CreateGameRequest) -> SqliteRes
             INSERT INTO games (
                 name, description, publisher, year_published,
                 min_players, max_players, play_time_minutes, complexity_rating,
-                bgg_id, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                bgg_id, base_game_id, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             params![
                 request.name,
@@ -116,6 +432,7 @@ pub async fn create_game(db: &Database, request: CreateGameRequest) -> SqliteRes
                 request.play_time_minutes,
                 request.complexity_rating,
                 request.bgg_id,
+                request.base_game_id,
                 now_str,
                 now_str
             ],
@@ -128,29 +445,13 @@ pub async fn create_game(db: &Database, request: CreateGameRequest) -> SqliteRes
             r#"
             SELECT id, name, description, publisher, year_published,
                    min_players, max_players, play_time_minutes, complexity_rating,
-                   bgg_id, rules_pdf_path, rules_text, created_at, updated_at
+                   bgg_id, rules_pdf_path, rules_text, created_at, updated_at, last_played_at,
+                   base_game_id
             FROM games WHERE id = ?
             "#,
         )?;
 
-        stmt.query_row(params![game_id], |row| {
-            Ok(Game {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                publisher: row.get(3)?,
-                year_published: row.get(4)?,
-                min_players: row.get(5)?,
-                max_players: row.get(6)?,
-                play_time_minutes: row.get(7)?,
-                complexity_rating: row.get(8)?,
-                bgg_id: row.get(9)?,
-                rules_pdf_path: row.get(10)?,
-                rules_text: row.get(11)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        })
+        stmt.query_row(params![game_id], game_from_row)
     })
 }
 
@@ -213,6 +514,19 @@ pub async fn update_game(
             update_parts.push("bgg_id = ?");
             params_vec.push(bgg_id as &dyn rusqlite::ToSql);
         }
+        if let Some(base_game_id) = &request.base_game_id {
+            if request.clear_base_game {
+                return Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some("Base game cannot be set and cleared at once".to_string()),
+                ));
+            }
+            check_base_game_sync(conn, Some(game_id), *base_game_id)?;
+            update_parts.push("base_game_id = ?");
+            params_vec.push(base_game_id as &dyn rusqlite::ToSql);
+        } else if request.clear_base_game {
+            update_parts.push("base_game_id = NULL");
+        }
 
         if update_parts.is_empty() {
             // No updates requested, just return the current game
@@ -227,12 +541,63 @@ pub async fn update_game(
 
         conn.execute(&query, params_vec.as_slice())?;
 
+        if request.base_game_id.is_some() || request.clear_base_game {
+            // Only a base game's own expansions can be in play in its sessions
+            conn.execute(
+                r#"
+                DELETE FROM chat_session_expansions
+                WHERE game_id = ?1 AND session_id NOT IN (
+                    SELECT s.id FROM chat_sessions s
+                    JOIN games g ON g.base_game_id = s.game_id
+                    WHERE g.id = ?1
+                )
+                "#,
+                params![game_id],
+            )?;
+        }
+
         get_game_by_id_sync(conn, game_id).map(Some)
     })
 }
 
 pub async fn delete_game(db: &Database, game_id: GameId) -> SqliteResult<bool> {
-    db.with_connection(|conn| {
+    db.with_transaction(|conn| {
+        // Expansions outlive their base game as standalone games
+        conn.execute(
+            "UPDATE games SET base_game_id = NULL WHERE base_game_id = ?",
+            params![game_id],
+        )?;
+        delete_game_plays_sync(conn, game_id)?;
+        delete_game_collection_sync(conn, game_id)?;
+        delete_description_embedding_sync(conn, game_id)?;
+        invalidate_answer_cache_sync(conn, Some(game_id))?;
+        delete_game_faq_sync(conn, game_id)?;
+        conn.execute(
+            "DELETE FROM quick_references WHERE game_id = ?",
+            params![game_id],
+        )?;
+        conn.execute(
+            "DELETE FROM eval_questions WHERE game_id = ?",
+            params![game_id],
+        )?;
+        conn.execute("DELETE FROM eval_runs WHERE game_id = ?", params![game_id])?;
+        conn.execute(
+            "DELETE FROM chat_session_games WHERE game_id = ?",
+            params![game_id],
+        )?;
+        conn.execute(
+            "DELETE FROM chat_session_expansions WHERE game_id = ?",
+            params![game_id],
+        )?;
+        conn.execute("DELETE FROM game_tags WHERE game_id = ?", params![game_id])?;
+        conn.execute(
+            "DELETE FROM league_games WHERE game_id = ?",
+            params![game_id],
+        )?;
+        conn.execute(
+            "DELETE FROM prompt_templates WHERE game_id = ?",
+            params![game_id],
+        )?;
         let rows_affected = conn.execute("DELETE FROM games WHERE id = ?", params![game_id])?;
         Ok(rows_affected > 0)
     })
@@ -294,33 +659,152 @@ pub async fn get_game_rules_info(
     })
 }
 
+fn game_id_by_bgg_id_sync(conn: &Connection, bgg_id: i32) -> SqliteResult<Option<GameId>> {
+    conn.query_row(
+        "SELECT id FROM games WHERE bgg_id = ?",
+        params![bgg_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Create or update games from BGG ranks, matched by `bgg_id`.
+/// Only the name and year are known from the ranks file, so nothing else is touched
+pub async fn import_bgg_ranks(db: &Database, ranks: &[BggRank]) -> SqliteResult<BggImportSummary> {
+    db.with_transaction(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut summary = BggImportSummary::default();
+
+        for rank in ranks {
+            let game_id = match game_id_by_bgg_id_sync(conn, rank.bgg_id)? {
+                Some(game_id) => {
+                    conn.execute(
+                        r#"
+                        UPDATE games
+                        SET name = ?, year_published = COALESCE(?, year_published), updated_at = ?
+                        WHERE id = ?
+                        "#,
+                        params![rank.name, rank.year_published, now_str, game_id],
+                    )?;
+                    summary.updated += 1;
+                    game_id
+                }
+                None => {
+                    conn.execute(
+                        r#"
+                        INSERT INTO games (name, year_published, bgg_id, created_at, updated_at)
+                        VALUES (?, ?, ?, ?, ?)
+                        "#,
+                        params![
+                            rank.name,
+                            rank.year_published,
+                            rank.bgg_id,
+                            now_str,
+                            now_str
+                        ],
+                    )?;
+                    summary.created += 1;
+                    conn.last_insert_rowid()
+                }
+            };
+            summary.game_ids.push(game_id);
+        }
+
+        Ok(summary)
+    })
+}
+
+/// Create or update games from BGG `thing` items, matched by `bgg_id`.
+/// Fields BGG leaves blank keep their current values; mechanic and category tags are replaced
+pub async fn import_bgg_things(
+    db: &Database,
+    things: &[BggThing],
+) -> SqliteResult<BggImportSummary> {
+    db.with_transaction(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut summary = BggImportSummary::default();
+
+        for thing in things {
+            let game_id = match game_id_by_bgg_id_sync(conn, thing.bgg_id)? {
+                Some(game_id) => {
+                    conn.execute(
+                        r#"
+                        UPDATE games SET
+                            name = ?,
+                            description = COALESCE(?, description),
+                            publisher = COALESCE(?, publisher),
+                            year_published = COALESCE(?, year_published),
+                            min_players = COALESCE(?, min_players),
+                            max_players = COALESCE(?, max_players),
+                            play_time_minutes = COALESCE(?, play_time_minutes),
+                            complexity_rating = COALESCE(?, complexity_rating),
+                            updated_at = ?
+                        WHERE id = ?
+                        "#,
+                        params![
+                            thing.name,
+                            thing.description,
+                            thing.publisher,
+                            thing.year_published,
+                            thing.min_players,
+                            thing.max_players,
+                            thing.play_time_minutes,
+                            thing.weight,
+                            now_str,
+                            game_id
+                        ],
+                    )?;
+                    summary.updated += 1;
+                    game_id
+                }
+                None => {
+                    conn.execute(
+                        r#"
+                        INSERT INTO games (
+                            name, description, publisher, year_published,
+                            min_players, max_players, play_time_minutes, complexity_rating,
+                            bgg_id, created_at, updated_at
+                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        "#,
+                        params![
+                            thing.name,
+                            thing.description,
+                            thing.publisher,
+                            thing.year_published,
+                            thing.min_players,
+                            thing.max_players,
+                            thing.play_time_minutes,
+                            thing.weight,
+                            thing.bgg_id,
+                            now_str,
+                            now_str
+                        ],
+                    )?;
+                    summary.created += 1;
+                    conn.last_insert_rowid()
+                }
+            };
+
+            set_game_tags_sync(conn, game_id, TagType::Mechanic, &thing.mechanics)?;
+            set_game_tags_sync(conn, game_id, TagType::Category, &thing.categories)?;
+            summary.game_ids.push(game_id);
+        }
+
+        Ok(summary)
+    })
+}
+
 // Helper function for synchronous game retrieval within transactions
-fn get_game_by_id_sync(conn: &rusqlite::Connection, game_id: GameId) -> SqliteResult<Game> {
+fn get_game_by_id_sync(conn: &Connection, game_id: GameId) -> SqliteResult<Game> {
     let mut stmt = conn.prepare(
         r#"
         SELECT id, name, description, publisher, year_published,
                min_players, max_players, play_time_minutes, complexity_rating,
-               bgg_id, rules_pdf_path, rules_text, created_at, updated_at
+               bgg_id, rules_pdf_path, rules_text, created_at, updated_at, last_played_at,
+               base_game_id
         FROM games WHERE id = ?
         "#,
     )?;
 
-    stmt.query_row(params![game_id], |row| {
-        Ok(Game {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            publisher: row.get(3)?,
-            year_published: row.get(4)?,
-            min_players: row.get(5)?,
-            max_players: row.get(6)?,
-            play_time_minutes: row.get(7)?,
-            complexity_rating: row.get(8)?,
-            bgg_id: row.get(9)?,
-            rules_pdf_path: row.get(10)?,
-            rules_text: row.get(11)?,
-            created_at: parse_datetime(row, "created_at")?,
-            updated_at: parse_datetime(row, "updated_at")?,
-        })
-    })
+    stmt.query_row(params![game_id], game_from_row)
 }
@@ -1,6 +1,6 @@
 use rusqlite::{params, Result as SqliteResult};
 use chrono::Utc;
-use crate::models::{HouseRule, HouseRuleId, GameId, CreateHouseRuleRequest, UpdateHouseRuleRequest, PaginatedResponse};
+use crate::models::{HouseRule, HouseRuleAnalysis, HouseRuleId, GameId, CreateHouseRuleRequest, UpdateHouseRuleRequest, PaginatedResponse};
 use super::{Database, parse_datetime, PaginationInfo};
 
 pub async fn list_house_rules(db: &Database, game_id: GameId, page: u32, limit: u32) -> SqliteResult<PaginatedResponse<HouseRule>> {
@@ -17,7 +17,7 @@ pub async fn list_house_rules(db: &Database, game_id: GameId, page: u32, limit:
         // Get house rules for the game
         let mut stmt = conn.prepare(
             r#"
-            SELECT id, game_id, title, description, category, is_active, created_at, updated_at
+            SELECT id, game_id, title, description, category, is_active, created_at, updated_at, analysis
             FROM house_rules 
             WHERE game_id = ?
             ORDER BY created_at DESC
@@ -25,18 +25,7 @@ pub async fn list_house_rules(db: &Database, game_id: GameId, page: u32, limit:
             "#
         )?;
 
-        let house_rule_iter = stmt.query_map(params![game_id, pagination.limit, pagination.offset], |row| {
-            Ok(HouseRule {
-                id: row.get(0)?,
-                game_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                category: row.get(4)?,
-                is_active: row.get(5)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        })?;
+        let house_rule_iter = stmt.query_map(params![game_id, pagination.limit, pagination.offset], house_rule_from_row)?;
 
         let house_rules: Result<Vec<HouseRule>, _> = house_rule_iter.collect();
         let house_rules = house_rules?;
@@ -49,23 +38,12 @@ pub async fn get_house_rule(db: &Database, house_rule_id: HouseRuleId) -> Sqlite
     db.with_connection(|conn| {
         let mut stmt = conn.prepare(
             r#"
-            SELECT id, game_id, title, description, category, is_active, created_at, updated_at
+            SELECT id, game_id, title, description, category, is_active, created_at, updated_at, analysis
             FROM house_rules WHERE id = ?
             "#
         )?;
 
-        let result = stmt.query_row(params![house_rule_id], |row| {
-            Ok(HouseRule {
-                id: row.get(0)?,
-                game_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                category: row.get(4)?,
-                is_active: row.get(5)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        });
+        let result = stmt.query_row(params![house_rule_id], house_rule_from_row);
 
         match result {
             Ok(house_rule) => Ok(Some(house_rule)),
@@ -116,23 +94,12 @@ pub async fn create_house_rule(db: &Database, request: CreateHouseRuleRequest) -
         // Fetch the created house rule
         let mut stmt = conn.prepare(
             r#"
-            SELECT id, game_id, title, description, category, is_active, created_at, updated_at
+            SELECT id, game_id, title, description, category, is_active, created_at, updated_at, analysis
             FROM house_rules WHERE id = ?
             "#
         )?;
 
-        stmt.query_row(params![house_rule_id], |row| {
-            Ok(HouseRule {
-                id: row.get(0)?,
-                game_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                category: row.get(4)?,
-                is_active: row.get(5)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        })
+        stmt.query_row(params![house_rule_id], house_rule_from_row)
     })
 }
 
@@ -172,6 +139,11 @@ pub async fn update_house_rule(db: &Database, house_rule_id: HouseRuleId, reques
             params_vec.push(is_active as &dyn rusqlite::ToSql);
         }
 
+        // The stored analysis judged the old wording
+        if request.title.is_some() || request.description.is_some() {
+            update_parts.push("analysis = NULL");
+        }
+
         if update_parts.is_empty() {
             // No updates requested, just return the current house rule
             return get_house_rule_by_id_sync(conn, house_rule_id).map(Some);
@@ -206,14 +178,14 @@ pub async fn list_house_rules_by_game(db: &Database, game_id: GameId, active_onl
     db.with_connection(|conn| {
         let query = if active_only {
             r#"
-            SELECT id, game_id, title, description, category, is_active, created_at, updated_at
+            SELECT id, game_id, title, description, category, is_active, created_at, updated_at, analysis
             FROM house_rules 
             WHERE game_id = ? AND is_active = TRUE
             ORDER BY created_at DESC
             "#
         } else {
             r#"
-            SELECT id, game_id, title, description, category, is_active, created_at, updated_at
+            SELECT id, game_id, title, description, category, is_active, created_at, updated_at, analysis
             FROM house_rules 
             WHERE game_id = ?
             ORDER BY created_at DESC
@@ -222,43 +194,53 @@ pub async fn list_house_rules_by_game(db: &Database, game_id: GameId, active_onl
 
         let mut stmt = conn.prepare(query)?;
 
-        let house_rule_iter = stmt.query_map(params![game_id], |row| {
-            Ok(HouseRule {
-                id: row.get(0)?,
-                game_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                category: row.get(4)?,
-                is_active: row.get(5)?,
-                created_at: parse_datetime(row, "created_at")?,
-                updated_at: parse_datetime(row, "updated_at")?,
-            })
-        })?;
+        let house_rule_iter = stmt.query_map(params![game_id], house_rule_from_row)?;
 
         let house_rules: Result<Vec<HouseRule>, _> = house_rule_iter.collect();
         house_rules
     })
 }
 
+pub async fn set_house_rule_analysis(db: &Database, house_rule_id: HouseRuleId, analysis: &HouseRuleAnalysis) -> SqliteResult<bool> {
+    db.with_connection(|conn| {
+        let analysis_json = serde_json::to_string(analysis)
+            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))?;
+
+        let rows_affected = conn.execute(
+            "UPDATE house_rules SET analysis = ? WHERE id = ?",
+            params![analysis_json, house_rule_id]
+        )?;
+        Ok(rows_affected > 0)
+    })
+}
+
+fn house_rule_from_row(row: &rusqlite::Row) -> SqliteResult<HouseRule> {
+    let analysis: Option<String> = row.get(8)?;
+    let analysis = analysis.and_then(|s| {
+        serde_json::from_str::<HouseRuleAnalysis>(&s).ok()
+    });
+
+    Ok(HouseRule {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        category: row.get(4)?,
+        is_active: row.get(5)?,
+        analysis,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+    })
+}
+
 // Helper function for synchronous house rule retrieval within transactions
 fn get_house_rule_by_id_sync(conn: &rusqlite::Connection, house_rule_id: HouseRuleId) -> SqliteResult<HouseRule> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, game_id, title, description, category, is_active, created_at, updated_at
+        SELECT id, game_id, title, description, category, is_active, created_at, updated_at, analysis
         FROM house_rules WHERE id = ?
         "#
     )?;
 
-    stmt.query_row(params![house_rule_id], |row| {
-        Ok(HouseRule {
-            id: row.get(0)?,
-            game_id: row.get(1)?,
-            title: row.get(2)?,
-            description: row.get(3)?,
-            category: row.get(4)?,
-            is_active: row.get(5)?,
-            created_at: parse_datetime(row, "created_at")?,
-            updated_at: parse_datetime(row, "updated_at")?,
-        })
-    })
+    stmt.query_row(params![house_rule_id], house_rule_from_row)
 }
\ No newline at end of file
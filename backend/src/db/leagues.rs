@@ -0,0 +1,213 @@
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, format_datetime, parse_datetime, plays::plays_in_order_sync};
+use crate::models::{
+    CreateLeagueRequest, GameId, League, LeagueId, Play, PlayerId, UpdateLeagueRequest,
+};
+
+const LEAGUE_COLUMNS: &str = r#"
+    l.id, l.name, l.description, l.starts_at, l.ends_at, l.created_at, l.updated_at,
+    (SELECT json_group_array(lp.player_id ORDER BY lp.player_id)
+     FROM league_players lp WHERE lp.league_id = l.id) AS player_ids,
+    (SELECT json_group_array(lg.game_id ORDER BY lg.game_id)
+     FROM league_games lg WHERE lg.league_id = l.id) AS game_ids
+"#;
+
+fn ids_column(row: &Row, index: usize) -> SqliteResult<Vec<i64>> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn league_from_row(row: &Row) -> SqliteResult<League> {
+    let starts_at: Option<String> = row.get(3)?;
+    let ends_at: Option<String> = row.get(4)?;
+    Ok(League {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        starts_at: match starts_at {
+            Some(_) => Some(parse_datetime(row, "starts_at")?),
+            None => None,
+        },
+        ends_at: match ends_at {
+            Some(_) => Some(parse_datetime(row, "ends_at")?),
+            None => None,
+        },
+        player_ids: ids_column(row, 7)?,
+        game_ids: ids_column(row, 8)?,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+    })
+}
+
+fn invalid_reference<T>(message: String) -> SqliteResult<T> {
+    Err(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+        Some(message),
+    ))
+}
+
+fn get_league_sync(conn: &Connection, league_id: LeagueId) -> SqliteResult<Option<League>> {
+    conn.query_row(
+        &format!("SELECT {} FROM leagues l WHERE l.id = ?", LEAGUE_COLUMNS),
+        params![league_id],
+        league_from_row,
+    )
+    .optional()
+}
+
+fn set_league_players_sync(
+    conn: &Connection,
+    league_id: LeagueId,
+    player_ids: &[PlayerId],
+) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM league_players WHERE league_id = ?",
+        params![league_id],
+    )?;
+    for player_id in player_ids {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM players WHERE id = ?)",
+            params![player_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return invalid_reference(format!("Player {} does not exist", player_id));
+        }
+        conn.execute(
+            "INSERT OR IGNORE INTO league_players (league_id, player_id) VALUES (?, ?)",
+            params![league_id, player_id],
+        )?;
+    }
+    Ok(())
+}
+
+fn set_league_games_sync(
+    conn: &Connection,
+    league_id: LeagueId,
+    game_ids: &[GameId],
+) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM league_games WHERE league_id = ?",
+        params![league_id],
+    )?;
+    for game_id in game_ids {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return invalid_reference(format!("Game {} does not exist", game_id));
+        }
+        conn.execute(
+            "INSERT OR IGNORE INTO league_games (league_id, game_id) VALUES (?, ?)",
+            params![league_id, game_id],
+        )?;
+    }
+    Ok(())
+}
+
+pub async fn list_leagues(db: &Database) -> SqliteResult<Vec<League>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM leagues l ORDER BY l.name COLLATE NOCASE",
+            LEAGUE_COLUMNS
+        ))?;
+        stmt.query_map([], league_from_row)?.collect()
+    })
+}
+
+pub async fn get_league(db: &Database, league_id: LeagueId) -> SqliteResult<Option<League>> {
+    db.with_connection(|conn| get_league_sync(conn, league_id))
+}
+
+pub async fn create_league(db: &Database, request: CreateLeagueRequest) -> SqliteResult<League> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT INTO leagues (name, description, starts_at, ends_at) VALUES (?, ?, ?, ?)",
+            params![
+                request.name.trim(),
+                request.description,
+                request.starts_at.map(format_datetime),
+                request.ends_at.map(format_datetime)
+            ],
+        )?;
+        let league_id = conn.last_insert_rowid();
+        set_league_players_sync(conn, league_id, &request.player_ids)?;
+        set_league_games_sync(conn, league_id, &request.game_ids)?;
+        get_league_sync(conn, league_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+pub async fn update_league(
+    db: &Database,
+    league_id: LeagueId,
+    request: UpdateLeagueRequest,
+) -> SqliteResult<Option<League>> {
+    db.with_transaction(|conn| {
+        let rows_affected = conn.execute(
+            r#"
+            UPDATE leagues SET
+                name = COALESCE(?, name),
+                description = COALESCE(?, description),
+                starts_at = COALESCE(?, starts_at),
+                ends_at = COALESCE(?, ends_at),
+                updated_at = ?
+            WHERE id = ?
+            "#,
+            params![
+                request.name.as_deref().map(str::trim),
+                request.description,
+                request.starts_at.map(format_datetime),
+                request.ends_at.map(format_datetime),
+                format_datetime(Utc::now()),
+                league_id
+            ],
+        )?;
+        if rows_affected == 0 {
+            return Ok(None);
+        }
+        if let Some(player_ids) = &request.player_ids {
+            set_league_players_sync(conn, league_id, player_ids)?;
+        }
+        if let Some(game_ids) = &request.game_ids {
+            set_league_games_sync(conn, league_id, game_ids)?;
+        }
+        get_league_sync(conn, league_id)
+    })
+}
+
+pub async fn delete_league(db: &Database, league_id: LeagueId) -> SqliteResult<bool> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "DELETE FROM league_players WHERE league_id = ?",
+            params![league_id],
+        )?;
+        conn.execute(
+            "DELETE FROM league_games WHERE league_id = ?",
+            params![league_id],
+        )?;
+        let rows_affected = conn.execute("DELETE FROM leagues WHERE id = ?", params![league_id])?;
+        Ok(rows_affected > 0)
+    })
+}
+
+/// Plays of the league's games during its season, oldest first, or of just
+/// `game_id` when given
+pub async fn league_plays(
+    db: &Database,
+    league: &League,
+    game_id: Option<GameId>,
+) -> SqliteResult<Vec<Play>> {
+    let game_ids = match game_id {
+        Some(game_id) => vec![game_id],
+        None => league.game_ids.clone(),
+    };
+    db.with_connection(|conn| {
+        plays_in_order_sync(conn, &game_ids, league.starts_at, league.ends_at)
+    })
+}
@@ -1,10 +1,24 @@
 use rusqlite::{Connection, Result as SqliteResult, Row};
 use std::sync::{Arc, Mutex};
 
+pub mod answer_cache;
+pub mod backup;
 pub mod chat;
+pub mod collection;
+pub mod embedding_cache;
 pub mod embeddings;
+pub mod evaluations;
+pub mod faq;
+pub mod game_descriptions;
 pub mod games;
 pub mod house_rules;
+pub mod leagues;
+pub mod players;
+pub mod plays;
+pub mod prompt_templates;
+pub mod quick_references;
+pub mod stats;
+pub mod tags;
 
 // Re-exports are available but not used globally to avoid namespace pollution
 
@@ -0,0 +1,70 @@
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{CreatePlayerRequest, Player, PlayerId, UpdatePlayerRequest};
+
+const PLAYER_COLUMNS: &str = "pl.id, pl.name, pl.created_at, \
+                              (SELECT COUNT(*) FROM play_players pp WHERE pp.player_id = pl.id) AS play_count, \
+                              (SELECT COUNT(*) FROM play_players pp WHERE pp.player_id = pl.id AND pp.is_winner) AS win_count";
+
+fn player_from_row(row: &Row) -> SqliteResult<Player> {
+    Ok(Player {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        play_count: row.get(3)?,
+        win_count: row.get(4)?,
+        created_at: parse_datetime(row, "created_at")?,
+    })
+}
+
+pub fn get_player_sync(conn: &Connection, player_id: PlayerId) -> SqliteResult<Option<Player>> {
+    conn.query_row(
+        &format!("SELECT {} FROM players pl WHERE pl.id = ?", PLAYER_COLUMNS),
+        params![player_id],
+        player_from_row,
+    )
+    .optional()
+}
+
+/// Every player with their play and win counts, sorted by name
+pub async fn list_players(db: &Database) -> SqliteResult<Vec<Player>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM players pl ORDER BY pl.name COLLATE NOCASE",
+            PLAYER_COLUMNS
+        ))?;
+        stmt.query_map([], player_from_row)?.collect()
+    })
+}
+
+pub async fn get_player(db: &Database, player_id: PlayerId) -> SqliteResult<Option<Player>> {
+    db.with_connection(|conn| get_player_sync(conn, player_id))
+}
+
+pub async fn create_player(db: &Database, request: CreatePlayerRequest) -> SqliteResult<Player> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT INTO players (name) VALUES (?)",
+            params![request.name.trim()],
+        )?;
+        get_player_sync(conn, conn.last_insert_rowid())?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+pub async fn update_player(
+    db: &Database,
+    player_id: PlayerId,
+    request: UpdatePlayerRequest,
+) -> SqliteResult<Option<Player>> {
+    db.with_transaction(|conn| {
+        let name = request.name.as_deref().map(str::trim);
+        let rows_affected = conn.execute(
+            "UPDATE players SET name = COALESCE(?, name) WHERE id = ?",
+            params![name, player_id],
+        )?;
+        if rows_affected == 0 {
+            return Ok(None);
+        }
+        get_player_sync(conn, player_id)
+    })
+}
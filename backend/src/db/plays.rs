@@ -0,0 +1,447 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, PaginationInfo, format_datetime, parse_datetime};
+use crate::models::{
+    ChatSessionId, CreatePlayRequest, GameId, PaginatedResponse, Play, PlayId, PlayListQuery,
+    PlayPlayerRequest, PlayerId, UpdatePlayRequest,
+};
+
+const PLAY_COLUMNS: &str = r#"
+    p.id, p.game_id, g.name, p.played_at, p.duration_minutes, p.location, p.notes,
+    p.created_at, p.updated_at,
+    (SELECT json_group_array(json_object(
+                'player_id', pl.id, 'name', pl.name, 'score', pp.score,
+                'winner', json(CASE WHEN pp.is_winner THEN 'true' ELSE 'false' END),
+                'team', pp.team)
+            ORDER BY pp.position)
+     FROM play_players pp JOIN players pl ON pl.id = pp.player_id
+     WHERE pp.play_id = p.id) AS players,
+    (SELECT json_group_array(pe.game_id ORDER BY pe.position)
+     FROM play_expansions pe WHERE pe.play_id = p.id) AS expansion_ids,
+    (SELECT json_group_array(pcs.session_id ORDER BY pcs.session_id)
+     FROM play_chat_sessions pcs WHERE pcs.play_id = p.id) AS chat_session_ids
+"#;
+
+fn json_column<T: serde::de::DeserializeOwned>(row: &Row, index: usize) -> SqliteResult<T> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn play_from_row(row: &Row) -> SqliteResult<Play> {
+    Ok(Play {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        game_name: row.get(2)?,
+        played_at: parse_datetime(row, "played_at")?,
+        duration_minutes: row.get(4)?,
+        location: row.get(5)?,
+        notes: row.get(6)?,
+        players: json_column(row, 9)?,
+        expansion_ids: json_column(row, 10)?,
+        chat_session_ids: json_column(row, 11)?,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+    })
+}
+
+fn invalid_reference<T>(message: String) -> SqliteResult<T> {
+    Err(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+        Some(message),
+    ))
+}
+
+fn get_play_sync(conn: &Connection, play_id: PlayId) -> SqliteResult<Option<Play>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM plays p JOIN games g ON g.id = p.game_id WHERE p.id = ?",
+            PLAY_COLUMNS
+        ),
+        params![play_id],
+        play_from_row,
+    )
+    .optional()
+}
+
+/// The player with this name, ignoring case, created if needed
+pub fn player_id_sync(conn: &Connection, name: &str) -> SqliteResult<PlayerId> {
+    let name = name.trim();
+    conn.execute(
+        "INSERT OR IGNORE INTO players (name) VALUES (?)",
+        params![name],
+    )?;
+    conn.query_row(
+        "SELECT id FROM players WHERE name = ?",
+        params![name],
+        |row| row.get(0),
+    )
+}
+
+fn set_play_players_sync(
+    conn: &Connection,
+    play_id: PlayId,
+    players: &[PlayPlayerRequest],
+) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM play_players WHERE play_id = ?",
+        params![play_id],
+    )?;
+    for (position, player) in players.iter().enumerate() {
+        let player_id = player_id_sync(conn, &player.name)?;
+        conn.execute(
+            r#"
+            INSERT INTO play_players (play_id, player_id, position, score, is_winner, team)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                play_id,
+                player_id,
+                position as i64,
+                player.score,
+                player.winner,
+                player
+                    .team
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|team| !team.is_empty())
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Only expansions of the played game can be used
+fn set_play_expansions_sync(
+    conn: &Connection,
+    play_id: PlayId,
+    game_id: GameId,
+    expansion_ids: &[GameId],
+) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM play_expansions WHERE play_id = ?",
+        params![play_id],
+    )?;
+    for (position, expansion_id) in expansion_ids.iter().enumerate() {
+        let is_expansion: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ? AND base_game_id = ?)",
+            params![expansion_id, game_id],
+            |row| row.get(0),
+        )?;
+        if !is_expansion {
+            return invalid_reference(format!(
+                "Game {} is not an expansion of game {}",
+                expansion_id, game_id
+            ));
+        }
+        conn.execute(
+            "INSERT OR IGNORE INTO play_expansions (play_id, game_id, position) VALUES (?, ?, ?)",
+            params![play_id, expansion_id, position as i64],
+        )?;
+    }
+    Ok(())
+}
+
+/// Only sessions about the played game, alone or in a comparison, can be linked
+fn set_play_chat_sessions_sync(
+    conn: &Connection,
+    play_id: PlayId,
+    game_id: GameId,
+    session_ids: &[ChatSessionId],
+) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM play_chat_sessions WHERE play_id = ?",
+        params![play_id],
+    )?;
+    for session_id in session_ids {
+        let session_game_id: Option<GameId> = conn
+            .query_row(
+                "SELECT game_id FROM chat_sessions WHERE id = ?",
+                params![session_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(session_game_id) = session_game_id else {
+            return invalid_reference(format!("Chat session {} does not exist", session_id));
+        };
+        let compares_game: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM chat_session_games WHERE session_id = ? AND game_id = ?)",
+            params![session_id, game_id],
+            |row| row.get(0),
+        )?;
+        if session_game_id != game_id && !compares_game {
+            return invalid_reference(format!(
+                "Chat session {} is not about game {}",
+                session_id, game_id
+            ));
+        }
+        conn.execute(
+            "INSERT OR IGNORE INTO play_chat_sessions (play_id, session_id) VALUES (?, ?)",
+            params![play_id, session_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Keep the game's last played time in step with its most recent play
+fn refresh_last_played_sync(conn: &Connection, game_id: GameId) -> SqliteResult<()> {
+    conn.execute(
+        "UPDATE games SET last_played_at = (SELECT MAX(played_at) FROM plays WHERE game_id = ?1) WHERE id = ?1",
+        params![game_id],
+    )?;
+    Ok(())
+}
+
+/// Drop a game's own plays
+pub fn delete_plays_of_game_sync(conn: &Connection, game_id: GameId) -> SqliteResult<()> {
+    for table in ["play_players", "play_expansions", "play_chat_sessions"] {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE play_id IN (SELECT id FROM plays WHERE game_id = ?)",
+                table
+            ),
+            params![game_id],
+        )?;
+    }
+    conn.execute("DELETE FROM plays WHERE game_id = ?", params![game_id])?;
+    Ok(())
+}
+
+/// Drop a game's plays and its use as an expansion in other plays
+pub fn delete_game_plays_sync(conn: &Connection, game_id: GameId) -> SqliteResult<()> {
+    delete_plays_of_game_sync(conn, game_id)?;
+    conn.execute(
+        "DELETE FROM play_expansions WHERE game_id = ?",
+        params![game_id],
+    )?;
+    Ok(())
+}
+
+pub async fn list_plays(
+    db: &Database,
+    query: &PlayListQuery,
+) -> SqliteResult<PaginatedResponse<Play>> {
+    let pagination = PaginationInfo::new(query.page, query.limit);
+
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params_vec: Vec<&dyn rusqlite::ToSql> = Vec::new();
+
+    if let Some(game_id) = &query.game_id {
+        conditions.push("p.game_id = ?");
+        params_vec.push(game_id);
+    }
+    let player = query.player.as_deref().map(str::trim);
+    if let Some(player) = &player {
+        conditions.push(
+            "EXISTS (SELECT 1 FROM play_players pp JOIN players pl ON pl.id = pp.player_id \
+             WHERE pp.play_id = p.id AND pl.name = ?)",
+        );
+        params_vec.push(player);
+    }
+    let from = query.from.map(format_datetime);
+    if let Some(from) = &from {
+        conditions.push("p.played_at >= ?");
+        params_vec.push(from);
+    }
+    let to = query.to.map(format_datetime);
+    if let Some(to) = &to {
+        conditions.push("p.played_at <= ?");
+        params_vec.push(to);
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    db.with_connection(|conn| {
+        let total: u32 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM plays p {}", where_clause),
+            params_vec.as_slice(),
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT {} FROM plays p JOIN games g ON g.id = p.game_id
+            {}
+            ORDER BY p.played_at DESC, p.id DESC
+            LIMIT ? OFFSET ?
+            "#,
+            PLAY_COLUMNS, where_clause
+        ))?;
+
+        let mut page_params = params_vec.clone();
+        page_params.push(&pagination.limit);
+        page_params.push(&pagination.offset);
+
+        let plays = stmt
+            .query_map(page_params.as_slice(), play_from_row)?
+            .collect::<SqliteResult<Vec<Play>>>()?;
+
+        Ok(PaginatedResponse::new(
+            plays,
+            total,
+            query.page,
+            query.limit,
+        ))
+    })
+}
+
+/// Plays of the given games (any game when empty) within a period, oldest first
+pub fn plays_in_order_sync(
+    conn: &Connection,
+    game_ids: &[GameId],
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> SqliteResult<Vec<Play>> {
+    let game_ids = serde_json::to_string(game_ids)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {} FROM plays p JOIN games g ON g.id = p.game_id
+        WHERE (?1 = '[]' OR p.game_id IN (SELECT value FROM json_each(?1)))
+          AND (?2 IS NULL OR p.played_at >= ?2) AND (?3 IS NULL OR p.played_at <= ?3)
+        ORDER BY p.played_at ASC, p.id ASC
+        "#,
+        PLAY_COLUMNS
+    ))?;
+    stmt.query_map(
+        params![game_ids, from.map(format_datetime), to.map(format_datetime)],
+        play_from_row,
+    )?
+    .collect()
+}
+
+pub async fn get_play(db: &Database, play_id: PlayId) -> SqliteResult<Option<Play>> {
+    db.with_connection(|conn| get_play_sync(conn, play_id))
+}
+
+pub async fn create_play(db: &Database, request: CreatePlayRequest) -> SqliteResult<Play> {
+    db.with_transaction(|conn| {
+        let game_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![request.game_id],
+            |row| row.get(0),
+        )?;
+        if !game_exists {
+            return invalid_reference(format!("Game {} does not exist", request.game_id));
+        }
+
+        let now_str = format_datetime(Utc::now());
+        let played_at = request
+            .played_at
+            .map(format_datetime)
+            .unwrap_or_else(|| now_str.clone());
+        conn.execute(
+            r#"
+            INSERT INTO plays (
+                game_id, played_at, duration_minutes, location, notes, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                request.game_id,
+                played_at,
+                request.duration_minutes,
+                request.location,
+                request.notes,
+                now_str,
+                now_str
+            ],
+        )?;
+        let play_id = conn.last_insert_rowid();
+
+        set_play_players_sync(conn, play_id, &request.players)?;
+        set_play_expansions_sync(conn, play_id, request.game_id, &request.expansion_ids)?;
+        set_play_chat_sessions_sync(conn, play_id, request.game_id, &request.chat_session_ids)?;
+        refresh_last_played_sync(conn, request.game_id)?;
+
+        get_play_sync(conn, play_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+pub async fn update_play(
+    db: &Database,
+    play_id: PlayId,
+    request: UpdatePlayRequest,
+) -> SqliteResult<Option<Play>> {
+    db.with_transaction(|conn| {
+        let game_id: Option<GameId> = conn
+            .query_row(
+                "SELECT game_id FROM plays WHERE id = ?",
+                params![play_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(game_id) = game_id else {
+            return Ok(None);
+        };
+
+        conn.execute(
+            r#"
+            UPDATE plays SET
+                played_at = COALESCE(?, played_at),
+                duration_minutes = COALESCE(?, duration_minutes),
+                location = COALESCE(?, location),
+                notes = COALESCE(?, notes),
+                updated_at = ?
+            WHERE id = ?
+            "#,
+            params![
+                request.played_at.map(format_datetime),
+                request.duration_minutes,
+                request.location,
+                request.notes,
+                format_datetime(Utc::now()),
+                play_id
+            ],
+        )?;
+
+        if let Some(players) = &request.players {
+            set_play_players_sync(conn, play_id, players)?;
+        }
+        if let Some(expansion_ids) = &request.expansion_ids {
+            set_play_expansions_sync(conn, play_id, game_id, expansion_ids)?;
+        }
+        if let Some(session_ids) = &request.chat_session_ids {
+            set_play_chat_sessions_sync(conn, play_id, game_id, session_ids)?;
+        }
+        refresh_last_played_sync(conn, game_id)?;
+
+        get_play_sync(conn, play_id)
+    })
+}
+
+pub async fn delete_play(db: &Database, play_id: PlayId) -> SqliteResult<bool> {
+    db.with_transaction(|conn| {
+        let game_id: Option<GameId> = conn
+            .query_row(
+                "SELECT game_id FROM plays WHERE id = ?",
+                params![play_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(game_id) = game_id else {
+            return Ok(false);
+        };
+
+        conn.execute(
+            "DELETE FROM play_players WHERE play_id = ?",
+            params![play_id],
+        )?;
+        conn.execute(
+            "DELETE FROM play_expansions WHERE play_id = ?",
+            params![play_id],
+        )?;
+        conn.execute(
+            "DELETE FROM play_chat_sessions WHERE play_id = ?",
+            params![play_id],
+        )?;
+        conn.execute("DELETE FROM plays WHERE id = ?", params![play_id])?;
+        refresh_last_played_sync(conn, game_id)?;
+        Ok(true)
+    })
+}
@@ -0,0 +1,200 @@
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{
+    CreatePromptTemplateRequest, GameId, PromptTemplate, PromptTemplateId,
+    UpdatePromptTemplateRequest,
+};
+
+const TEMPLATE_COLUMNS: &str =
+    "id, game_id, name, persona, template, is_active, created_at, updated_at";
+
+fn template_from_row(row: &Row) -> SqliteResult<PromptTemplate> {
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        game_id: row.get(1)?,
+        name: row.get(2)?,
+        persona: row.get(3)?,
+        template: row.get(4)?,
+        is_active: row.get(5)?,
+        created_at: parse_datetime(row, "created_at")?,
+        updated_at: parse_datetime(row, "updated_at")?,
+    })
+}
+
+fn get_prompt_template_sync(
+    conn: &Connection,
+    template_id: PromptTemplateId,
+) -> SqliteResult<Option<PromptTemplate>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM prompt_templates WHERE id = ?",
+            TEMPLATE_COLUMNS
+        ),
+        params![template_id],
+        template_from_row,
+    )
+    .optional()
+}
+
+/// Only one template may be active per scope; `IS` also matches the NULL global scope
+fn deactivate_scope(
+    conn: &Connection,
+    game_id: Option<GameId>,
+    except: Option<PromptTemplateId>,
+) -> SqliteResult<()> {
+    conn.execute(
+        "UPDATE prompt_templates SET is_active = 0 WHERE game_id IS ? AND id IS NOT ?",
+        params![game_id, except],
+    )?;
+    Ok(())
+}
+
+/// List templates, optionally limited to one game plus the global defaults
+pub async fn list_prompt_templates(
+    db: &Database,
+    game_id: Option<GameId>,
+) -> SqliteResult<Vec<PromptTemplate>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT {}
+            FROM prompt_templates
+            WHERE ?1 IS NULL OR game_id = ?1 OR game_id IS NULL
+            ORDER BY game_id IS NULL, game_id, created_at DESC
+            "#,
+            TEMPLATE_COLUMNS
+        ))?;
+
+        let templates = stmt.query_map(params![game_id], template_from_row)?;
+        templates.collect()
+    })
+}
+
+pub async fn get_prompt_template(
+    db: &Database,
+    template_id: PromptTemplateId,
+) -> SqliteResult<Option<PromptTemplate>> {
+    db.with_connection(|conn| get_prompt_template_sync(conn, template_id))
+}
+
+/// The template chat should use for a game: its own active template, else the active global one
+pub async fn get_active_prompt_template(
+    db: &Database,
+    game_id: GameId,
+) -> SqliteResult<Option<PromptTemplate>> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            &format!(
+                r#"
+                SELECT {}
+                FROM prompt_templates
+                WHERE is_active = 1 AND (game_id = ? OR game_id IS NULL)
+                ORDER BY game_id IS NULL
+                LIMIT 1
+                "#,
+                TEMPLATE_COLUMNS
+            ),
+            params![game_id],
+            template_from_row,
+        )
+        .optional()
+    })
+}
+
+pub async fn create_prompt_template(
+    db: &Database,
+    request: CreatePromptTemplateRequest,
+) -> SqliteResult<PromptTemplate> {
+    db.with_transaction(|conn| {
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        if let Some(game_id) = request.game_id {
+            let game_exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+                params![game_id],
+                |row| row.get(0),
+            )?;
+
+            if !game_exists {
+                return Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some("Game does not exist".to_string()),
+                ));
+            }
+        }
+
+        if request.is_active {
+            deactivate_scope(conn, request.game_id, None)?;
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO prompt_templates (
+                game_id, name, persona, template, is_active, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                request.game_id,
+                request.name,
+                request.persona,
+                request.template,
+                request.is_active,
+                now_str,
+                now_str
+            ],
+        )?;
+
+        let template_id = conn.last_insert_rowid();
+        get_prompt_template_sync(conn, template_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+pub async fn update_prompt_template(
+    db: &Database,
+    template_id: PromptTemplateId,
+    request: UpdatePromptTemplateRequest,
+) -> SqliteResult<Option<PromptTemplate>> {
+    db.with_transaction(|conn| {
+        let Some(existing) = get_prompt_template_sync(conn, template_id)? else {
+            return Ok(None);
+        };
+
+        if request.is_active == Some(true) {
+            deactivate_scope(conn, existing.game_id, Some(template_id))?;
+        }
+
+        let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        conn.execute(
+            r#"
+            UPDATE prompt_templates
+            SET name = ?, persona = ?, template = ?, is_active = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            params![
+                request.name.unwrap_or(existing.name),
+                request.persona.or(existing.persona),
+                request.template.unwrap_or(existing.template),
+                request.is_active.unwrap_or(existing.is_active),
+                now_str,
+                template_id
+            ],
+        )?;
+
+        get_prompt_template_sync(conn, template_id)
+    })
+}
+
+pub async fn delete_prompt_template(
+    db: &Database,
+    template_id: PromptTemplateId,
+) -> SqliteResult<bool> {
+    db.with_connection(|conn| {
+        let rows_affected = conn.execute(
+            "DELETE FROM prompt_templates WHERE id = ?",
+            params![template_id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+}
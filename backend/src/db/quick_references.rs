@@ -0,0 +1,84 @@
+use rusqlite::{OptionalExtension, Result as SqliteResult, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{GameId, QuickReference, QuickReferenceSection};
+
+/// Sheets that are not for a specific player count are stored under 0
+fn player_count_key(player_count: Option<i32>) -> i32 {
+    player_count.unwrap_or(0)
+}
+
+/// The stored sheet for a game and player count, with the fingerprint of the rules it was
+/// generated from
+pub async fn get_quick_reference(
+    db: &Database,
+    game_id: GameId,
+    player_count: Option<i32>,
+) -> SqliteResult<Option<(QuickReference, String)>> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            r#"
+            SELECT sections, source_fingerprint, model, generated_at
+            FROM quick_references
+            WHERE game_id = ? AND player_count = ?
+            "#,
+            params![game_id, player_count_key(player_count)],
+            |row| {
+                let sections: String = row.get(0)?;
+                let sections: Vec<QuickReferenceSection> =
+                    serde_json::from_str(&sections).unwrap_or_default();
+
+                Ok((
+                    QuickReference {
+                        game_id,
+                        player_count,
+                        sections,
+                        model: row.get(2)?,
+                        generated_at: parse_datetime(row, "generated_at")?,
+                        cached: true,
+                    },
+                    row.get(1)?,
+                ))
+            },
+        )
+        .optional()
+    })
+}
+
+/// Store a sheet, replacing any earlier one for the same game and player count
+pub async fn save_quick_reference(
+    db: &Database,
+    quick_reference: &QuickReference,
+    source_fingerprint: &str,
+) -> SqliteResult<()> {
+    let sections = serde_json::to_string(&quick_reference.sections)
+        .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::fmt::Error)))?;
+    let generated_at = quick_reference
+        .generated_at
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
+    db.with_connection(|conn| {
+        conn.execute(
+            r#"
+            INSERT INTO quick_references (
+                game_id, player_count, sections, source_fingerprint, model, generated_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (game_id, player_count) DO UPDATE SET
+                sections = excluded.sections,
+                source_fingerprint = excluded.source_fingerprint,
+                model = excluded.model,
+                generated_at = excluded.generated_at
+            "#,
+            params![
+                quick_reference.game_id,
+                player_count_key(quick_reference.player_count),
+                sections,
+                source_fingerprint,
+                quick_reference.model,
+                generated_at
+            ],
+        )?;
+        Ok(())
+    })
+}
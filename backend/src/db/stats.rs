@@ -0,0 +1,327 @@
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params};
+
+use super::{
+    Database, collection::COLLECTION_STATUS, format_datetime, parse_datetime,
+    players::get_player_sync,
+};
+use crate::models::{
+    GameId, GamePlayCount, GamePlayerStats, GameStats, MostPlayedQuery, PlayStatsSummary,
+    PlayerGameStats, PlayerId, PlayerStats, StatsPeriodQuery, UnplayedGame,
+};
+
+/// Plays within the period bound to ?1 and ?2; either end may be NULL
+const PLAYS_IN_PERIOD: &str =
+    "(?1 IS NULL OR p.played_at >= ?1) AND (?2 IS NULL OR p.played_at <= ?2)";
+
+/// h-index over the per-game play counts selected by `play_counts` as `plays`:
+/// rank games by plays and keep the last position that still has that many plays
+fn h_index_sync(
+    conn: &Connection,
+    play_counts: &str,
+    params: impl rusqlite::Params,
+) -> SqliteResult<i64> {
+    conn.query_row(
+        &format!(
+            r#"
+            SELECT COALESCE(MAX(position), 0) FROM (
+                SELECT plays, ROW_NUMBER() OVER (ORDER BY plays DESC) AS position
+                FROM ({}) counts
+            )
+            WHERE plays >= position
+            "#,
+            play_counts
+        ),
+        params,
+        |row| row.get(0),
+    )
+}
+
+/// Totals over the plays in a period
+pub async fn play_summary(
+    db: &Database,
+    period: &StatsPeriodQuery,
+) -> SqliteResult<PlayStatsSummary> {
+    let from = period.from.map(format_datetime);
+    let to = period.to.map(format_datetime);
+
+    db.with_connection(|conn| {
+        let (total_plays, games_played, total_minutes) = conn.query_row(
+            &format!(
+                r#"
+                SELECT COUNT(*), COUNT(DISTINCT p.game_id), COALESCE(SUM(p.duration_minutes), 0)
+                FROM plays p WHERE {}
+                "#,
+                PLAYS_IN_PERIOD
+            ),
+            params![from, to],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let players = conn.query_row(
+            &format!(
+                r#"
+                SELECT COUNT(DISTINCT pp.player_id)
+                FROM play_players pp JOIN plays p ON p.id = pp.play_id
+                WHERE {}
+                "#,
+                PLAYS_IN_PERIOD
+            ),
+            params![from, to],
+            |row| row.get(0),
+        )?;
+        let h_index = h_index_sync(
+            conn,
+            &format!(
+                "SELECT COUNT(*) AS plays FROM plays p WHERE {} GROUP BY p.game_id",
+                PLAYS_IN_PERIOD
+            ),
+            params![from, to],
+        )?;
+
+        Ok(PlayStatsSummary {
+            total_plays,
+            games_played,
+            players,
+            total_minutes,
+            h_index,
+        })
+    })
+}
+
+/// Games with the most plays in a period, ties broken by the most recent play
+pub async fn most_played_games(
+    db: &Database,
+    query: &MostPlayedQuery,
+) -> SqliteResult<Vec<GamePlayCount>> {
+    let from = query.from.map(format_datetime);
+    let to = query.to.map(format_datetime);
+
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT
+                g.id, g.name, COUNT(*) AS plays,
+                (SELECT COUNT(DISTINCT pp.player_id)
+                 FROM play_players pp JOIN plays p ON p.id = pp.play_id
+                 WHERE p.game_id = g.id AND {0}) AS players,
+                COALESCE(SUM(p.duration_minutes), 0) AS total_minutes,
+                MAX(p.played_at) AS last_played_at
+            FROM plays p JOIN games g ON g.id = p.game_id
+            WHERE {0}
+            GROUP BY g.id
+            ORDER BY plays DESC, last_played_at DESC, g.name COLLATE NOCASE
+            LIMIT ?3
+            "#,
+            PLAYS_IN_PERIOD
+        ))?;
+
+        stmt.query_map(params![from, to, query.limit], |row| {
+            Ok(GamePlayCount {
+                game_id: row.get(0)?,
+                game_name: row.get(1)?,
+                plays: row.get(2)?,
+                players: row.get(3)?,
+                total_minutes: row.get(4)?,
+                last_played_at: parse_datetime(row, "last_played_at")?,
+            })
+        })?
+        .collect()
+    })
+}
+
+/// Owned base games that have never been played, longest waiting first.
+/// Expansions count as played along with their base game, so they are left out
+pub async fn shelf_of_shame(db: &Database) -> SqliteResult<Vec<UnplayedGame>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT g.id, g.name, g.created_at
+            FROM games g
+            WHERE g.base_game_id IS NULL
+              AND {} IN ('owned', 'for_trade')
+              AND NOT EXISTS (SELECT 1 FROM plays p WHERE p.game_id = g.id)
+            ORDER BY g.created_at ASC, g.id ASC
+            "#,
+            COLLECTION_STATUS
+        ))?;
+
+        stmt.query_map([], |row| {
+            Ok(UnplayedGame {
+                game_id: row.get(0)?,
+                game_name: row.get(1)?,
+                added_at: parse_datetime(row, "created_at")?,
+            })
+        })?
+        .collect()
+    })
+}
+
+/// Play statistics for a game, or `None` if the game does not exist
+pub async fn game_stats(db: &Database, game_id: GameId) -> SqliteResult<Option<GameStats>> {
+    db.with_connection(|conn| {
+        let game_name: Option<String> = conn
+            .query_row(
+                "SELECT name FROM games WHERE id = ?",
+                params![game_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(game_name) = game_name else {
+            return Ok(None);
+        };
+
+        let (plays, average_duration_minutes, last_played_at) = conn.query_row(
+            r#"
+            SELECT COUNT(*), AVG(duration_minutes), MAX(played_at) AS last_played_at
+            FROM plays WHERE game_id = ?
+            "#,
+            params![game_id],
+            |row| {
+                let last_played_at: Option<String> = row.get(2)?;
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    match last_played_at {
+                        Some(_) => Some(parse_datetime(row, "last_played_at")?),
+                        None => None,
+                    },
+                ))
+            },
+        )?;
+        let (average_score, best_score) = conn.query_row(
+            r#"
+            SELECT AVG(pp.score), MAX(pp.score)
+            FROM play_players pp JOIN plays p ON p.id = pp.play_id
+            WHERE p.game_id = ?
+            "#,
+            params![game_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                pl.id, pl.name, COUNT(*) AS plays,
+                SUM(pp.is_winner) AS wins,
+                CAST(SUM(pp.is_winner) AS REAL) / COUNT(*) AS win_rate,
+                AVG(pp.score), MAX(pp.score)
+            FROM play_players pp
+            JOIN plays p ON p.id = pp.play_id
+            JOIN players pl ON pl.id = pp.player_id
+            WHERE p.game_id = ?
+            GROUP BY pl.id
+            ORDER BY wins DESC, win_rate DESC, pl.name COLLATE NOCASE
+            "#,
+        )?;
+        let players = stmt
+            .query_map(params![game_id], |row| {
+                Ok(GamePlayerStats {
+                    player_id: row.get(0)?,
+                    name: row.get(1)?,
+                    plays: row.get(2)?,
+                    wins: row.get(3)?,
+                    win_rate: row.get(4)?,
+                    average_score: row.get(5)?,
+                    best_score: row.get(6)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(Some(GameStats {
+            game_id,
+            game_name,
+            plays,
+            average_duration_minutes,
+            average_score,
+            best_score,
+            last_played_at,
+            players,
+        }))
+    })
+}
+
+/// A player's statistics across games, or `None` if the player does not exist
+pub async fn player_stats(db: &Database, player_id: PlayerId) -> SqliteResult<Option<PlayerStats>> {
+    db.with_connection(|conn| {
+        let Some(player) = get_player_sync(conn, player_id)? else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                g.id, g.name, COUNT(*) AS plays,
+                SUM(pp.is_winner) AS wins,
+                CAST(SUM(pp.is_winner) AS REAL) / COUNT(*) AS win_rate,
+                AVG(pp.score), MAX(pp.score),
+                MAX(p.played_at) AS last_played_at
+            FROM play_players pp
+            JOIN plays p ON p.id = pp.play_id
+            JOIN games g ON g.id = p.game_id
+            WHERE pp.player_id = ?
+            GROUP BY g.id
+            ORDER BY plays DESC, last_played_at DESC
+            "#,
+        )?;
+        let games = stmt
+            .query_map(params![player_id], |row| {
+                Ok(PlayerGameStats {
+                    game_id: row.get(0)?,
+                    game_name: row.get(1)?,
+                    plays: row.get(2)?,
+                    wins: row.get(3)?,
+                    win_rate: row.get(4)?,
+                    average_score: row.get(5)?,
+                    best_score: row.get(6)?,
+                    last_played_at: parse_datetime(row, "last_played_at")?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let h_index = h_index_sync(
+            conn,
+            r#"
+            SELECT COUNT(*) AS plays
+            FROM play_players pp JOIN plays p ON p.id = pp.play_id
+            WHERE pp.player_id = ?
+            GROUP BY p.game_id
+            "#,
+            params![player_id],
+        )?;
+        let win_rate =
+            (player.play_count > 0).then(|| player.win_count as f64 / player.play_count as f64);
+
+        Ok(Some(PlayerStats {
+            player,
+            win_rate,
+            h_index,
+            games,
+        }))
+    })
+}
+
+/// Games the named players have played most together, ignoring case in names
+pub async fn most_played_by(
+    db: &Database,
+    player_names: &[String],
+    limit: u32,
+) -> SqliteResult<Vec<GameId>> {
+    let player_names = serde_json::to_string(player_names)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT p.game_id
+            FROM play_players pp
+            JOIN plays p ON p.id = pp.play_id
+            JOIN players pl ON pl.id = pp.player_id
+            WHERE pl.name IN (SELECT value FROM json_each(?1))
+            GROUP BY p.game_id
+            ORDER BY COUNT(*) DESC, MAX(p.played_at) DESC
+            LIMIT ?2
+            "#,
+        )?;
+        stmt.query_map(params![player_names, limit], |row| row.get(0))?
+            .collect()
+    })
+}
@@ -0,0 +1,219 @@
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
+
+use super::{Database, parse_datetime};
+use crate::models::{CreateTagRequest, GameId, Tag, TagId, TagSummary, TagType, UpdateTagRequest};
+
+const TAG_COLUMNS: &str = "t.id, t.name, t.tag_type, t.created_at, \
+                           (SELECT COUNT(*) FROM game_tags gt WHERE gt.tag_id = t.id) AS game_count";
+
+fn tag_type_from_row(row: &Row, index: usize) -> SqliteResult<TagType> {
+    let tag_type: String = row.get(index)?;
+    TagType::from_str(&tag_type).ok_or_else(|| {
+        rusqlite::Error::InvalidColumnType(
+            index,
+            "tag_type".to_string(),
+            rusqlite::types::Type::Text,
+        )
+    })
+}
+
+fn tag_from_row(row: &Row) -> SqliteResult<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tag_type: tag_type_from_row(row, 2)?,
+        game_count: row.get(4)?,
+        created_at: parse_datetime(row, "created_at")?,
+    })
+}
+
+fn get_tag_sync(conn: &Connection, tag_id: TagId) -> SqliteResult<Option<Tag>> {
+    conn.query_row(
+        &format!("SELECT {} FROM tags t WHERE t.id = ?", TAG_COLUMNS),
+        params![tag_id],
+        tag_from_row,
+    )
+    .optional()
+}
+
+fn list_game_tags_sync(conn: &Connection, game_id: GameId) -> SqliteResult<Vec<TagSummary>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT t.id, t.name, t.tag_type
+        FROM game_tags gt
+        JOIN tags t ON t.id = gt.tag_id
+        WHERE gt.game_id = ?
+        ORDER BY t.tag_type, t.name COLLATE NOCASE
+        "#,
+    )?;
+    stmt.query_map(params![game_id], |row| {
+        Ok(TagSummary {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            tag_type: tag_type_from_row(row, 2)?,
+        })
+    })?
+    .collect()
+}
+
+/// Tags with how many games carry each, grouped by type and sorted by name
+pub async fn list_tags(db: &Database, tag_type: Option<TagType>) -> SqliteResult<Vec<Tag>> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT {} FROM tags t
+            WHERE ?1 IS NULL OR t.tag_type = ?1
+            ORDER BY t.tag_type, t.name COLLATE NOCASE
+            "#,
+            TAG_COLUMNS
+        ))?;
+        stmt.query_map(params![tag_type.map(|t| t.as_str())], tag_from_row)?
+            .collect()
+    })
+}
+
+pub async fn get_tag(db: &Database, tag_id: TagId) -> SqliteResult<Option<Tag>> {
+    db.with_connection(|conn| get_tag_sync(conn, tag_id))
+}
+
+pub async fn create_tag(db: &Database, request: CreateTagRequest) -> SqliteResult<Tag> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT INTO tags (name, tag_type) VALUES (?, ?)",
+            params![request.name.trim(), request.tag_type.as_str()],
+        )?;
+        get_tag_sync(conn, conn.last_insert_rowid())?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    })
+}
+
+pub async fn update_tag(
+    db: &Database,
+    tag_id: TagId,
+    request: UpdateTagRequest,
+) -> SqliteResult<Option<Tag>> {
+    db.with_transaction(|conn| {
+        let name = request.name.as_deref().map(str::trim);
+        let rows_affected = conn.execute(
+            r#"
+            UPDATE tags SET name = COALESCE(?, name), tag_type = COALESCE(?, tag_type)
+            WHERE id = ?
+            "#,
+            params![name, request.tag_type.map(|t| t.as_str()), tag_id],
+        )?;
+        if rows_affected == 0 {
+            return Ok(None);
+        }
+        get_tag_sync(conn, tag_id)
+    })
+}
+
+pub async fn delete_tag(db: &Database, tag_id: TagId) -> SqliteResult<bool> {
+    db.with_transaction(|conn| {
+        conn.execute("DELETE FROM game_tags WHERE tag_id = ?", params![tag_id])?;
+        let rows_affected = conn.execute("DELETE FROM tags WHERE id = ?", params![tag_id])?;
+        Ok(rows_affected > 0)
+    })
+}
+
+/// A game's tags, or `None` if the game does not exist
+pub async fn list_game_tags(
+    db: &Database,
+    game_id: GameId,
+) -> SqliteResult<Option<Vec<TagSummary>>> {
+    db.with_connection(|conn| {
+        let game_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if !game_exists {
+            return Ok(None);
+        }
+        list_game_tags_sync(conn, game_id).map(Some)
+    })
+}
+
+/// Replace all of a game's tags, or `None` if the game does not exist
+pub async fn set_game_tags(
+    db: &Database,
+    game_id: GameId,
+    tag_ids: &[TagId],
+) -> SqliteResult<Option<Vec<TagSummary>>> {
+    db.with_transaction(|conn| {
+        let game_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM games WHERE id = ?)",
+            params![game_id],
+            |row| row.get(0),
+        )?;
+        if !game_exists {
+            return Ok(None);
+        }
+
+        conn.execute("DELETE FROM game_tags WHERE game_id = ?", params![game_id])?;
+        let mut link =
+            conn.prepare("INSERT OR IGNORE INTO game_tags (game_id, tag_id) VALUES (?, ?)")?;
+        for tag_id in tag_ids {
+            let tag_exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?)",
+                params![tag_id],
+                |row| row.get(0),
+            )?;
+            if !tag_exists {
+                return Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some(format!("Tag {} does not exist", tag_id)),
+                ));
+            }
+            link.execute(params![game_id, tag_id])?;
+        }
+
+        list_game_tags_sync(conn, game_id).map(Some)
+    })
+}
+
+/// Give a game a tag by name, creating the tag if needed
+pub fn add_game_tag_sync(
+    conn: &Connection,
+    game_id: GameId,
+    tag_type: TagType,
+    name: &str,
+) -> SqliteResult<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT OR IGNORE INTO tags (name, tag_type) VALUES (?, ?)",
+        params![name, tag_type.as_str()],
+    )?;
+    conn.execute(
+        r#"
+        INSERT OR IGNORE INTO game_tags (game_id, tag_id)
+        SELECT ?, id FROM tags WHERE tag_type = ? AND name = ? COLLATE NOCASE
+        "#,
+        params![game_id, tag_type.as_str(), name],
+    )?;
+    Ok(())
+}
+
+/// Replace a game's tags of one type, creating any tags that do not exist yet.
+/// Names match existing tags case-insensitively
+pub fn set_game_tags_sync(
+    conn: &Connection,
+    game_id: GameId,
+    tag_type: TagType,
+    names: &[String],
+) -> SqliteResult<()> {
+    conn.execute(
+        r#"
+        DELETE FROM game_tags
+        WHERE game_id = ? AND tag_id IN (SELECT id FROM tags WHERE tag_type = ?)
+        "#,
+        params![game_id, tag_type.as_str()],
+    )?;
+
+    for name in names {
+        add_game_tag_sync(conn, game_id, tag_type, name)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    db::{Database, game_descriptions},
+    embeddings::{self, Embedder},
+    models::{GameDescriptionSource, GameId},
+};
+
+/// Most games one similarity or search request can ask for
+pub const MAX_SIMILAR_GAMES: u32 = 50;
+
+/// Descriptions sent to the embedder in one call; each batch is stored before the next
+/// is embedded, so a large import makes progress even if a later batch fails
+const DESCRIPTION_BATCH_SIZE: usize = 32;
+
+/// Text a game's description vector is embedded from: its name, description and
+/// tags, or `None` for games with neither a description nor tags
+pub fn description_text(source: &GameDescriptionSource) -> Option<String> {
+    let description = source
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty());
+    match (description, source.tags.is_empty()) {
+        (None, true) => None,
+        (Some(description), true) => Some(format!("{}: {}", source.name, description)),
+        (None, false) => Some(format!("{}. Tags: {}", source.name, source.tags.join(", "))),
+        (Some(description), false) => Some(format!(
+            "{}: {}\nTags: {}",
+            source.name,
+            description,
+            source.tags.join(", ")
+        )),
+    }
+}
+
+/// Changes with the text and with the model, so either one going stale re-embeds
+pub fn text_hash(model: &str, text: &str) -> String {
+    embeddings::cache_key(&embeddings::normalize_text(&format!("{}\n{}", model, text)))
+}
+
+/// Games whose text is new or changed since it was embedded, and games with a
+/// stored vector that no longer have any text
+fn stale_games(
+    texts: &[(GameId, String, String)],
+    stored: &HashMap<GameId, String>,
+) -> (Vec<usize>, Vec<GameId>) {
+    let to_embed = texts
+        .iter()
+        .enumerate()
+        .filter(|(_, (game_id, hash, _))| stored.get(game_id) != Some(hash))
+        .map(|(index, _)| index)
+        .collect();
+    let to_remove = stored
+        .keys()
+        .filter(|game_id| !texts.iter().any(|(id, _, _)| id == *game_id))
+        .copied()
+        .collect();
+    (to_embed, to_remove)
+}
+
+/// Bring the description index in line with the library, embedding only the games
+/// added or edited since it was last refreshed. Returns how many games were embedded
+pub async fn refresh_description_index(db: &Database, embedder: &Embedder) -> Result<usize> {
+    let sources = game_descriptions::list_description_sources(db)
+        .await
+        .context("Failed to load game descriptions")?;
+    let stored = game_descriptions::description_hashes(db)
+        .await
+        .context("Failed to load the description index")?;
+
+    let texts: Vec<(GameId, String, String)> = sources
+        .iter()
+        .filter_map(|source| {
+            let text = description_text(source)?;
+            Some((source.game_id, text_hash(embedder.get_model(), &text), text))
+        })
+        .collect();
+    let (to_embed, to_remove) = stale_games(&texts, &stored);
+
+    if !to_remove.is_empty() {
+        game_descriptions::delete_description_embeddings(db, &to_remove)
+            .await
+            .context("Failed to remove stale game descriptions from the index")?;
+    }
+
+    let mut embedded = 0;
+    for batch in to_embed.chunks(DESCRIPTION_BATCH_SIZE) {
+        let batch_texts: Vec<String> = batch.iter().map(|&index| texts[index].2.clone()).collect();
+        let vectors = embedder
+            .generate_embeddings(&batch_texts)
+            .await
+            .context("Failed to embed game descriptions")?;
+        let entries: Vec<(GameId, String, Vec<f32>)> = batch
+            .iter()
+            .zip(vectors)
+            .map(|(&index, vector)| (texts[index].0, texts[index].1.clone(), vector))
+            .collect();
+        game_descriptions::store_description_embeddings(db, &entries)
+            .await
+            .context("Failed to store game description vectors")?;
+        embedded += entries.len();
+    }
+
+    Ok(embedded)
+}
+
+/// Refresh the description index after game names, descriptions or tags change.
+/// A failure is only logged: the write stands, and the games it missed are picked
+/// up by the next refresh
+pub async fn index_descriptions(db: &Database, embedder: &Embedder) {
+    match refresh_description_index(db, embedder).await {
+        Ok(0) => {}
+        Ok(embedded) => tracing::info!("Indexed the descriptions of {} games", embedded),
+        Err(e) => tracing::warn!("Failed to refresh the game description index: {:#}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(description: Option<&str>, tags: &[&str]) -> GameDescriptionSource {
+        GameDescriptionSource {
+            game_id: 1,
+            name: "Crypt Raiders".to_string(),
+            description: description.map(str::to_string),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_description_text_uses_description_and_tags() {
+        assert_eq!(description_text(&source(None, &[])), None);
+        assert_eq!(description_text(&source(Some("  "), &[])), None);
+        assert_eq!(
+            description_text(&source(Some("Delve into crypts."), &[])).unwrap(),
+            "Crypt Raiders: Delve into crypts."
+        );
+        assert_eq!(
+            description_text(&source(None, &["Cooperative", "Dungeon"])).unwrap(),
+            "Crypt Raiders. Tags: Cooperative, Dungeon"
+        );
+        assert_eq!(
+            description_text(&source(Some("Delve into crypts."), &["Campaign"])).unwrap(),
+            "Crypt Raiders: Delve into crypts.\nTags: Campaign"
+        );
+    }
+
+    #[test]
+    fn test_text_hash_changes_with_model_and_text() {
+        let hash = text_hash("model-a", "Crypt Raiders: Delve into crypts.");
+        assert_eq!(
+            hash,
+            text_hash("model-a", "Crypt Raiders:  Delve into crypts.")
+        );
+        assert_ne!(
+            hash,
+            text_hash("model-b", "Crypt Raiders: Delve into crypts.")
+        );
+        assert_ne!(
+            hash,
+            text_hash("model-a", "Crypt Raiders: Delve into tombs.")
+        );
+    }
+
+    #[test]
+    fn test_stale_games_finds_changed_and_orphaned_vectors() {
+        let texts = vec![
+            (1, "same".to_string(), "One".to_string()),
+            (2, "edited".to_string(), "Two".to_string()),
+            (3, "new".to_string(), "Three".to_string()),
+        ];
+        let stored = HashMap::from([
+            (1, "same".to_string()),
+            (2, "original".to_string()),
+            (4, "deleted".to_string()),
+        ]);
+
+        let (to_embed, to_remove) = stale_games(&texts, &stored);
+        assert_eq!(to_embed, vec![1, 2]);
+        assert_eq!(to_remove, vec![4]);
+    }
+}
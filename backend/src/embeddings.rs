@@ -1,12 +1,52 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use anyhow::{Result, anyhow};
 use async_openai::{Client, config::OpenAIConfig, types::CreateEmbeddingRequestArgs};
+use sha2::{Digest, Sha256};
+
+use crate::db::{self, Database};
+use crate::models::EmbeddingCacheStats;
 
 const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text:latest";
+pub const DEFAULT_CACHE_MAX_ENTRIES: u64 = 50_000;
+
+/// Model name reported by the offline hash-based embedder
+pub const MOCK_EMBEDDING_MODEL: &str = "mock-hash";
+
+/// Vector size the vec0 tables are declared with (nomic-embed-text's output size)
+pub const EMBEDDING_DIMENSIONS: usize = 768;
 
 /// Service for generating embeddings using OpenAI-compatible APIs (like Ollama)
 pub struct Embedder {
-    client: Client<OpenAIConfig>,
+    backend: Backend,
     embedding_model: String,
+    cache: Option<EmbeddingCache>,
+}
+
+/// Where embeddings come from
+enum Backend {
+    OpenAi(Client<OpenAIConfig>),
+    Mock,
+}
+
+/// Persistent embedding cache keyed by normalized text hash and model name
+pub struct EmbeddingCache {
+    db: Database,
+    max_entries: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EmbeddingCache {
+    pub fn new(db: Database, max_entries: u64) -> Self {
+        Self {
+            db,
+            max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
 }
 
 /// Initialize a new embedding service configured for Ollama
@@ -23,8 +63,9 @@ impl Default for Embedder {
         let client = Client::with_config(config);
 
         Self {
-            client,
+            backend: Backend::OpenAi(client),
             embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            cache: None,
         }
     }
 }
@@ -44,50 +85,100 @@ impl Embedder {
         let client = Client::with_config(config);
 
         Self {
-            client,
+            backend: Backend::OpenAi(client),
             embedding_model: embedding_model.to_string(),
+            cache: None,
+        }
+    }
+
+    /// Create an offline embedder producing deterministic hash-based vectors
+    pub fn mock() -> Self {
+        Self {
+            backend: Backend::Mock,
+            embedding_model: MOCK_EMBEDDING_MODEL.to_string(),
+            cache: None,
         }
     }
 
+    /// Consult a persistent cache before calling the embedding provider
+    pub fn with_cache(mut self, cache: EmbeddingCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Generate an embedding for a single text
     pub async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        let request = CreateEmbeddingRequestArgs::default()
-            .model(&self.embedding_model)
-            .input([text])
-            .build()
-            .map_err(|e| anyhow!("Failed to build embedding request: {}", e))?;
+        let mut embeddings = self.generate_embeddings(&[text.to_string()]).await?;
 
-        let response = self
-            .client
-            .embeddings()
-            .create(request)
-            .await
-            .map_err(|e| anyhow!("Failed to create embedding: {}", e))?;
-
-        if response.data.is_empty() {
+        if embeddings.is_empty() {
             return Err(anyhow!("No embedding data returned"));
         }
 
-        Ok(response.data[0].embedding.clone())
+        Ok(embeddings.swap_remove(0))
     }
 
-    /// Generate embeddings for multiple texts in a single request
+    /// Generate embeddings for multiple texts, only sending cache misses to the provider
     pub async fn generate_embeddings(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         if texts.is_empty() {
             return Ok(vec![]);
         }
 
-        // Convert Vec<String> to Vec<&str> for compatibility
-        let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
+        let normalized: Vec<String> = texts.iter().map(|t| normalize_text(t)).collect();
+        let keys: Vec<String> = normalized.iter().map(|t| cache_key(t)).collect();
+
+        let mut resolved = self.lookup_cached(&keys).await;
+
+        // Request each distinct missing text once, even if it repeats within the batch
+        let mut missing_keys = Vec::new();
+        let mut missing_texts = Vec::new();
+        for (key, text) in keys.iter().zip(normalized.iter()) {
+            if !resolved.contains_key(key) && !missing_keys.contains(key) {
+                missing_keys.push(key.clone());
+                missing_texts.push(text.as_str());
+            }
+        }
+
+        if let Some(cache) = &self.cache {
+            let misses = keys.iter().filter(|k| !resolved.contains_key(*k)).count() as u64;
+            cache
+                .hits
+                .fetch_add(keys.len() as u64 - misses, Ordering::Relaxed);
+            cache.misses.fetch_add(misses, Ordering::Relaxed);
+        }
+
+        if !missing_texts.is_empty() {
+            let fresh = self.request_embeddings(&missing_texts).await?;
+            let fresh_entries: Vec<(String, Vec<f32>)> =
+                missing_keys.into_iter().zip(fresh).collect();
+
+            self.store_cached(&fresh_entries).await;
+            resolved.extend(fresh_entries);
+        }
+
+        keys.iter()
+            .map(|key| {
+                resolved
+                    .get(key)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Missing embedding for cache key {}", key))
+            })
+            .collect()
+    }
+
+    /// Send texts to the embedding provider, bypassing the cache
+    async fn request_embeddings(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let client = match &self.backend {
+            Backend::OpenAi(client) => client,
+            Backend::Mock => return Ok(texts.iter().map(|text| hash_embedding(text)).collect()),
+        };
 
         let request = CreateEmbeddingRequestArgs::default()
             .model(&self.embedding_model)
-            .input(text_refs)
+            .input(texts.to_vec())
             .build()
             .map_err(|e| anyhow!("Failed to build embedding request: {}", e))?;
 
-        let response = self
-            .client
+        let response = client
             .embeddings()
             .create(request)
             .await
@@ -108,9 +199,97 @@ impl Embedder {
         Ok(data.into_iter().map(|d| d.embedding).collect())
     }
 
+    async fn lookup_cached(&self, keys: &[String]) -> HashMap<String, Vec<f32>> {
+        let Some(cache) = &self.cache else {
+            return HashMap::new();
+        };
+
+        match db::embedding_cache::get_cached_embeddings(&cache.db, &self.embedding_model, keys)
+            .await
+        {
+            Ok(found) => found,
+            Err(e) => {
+                tracing::warn!("Embedding cache lookup failed: {}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn store_cached(&self, entries: &[(String, Vec<f32>)]) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+
+        if let Err(e) =
+            db::embedding_cache::store_cached_embeddings(&cache.db, &self.embedding_model, entries)
+                .await
+        {
+            tracing::warn!("Failed to store embeddings in cache: {}", e);
+            return;
+        }
+
+        match db::embedding_cache::evict_embedding_cache(&cache.db, cache.max_entries).await {
+            Ok(0) => {}
+            Ok(evicted) => tracing::debug!("Evicted {} embedding cache entries", evicted),
+            Err(e) => tracing::warn!("Embedding cache eviction failed: {}", e),
+        }
+    }
+
+    /// Hit-rate statistics for the embedding cache
+    pub async fn cache_stats(&self) -> Result<EmbeddingCacheStats> {
+        let Some(cache) = &self.cache else {
+            return Ok(EmbeddingCacheStats {
+                model: self.embedding_model.clone(),
+                enabled: false,
+                entries: 0,
+                max_entries: 0,
+                hits: 0,
+                misses: 0,
+                hit_rate: 0.0,
+                lifetime_hits: 0,
+                oldest_entry: None,
+            });
+        };
+
+        let totals = db::embedding_cache::get_cache_totals(&cache.db).await?;
+        let hits = cache.hits.load(Ordering::Relaxed);
+        let misses = cache.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+
+        Ok(EmbeddingCacheStats {
+            model: self.embedding_model.clone(),
+            enabled: true,
+            entries: totals.entries,
+            max_entries: cache.max_entries,
+            hits,
+            misses,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                hits as f64 / lookups as f64
+            },
+            lifetime_hits: totals.lifetime_hits,
+            oldest_entry: totals.oldest_entry,
+        })
+    }
+
+    /// Drop cached vectors, optionally only those from the current model
+    pub async fn clear_cache(&self, current_model_only: bool) -> Result<u64> {
+        let Some(cache) = &self.cache else {
+            return Ok(0);
+        };
+
+        let model = current_model_only.then_some(self.embedding_model.as_str());
+        let removed = db::embedding_cache::clear_embedding_cache(&cache.db, model).await?;
+        cache.hits.store(0, Ordering::Relaxed);
+        cache.misses.store(0, Ordering::Relaxed);
+
+        Ok(removed)
+    }
+
     /// Test the connection to the embedding service
     pub async fn test_connection(&self) -> Result<()> {
-        self.generate_embedding("test").await?;
+        self.request_embeddings(&["test"]).await?;
         Ok(())
     }
 
@@ -120,6 +299,46 @@ impl Embedder {
     }
 }
 
+/// Weight of the component every mock embedding shares
+const MOCK_BASELINE_WEIGHT: f32 = 2.0;
+
+/// Deterministic bag-of-words vector: every word hashes to a signed dimension,
+/// so texts that share words point in similar directions. Dimension 0 is shared
+/// by all texts, giving unrelated ones the baseline similarity real models show
+pub fn hash_embedding(text: &str) -> Vec<f32> {
+    let mut words = vec![0.0f32; EMBEDDING_DIMENSIONS];
+
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+    {
+        let digest = Sha256::digest(word.to_lowercase().as_bytes());
+        let index =
+            1 + u16::from_le_bytes([digest[0], digest[1]]) as usize % (EMBEDDING_DIMENSIONS - 1);
+        words[index] += if digest[2] & 1 == 0 { 1.0 } else { -1.0 };
+    }
+
+    let words_norm = words.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if words_norm > 0.0 {
+        words.iter_mut().for_each(|v| *v /= words_norm);
+    }
+    words[0] = MOCK_BASELINE_WEIGHT;
+
+    let norm = words.iter().map(|v| v * v).sum::<f32>().sqrt();
+    words.iter_mut().for_each(|v| *v /= norm);
+    words
+}
+
+/// Collapse whitespace so trivially different copies of a text share a cache entry
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Hex-encoded SHA-256 of already normalized text
+pub fn cache_key(normalized_text: &str) -> String {
+    format!("{:x}", Sha256::digest(normalized_text.as_bytes()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +450,106 @@ mod tests {
         assert_eq!(result.unwrap().len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_mock_embeddings_are_deterministic_and_word_based() {
+        let service = Embedder::mock();
+        assert!(service.test_connection().await.is_ok());
+
+        let texts = vec![
+            "When a seven is rolled, move the robber".to_string(),
+            "How does the robber move?".to_string(),
+            "Cities cost three ore and two grain".to_string(),
+        ];
+        let embeddings = service.generate_embeddings(&texts).await.unwrap();
+        let again = service.generate_embedding(&texts[0]).await.unwrap();
+
+        assert_eq!(embeddings[0], again);
+        assert!(embeddings.iter().all(|e| e.len() == EMBEDDING_DIMENSIONS));
+
+        let similarity = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+        assert!(
+            similarity(&embeddings[1], &embeddings[0]) > similarity(&embeddings[1], &embeddings[2])
+        );
+        assert!((similarity(&again, &again) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_normalize_text() {
+        assert_eq!(
+            normalize_text("  Roll the\n\tdice   twice. "),
+            "Roll the dice twice."
+        );
+        assert_eq!(
+            cache_key(&normalize_text("Roll  the dice")),
+            cache_key(&normalize_text("Roll the\ndice"))
+        );
+        assert_ne!(cache_key("Roll the dice"), cache_key("roll the dice"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_skips_provider() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!(
+            "../../migrations/V005__create_embedding_cache_table.sql"
+        ))
+        .unwrap();
+        let db = Database::new(conn);
+
+        // Point at a closed port so any provider call would fail
+        let service = Embedder::with_config("http://127.0.0.1:9/v1", "test-key", "custom-model")
+            .with_cache(EmbeddingCache::new(db.clone(), 10));
+
+        let key = cache_key(&normalize_text("Victory points"));
+        db::embedding_cache::store_cached_embeddings(
+            &db,
+            "custom-model",
+            &[(key, vec![0.1, 0.2, 0.3])],
+        )
+        .await
+        .unwrap();
+
+        let embedding = service
+            .generate_embedding(" Victory  points ")
+            .await
+            .unwrap();
+        assert_eq!(embedding, vec![0.1, 0.2, 0.3]);
+
+        let stats = service.cache_stats().await.unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(stats.lifetime_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_eviction_keeps_max_entries() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!(
+            "../../migrations/V005__create_embedding_cache_table.sql"
+        ))
+        .unwrap();
+        let db = Database::new(conn);
+
+        let entries: Vec<(String, Vec<f32>)> = (0..5)
+            .map(|i| (cache_key(&format!("chunk {}", i)), vec![i as f32]))
+            .collect();
+        db::embedding_cache::store_cached_embeddings(&db, "custom-model", &entries)
+            .await
+            .unwrap();
+
+        let evicted = db::embedding_cache::evict_embedding_cache(&db, 3)
+            .await
+            .unwrap();
+        assert_eq!(evicted, 2);
+        assert_eq!(
+            db::embedding_cache::get_cache_totals(&db)
+                .await
+                .unwrap()
+                .entries,
+            3
+        );
+    }
+
     #[tokio::test]
     async fn test_service_configuration() {
         let custom_service =
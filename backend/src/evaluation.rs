@@ -0,0 +1,263 @@
+use crate::{
+    models::{
+        EmbeddingId, EmbeddingSearchResult, EvalDiff, EvalQuestion, EvalQuestionChange,
+        EvalQuestionResult, EvalRun, EvalSummary,
+    },
+    query_transform,
+};
+
+pub const DEFAULT_K: u32 = 5;
+pub const MAX_K: u32 = 20;
+
+/// Score one question's retrieval against its expected chunks and keywords
+pub fn score_retrieval(
+    question: &EvalQuestion,
+    retrieved: &[EmbeddingSearchResult],
+) -> EvalQuestionResult {
+    let retrieved_chunk_ids: Vec<EmbeddingId> = retrieved.iter().map(|result| result.id).collect();
+
+    let (recall, mut reciprocal_rank) = if question.expected_chunk_ids.is_empty() {
+        (None, None)
+    } else {
+        let (recall, reciprocal_rank) =
+            query_transform::retrieval_scores(&retrieved_chunk_ids, &question.expected_chunk_ids);
+        (Some(recall), Some(reciprocal_rank))
+    };
+
+    let keyword_recall = if question.expected_keywords.is_empty() {
+        None
+    } else {
+        let texts: Vec<&str> = retrieved
+            .iter()
+            .map(|result| result.chunk_text.as_str())
+            .collect();
+        let found = question
+            .expected_keywords
+            .iter()
+            .filter(|keyword| texts.iter().any(|text| contains_keyword(text, keyword)))
+            .count();
+
+        // Without expected chunks, the first chunk mentioning a keyword counts as relevant
+        if reciprocal_rank.is_none() {
+            reciprocal_rank = Some(
+                texts
+                    .iter()
+                    .position(|text| {
+                        question
+                            .expected_keywords
+                            .iter()
+                            .any(|keyword| contains_keyword(text, keyword))
+                    })
+                    .map_or(0.0, |rank| 1.0 / (rank as f32 + 1.0)),
+            );
+        }
+        Some(found as f32 / question.expected_keywords.len() as f32)
+    };
+
+    EvalQuestionResult {
+        question_id: question.id,
+        question: question.question.clone(),
+        retrieved_chunk_ids,
+        recall,
+        reciprocal_rank,
+        keyword_recall,
+        answer: None,
+        answer_match: None,
+    }
+}
+
+/// Whether an answer mentions every expected keyword; `None` when there are none to check
+pub fn answer_matches(answer: &str, keywords: &[String]) -> Option<bool> {
+    (!keywords.is_empty()).then(|| {
+        keywords
+            .iter()
+            .all(|keyword| contains_keyword(answer, keyword))
+    })
+}
+
+fn contains_keyword(text: &str, keyword: &str) -> bool {
+    text.to_lowercase().contains(&keyword.trim().to_lowercase())
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Average each metric over the questions it applies to
+pub fn summarize(results: &[EvalQuestionResult]) -> EvalSummary {
+    EvalSummary {
+        questions: results.len(),
+        recall_at_k: mean(results.iter().filter_map(|r| r.recall)),
+        mrr: mean(results.iter().filter_map(|r| r.reciprocal_rank)),
+        keyword_recall: mean(results.iter().filter_map(|r| r.keyword_recall)),
+        answer_match_rate: mean(results.iter().filter_map(|r| {
+            r.answer_match
+                .map(|matched| if matched { 1.0 } else { 0.0 })
+        })),
+    }
+}
+
+/// One number per question for spotting regressions: the mean of its available metrics
+fn question_score(result: &EvalQuestionResult) -> Option<f32> {
+    mean(
+        [
+            result.recall,
+            result.keyword_recall,
+            result
+                .answer_match
+                .map(|matched| if matched { 1.0 } else { 0.0 }),
+        ]
+        .into_iter()
+        .flatten(),
+    )
+}
+
+fn delta(before: Option<f32>, after: Option<f32>) -> Option<f32> {
+    Some(after? - before?)
+}
+
+/// Compare a run with an earlier one, question by question
+pub fn diff_runs(baseline: &EvalRun, current: &EvalRun) -> EvalDiff {
+    let mut improved = Vec::new();
+    let mut regressed = Vec::new();
+
+    for result in &current.results {
+        let Some(before) = baseline
+            .results
+            .iter()
+            .find(|r| r.question_id == result.question_id)
+        else {
+            continue;
+        };
+        let (Some(before), Some(after)) = (question_score(before), question_score(result)) else {
+            continue;
+        };
+
+        let change = EvalQuestionChange {
+            question_id: result.question_id,
+            question: result.question.clone(),
+            before,
+            after,
+        };
+        if after > before + f32::EPSILON {
+            improved.push(change);
+        } else if after + f32::EPSILON < before {
+            regressed.push(change);
+        }
+    }
+
+    EvalDiff {
+        baseline_run_id: baseline.id,
+        recall_at_k_delta: delta(baseline.summary.recall_at_k, current.summary.recall_at_k),
+        mrr_delta: delta(baseline.summary.mrr, current.summary.mrr),
+        keyword_recall_delta: delta(
+            baseline.summary.keyword_recall,
+            current.summary.keyword_recall,
+        ),
+        answer_match_rate_delta: delta(
+            baseline.summary.answer_match_rate,
+            current.summary.answer_match_rate,
+        ),
+        improved,
+        regressed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EmbeddingSourceType, QueryStrategy};
+
+    fn question(chunk_ids: Vec<EmbeddingId>, keywords: &[&str]) -> EvalQuestion {
+        EvalQuestion {
+            id: 1,
+            game_id: 1,
+            question: "What happens on a seven?".to_string(),
+            expected_chunk_ids: chunk_ids,
+            expected_keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn result(id: EmbeddingId, text: &str) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id: 1,
+            chunk_text: text.to_string(),
+            similarity_score: 0.8,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    fn run(id: i64, results: Vec<EvalQuestionResult>) -> EvalRun {
+        EvalRun {
+            id,
+            game_id: 1,
+            strategy: QueryStrategy::Direct,
+            k: 5,
+            generate_answers: false,
+            embedding_model: "mock".to_string(),
+            llm_model: None,
+            summary: summarize(&results),
+            results,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_score_retrieval() {
+        let retrieved = vec![
+            result(4, "Roads cost one brick and one lumber."),
+            result(2, "Move the Robber and steal a card."),
+        ];
+
+        let scored = score_retrieval(&question(vec![2, 7], &["robber", "discard"]), &retrieved);
+        assert_eq!(scored.recall, Some(0.5));
+        assert_eq!(scored.reciprocal_rank, Some(0.5));
+        assert_eq!(scored.keyword_recall, Some(0.5));
+
+        // Keywords alone still give a reciprocal rank
+        let scored = score_retrieval(&question(Vec::new(), &["robber"]), &retrieved);
+        assert_eq!(scored.recall, None);
+        assert_eq!(scored.reciprocal_rank, Some(0.5));
+    }
+
+    #[test]
+    fn test_answer_matches() {
+        let keywords = vec!["Robber".to_string(), "half".to_string()];
+        assert_eq!(
+            answer_matches("Discard half, then move the robber.", &keywords),
+            Some(true)
+        );
+        assert_eq!(answer_matches("Move the robber.", &keywords), Some(false));
+        assert_eq!(answer_matches("Anything", &[]), None);
+    }
+
+    #[test]
+    fn test_summarize_and_diff_runs() {
+        let retrieved = vec![result(2, "Move the robber.")];
+        let mut hit = score_retrieval(&question(vec![2], &[]), &retrieved);
+        let mut miss = score_retrieval(&question(vec![9], &[]), &retrieved);
+        miss.question_id = 2;
+        hit.question_id = 3;
+
+        let baseline = run(1, vec![hit.clone(), miss.clone()]);
+        assert_eq!(baseline.summary.recall_at_k, Some(0.5));
+        assert_eq!(baseline.summary.answer_match_rate, None);
+
+        let mut fixed = miss.clone();
+        fixed.recall = Some(1.0);
+        let mut broken = hit.clone();
+        broken.recall = Some(0.0);
+        let current = run(2, vec![broken, fixed]);
+
+        let diff = diff_runs(&baseline, &current);
+        assert_eq!(diff.baseline_run_id, 1);
+        assert_eq!(diff.recall_at_k_delta, Some(0.0));
+        assert_eq!(diff.improved[0].question_id, 2);
+        assert_eq!(diff.regressed[0].question_id, 3);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::{EmbeddingSearchResult, GameId};
+
+/// Most expansions one chat session may have in play
+pub const MAX_EXPANSIONS_IN_PLAY: usize = 6;
+
+/// Rule chunks retrieved for a question about a game played with expansions
+pub const EXPANSION_CHUNK_LIMIT: usize = 8;
+
+/// Similarity bonus for expansion chunks when ranking them against the base rules,
+/// so text that amends a base rule is seen first and survives prompt trimming
+const EXPANSION_PREFERENCE: f32 = 0.05;
+
+/// Check the expansions for a new chat session: no duplicates, not too many,
+/// and not mixed with a comparison
+pub fn validate_session_expansions(
+    compare_game_ids: &[GameId],
+    expansion_ids: &[GameId],
+) -> Result<(), String> {
+    if expansion_ids.is_empty() {
+        return Ok(());
+    }
+    if !compare_game_ids.is_empty() {
+        return Err("A comparison session cannot have expansions in play".to_string());
+    }
+
+    let mut seen = Vec::with_capacity(expansion_ids.len());
+    for id in expansion_ids {
+        if seen.contains(id) {
+            return Err(format!("Expansion {} is listed more than once", id));
+        }
+        seen.push(*id);
+    }
+
+    if seen.len() > MAX_EXPANSIONS_IN_PLAY {
+        return Err(format!(
+            "A session can have at most {} expansions in play",
+            MAX_EXPANSIONS_IN_PLAY
+        ));
+    }
+    Ok(())
+}
+
+/// Merge base game results with each expansion's results, best match first.
+///
+/// Expansion chunks rank slightly ahead of equally similar base chunks; the
+/// reported similarity scores are left untouched.
+pub fn merge_results(
+    base: Vec<EmbeddingSearchResult>,
+    per_expansion: Vec<Vec<EmbeddingSearchResult>>,
+    limit: usize,
+) -> Vec<EmbeddingSearchResult> {
+    let mut ranked: Vec<(f32, EmbeddingSearchResult)> = base
+        .into_iter()
+        .map(|result| (result.similarity_score, result))
+        .chain(
+            per_expansion
+                .into_iter()
+                .flatten()
+                .map(|result| (result.similarity_score + EXPANSION_PREFERENCE, result)),
+        )
+        .collect();
+
+    ranked.sort_by(|(a_score, a), (b_score, b)| b_score.total_cmp(a_score).then(a.id.cmp(&b.id)));
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, result)| result)
+        .collect()
+}
+
+/// Prompt instructions naming the expansions in play and how their rules relate to the base game
+pub fn expansion_instructions(base_game: &str, expansions: &[&str]) -> String {
+    format!(
+        "Expansions in play: {}. Rules passages and house rules labelled with an expansion \
+         amend the base rules of {}; where they conflict with a base rule, follow the expansion.",
+        expansions.join(", "),
+        base_game
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EmbeddingSourceType;
+
+    fn result(id: i64, game_id: GameId, similarity_score: f32) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id,
+            chunk_text: format!("chunk {}", id),
+            similarity_score,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_prefers_expansion_text() {
+        let merged = merge_results(
+            vec![result(1, 1, 0.80), result(2, 1, 0.60), result(3, 1, 0.40)],
+            vec![vec![result(10, 2, 0.78)], vec![result(20, 3, 0.30)]],
+            4,
+        );
+
+        let ids: Vec<i64> = merged.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![10, 1, 2, 3]);
+        assert_eq!(merged[0].similarity_score, 0.78);
+    }
+
+    #[test]
+    fn test_validate_session_expansions() {
+        assert!(validate_session_expansions(&[], &[]).is_ok());
+        assert!(validate_session_expansions(&[], &[2, 3]).is_ok());
+        assert!(validate_session_expansions(&[], &[2, 2]).is_err());
+        assert!(validate_session_expansions(&[4], &[2]).is_err());
+        assert!(validate_session_expansions(&[], &[1, 2, 3, 4, 5, 6, 7]).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::{
+    answers,
+    db::{self, Database},
+    llm::{ChatMessage, LLMClient},
+    models::{EmbeddingId, FaqEntry, GameId, RuleChunk},
+};
+
+pub const DEFAULT_FAQ_QUESTIONS: usize = 20;
+pub const MAX_FAQ_QUESTIONS: usize = 50;
+
+/// Cosine similarity a chat question needs to be answered from a FAQ entry
+pub const FAQ_SIMILARITY_THRESHOLD: f32 = 0.9;
+
+/// Rule chunks shown to the model per generation request
+const CHUNKS_PER_BATCH: usize = 6;
+
+const FAQ_MAX_TOKENS: u16 = 2048;
+
+/// A question and answer produced from the rules, before it is embedded and stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFaq {
+    pub question: String,
+    pub answer: String,
+    pub cited_chunks: Vec<EmbeddingId>,
+}
+
+/// Generate up to `max_questions` likely player questions with answers grounded in the chunks.
+///
+/// The rules are covered in batches so every part of the rulebook gets questions; a batch
+/// the model fails on is skipped rather than failing the whole FAQ.
+pub async fn generate_faq(
+    llm: &LLMClient,
+    game_name: &str,
+    chunks: &[RuleChunk],
+    max_questions: usize,
+) -> Vec<GeneratedFaq> {
+    if chunks.is_empty() || max_questions == 0 {
+        return Vec::new();
+    }
+
+    let batch_count = chunks.len().div_ceil(CHUNKS_PER_BATCH);
+    let per_batch = max_questions.div_ceil(batch_count);
+
+    let mut faq: Vec<GeneratedFaq> = Vec::new();
+    for batch in chunks.chunks(CHUNKS_PER_BATCH) {
+        let available_ids: Vec<EmbeddingId> = batch.iter().map(|chunk| chunk.id).collect();
+        let response = llm
+            .chat_completion_json(
+                vec![ChatMessage::user(format!(
+                    "Write up to {} FAQ entries for these rules.",
+                    per_batch
+                ))],
+                Some(faq_prompt(game_name, batch, per_batch)),
+                Some(FAQ_MAX_TOKENS),
+                Some(0.3),
+            )
+            .await;
+
+        let entries = match response {
+            Ok(raw) => parse_faq(&raw, &available_ids),
+            Err(e) => {
+                tracing::warn!("Skipping FAQ batch after LLM failure: {}", e);
+                continue;
+            }
+        };
+
+        for entry in entries.into_iter().take(per_batch) {
+            let key = normalize_question(&entry.question);
+            if !faq.iter().any(|e| normalize_question(&e.question) == key) {
+                faq.push(entry);
+            }
+        }
+    }
+
+    faq.truncate(max_questions);
+    faq
+}
+
+fn faq_prompt(game_name: &str, chunks: &[RuleChunk], count: usize) -> String {
+    let passages = chunks
+        .iter()
+        .map(|chunk| format!("[chunk {}] {}", chunk.id, chunk.chunk_text))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!(
+        "You write the FAQ for the board game {}. Below are passages from its rulebook.
+
+Rulebook passages:
+{}
+
+Write up to {} questions players are likely to ask while learning or playing that these \
+passages answer. Answer each one using only the passages, in plain prose, and cite the \
+passages each answer relies on. Skip questions the passages do not settle.
+Respond with a single JSON object and nothing else, in the form
+{{\"faq\": [{{\"question\": \"...\", \"answer\": \"...\", \"cited_chunk_ids\": [<ids of [chunk N] passages>]}}]}}",
+        game_name, passages, count
+    )
+}
+
+#[derive(Deserialize)]
+struct RawFaq {
+    #[serde(default, alias = "entries", alias = "questions")]
+    faq: Vec<RawFaqEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawFaqEntry {
+    #[serde(default)]
+    question: String,
+    #[serde(default)]
+    answer: String,
+    #[serde(default, alias = "citations", alias = "cited_chunks")]
+    cited_chunk_ids: Vec<serde_json::Value>,
+}
+
+/// Keep entries with a question, an answer and at least one citation of a provided chunk
+fn parse_faq(raw: &str, available_ids: &[EmbeddingId]) -> Vec<GeneratedFaq> {
+    let Some(parsed) = answers::extract_json_object(raw)
+        .and_then(|json| serde_json::from_str::<RawFaq>(json).ok())
+    else {
+        tracing::warn!("FAQ response is not in the requested format");
+        return Vec::new();
+    };
+
+    parsed
+        .faq
+        .into_iter()
+        .filter_map(|entry| {
+            let mut cited_chunks = Vec::new();
+            for value in &entry.cited_chunk_ids {
+                let id = value
+                    .as_i64()
+                    .or_else(|| value.as_str().and_then(answers::parse_chunk_reference));
+                if let Some(id) = id
+                    && available_ids.contains(&id)
+                    && !cited_chunks.contains(&id)
+                {
+                    cited_chunks.push(id);
+                }
+            }
+
+            let question = entry.question.trim();
+            let answer = entry.answer.trim();
+            // An uncited answer is not grounded in the rules
+            (!question.is_empty() && !answer.is_empty() && !cited_chunks.is_empty()).then(|| {
+                GeneratedFaq {
+                    question: question.to_string(),
+                    answer: answer.to_string(),
+                    cited_chunks,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Lowercase words only, so trivially different phrasings count as the same question
+pub fn normalize_question(question: &str) -> String {
+    question
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Find the FAQ entry whose question is closest to a chat question, if it is close enough
+pub async fn lookup(
+    db: &Database,
+    game_id: GameId,
+    question_embedding: &[f32],
+    threshold: f32,
+) -> Result<Option<(FaqEntry, f32)>> {
+    let closest = db::faq::find_similar_faq_entries(db, game_id, question_embedding, 1).await?;
+    Ok(closest
+        .into_iter()
+        .next()
+        .filter(|(_, similarity)| *similarity >= threshold))
+}
+
+/// Drop a game's generated entries after its rulebook changes, since they cite chunks
+/// that no longer exist. Edited entries are kept
+pub async fn invalidate(db: &Database, game_id: GameId) {
+    match db::faq::delete_generated_faq(db, game_id).await {
+        Ok(0) => {}
+        Ok(removed) => tracing::info!(
+            "Dropped {} generated FAQ entries for game {}",
+            removed,
+            game_id
+        ),
+        Err(e) => tracing::error!("Failed to drop generated FAQ entries: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{llm::MockReply, models::EmbeddingSourceType};
+
+    fn chunk(id: EmbeddingId, text: &str) -> RuleChunk {
+        RuleChunk {
+            id,
+            game_id: 1,
+            chunk_text: text.to_string(),
+            chunk_index: id as i32,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_faq_requires_grounded_entries() {
+        let raw = r#"Sure! {"faq": [
+            {"question": "When does the robber move?", "answer": "On a seven.", "cited_chunk_ids": [2, "chunk 2"]},
+            {"question": "Can I trade?", "answer": "Yes.", "cited_chunk_ids": [99]},
+            {"question": " ", "answer": "Orphan answer.", "cited_chunk_ids": [2]}
+        ]}"#;
+        let faq = parse_faq(raw, &[1, 2]);
+
+        assert_eq!(
+            faq,
+            vec![GeneratedFaq {
+                question: "When does the robber move?".to_string(),
+                answer: "On a seven.".to_string(),
+                cited_chunks: vec![2],
+            }]
+        );
+        assert!(parse_faq("no json here", &[1]).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_question() {
+        assert_eq!(
+            normalize_question("When does the Robber move?"),
+            normalize_question("when does the robber move")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_faq_batches_dedupes_and_caps() {
+        let chunks: Vec<RuleChunk> = (1..=8)
+            .map(|id| chunk(id, &format!("Rule number {}", id)))
+            .collect();
+        let llm = LLMClient::scripted(vec![
+            MockReply::Text(
+                r#"{"faq": [
+                    {"question": "What is rule one?", "answer": "Rule number 1.", "cited_chunk_ids": [1]},
+                    {"question": "What is rule two?", "answer": "Rule number 2.", "cited_chunk_ids": [2]}
+                ]}"#
+                .to_string(),
+            ),
+            MockReply::Text(
+                r#"{"faq": [
+                    {"question": "what is rule ONE", "answer": "Again.", "cited_chunk_ids": [7]},
+                    {"question": "What is rule eight?", "answer": "Rule number 8.", "cited_chunk_ids": [8]},
+                    {"question": "Cites another batch?", "answer": "No.", "cited_chunk_ids": [1]}
+                ]}"#
+                .to_string(),
+            ),
+        ]);
+
+        let faq = generate_faq(&llm, "Harbor Traders", &chunks, 3).await;
+
+        let questions: Vec<&str> = faq.iter().map(|entry| entry.question.as_str()).collect();
+        assert_eq!(
+            questions,
+            vec![
+                "What is rule one?",
+                "What is rule two?",
+                "What is rule eight?"
+            ]
+        );
+    }
+}
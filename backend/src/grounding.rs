@@ -0,0 +1,352 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{
+    llm::{ChatMessage, LLMClient},
+    models::{ContextSource, GroundingMethod, GroundingReport, UnsupportedSentence},
+};
+
+/// Share of a sentence's key terms a passage must contain for the lexical check to accept it
+pub const MIN_LEXICAL_SUPPORT: f32 = 0.5;
+
+/// Sentences with fewer key terms than this carry no checkable claim
+const MIN_CLAIM_TERMS: usize = 3;
+
+const JUDGE_MAX_TOKENS: u16 = 800;
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "because", "been", "before", "being", "both", "can't", "cannot",
+    "could", "does", "doesn't", "each", "either", "every", "from", "have", "into", "just", "more",
+    "most", "must", "only", "other", "over", "same", "should", "some", "such", "than", "that",
+    "their", "them", "then", "there", "these", "they", "this", "those", "through", "under",
+    "until", "very", "what", "when", "where", "whether", "which", "while", "will", "with", "would",
+    "your", "you're",
+];
+
+/// A passage the answer may rely on, labelled the way the prompt showed it
+#[derive(Debug, Clone)]
+pub struct Evidence {
+    pub label: String,
+    pub text: String,
+}
+
+/// Passages the model saw: every context chunk, plus the house rules section if any
+pub fn evidence_from_sources(sources: &[ContextSource], house_rules: &str) -> Vec<Evidence> {
+    let mut evidence: Vec<Evidence> = sources
+        .iter()
+        .map(|source| Evidence {
+            label: format!("[chunk {}]", source.embedding_id),
+            text: source.chunk_text.clone(),
+        })
+        .collect();
+    if !house_rules.trim().is_empty() {
+        evidence.push(Evidence {
+            label: "[house rules]".to_string(),
+            text: house_rules.to_string(),
+        });
+    }
+    evidence
+}
+
+/// Check every sentence of an answer against the evidence, asking the model to judge
+/// and falling back to word overlap when it gives no usable verdict
+pub async fn verify_answer(
+    llm: &LLMClient,
+    answer: &str,
+    evidence: &[Evidence],
+) -> GroundingReport {
+    let sentences = split_sentences(answer);
+
+    let judged = if sentences.is_empty() || evidence.is_empty() {
+        None
+    } else {
+        judge(llm, &sentences, evidence).await
+    };
+
+    let (method, unsupported_sentences) = match judged {
+        Some(unsupported) => (GroundingMethod::LlmJudge, unsupported),
+        None => (
+            GroundingMethod::Lexical,
+            lexical_check(&sentences, evidence),
+        ),
+    };
+
+    GroundingReport {
+        method,
+        sentences_checked: sentences.len(),
+        unsupported_sentences,
+        retried: false,
+    }
+}
+
+/// Extra instructions for regenerating an answer that failed verification
+pub fn stricter_instructions(unsupported: &[UnsupportedSentence]) -> String {
+    let mut instructions = String::from(
+        "Strict Grounding:
+A previous answer to this question made claims the rules above do not support:
+",
+    );
+    for sentence in unsupported {
+        instructions.push_str(&format!("- {}\n", sentence.sentence));
+    }
+    instructions.push_str(
+        "Answer again using only statements the rules passages state or directly imply. \
+Leave out anything you cannot back with a passage, and set \"not_covered_by_rules\" \
+to true if that leaves nothing to say.",
+    );
+    instructions
+}
+
+/// Split an answer into sentences, treating line breaks as boundaries too
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+
+    for line in text.lines() {
+        let mut current = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            current.push(c);
+            if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|next| next.is_whitespace())
+            {
+                push_sentence(&mut sentences, &current);
+                current.clear();
+            }
+        }
+        push_sentence(&mut sentences, &current);
+    }
+
+    sentences
+}
+
+fn push_sentence(sentences: &mut Vec<String>, text: &str) {
+    let sentence = text.trim().trim_start_matches(['-', '*', '•']).trim();
+    if sentence.chars().any(char::is_alphanumeric) {
+        sentences.push(sentence.to_string());
+    }
+}
+
+/// Flag sentences whose key terms mostly appear in no single passage
+pub fn lexical_check(sentences: &[String], evidence: &[Evidence]) -> Vec<UnsupportedSentence> {
+    let passages: Vec<HashSet<String>> = evidence
+        .iter()
+        .map(|passage| key_terms(&passage.text))
+        .collect();
+
+    sentences
+        .iter()
+        .filter_map(|sentence| {
+            let terms = key_terms(sentence);
+            if terms.len() < MIN_CLAIM_TERMS {
+                return None;
+            }
+
+            let support = passages
+                .iter()
+                .map(|passage| terms.intersection(passage).count() as f32 / terms.len() as f32)
+                .fold(0.0, f32::max);
+
+            (support < MIN_LEXICAL_SUPPORT).then(|| UnsupportedSentence {
+                sentence: sentence.clone(),
+                reason: Some(format!(
+                    "Only {:.0}% of its key terms appear in any retrieved passage",
+                    support * 100.0
+                )),
+            })
+        })
+        .collect()
+}
+
+/// Lowercased content words with a plural "s" stripped
+fn key_terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| word.len() > 3 && !STOPWORDS.contains(&word.as_str()))
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if !stem.ends_with('s') => stem.to_string(),
+            _ => word,
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct JudgeVerdict {
+    #[serde(default)]
+    unsupported_sentences: Vec<JudgedSentence>,
+}
+
+#[derive(Deserialize)]
+struct JudgedSentence {
+    sentence: serde_json::Value,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Ask the model which sentences the passages do not support
+async fn judge(
+    llm: &LLMClient,
+    sentences: &[String],
+    evidence: &[Evidence],
+) -> Option<Vec<UnsupportedSentence>> {
+    let response = llm
+        .chat_completion_json(
+            vec![ChatMessage::user("Check the answer sentences.")],
+            Some(judge_prompt(sentences, evidence)),
+            Some(JUDGE_MAX_TOKENS),
+            Some(0.0),
+        )
+        .await;
+
+    match response {
+        Ok(raw) => {
+            let verdict = parse_judge_response(&raw, sentences);
+            if verdict.is_none() {
+                tracing::warn!("Grounding judge gave no usable verdict, using word overlap");
+            }
+            verdict
+        }
+        Err(e) => {
+            tracing::warn!("Grounding judge failed, using word overlap: {}", e);
+            None
+        }
+    }
+}
+
+fn judge_prompt(sentences: &[String], evidence: &[Evidence]) -> String {
+    let passages = evidence
+        .iter()
+        .map(|passage| format!("{} {}", passage.label, passage.text))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let numbered = sentences
+        .iter()
+        .enumerate()
+        .map(|(i, sentence)| format!("{}. {}", i + 1, sentence))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "You check whether an answer about board game rules is supported by the rules passages it was based on.
+
+Passages:
+{}
+
+Answer sentences:
+{}
+
+A sentence is supported when the passages state it or directly imply it. Greetings, hedges and \
+restatements of the question count as supported.
+Respond with a single JSON object and nothing else, in the form
+{{\"unsupported_sentences\": [{{\"sentence\": <number>, \"reason\": \"<what the passages do not back up>\"}}]}}
+Use an empty list when every sentence is supported.",
+        passages, numbered
+    )
+}
+
+/// Map the judge's sentence numbers back to sentences, ignoring numbers out of range
+fn parse_judge_response(raw: &str, sentences: &[String]) -> Option<Vec<UnsupportedSentence>> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    let verdict: JudgeVerdict = serde_json::from_str(raw.get(start..=end)?).ok()?;
+
+    let mut unsupported: Vec<UnsupportedSentence> = Vec::new();
+    for judged in verdict.unsupported_sentences {
+        let number = judged
+            .sentence
+            .as_u64()
+            .or_else(|| judged.sentence.as_str().and_then(|s| s.trim().parse().ok()));
+        let Some(sentence) = number
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| sentences.get(i as usize))
+        else {
+            continue;
+        };
+        if !unsupported.iter().any(|u| &u.sentence == sentence) {
+            unsupported.push(UnsupportedSentence {
+                sentence: sentence.clone(),
+                reason: judged.reason.filter(|reason| !reason.trim().is_empty()),
+            });
+        }
+    }
+
+    Some(unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockReply;
+
+    fn robber_evidence() -> Vec<Evidence> {
+        vec![Evidence {
+            label: "[chunk 3]".to_string(),
+            text: "When a seven is rolled, the active player moves the robber to another hex \
+                   and steals one resource card from an adjacent player."
+                .to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let sentences =
+            split_sentences("Move the robber. Then steal a card!\n- Cities cost 3.5 ore?\n\nYes");
+        assert_eq!(
+            sentences,
+            vec![
+                "Move the robber.",
+                "Then steal a card!",
+                "Cities cost 3.5 ore?",
+                "Yes"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexical_check_flags_unsupported_claims() {
+        let sentences = vec![
+            "The active player moves the robber and steals a resource card.".to_string(),
+            "Players may also trade development cards with the bank.".to_string(),
+            "Sure thing.".to_string(),
+        ];
+        let unsupported = lexical_check(&sentences, &robber_evidence());
+
+        assert_eq!(unsupported.len(), 1);
+        assert_eq!(unsupported[0].sentence, sentences[1]);
+    }
+
+    #[test]
+    fn test_parse_judge_response() {
+        let sentences = vec!["A.".to_string(), "B.".to_string()];
+        let raw = r#"```json
+{"unsupported_sentences": [{"sentence": 2, "reason": "not in the rules"}, {"sentence": "2"}, {"sentence": 9}]}
+```"#;
+        let unsupported = parse_judge_response(raw, &sentences).unwrap();
+
+        assert_eq!(unsupported.len(), 1);
+        assert_eq!(unsupported[0].sentence, "B.");
+        assert_eq!(unsupported[0].reason.as_deref(), Some("not in the rules"));
+        assert!(parse_judge_response("all good", &sentences).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_answer_uses_judge_then_falls_back() {
+        let answer = "Move the robber when a seven is rolled. You also lose your turn.";
+
+        let llm = LLMClient::scripted(vec![MockReply::Text(
+            r#"{"unsupported_sentences": [{"sentence": 2, "reason": "turn loss is not mentioned"}]}"#
+                .to_string(),
+        )]);
+        let report = verify_answer(&llm, answer, &robber_evidence()).await;
+        assert_eq!(report.method, GroundingMethod::LlmJudge);
+        assert_eq!(report.sentences_checked, 2);
+        assert_eq!(
+            report.unsupported_sentences[0].sentence,
+            "You also lose your turn."
+        );
+
+        let llm = LLMClient::scripted(vec![MockReply::Text("Looks fine to me".to_string())]);
+        let report = verify_answer(&llm, answer, &robber_evidence()).await;
+        assert_eq!(report.method, GroundingMethod::Lexical);
+        assert!(report.is_grounded());
+    }
+}
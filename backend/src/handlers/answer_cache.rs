@@ -0,0 +1,68 @@
+use dropshot::{Query, RequestContext, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::answer_cache,
+    handlers::{HttpError, HttpOk, internal_error, success_response},
+    models::{AnswerCacheStats, ClearAnswerCacheResponse, GameId},
+    semantic_cache::DEFAULT_SIMILARITY_THRESHOLD,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct AnswerCacheQuery {
+    /// Limit to one game; all games when omitted
+    pub game_id: Option<GameId>,
+}
+
+/// Get answer cache size and hit statistics
+#[endpoint {
+    method = GET,
+    path = "/api/chat/cache"
+}]
+pub async fn get_answer_cache_stats(
+    rqctx: RequestContext<AppState>,
+    query: Query<AnswerCacheQuery>,
+) -> Result<HttpOk<AnswerCacheStats>, HttpError> {
+    let app_state = rqctx.context();
+    let query = query.into_inner();
+    let db = app_state.db();
+
+    match answer_cache::get_answer_cache_totals(&db, query.game_id).await {
+        Ok((entries, total_hits)) => success_response(AnswerCacheStats {
+            game_id: query.game_id,
+            entries,
+            total_hits,
+            similarity_threshold: DEFAULT_SIMILARITY_THRESHOLD,
+        }),
+        Err(e) => {
+            tracing::error!("Failed to get answer cache stats: {}", e);
+            Err(internal_error(
+                "Failed to get answer cache stats".to_string(),
+            ))
+        }
+    }
+}
+
+/// Clear cached answers
+#[endpoint {
+    method = DELETE,
+    path = "/api/chat/cache"
+}]
+pub async fn clear_answer_cache(
+    rqctx: RequestContext<AppState>,
+    query: Query<AnswerCacheQuery>,
+) -> Result<HttpOk<ClearAnswerCacheResponse>, HttpError> {
+    let app_state = rqctx.context();
+    let query = query.into_inner();
+    let db = app_state.db();
+
+    match answer_cache::invalidate_answer_cache(&db, query.game_id).await {
+        Ok(entries_removed) => success_response(ClearAnswerCacheResponse { entries_removed }),
+        Err(e) => {
+            tracing::error!("Failed to clear answer cache: {}", e);
+            Err(internal_error("Failed to clear answer cache".to_string()))
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+
+use dropshot::{Query, RequestContext, TypedBody, endpoint};
+
+use crate::{
+    AppState, backup,
+    db::backup as backup_db,
+    discovery,
+    handlers::{HttpError, HttpOk, bad_request_error, internal_error, success_response},
+    models::{LibraryArchive, RestoreLibraryQuery, RestoreSummary},
+    pdf::generate_pdf_filename,
+    semantic_cache,
+};
+
+/// Archives carry every rulebook, so they may be far larger than a single upload
+const MAX_ARCHIVE_BYTES: usize = 512 * 1024 * 1024;
+
+/// Export the whole library as a versioned archive: games, house rules,
+/// rulebook files, chunks with their embeddings, and chat history
+#[endpoint {
+    method = GET,
+    path = "/api/backup"
+}]
+pub async fn export_library(
+    rqctx: RequestContext<AppState>,
+) -> Result<HttpOk<LibraryArchive>, HttpError> {
+    let app_state = rqctx.context();
+
+    match backup::export_library(app_state).await {
+        Ok(archive) => success_response(archive),
+        Err(e) => {
+            tracing::error!("Failed to export library: {:#}", e);
+            Err(internal_error("Failed to export library".to_string()))
+        }
+    }
+}
+
+/// Restore an exported archive into the library. Games already in the library
+/// are skipped, overwritten or merged according to `conflict`
+#[endpoint {
+    method = POST,
+    path = "/api/backup/restore",
+    request_body_max_bytes = MAX_ARCHIVE_BYTES,
+}]
+pub async fn restore_library(
+    rqctx: RequestContext<AppState>,
+    query: Query<RestoreLibraryQuery>,
+    body: TypedBody<LibraryArchive>,
+) -> Result<HttpOk<RestoreSummary>, HttpError> {
+    let app_state = rqctx.context();
+    let conflict = query.into_inner().conflict;
+    let mut archive = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the archive before touching the library
+    backup::check_format_version(archive.format_version)
+        .map_err(|e| bad_request_error(e.to_string()))?;
+    let mut documents = Vec::with_capacity(archive.games.len());
+    for archived in &archive.games {
+        documents.push(match &archived.rules_document {
+            Some(document) => Some(backup::decode_document(document).map_err(|e| {
+                bad_request_error(format!(
+                    "Invalid rulebook for {}: {:#}",
+                    archived.game.name, e
+                ))
+            })?),
+            None => None,
+        });
+    }
+
+    let reembedded_chunks = backup::reembed_chunks(app_state, &mut archive)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to re-embed archived chunks: {:#}", e);
+            internal_error(format!("Failed to re-embed archived chunks: {:#}", e))
+        })?;
+
+    let (mut summary, restored_documents) =
+        backup_db::restore_library(&db, &archive.games, &archive.leagues, conflict)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to restore library: {}", e);
+                internal_error("Failed to restore library".to_string())
+            })?;
+    summary.reembedded_chunks = reembedded_chunks;
+
+    let uploads_dir = app_state.uploads_dir();
+    if !restored_documents.is_empty() && !uploads_dir.exists() {
+        fs::create_dir_all(uploads_dir)
+            .map_err(|e| internal_error(format!("Failed to create uploads directory: {}", e)))?;
+    }
+    for position in restored_documents {
+        let Some(bytes) = &documents[position] else {
+            continue;
+        };
+        let game_id = summary.game_ids[position];
+        let file_path = uploads_dir.join(generate_pdf_filename(game_id, "rules.pdf"));
+
+        fs::write(&file_path, bytes)
+            .map_err(|e| internal_error(format!("Failed to save file: {}", e)))?;
+        backup_db::set_rules_pdf_path(&db, game_id, &file_path.to_string_lossy())
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to record rulebook of game {}: {}", game_id, e);
+                internal_error("Failed to record restored rulebook".to_string())
+            })?;
+    }
+
+    // Cached answers for replaced or merged games may no longer match their rules
+    for game_id in &summary.game_ids {
+        semantic_cache::invalidate(&db, Some(*game_id)).await;
+    }
+    discovery::index_descriptions(&db, app_state.embedder()).await;
+
+    success_response(summary)
+}
@@ -1,20 +1,48 @@
+use std::collections::HashMap;
+
 use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::{created_response, internal_error, not_found_error, success_response};
+use super::{
+    bad_request_error, created_response, internal_error, not_found_error, success_response,
+};
 use crate::{
     AppState,
-    db::chat,
+    answers::{self, StructuredAnswer},
+    comparison,
+    db::{Database, chat},
+    expansions, faq_generation,
+    grounding::{self, Evidence},
     handlers::{HttpCreated, HttpError, HttpOk},
-    llm::ChatMessage,
+    llm::{ChatMessage, LLMClient},
     models::{
         ChatHistory, ChatRequest, ChatResponse, ChatSession, ChatSessionId, ChatSessionSummary,
-        ContextSource, CreateChatSessionRequest, GameId, MessageRole, PaginatedResponse,
-        SimilaritySearchRequest,
+        ContextSource, CreateCachedAnswerRequest, CreateChatMessageRequest,
+        CreateChatSessionRequest, EmbeddingId, EmbeddingSearchResult, EmbeddingSourceType,
+        FaqEntry, FaqMatch, Game, GameId, GroundingReport, MessageRole, PaginatedResponse,
+        PromptTemplateId, PromptTemplateSource, QueryStrategy, QueryStrategyComparison,
+        QueryStrategyComparisonRequest, QueryStrategyRun, RetrievedChunk, SimilaritySearchRequest,
     },
+    prompt::{PromptBudget, PromptInputs, PromptSections},
+    query_transform,
+    semantic_cache::{self, CacheHit},
+    templates,
+    tools::{self, ToolContext, ToolLoopOutcome},
 };
 
+/// Completion budget for chat answers; the JSON wrapper needs headroom beyond the prose itself
+const ANSWER_MAX_TOKENS: u16 = 1024;
+
+/// Chunks retrieved for a single-game question
+const RULES_CHUNK_LIMIT: usize = 5;
+
+/// Similarity a chunk needs before it is offered to the model
+const RELEVANCE_THRESHOLD: f32 = 0.3;
+
+/// Most chunks a strategy comparison may return per strategy
+const MAX_COMPARISON_RESULTS: u32 = 20;
+
 #[derive(Deserialize, JsonSchema)]
 pub struct ChatSessionPathParam {
     pub id: ChatSessionId,
@@ -32,12 +60,16 @@ pub struct RulesSearchQuery {
     pub game_id: String,
     pub query: String,
     pub limit: Option<usize>,
+    /// How to turn the query into search text (defaults to direct)
+    pub strategy: Option<QueryStrategy>,
 }
 
 #[derive(Serialize, JsonSchema)]
 pub struct RulesSearchResponse {
     pub game_id: i64,
     pub query: String,
+    /// Texts that were embedded and searched
+    pub queries: Vec<String>,
     pub results: Vec<SearchResult>,
     pub total_results: usize,
 }
@@ -118,8 +150,21 @@ pub async fn create_chat_session(
     let create_request = body.into_inner();
     let db = app_state.db();
 
+    comparison::validate_compared_games(create_request.game_id, &create_request.compare_game_ids)
+        .map_err(bad_request_error)?;
+    expansions::validate_session_expansions(
+        &create_request.compare_game_ids,
+        &create_request.expansion_ids,
+    )
+    .map_err(bad_request_error)?;
+
     match chat::create_chat_session(&db, create_request).await {
         Ok(session) => created_response(session),
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message == "Game does not exist" || message.contains("is not an expansion") =>
+        {
+            Err(bad_request_error(message))
+        }
         Err(e) => {
             tracing::error!("Failed to create chat session: {}", e);
             Err(internal_error("Failed to create chat session".to_string()))
@@ -139,7 +184,7 @@ pub async fn search_rules(
     let app_state = rqctx.context();
     let search_query = query.into_inner();
     let limit = search_query.limit.unwrap_or(5);
-    let db = app_state.db();
+    let strategy = search_query.strategy.unwrap_or_default();
 
     // Parse game_id from string
     let game_id: GameId = search_query
@@ -147,27 +192,11 @@ pub async fn search_rules(
         .parse()
         .map_err(|_| super::bad_request_error("Invalid game_id parameter".to_string()))?;
 
-    // Preprocess and enhance the search query for better embedding matching
-    let enhanced_query = enhance_search_query(&search_query.query);
-
-    // Generate embedding for the enhanced search query
-    let query_embedding = app_state
-        .embedder()
-        .generate_embedding(&enhanced_query)
-        .await
-        .map_err(|e| internal_error(format!("Failed to generate query embedding: {}", e)))?;
-
-    // Search using database layer directly
-    let similarity_request = SimilaritySearchRequest {
-        game_id,
-        query_embedding,
-        similarity_threshold: 0.0, // Include all results, let sorting handle ranking
-        limit: limit as u32,
-    };
-
-    let search_results = crate::db::embeddings::similarity_search(&db, similarity_request)
-        .await
-        .map_err(|e| internal_error(format!("Search failed: {}", e)))?;
+    // Turn the query into search text with the chosen strategy
+    let RuleRetrieval {
+        queries,
+        results: search_results,
+    } = retrieve_top_rules(app_state, game_id, &search_query.query, strategy, limit).await?;
 
     let results: Vec<SearchResult> = search_results
         .into_iter()
@@ -183,6 +212,7 @@ pub async fn search_rules(
     let response = RulesSearchResponse {
         game_id,
         query: search_query.query,
+        queries,
         total_results: results.len(),
         results,
     };
@@ -190,6 +220,69 @@ pub async fn search_rules(
     success_response(response)
 }
 
+/// Run one question through several query strategies to compare their retrieval
+#[endpoint {
+    method = POST,
+    path = "/api/chat/query-strategies"
+}]
+pub async fn compare_query_strategies(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<QueryStrategyComparisonRequest>,
+) -> Result<HttpOk<QueryStrategyComparison>, HttpError> {
+    let app_state = rqctx.context();
+    let request = body.into_inner();
+
+    if request.question.trim().is_empty() {
+        return Err(bad_request_error("Question cannot be empty".to_string()));
+    }
+    let limit = request.limit.unwrap_or(5).clamp(1, MAX_COMPARISON_RESULTS) as usize;
+    let strategies = request
+        .strategies
+        .unwrap_or_else(|| QueryStrategy::ALL.to_vec());
+
+    let mut runs = Vec::with_capacity(strategies.len());
+    for strategy in strategies {
+        let started = std::time::Instant::now();
+        let RuleRetrieval { queries, results } = retrieve_top_rules(
+            app_state,
+            request.game_id,
+            &request.question,
+            strategy,
+            limit,
+        )
+        .await?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let retrieved: Vec<i64> = results.iter().map(|result| result.id).collect();
+        let scores = request
+            .expected_chunk_ids
+            .as_deref()
+            .map(|expected| query_transform::retrieval_scores(&retrieved, expected));
+
+        runs.push(QueryStrategyRun {
+            strategy,
+            queries,
+            results: results
+                .into_iter()
+                .map(|result| RetrievedChunk {
+                    embedding_id: result.id,
+                    similarity_score: result.similarity_score,
+                    chunk_text: result.chunk_text,
+                })
+                .collect(),
+            latency_ms,
+            recall: scores.map(|(recall, _)| recall),
+            reciprocal_rank: scores.map(|(_, reciprocal_rank)| reciprocal_rank),
+        });
+    }
+
+    success_response(QueryStrategyComparison {
+        game_id: request.game_id,
+        question: request.question,
+        runs,
+    })
+}
+
 /// Send a message and get AI response
 #[endpoint {
     method = POST,
@@ -222,14 +315,19 @@ pub async fn chat_with_rules(
         })?;
 
     let game_id = session_history.session.game_id;
+    let is_comparison = session_history.session.is_comparison();
+    let expansion_ids = &session_history.session.expansion_ids;
+    // Answers covering several games' rules are never stored per game
+    let single_game = !is_comparison && expansion_ids.is_empty();
 
     // 2. Save user message to database
     let _user_message = chat::add_message_to_session(
         &db,
-        chat_request.session_id,
-        crate::models::MessageRole::User,
-        chat_request.message.clone(),
-        None,
+        CreateChatMessageRequest::new(
+            chat_request.session_id,
+            MessageRole::User,
+            chat_request.message.clone(),
+        ),
     )
     .await
     .map_err(|e| {
@@ -237,97 +335,533 @@ pub async fn chat_with_rules(
         internal_error("Failed to save message".to_string())
     })?;
 
-    // 3. Generate embedding for user's question
-    let query_embedding = app_state
-        .embedder()
-        .generate_embedding(&chat_request.message)
+    // 3. Answer from the game's curated FAQ, or reuse an earlier answer to the same
+    //    question while its rules text is unchanged. Both are per game, so comparisons
+    //    and sessions with expansions always go to the model
+    let use_faq = chat_request.use_faq.unwrap_or(true) && single_game;
+    let use_cache = chat_request.use_cache.unwrap_or(true) && single_game;
+    let question_embedding = if use_faq || use_cache {
+        let question_embedding = app_state
+            .embedder()
+            .generate_embedding(&chat_request.message)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to generate query embedding: {}", e);
+                internal_error("Failed to process question".to_string())
+            })?;
+
+        if use_faq {
+            match faq_generation::lookup(
+                &db,
+                game_id,
+                &question_embedding,
+                faq_generation::FAQ_SIMILARITY_THRESHOLD,
+            )
+            .await
+            {
+                Ok(Some((entry, similarity))) => {
+                    if let Some(response) =
+                        respond_from_faq(&db, chat_request.session_id, entry, similarity).await?
+                    {
+                        return Ok(response);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("FAQ lookup failed: {}", e),
+            }
+        }
+
+        if use_cache {
+            match semantic_cache::lookup(
+                &db,
+                game_id,
+                &question_embedding,
+                semantic_cache::DEFAULT_SIMILARITY_THRESHOLD,
+            )
+            .await
+            {
+                Ok(Some(hit)) => {
+                    return respond_from_cache(&db, chat_request.session_id, hit).await;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Answer cache lookup failed: {}", e),
+            }
+        }
+        use_cache.then_some(question_embedding)
+    } else {
+        None
+    };
+
+    // Search for relevant rule chunks using similarity search
+    let strategy = chat_request.query_strategy.unwrap_or_default();
+    let retrieval = if is_comparison {
+        retrieve_comparison_rules(
+            app_state,
+            &session_history.session.game_ids(),
+            &chat_request.message,
+            strategy,
+        )
+        .await?
+    } else if !expansion_ids.is_empty() {
+        retrieve_expansion_rules(
+            app_state,
+            game_id,
+            expansion_ids,
+            &chat_request.message,
+            strategy,
+        )
+        .await?
+    } else {
+        retrieve_rules(app_state, game_id, &chat_request.message, strategy).await?
+    };
+    tracing::debug!(
+        "Retrieved rules with the {} strategy using {:?}",
+        strategy.as_str(),
+        retrieval.queries
+    );
+    let search_results = retrieval.results;
+
+    // 4. Render the game's prompt template around rules, house rules and history
+    //    fitted into the model's context window
+    // Lookup tools only see one game's rules, so comparisons and expansions rely on retrieval alone
+    let use_tools = chat_request.use_tools.unwrap_or(true) && single_game;
+    let max_tokens = Some(ANSWER_MAX_TOKENS);
+    let temperature = Some(0.7); // Balanced creativity/consistency
+
+    let RulesPrompt {
+        system_prompt,
+        sections,
+        ..
+    } = build_rules_prompt(
+        app_state,
+        RulesPromptRequest {
+            game_id,
+            compare_game_ids: &session_history.session.compare_game_ids,
+            expansion_ids,
+            question: &chat_request.message,
+            search_results: &search_results,
+            history: &session_history.messages,
+            use_tools,
+            template_override: None,
+            persona_override: None,
+        },
+    )
+    .await?;
+
+    // Only chunks the model actually sees count as context
+    let mut context_sources: Vec<ContextSource> = search_results
+        .iter()
+        .filter(|result| sections.included_chunk_ids.contains(&result.id))
+        .map(ContextSource::from)
+        .collect();
+
+    // 5. Send to LLM API with context
+    let llm = app_state.llm();
+    let messages = vec![ChatMessage::user(chat_request.message.clone())];
+
+    // Let the model look things up with tools, falling back to a plain completion
+    // for models that do not support tool calling
+    let tool_outcome = if use_tools {
+        let tool_context = ToolContext {
+            db: &db,
+            embedder: app_state.embedder(),
+            game_id,
+        };
+        match tools::run_tool_loop(
+            llm,
+            &tool_context,
+            messages.clone(),
+            system_prompt.clone(),
+            max_tokens,
+            temperature,
+        )
+        .await
+        {
+            Ok(outcome) => Some(outcome),
+            Err(e) => {
+                tracing::warn!("Tool-calling chat failed, retrying without tools: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let ToolLoopOutcome {
+        answer: assistant_response,
+        trace: tool_trace,
+        sources: tool_sources,
+    } = match tool_outcome {
+        Some(outcome) => outcome,
+        None => {
+            let answer = llm
+                .chat_completion_json(
+                    messages,
+                    Some(system_prompt.clone()),
+                    max_tokens,
+                    temperature,
+                )
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to generate LLM response: {}", e);
+                    internal_error("Failed to generate response".to_string())
+                })?;
+            ToolLoopOutcome {
+                answer,
+                trace: Vec::new(),
+                sources: Vec::new(),
+            }
+        }
+    };
+
+    // Chunks the model fetched through tools count as context too
+    for source in tool_sources {
+        if !context_sources
+            .iter()
+            .any(|s| s.embedding_id == source.embedding_id)
+        {
+            context_sources.push(source);
+        }
+    }
+
+    // 6. Parse the structured answer
+    let context_chunk_ids: Vec<i64> = context_sources.iter().map(|s| s.embedding_id).collect();
+    let mut parsed = answers::parse_answer(&assistant_response, &context_chunk_ids);
+    if !parsed.structured {
+        tracing::warn!("LLM ignored the structured answer format, using free text");
+    }
+
+    // 7. Check the answer's claims against the passages it was given.
+    //    "Not covered" answers make no claims worth checking
+    let grounding = if chat_request.verify_grounding.unwrap_or(true) && !parsed.not_covered_by_rules
+    {
+        let evidence = grounding::evidence_from_sources(&context_sources, &sections.house_rules);
+        let (checked, report) = check_grounding(
+            llm,
+            parsed,
+            &evidence,
+            &system_prompt,
+            &chat_request.message,
+            &context_chunk_ids,
+            chat_request.retry_ungrounded.unwrap_or(false),
+        )
+        .await;
+        parsed = checked;
+        Some(report)
+    } else {
+        None
+    };
+
+    // Mark which sources the answer cited
+    for source in context_sources.iter_mut() {
+        source.cited = parsed.cited_chunk_ids.contains(&source.embedding_id);
+    }
+
+    // 8. Save assistant response to database
+    let mut message_request = CreateChatMessageRequest::new(
+        chat_request.session_id,
+        MessageRole::Assistant,
+        parsed.answer,
+    );
+    message_request.context_chunks = Some(context_chunk_ids);
+    message_request.tool_trace = (!tool_trace.is_empty()).then_some(tool_trace);
+    message_request.cited_chunks = Some(parsed.cited_chunk_ids);
+    message_request.confidence = parsed.confidence;
+    message_request.not_covered_by_rules = parsed.structured.then_some(parsed.not_covered_by_rules);
+
+    let assistant_message = chat::add_message_to_session(&db, message_request)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to generate query embedding: {}", e);
-            internal_error("Failed to process question".to_string())
+            tracing::error!("Failed to save assistant message: {}", e);
+            internal_error("Failed to save response".to_string())
         })?;
 
-    // 4. Search for relevant rule chunks using similarity search
-    let similarity_request = SimilaritySearchRequest {
-        game_id,
-        query_embedding,
-        similarity_threshold: 0.3, // Reasonable threshold for relevance
-        limit: 5,                  // Get top 10 most relevant chunks
+    // Only opening questions are cached; later answers may lean on earlier turns.
+    // Answers that failed verification are not worth replaying
+    if let Some(question_embedding) = question_embedding
+        && session_history.messages.is_empty()
+        && grounding.as_ref().is_none_or(GroundingReport::is_grounded)
+    {
+        let cache_request = CreateCachedAnswerRequest {
+            game_id,
+            question: chat_request.message.clone(),
+            question_embedding,
+            answer: assistant_message.content.clone(),
+            context_sources: context_sources.clone(),
+            cited_chunks: assistant_message.cited_chunks.clone().unwrap_or_default(),
+            confidence: parsed.confidence,
+            not_covered_by_rules: parsed.not_covered_by_rules,
+            structured: parsed.structured,
+            source_fingerprint: String::new(),
+        };
+        if let Err(e) = semantic_cache::store(&db, cache_request).await {
+            tracing::warn!("Failed to cache answer: {}", e);
+        }
+    }
+
+    // 9. Return response with context sources
+    let chat_response = ChatResponse {
+        message: assistant_message,
+        context_sources,
+        confidence: parsed.confidence,
+        not_covered_by_rules: parsed.not_covered_by_rules,
+        structured: parsed.structured,
+        cached: false,
+        cache_similarity: None,
+        grounding,
+        faq_match: None,
+    };
+
+    success_response(chat_response)
+}
+
+/// Verify an answer against its evidence; when `retry` is set and sentences are
+/// unsupported, regenerate once with a stricter prompt and keep whichever answer
+/// has fewer unsupported sentences
+async fn check_grounding(
+    llm: &LLMClient,
+    parsed: StructuredAnswer,
+    evidence: &[Evidence],
+    system_prompt: &str,
+    question: &str,
+    context_chunk_ids: &[EmbeddingId],
+    retry: bool,
+) -> (StructuredAnswer, GroundingReport) {
+    let report = grounding::verify_answer(llm, &parsed.answer, evidence).await;
+    if report.is_grounded() || !retry {
+        return (parsed, report);
+    }
+
+    tracing::info!(
+        "{} unsupported sentence(s) in answer, retrying with a stricter prompt",
+        report.unsupported_sentences.len()
+    );
+    let strict_prompt = format!(
+        "{}\n\n{}",
+        system_prompt,
+        grounding::stricter_instructions(&report.unsupported_sentences)
+    );
+    let raw = match llm
+        .chat_completion_json(
+            vec![ChatMessage::user(question)],
+            Some(strict_prompt),
+            Some(ANSWER_MAX_TOKENS),
+            Some(0.2),
+        )
+        .await
+    {
+        Ok(raw) => raw,
+        Err(e) => {
+            tracing::warn!("Stricter retry failed, keeping the original answer: {}", e);
+            return (parsed, report);
+        }
     };
 
-    let search_results = crate::db::embeddings::similarity_search(&db, similarity_request)
+    let retried = answers::parse_answer(&raw, context_chunk_ids);
+    let mut retried_report = grounding::verify_answer(llm, &retried.answer, evidence).await;
+    if retried_report.unsupported_sentences.len() > report.unsupported_sentences.len() {
+        return (parsed, report);
+    }
+    retried_report.retried = true;
+    (retried, retried_report)
+}
+
+/// Replay a cached answer into the session as if it had just been generated
+async fn respond_from_cache(
+    db: &Database,
+    session_id: ChatSessionId,
+    hit: CacheHit,
+) -> Result<HttpOk<ChatResponse>, HttpError> {
+    let CacheHit { answer, similarity } = hit;
+    tracing::info!(
+        "Answering from cache entry {} (similarity {:.3})",
+        answer.id,
+        similarity
+    );
+
+    let mut message_request =
+        CreateChatMessageRequest::new(session_id, MessageRole::Assistant, answer.answer);
+    message_request.context_chunks = Some(
+        answer
+            .context_sources
+            .iter()
+            .map(|s| s.embedding_id)
+            .collect(),
+    );
+    message_request.cited_chunks = Some(answer.cited_chunks);
+    message_request.confidence = answer.confidence;
+    message_request.not_covered_by_rules = answer.structured.then_some(answer.not_covered_by_rules);
+
+    let assistant_message = chat::add_message_to_session(db, message_request)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to search embeddings: {}", e);
-            internal_error("Failed to search rules".to_string())
+            tracing::error!("Failed to save assistant message: {}", e);
+            internal_error("Failed to save response".to_string())
         })?;
 
-    // 5. Prepare context with relevant rules
-    let context_sources: Vec<ContextSource> = search_results
-        .iter()
-        .map(|result| ContextSource {
-            embedding_id: result.id,
-            chunk_text: result.chunk_text.clone(),
-            source_type: result.source_type.as_str().to_string(),
-            similarity_score: result.similarity_score,
-            metadata: result.metadata.clone(),
+    success_response(ChatResponse {
+        message: assistant_message,
+        context_sources: answer.context_sources,
+        confidence: answer.confidence,
+        not_covered_by_rules: answer.not_covered_by_rules,
+        structured: answer.structured,
+        cached: true,
+        cache_similarity: Some(similarity),
+        grounding: None,
+        faq_match: None,
+    })
+}
+
+/// Answer with a curated FAQ entry, citing the chunks the entry is based on
+async fn respond_from_faq(
+    db: &Database,
+    session_id: ChatSessionId,
+    entry: FaqEntry,
+    similarity: f32,
+) -> Result<Option<HttpOk<ChatResponse>>, HttpError> {
+    // Chunks deleted since the entry was written are left out
+    let chunks = crate::db::embeddings::get_chunk_texts(db, &entry.cited_chunks)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load FAQ sources: {}", e);
+            internal_error("Failed to load FAQ sources".to_string())
+        })?;
+    if !entry.cited_chunks.is_empty() && chunks.is_empty() {
+        tracing::info!(
+            "Skipping FAQ entry {}: the rules it cites are gone",
+            entry.id
+        );
+        return Ok(None);
+    }
+
+    tracing::info!(
+        "Answering from FAQ entry {} (similarity {:.3})",
+        entry.id,
+        similarity
+    );
+    let context_sources: Vec<ContextSource> = chunks
+        .into_iter()
+        .map(|(embedding_id, chunk_text)| ContextSource {
+            embedding_id,
+            game_id: entry.game_id,
+            chunk_text,
+            source_type: EmbeddingSourceType::RulesPdf.as_str().to_string(),
+            // Matched through the FAQ question rather than by chunk similarity
+            similarity_score: similarity,
+            metadata: None,
+            cited: true,
         })
         .collect();
 
-    let context_text = if search_results.is_empty() {
-        "No specific rules found for this question.".to_string()
-    } else {
-        search_results
-            .iter()
-            .map(|result| format!("Rule: {}", result.chunk_text))
-            .collect::<Vec<_>>()
-            .join("\n\n")
-    };
+    let mut message_request =
+        CreateChatMessageRequest::new(session_id, MessageRole::Assistant, entry.answer.clone());
+    let cited_chunks: Vec<EmbeddingId> = context_sources.iter().map(|s| s.embedding_id).collect();
+    message_request.context_chunks = Some(cited_chunks.clone());
+    message_request.cited_chunks = Some(cited_chunks);
+    message_request.not_covered_by_rules = Some(false);
 
-    // Get recent message history for better context
-    let recent_messages = session_history
-        .messages
-        .iter()
-        .rev()
-        .take(6) // Last 6 messages (3 exchanges)
-        .rev()
-        .map(|msg| {
-            let chat_msg = crate::llm::ChatMessage::from(msg);
-            format!("{}: {}", chat_msg.role, chat_msg.content)
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    // 6. Send to LLM API with context
-    let system_prompt = format!(
-        "You are a helpful assistant that explains board game rules. Use the following game rules to answer questions accurately and clearly. If the rules don't contain enough information to answer the question, say so honestly.
-
-Game Rules Context:
-{}
-
-Conversation History:
-{}
-
-Instructions:
-- Answer based on the provided rules context
-- Be concise but thorough
-- If rules are unclear or missing, acknowledge this
-- Use examples when helpful
-- Focus on practical gameplay guidance",
-        context_text,
-        recent_messages,
-    );
+    let assistant_message = chat::add_message_to_session(db, message_request)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save assistant message: {}", e);
+            internal_error("Failed to save response".to_string())
+        })?;
+
+    success_response(ChatResponse {
+        message: assistant_message,
+        context_sources,
+        confidence: None,
+        not_covered_by_rules: false,
+        structured: true,
+        cached: false,
+        cache_similarity: None,
+        grounding: None,
+        faq_match: Some(FaqMatch {
+            entry_id: entry.id,
+            question: entry.question,
+            similarity,
+        }),
+    })
+    .map(Some)
+}
 
-    let assistant_response = app_state
+/// Retrieved chunks and the texts that were searched to find them
+pub struct RuleRetrieval {
+    pub queries: Vec<String>,
+    pub results: Vec<EmbeddingSearchResult>,
+}
+
+/// Retrieve the rule chunks most relevant to a question, best match first
+pub async fn retrieve_rules(
+    app_state: &AppState,
+    game_id: GameId,
+    question: &str,
+    strategy: QueryStrategy,
+) -> Result<RuleRetrieval, HttpError> {
+    let queries = transform_question(app_state, &[game_id], question, strategy).await?;
+    let query_embeddings = embed_queries(app_state, &queries).await?;
+    let results = search_game_rules(
+        app_state,
+        game_id,
+        &query_embeddings,
+        RULES_CHUNK_LIMIT,
+        RELEVANCE_THRESHOLD,
+    )
+    .await?;
+
+    Ok(RuleRetrieval { queries, results })
+}
+
+/// Retrieve the `limit` closest rule chunks however weakly they match, best match first
+pub async fn retrieve_top_rules(
+    app_state: &AppState,
+    game_id: GameId,
+    question: &str,
+    strategy: QueryStrategy,
+    limit: usize,
+) -> Result<RuleRetrieval, HttpError> {
+    let queries = transform_question(app_state, &[game_id], question, strategy).await?;
+    let query_embeddings = embed_queries(app_state, &queries).await?;
+    let results = search_game_rules(app_state, game_id, &query_embeddings, limit, 0.0).await?;
+
+    Ok(RuleRetrieval { queries, results })
+}
+
+/// Answer a single question from already retrieved chunks, without a session, tools,
+/// caches or grounding checks
+pub async fn answer_from_rules(
+    app_state: &AppState,
+    game_id: GameId,
+    question: &str,
+    search_results: &[EmbeddingSearchResult],
+) -> Result<StructuredAnswer, HttpError> {
+    let RulesPrompt {
+        system_prompt,
+        sections,
+        ..
+    } = build_rules_prompt(
+        app_state,
+        RulesPromptRequest {
+            game_id,
+            compare_game_ids: &[],
+            expansion_ids: &[],
+            question,
+            search_results,
+            history: &[],
+            use_tools: false,
+            template_override: None,
+            persona_override: None,
+        },
+    )
+    .await?;
+
+    let raw = app_state
         .llm()
-        .chat_completion(
-            vec![ChatMessage {
-                role: "user".to_string(),
-                content: chat_request.message.clone(),
-            }],
+        .chat_completion_json(
+            vec![ChatMessage::user(question)],
             Some(system_prompt),
-            Some(512), // Reasonable response length
-            Some(0.7), // Balanced creativity/consistency
+            Some(ANSWER_MAX_TOKENS),
+            Some(0.0),
         )
         .await
         .map_err(|e| {
@@ -335,28 +869,316 @@ Instructions:
             internal_error("Failed to generate response".to_string())
         })?;
 
-    // 7. Save assistant response to database
-    let context_chunk_ids: Vec<i64> = search_results.iter().map(|r| r.id).collect();
-    let assistant_message = chat::add_message_to_session(
-        &db,
-        chat_request.session_id,
-        MessageRole::Assistant,
-        assistant_response,
-        Some(context_chunk_ids),
+    Ok(answers::parse_answer(&raw, &sections.included_chunk_ids))
+}
+
+/// Retrieve an equal share of chunks from every compared game, interleaved by rank
+pub async fn retrieve_comparison_rules(
+    app_state: &AppState,
+    game_ids: &[GameId],
+    question: &str,
+    strategy: QueryStrategy,
+) -> Result<RuleRetrieval, HttpError> {
+    let queries = transform_question(app_state, game_ids, question, strategy).await?;
+    let query_embeddings = embed_queries(app_state, &queries).await?;
+    let quota = comparison::per_game_quota(game_ids.len());
+
+    let mut per_game = Vec::with_capacity(game_ids.len());
+    for &game_id in game_ids {
+        per_game.push(
+            search_game_rules(
+                app_state,
+                game_id,
+                &query_embeddings,
+                quota,
+                RELEVANCE_THRESHOLD,
+            )
+            .await?,
+        );
+    }
+
+    Ok(RuleRetrieval {
+        queries,
+        results: comparison::interleave(per_game),
+    })
+}
+
+/// Retrieve chunks from a base game and the expansions in play, preferring expansion
+/// text over base rules that match about as well
+pub async fn retrieve_expansion_rules(
+    app_state: &AppState,
+    game_id: GameId,
+    expansion_ids: &[GameId],
+    question: &str,
+    strategy: QueryStrategy,
+) -> Result<RuleRetrieval, HttpError> {
+    let queries = transform_question(app_state, &[game_id], question, strategy).await?;
+    let query_embeddings = embed_queries(app_state, &queries).await?;
+    let limit = expansions::EXPANSION_CHUNK_LIMIT;
+
+    let base = search_game_rules(
+        app_state,
+        game_id,
+        &query_embeddings,
+        limit,
+        RELEVANCE_THRESHOLD,
     )
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to save assistant message: {}", e);
-        internal_error("Failed to save response".to_string())
-    })?;
+    .await?;
+    let mut per_expansion = Vec::with_capacity(expansion_ids.len());
+    for &expansion_id in expansion_ids {
+        per_expansion.push(
+            search_game_rules(
+                app_state,
+                expansion_id,
+                &query_embeddings,
+                limit,
+                RELEVANCE_THRESHOLD,
+            )
+            .await?,
+        );
+    }
 
-    // 8. Return response with context sources
-    let chat_response = ChatResponse {
-        message: assistant_message,
-        context_sources,
+    Ok(RuleRetrieval {
+        queries,
+        results: expansions::merge_results(base, per_expansion, limit),
+    })
+}
+
+/// Turn a question into the texts to search for, following the chosen strategy
+async fn transform_question(
+    app_state: &AppState,
+    game_ids: &[GameId],
+    question: &str,
+    strategy: QueryStrategy,
+) -> Result<Vec<String>, HttpError> {
+    // Only the model-based strategies need to know what game they are writing for
+    let mut game_names = Vec::new();
+    if strategy.uses_llm() {
+        for &game_id in game_ids {
+            game_names.push(load_game(&app_state.db(), game_id).await?.name);
+        }
+    }
+
+    Ok(query_transform::transform_query(
+        app_state.llm(),
+        strategy,
+        question,
+        &game_names.join(" and "),
+    )
+    .await)
+}
+
+async fn embed_queries(
+    app_state: &AppState,
+    queries: &[String],
+) -> Result<Vec<Vec<f32>>, HttpError> {
+    app_state
+        .embedder()
+        .generate_embeddings(queries)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to generate query embedding: {}", e);
+            internal_error("Failed to process question".to_string())
+        })
+}
+
+/// Search one game's rules with every query embedding and fuse the rankings
+async fn search_game_rules(
+    app_state: &AppState,
+    game_id: GameId,
+    query_embeddings: &[Vec<f32>],
+    limit: usize,
+    similarity_threshold: f32,
+) -> Result<Vec<EmbeddingSearchResult>, HttpError> {
+    let db = app_state.db();
+
+    let mut rankings = Vec::with_capacity(query_embeddings.len());
+    for query_embedding in query_embeddings {
+        let similarity_request = SimilaritySearchRequest {
+            game_id,
+            query_embedding: query_embedding.clone(),
+            similarity_threshold,
+            limit: limit as u32,
+        };
+
+        let ranking = crate::db::embeddings::similarity_search(&db, similarity_request)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to search embeddings: {}", e);
+                internal_error("Failed to search rules".to_string())
+            })?;
+        rankings.push(ranking);
+    }
+
+    Ok(query_transform::fuse_results(rankings, limit))
+}
+
+/// Inputs for rendering the system prompt of a rules question
+pub struct RulesPromptRequest<'a> {
+    pub game_id: GameId,
+    /// Other games in a comparison session; empty for single-game questions
+    pub compare_game_ids: &'a [GameId],
+    /// Expansions of the game in play; their rules and house rules amend the game's
+    pub expansion_ids: &'a [GameId],
+    pub question: &'a str,
+    pub search_results: &'a [EmbeddingSearchResult],
+    pub history: &'a [crate::models::ChatMessage],
+    pub use_tools: bool,
+    /// Unsaved template text to render instead of the stored one
+    pub template_override: Option<&'a str>,
+    pub persona_override: Option<&'a str>,
+}
+
+/// A rendered system prompt and what went into it
+pub struct RulesPrompt {
+    pub system_prompt: String,
+    pub sections: PromptSections,
+    pub template_source: PromptTemplateSource,
+    pub template_id: Option<PromptTemplateId>,
+}
+
+/// Render the game's prompt template with rules, house rules and history trimmed to fit the model
+pub async fn build_rules_prompt(
+    app_state: &AppState,
+    request: RulesPromptRequest<'_>,
+) -> Result<RulesPrompt, HttpError> {
+    let db = app_state.db();
+    let game_id = request.game_id;
+
+    let mut games = Vec::with_capacity(1 + request.compare_game_ids.len());
+    for &id in std::iter::once(&game_id).chain(request.compare_game_ids) {
+        games.push(load_game(&db, id).await?);
+    }
+    let is_comparison = games.len() > 1;
+    let mut expansion_games = Vec::with_capacity(request.expansion_ids.len());
+    for &id in request.expansion_ids {
+        expansion_games.push(load_game(&db, id).await?);
+    }
+
+    let mut house_rules = Vec::new();
+    for game in games.iter().chain(&expansion_games) {
+        let game_rules = crate::db::house_rules::list_house_rules_by_game(&db, game.id, true)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load house rules for game {}: {}", game.id, e);
+                internal_error("Failed to load house rules".to_string())
+            })?;
+        house_rules.extend(game_rules);
+    }
+
+    // Stored templates are written for a single game, so comparisons use the built-in one
+    let stored = if is_comparison {
+        None
+    } else {
+        crate::db::prompt_templates::get_active_prompt_template(&db, game_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load prompt template for game {}: {}", game_id, e);
+                internal_error("Failed to load prompt template".to_string())
+            })?
     };
 
-    success_response(chat_response)
+    let (template_source, template_id, template, persona) =
+        match (request.template_override, stored) {
+            (Some(draft), _) => (PromptTemplateSource::Draft, None, draft.to_string(), None),
+            (None, Some(stored)) => {
+                let source = if stored.game_id.is_some() {
+                    PromptTemplateSource::Game
+                } else {
+                    PromptTemplateSource::Global
+                };
+                (source, Some(stored.id), stored.template, stored.persona)
+            }
+            (None, None) => (
+                PromptTemplateSource::BuiltIn,
+                None,
+                if is_comparison {
+                    templates::COMPARISON_TEMPLATE
+                } else {
+                    templates::DEFAULT_TEMPLATE
+                }
+                .to_string(),
+                None,
+            ),
+        };
+
+    // The answer format is always appended so custom templates cannot break answer parsing
+    let template = if expansion_games.is_empty() {
+        template
+    } else {
+        let names: Vec<&str> = expansion_games
+            .iter()
+            .map(|game| game.name.as_str())
+            .collect();
+        format!(
+            "{}\n\n{}",
+            template,
+            expansions::expansion_instructions(&games[0].name, &names)
+        )
+    };
+    let template = format!("{}\n\n{}", template, answers::answer_format_instructions());
+    let persona = request.persona_override.or(persona.as_deref());
+    let mut variables = if is_comparison {
+        templates::comparison_variables(&games, persona)
+    } else {
+        templates::game_variables(&games[0], persona)
+    };
+    let game_names: HashMap<GameId, String> = games
+        .iter()
+        .chain(&expansion_games)
+        .map(|game| (game.id, game.name.clone()))
+        .collect();
+    let label_games = is_comparison || !expansion_games.is_empty();
+
+    let llm = app_state.llm();
+    let tokenizer = llm.tokenizer();
+    let mut budget = PromptBudget::new(tokenizer, llm.context_window(), ANSWER_MAX_TOKENS.into());
+    if request.use_tools {
+        budget = budget.with_reserved_tokens(tools::prompt_reserve_tokens(&tokenizer));
+    }
+
+    let sections = budget.allocate(
+        &templates::render_template(&template, &variables),
+        &PromptInputs {
+            question: request.question,
+            house_rules: &house_rules,
+            chunks: request.search_results,
+            history: request.history,
+            game_names: label_games.then_some(&game_names),
+        },
+    );
+    tracing::debug!("Prompt budget for game {}: {:?}", game_id, sections.usage);
+    if sections.usage.dropped_chunks > 0 || sections.usage.truncated {
+        tracing::info!(
+            "Trimmed prompt to {} of {} tokens: dropped {} chunks, {} history messages, {} house rules",
+            sections.usage.prompt_tokens(),
+            sections.usage.context_window,
+            sections.usage.dropped_chunks,
+            sections.usage.dropped_history_messages,
+            sections.usage.dropped_house_rules
+        );
+    }
+
+    variables.insert("rules_context", sections.rules_context.clone());
+    variables.insert("house_rules", sections.house_rules.clone());
+    variables.insert("history", sections.history.clone());
+
+    Ok(RulesPrompt {
+        system_prompt: templates::render_template(&template, &variables),
+        sections,
+        template_source,
+        template_id,
+    })
+}
+
+async fn load_game(db: &Database, game_id: GameId) -> Result<Game, HttpError> {
+    crate::db::games::get_game(db, game_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get game {}: {}", game_id, e);
+            internal_error("Failed to load game".to_string())
+        })?
+        .ok_or_else(|| not_found_error(format!("Game with id {} not found", game_id)))
 }
 
 /// Enhance search results by grouping related chunks and providing better context
@@ -422,89 +1244,6 @@ fn create_context_preview(chunk_text: &str) -> String {
 }
 
 /// Calculate text similarity between two chunks (simple word overlap)
-/// Enhance search queries to better match rule document content
-fn enhance_search_query(query: &str) -> String {
-    let query_lower = query.to_lowercase();
-    let mut enhanced_parts = Vec::new();
-
-    // Convert questions to statement form for better embedding matching
-    if query_lower.starts_with("how do i") || query_lower.starts_with("how to") {
-        let without_prefix = query_lower
-            .strip_prefix("how do i ")
-            .or_else(|| query_lower.strip_prefix("how to "))
-            .unwrap_or(&query_lower);
-        enhanced_parts.push(without_prefix.to_string());
-        enhanced_parts.push(format!("rules for {}", without_prefix));
-        enhanced_parts.push(format!("instructions {}", without_prefix));
-    } else if query_lower.starts_with("what") {
-        if query_lower.contains("happens") {
-            enhanced_parts.push(query_lower.replace("what happens", "when"));
-            enhanced_parts.push(query_lower.replace("what happens", "rules"));
-        } else if query_lower.contains("can i") || query_lower.contains("may i") {
-            enhanced_parts.push(query_lower.replace("what can i", "player may"));
-            enhanced_parts.push(query_lower.replace("what may i", "player may"));
-            enhanced_parts.push("allowed actions".to_string());
-        } else {
-            enhanced_parts.push(query_lower.clone());
-        }
-    } else if query_lower.starts_with("when") {
-        enhanced_parts.push(query_lower.clone());
-        enhanced_parts.push(query_lower.replace("when", "if"));
-        enhanced_parts.push("timing rules".to_string());
-    } else if query_lower.starts_with("can i") || query_lower.starts_with("may i") {
-        let without_prefix = query_lower
-            .strip_prefix("can i ")
-            .or_else(|| query_lower.strip_prefix("may i "))
-            .unwrap_or(&query_lower);
-        enhanced_parts.push(format!("player may {}", without_prefix));
-        enhanced_parts.push(format!("allowed to {}", without_prefix));
-        enhanced_parts.push(without_prefix.to_string());
-    } else {
-        enhanced_parts.push(query_lower.clone());
-    }
-
-    // Add domain-specific game terms
-    let game_terms = extract_game_terms(&query_lower);
-    enhanced_parts.extend(game_terms);
-
-    // Join with the original query for comprehensive matching
-    let mut final_query = query.to_string();
-    if !enhanced_parts.is_empty() {
-        final_query.push_str(" ");
-        final_query.push_str(&enhanced_parts.join(" "));
-    }
-
-    final_query
-}
-
-/// Extract and enhance game-specific terms from the query
-fn extract_game_terms(query: &str) -> Vec<String> {
-    let mut terms = Vec::new();
-
-    // Common game concepts and their rule document equivalents
-    let concept_mappings = [
-        ("win", vec!["victory", "winning condition", "game end"]),
-        ("lose", vec!["defeat", "elimination", "losing condition"]),
-        ("turn", vec!["round", "phase", "player turn"]),
-        ("move", vec!["movement", "moving pieces", "relocate"]),
-        ("attack", vec!["combat", "battle", "fight"]),
-        ("defend", vec!["defense", "block", "protection"]),
-        ("points", vec!["score", "scoring", "victory points"]),
-        ("cards", vec!["hand", "deck", "draw"]),
-        ("dice", vec!["roll", "rolling", "die"]),
-        ("setup", vec!["preparation", "initial setup", "game setup"]),
-        ("end", vec!["finish", "conclusion", "game over"]),
-    ];
-
-    for (concept, equivalents) in &concept_mappings {
-        if query.contains(concept) {
-            terms.extend(equivalents.iter().map(|s| s.to_string()));
-        }
-    }
-
-    terms
-}
-
 fn text_similarity(text1: &str, text2: &str) -> f32 {
     let words1: std::collections::HashSet<&str> = text1
         .split_whitespace()
@@ -0,0 +1,273 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::collection,
+    handlers::{
+        HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        CollectionStatus, CreateLoanRequest, GameCollection, GameId, Loan, LoanId, LoanListQuery,
+        ReturnLoanRequest, UpdateGameCollectionRequest,
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CollectionGamePathParam {
+    pub id: GameId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LoanPathParam {
+    pub id: LoanId,
+}
+
+/// Missing games and loans that cannot be made or returned are reported by the database
+fn invalid_loan(error: &rusqlite::Error) -> Option<HttpError> {
+    match error {
+        rusqlite::Error::SqliteFailure(e, Some(message))
+            if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            Some(bad_request_error(message.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Get a game's collection status, acquisition details and current loan
+#[endpoint {
+    method = GET,
+    path = "/api/games/{id}/collection"
+}]
+pub async fn get_game_collection(
+    rqctx: RequestContext<AppState>,
+    path: Path<CollectionGamePathParam>,
+) -> Result<HttpOk<GameCollection>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match collection::get_game_collection(&db, game_id).await {
+        Ok(Some(collection)) => success_response(collection),
+        Ok(None) => Err(not_found_error(format!(
+            "Game with id {} not found",
+            game_id
+        ))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to get collection details of game {}: {}",
+                game_id,
+                e
+            );
+            Err(internal_error(
+                "Failed to get collection details".to_string(),
+            ))
+        }
+    }
+}
+
+/// Update a game's collection status and details
+#[endpoint {
+    method = PUT,
+    path = "/api/games/{id}/collection"
+}]
+pub async fn update_game_collection(
+    rqctx: RequestContext<AppState>,
+    path: Path<CollectionGamePathParam>,
+    body: TypedBody<UpdateGameCollectionRequest>,
+) -> Result<HttpOk<GameCollection>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(priority) = update_request.wishlist_priority {
+        if !(1..=5).contains(&priority) {
+            return Err(bad_request_error(
+                "Wishlist priority must be between 1 and 5".to_string(),
+            ));
+        }
+        if update_request
+            .status
+            .is_some_and(|status| status != CollectionStatus::Wishlist)
+        {
+            return Err(bad_request_error(
+                "Wishlist priority only applies to wishlisted games".to_string(),
+            ));
+        }
+    }
+    if update_request
+        .rating
+        .is_some_and(|rating| !(1.0..=10.0).contains(&rating))
+    {
+        return Err(bad_request_error(
+            "Rating must be between 1 and 10".to_string(),
+        ));
+    }
+    if update_request
+        .acquisition_price
+        .is_some_and(|price| price < 0.0)
+    {
+        return Err(bad_request_error(
+            "Acquisition price cannot be negative".to_string(),
+        ));
+    }
+
+    match collection::update_game_collection(&db, game_id, update_request).await {
+        Ok(Some(collection)) => success_response(collection),
+        Ok(None) => Err(not_found_error(format!(
+            "Game with id {} not found",
+            game_id
+        ))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to update collection details of game {}: {}",
+                game_id,
+                e
+            );
+            Err(internal_error(
+                "Failed to update collection details".to_string(),
+            ))
+        }
+    }
+}
+
+/// List loans, most recent first; overdue loans are listed longest overdue first
+#[endpoint {
+    method = GET,
+    path = "/api/loans"
+}]
+pub async fn list_loans(
+    rqctx: RequestContext<AppState>,
+    query: Query<LoanListQuery>,
+) -> Result<HttpOk<Vec<Loan>>, HttpError> {
+    let app_state = rqctx.context();
+    let query = query.into_inner();
+    let db = app_state.db();
+
+    match collection::list_loans(&db, &query).await {
+        Ok(loans) => success_response(loans),
+        Err(e) => {
+            tracing::error!("Failed to list loans: {}", e);
+            Err(internal_error("Failed to list loans".to_string()))
+        }
+    }
+}
+
+/// Get a loan
+#[endpoint {
+    method = GET,
+    path = "/api/loans/{id}"
+}]
+pub async fn get_loan(
+    rqctx: RequestContext<AppState>,
+    path: Path<LoanPathParam>,
+) -> Result<HttpOk<Loan>, HttpError> {
+    let app_state = rqctx.context();
+    let loan_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match collection::get_loan(&db, loan_id).await {
+        Ok(Some(loan)) => success_response(loan),
+        Ok(None) => Err(not_found_error(format!(
+            "Loan with id {} not found",
+            loan_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get loan {}: {}", loan_id, e);
+            Err(internal_error("Failed to get loan".to_string()))
+        }
+    }
+}
+
+/// Lend out an owned game that is not already on loan
+#[endpoint {
+    method = POST,
+    path = "/api/loans"
+}]
+pub async fn create_loan(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreateLoanRequest>,
+) -> Result<HttpCreated<Loan>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if create_request.borrower.trim().is_empty() {
+        return Err(bad_request_error("Borrower cannot be empty".to_string()));
+    }
+    if let (Some(loaned_at), Some(due_at)) = (create_request.loaned_at, create_request.due_at)
+        && due_at < loaned_at
+    {
+        return Err(bad_request_error(
+            "due_at cannot be earlier than loaned_at".to_string(),
+        ));
+    }
+
+    match collection::create_loan(&db, create_request).await {
+        Ok(loan) => created_response(loan),
+        Err(e) => Err(invalid_loan(&e).unwrap_or_else(|| {
+            tracing::error!("Failed to create loan: {}", e);
+            internal_error("Failed to create loan".to_string())
+        })),
+    }
+}
+
+/// Mark a loan as returned
+#[endpoint {
+    method = POST,
+    path = "/api/loans/{id}/return"
+}]
+pub async fn return_loan(
+    rqctx: RequestContext<AppState>,
+    path: Path<LoanPathParam>,
+    body: TypedBody<ReturnLoanRequest>,
+) -> Result<HttpOk<Loan>, HttpError> {
+    let app_state = rqctx.context();
+    let loan_id = path.into_inner().id;
+    let return_request = body.into_inner();
+    let db = app_state.db();
+
+    match collection::return_loan(&db, loan_id, return_request.returned_at).await {
+        Ok(Some(loan)) => success_response(loan),
+        Ok(None) => Err(not_found_error(format!(
+            "Loan with id {} not found",
+            loan_id
+        ))),
+        Err(e) => Err(invalid_loan(&e).unwrap_or_else(|| {
+            tracing::error!("Failed to return loan {}: {}", loan_id, e);
+            internal_error("Failed to return loan".to_string())
+        })),
+    }
+}
+
+/// Delete a loan record
+#[endpoint {
+    method = DELETE,
+    path = "/api/loans/{id}"
+}]
+pub async fn delete_loan(
+    rqctx: RequestContext<AppState>,
+    path: Path<LoanPathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let loan_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match collection::delete_loan(&db, loan_id).await {
+        Ok(true) => deleted_response(),
+        Ok(false) => Err(not_found_error(format!(
+            "Loan with id {} not found",
+            loan_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to delete loan {}: {}", loan_id, e);
+            Err(internal_error("Failed to delete loan".to_string()))
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use dropshot::{Query, RequestContext, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    handlers::{HttpError, HttpOk, internal_error, success_response},
+    models::{ClearEmbeddingCacheResponse, EmbeddingCacheStats},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ClearEmbeddingCacheQuery {
+    /// Only remove entries produced by the currently configured model
+    #[serde(default)]
+    pub current_model_only: bool,
+}
+
+/// Get embedding cache size and hit-rate statistics
+#[endpoint {
+    method = GET,
+    path = "/api/embeddings/cache"
+}]
+pub async fn get_embedding_cache_stats(
+    rqctx: RequestContext<AppState>,
+) -> Result<HttpOk<EmbeddingCacheStats>, HttpError> {
+    let app_state = rqctx.context();
+
+    match app_state.embedder().cache_stats().await {
+        Ok(stats) => success_response(stats),
+        Err(e) => {
+            tracing::error!("Failed to get embedding cache stats: {}", e);
+            Err(internal_error(
+                "Failed to get embedding cache stats".to_string(),
+            ))
+        }
+    }
+}
+
+/// Clear the embedding cache
+#[endpoint {
+    method = DELETE,
+    path = "/api/embeddings/cache"
+}]
+pub async fn clear_embedding_cache(
+    rqctx: RequestContext<AppState>,
+    query: Query<ClearEmbeddingCacheQuery>,
+) -> Result<HttpOk<ClearEmbeddingCacheResponse>, HttpError> {
+    let app_state = rqctx.context();
+    let query = query.into_inner();
+
+    match app_state
+        .embedder()
+        .clear_cache(query.current_model_only)
+        .await
+    {
+        Ok(entries_removed) => success_response(ClearEmbeddingCacheResponse { entries_removed }),
+        Err(e) => {
+            tracing::error!("Failed to clear embedding cache: {}", e);
+            Err(internal_error(
+                "Failed to clear embedding cache".to_string(),
+            ))
+        }
+    }
+}
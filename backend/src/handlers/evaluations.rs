@@ -0,0 +1,301 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::evaluations,
+    evaluation,
+    handlers::{
+        HttpDeleted, HttpError, HttpOk, bad_request_error,
+        chat::{answer_from_rules, retrieve_top_rules},
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        EvalQuestion, EvalQuestionId, EvalRun, EvalRunId, EvalRunRequest, EvalRunResponse, GameId,
+        ImportEvalQuestionsRequest, NewEvalRun,
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct EvalQuestionPathParam {
+    pub id: EvalQuestionId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct EvalRunPathParam {
+    pub id: EvalRunId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct EvalByGameQuery {
+    pub game_id: GameId,
+}
+
+/// List a game's golden questions
+#[endpoint {
+    method = GET,
+    path = "/api/eval/questions"
+}]
+pub async fn list_eval_questions(
+    rqctx: RequestContext<AppState>,
+    query: Query<EvalByGameQuery>,
+) -> Result<HttpOk<Vec<EvalQuestion>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = query.into_inner().game_id;
+    let db = app_state.db();
+
+    match evaluations::list_eval_questions(&db, game_id).await {
+        Ok(questions) => success_response(questions),
+        Err(e) => {
+            tracing::error!(
+                "Failed to list golden questions for game {}: {}",
+                game_id,
+                e
+            );
+            Err(internal_error(
+                "Failed to list golden questions".to_string(),
+            ))
+        }
+    }
+}
+
+/// Import golden questions for a game, e.g. from a fixture file
+#[endpoint {
+    method = POST,
+    path = "/api/eval/questions"
+}]
+pub async fn import_eval_questions(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<ImportEvalQuestionsRequest>,
+) -> Result<HttpOk<Vec<EvalQuestion>>, HttpError> {
+    let app_state = rqctx.context();
+    let import_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    for question in &import_request.questions {
+        if question.question.trim().is_empty() {
+            return Err(bad_request_error(
+                "Golden question cannot be empty".to_string(),
+            ));
+        }
+        if question.expected_chunk_ids.is_empty() && question.expected_keywords.is_empty() {
+            return Err(bad_request_error(format!(
+                "Golden question \"{}\" needs expected chunk ids or keywords",
+                question.question.trim()
+            )));
+        }
+    }
+
+    match evaluations::import_eval_questions(
+        &db,
+        import_request.game_id,
+        &import_request.questions,
+        import_request.replace,
+    )
+    .await
+    {
+        Ok(questions) => success_response(questions),
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message == "Game does not exist" =>
+        {
+            Err(bad_request_error(message))
+        }
+        Err(e) => {
+            tracing::error!("Failed to import golden questions: {}", e);
+            Err(internal_error(
+                "Failed to import golden questions".to_string(),
+            ))
+        }
+    }
+}
+
+/// Delete a golden question
+#[endpoint {
+    method = DELETE,
+    path = "/api/eval/questions/{id}"
+}]
+pub async fn delete_eval_question(
+    rqctx: RequestContext<AppState>,
+    path: Path<EvalQuestionPathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let question_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match evaluations::delete_eval_question(&db, question_id).await {
+        Ok(true) => deleted_response(),
+        Ok(false) => Err(not_found_error(format!(
+            "Golden question with id {} not found",
+            question_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to delete golden question {}: {}", question_id, e);
+            Err(internal_error(
+                "Failed to delete golden question".to_string(),
+            ))
+        }
+    }
+}
+
+/// Run a game's golden questions through retrieval, and optionally answering,
+/// then store the scores and diff them against an earlier run
+#[endpoint {
+    method = POST,
+    path = "/api/eval/runs"
+}]
+pub async fn run_evaluation(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<EvalRunRequest>,
+) -> Result<HttpOk<EvalRunResponse>, HttpError> {
+    let app_state = rqctx.context();
+    let run_request = body.into_inner();
+    let game_id = run_request.game_id;
+    let db = app_state.db();
+
+    let k = run_request.k.unwrap_or(evaluation::DEFAULT_K);
+    if k == 0 || k > evaluation::MAX_K {
+        return Err(bad_request_error(format!(
+            "k must be between 1 and {}",
+            evaluation::MAX_K
+        )));
+    }
+
+    let baseline = match run_request.compare_to {
+        Some(run_id) => Some(
+            evaluations::get_eval_run(&db, run_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to get evaluation run {}: {}", run_id, e);
+                    internal_error("Failed to load evaluation run".to_string())
+                })?
+                .ok_or_else(|| {
+                    not_found_error(format!("Evaluation run with id {} not found", run_id))
+                })?,
+        ),
+        None => None,
+    };
+
+    let questions = evaluations::list_eval_questions(&db, game_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to list golden questions for game {}: {}",
+                game_id,
+                e
+            );
+            internal_error("Failed to list golden questions".to_string())
+        })?;
+    if questions.is_empty() {
+        return Err(bad_request_error(format!(
+            "Game {} has no golden questions to evaluate",
+            game_id
+        )));
+    }
+
+    let mut results = Vec::with_capacity(questions.len());
+    for question in &questions {
+        let retrieval = retrieve_top_rules(
+            app_state,
+            game_id,
+            &question.question,
+            run_request.strategy,
+            k as usize,
+        )
+        .await?;
+        let mut result = evaluation::score_retrieval(question, &retrieval.results);
+
+        if run_request.generate_answers {
+            let answer =
+                answer_from_rules(app_state, game_id, &question.question, &retrieval.results)
+                    .await?;
+            result.answer_match =
+                evaluation::answer_matches(&answer.answer, &question.expected_keywords);
+            result.answer = Some(answer.answer);
+        }
+        results.push(result);
+    }
+
+    let run = evaluations::create_eval_run(
+        &db,
+        NewEvalRun {
+            game_id,
+            strategy: run_request.strategy,
+            k,
+            generate_answers: run_request.generate_answers,
+            embedding_model: app_state.embedder().get_model().to_string(),
+            llm_model: run_request
+                .generate_answers
+                .then(|| app_state.llm().get_model().to_string()),
+            summary: evaluation::summarize(&results),
+            results,
+        },
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to store evaluation run: {}", e);
+        internal_error("Failed to store evaluation run".to_string())
+    })?;
+
+    let baseline = match baseline {
+        Some(baseline) => Some(baseline),
+        None => evaluations::get_previous_eval_run(&db, game_id, run.id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get previous evaluation run: {}", e);
+                internal_error("Failed to load evaluation run".to_string())
+            })?,
+    };
+    let diff = baseline.map(|baseline| evaluation::diff_runs(&baseline, &run));
+
+    success_response(EvalRunResponse { run, diff })
+}
+
+/// List a game's evaluation runs, newest first
+#[endpoint {
+    method = GET,
+    path = "/api/eval/runs"
+}]
+pub async fn list_eval_runs(
+    rqctx: RequestContext<AppState>,
+    query: Query<EvalByGameQuery>,
+) -> Result<HttpOk<Vec<EvalRun>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = query.into_inner().game_id;
+    let db = app_state.db();
+
+    match evaluations::list_eval_runs(&db, game_id).await {
+        Ok(runs) => success_response(runs),
+        Err(e) => {
+            tracing::error!("Failed to list evaluation runs for game {}: {}", game_id, e);
+            Err(internal_error("Failed to list evaluation runs".to_string()))
+        }
+    }
+}
+
+/// Get a specific evaluation run by ID
+#[endpoint {
+    method = GET,
+    path = "/api/eval/runs/{id}"
+}]
+pub async fn get_eval_run(
+    rqctx: RequestContext<AppState>,
+    path: Path<EvalRunPathParam>,
+) -> Result<HttpOk<EvalRun>, HttpError> {
+    let app_state = rqctx.context();
+    let run_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match evaluations::get_eval_run(&db, run_id).await {
+        Ok(Some(run)) => success_response(run),
+        Ok(None) => Err(not_found_error(format!(
+            "Evaluation run with id {} not found",
+            run_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get evaluation run {}: {}", run_id, e);
+            Err(internal_error("Failed to get evaluation run".to_string()))
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::{self, faq},
+    faq_generation,
+    handlers::{
+        HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        CreateFaqEntryRequest, EmbeddingSourceType, FaqEntry, FaqEntryId, GameId,
+        GenerateFaqRequest, GenerateFaqResponse, NewFaqEntry, UpdateFaqEntryRequest,
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FaqEntryPathParam {
+    pub id: FaqEntryId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FaqGamePathParam {
+    pub id: GameId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FaqByGameQuery {
+    pub game_id: GameId,
+}
+
+fn validate_text(field: &str, value: &str) -> Result<(), HttpError> {
+    if value.trim().is_empty() {
+        return Err(bad_request_error(format!("FAQ {} cannot be empty", field)));
+    }
+    Ok(())
+}
+
+async fn embed_question(app_state: &AppState, question: &str) -> Result<Vec<f32>, HttpError> {
+    app_state
+        .embedder()
+        .generate_embedding(question)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to embed FAQ question: {}", e);
+            internal_error("Failed to process FAQ question".to_string())
+        })
+}
+
+/// List a game's FAQ in order
+#[endpoint {
+    method = GET,
+    path = "/api/faq"
+}]
+pub async fn list_faq_entries(
+    rqctx: RequestContext<AppState>,
+    query: Query<FaqByGameQuery>,
+) -> Result<HttpOk<Vec<FaqEntry>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = query.into_inner().game_id;
+    let db = app_state.db();
+
+    match faq::list_faq_entries(&db, game_id).await {
+        Ok(entries) => success_response(entries),
+        Err(e) => {
+            tracing::error!("Failed to list FAQ for game {}: {}", game_id, e);
+            Err(internal_error("Failed to list FAQ entries".to_string()))
+        }
+    }
+}
+
+/// Get a specific FAQ entry by ID
+#[endpoint {
+    method = GET,
+    path = "/api/faq/{id}"
+}]
+pub async fn get_faq_entry(
+    rqctx: RequestContext<AppState>,
+    path: Path<FaqEntryPathParam>,
+) -> Result<HttpOk<FaqEntry>, HttpError> {
+    let app_state = rqctx.context();
+    let entry_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match faq::get_faq_entry(&db, entry_id).await {
+        Ok(Some(entry)) => success_response(entry),
+        Ok(None) => Err(not_found_error(format!(
+            "FAQ entry with id {} not found",
+            entry_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get FAQ entry {}: {}", entry_id, e);
+            Err(internal_error("Failed to get FAQ entry".to_string()))
+        }
+    }
+}
+
+/// Add a hand-written FAQ entry
+#[endpoint {
+    method = POST,
+    path = "/api/faq"
+}]
+pub async fn create_faq_entry(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreateFaqEntryRequest>,
+) -> Result<HttpCreated<FaqEntry>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    validate_text("question", &create_request.question)?;
+    validate_text("answer", &create_request.answer)?;
+
+    let question_embedding = embed_question(app_state, &create_request.question).await?;
+    let entry = NewFaqEntry {
+        game_id: create_request.game_id,
+        question: create_request.question.trim().to_string(),
+        question_embedding,
+        answer: create_request.answer.trim().to_string(),
+        cited_chunks: create_request.cited_chunks,
+        edited: true,
+    };
+
+    match faq::create_faq_entry(&db, entry).await {
+        Ok(entry) => created_response(entry),
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message == "Game does not exist" =>
+        {
+            Err(bad_request_error(message))
+        }
+        Err(e) => {
+            tracing::error!("Failed to create FAQ entry: {}", e);
+            Err(internal_error("Failed to create FAQ entry".to_string()))
+        }
+    }
+}
+
+/// Edit a FAQ entry; edited entries survive regeneration
+#[endpoint {
+    method = PUT,
+    path = "/api/faq/{id}"
+}]
+pub async fn update_faq_entry(
+    rqctx: RequestContext<AppState>,
+    path: Path<FaqEntryPathParam>,
+    body: TypedBody<UpdateFaqEntryRequest>,
+) -> Result<HttpOk<FaqEntry>, HttpError> {
+    let app_state = rqctx.context();
+    let entry_id = path.into_inner().id;
+    let mut update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(ref question) = update_request.question {
+        validate_text("question", question)?;
+    }
+    if let Some(ref answer) = update_request.answer {
+        validate_text("answer", answer)?;
+    }
+    update_request.question = update_request.question.map(|q| q.trim().to_string());
+    update_request.answer = update_request.answer.map(|a| a.trim().to_string());
+
+    let question_embedding = match update_request.question {
+        Some(ref question) => Some(embed_question(app_state, question).await?),
+        None => None,
+    };
+
+    match faq::update_faq_entry(&db, entry_id, update_request, question_embedding).await {
+        Ok(Some(entry)) => success_response(entry),
+        Ok(None) => Err(not_found_error(format!(
+            "FAQ entry with id {} not found",
+            entry_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to update FAQ entry {}: {}", entry_id, e);
+            Err(internal_error("Failed to update FAQ entry".to_string()))
+        }
+    }
+}
+
+/// Delete a FAQ entry
+#[endpoint {
+    method = DELETE,
+    path = "/api/faq/{id}"
+}]
+pub async fn delete_faq_entry(
+    rqctx: RequestContext<AppState>,
+    path: Path<FaqEntryPathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let entry_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match faq::delete_faq_entry(&db, entry_id).await {
+        Ok(true) => deleted_response(),
+        Ok(false) => Err(not_found_error(format!(
+            "FAQ entry with id {} not found",
+            entry_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to delete FAQ entry {}: {}", entry_id, e);
+            Err(internal_error("Failed to delete FAQ entry".to_string()))
+        }
+    }
+}
+
+/// Generate a game's FAQ from its ingested rules, replacing earlier unedited entries
+#[endpoint {
+    method = POST,
+    path = "/api/games/{id}/faq"
+}]
+pub async fn generate_faq(
+    rqctx: RequestContext<AppState>,
+    path: Path<FaqGamePathParam>,
+    body: TypedBody<GenerateFaqRequest>,
+) -> Result<HttpOk<GenerateFaqResponse>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let generate_request = body.into_inner();
+    let db = app_state.db();
+
+    let max_questions = generate_request
+        .max_questions
+        .unwrap_or(faq_generation::DEFAULT_FAQ_QUESTIONS);
+    if max_questions == 0 || max_questions > faq_generation::MAX_FAQ_QUESTIONS {
+        return Err(bad_request_error(format!(
+            "max_questions must be between 1 and {}",
+            faq_generation::MAX_FAQ_QUESTIONS
+        )));
+    }
+
+    let game = db::games::get_game(&db, game_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get game {}: {}", game_id, e);
+            internal_error("Failed to load game".to_string())
+        })?
+        .ok_or_else(|| not_found_error(format!("Game with id {} not found", game_id)))?;
+
+    let chunks = db::embeddings::list_chunks_for_game(&db, game_id, EmbeddingSourceType::RulesPdf)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load rule chunks for game {}: {}", game_id, e);
+            internal_error("Failed to load rules".to_string())
+        })?;
+    if chunks.is_empty() {
+        return Err(bad_request_error(format!(
+            "Game {} has no uploaded rules to build a FAQ from",
+            game_id
+        )));
+    }
+
+    let generated =
+        faq_generation::generate_faq(app_state.llm(), &game.name, &chunks, max_questions).await;
+    if generated.is_empty() {
+        // Leave the current FAQ alone rather than replacing it with nothing
+        return Err(internal_error(
+            "The model did not produce any usable FAQ entries".to_string(),
+        ));
+    }
+
+    // Keep edited entries; generated questions they already cover are dropped
+    let existing = faq::list_faq_entries(&db, game_id).await.map_err(|e| {
+        tracing::error!("Failed to list FAQ for game {}: {}", game_id, e);
+        internal_error("Failed to list FAQ entries".to_string())
+    })?;
+    let generated: Vec<_> = generated
+        .into_iter()
+        .filter(|entry| {
+            let key = faq_generation::normalize_question(&entry.question);
+            !existing
+                .iter()
+                .any(|e| e.edited && faq_generation::normalize_question(&e.question) == key)
+        })
+        .collect();
+
+    let questions: Vec<String> = generated.iter().map(|e| e.question.clone()).collect();
+    let question_embeddings = app_state
+        .embedder()
+        .generate_embeddings(&questions)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to embed FAQ questions: {}", e);
+            internal_error("Failed to process FAQ questions".to_string())
+        })?;
+
+    let entries: Vec<NewFaqEntry> = generated
+        .into_iter()
+        .zip(question_embeddings)
+        .map(|(entry, question_embedding)| NewFaqEntry {
+            game_id,
+            question: entry.question,
+            question_embedding,
+            answer: entry.answer,
+            cited_chunks: entry.cited_chunks,
+            edited: false,
+        })
+        .collect();
+    let generated_count = entries.len();
+
+    match faq::replace_generated_faq(&db, game_id, entries).await {
+        Ok((entries, replaced)) => success_response(GenerateFaqResponse {
+            game_id,
+            entries,
+            generated: generated_count,
+            replaced,
+        }),
+        Err(e) => {
+            tracing::error!("Failed to store FAQ for game {}: {}", game_id, e);
+            Err(internal_error("Failed to store FAQ".to_string()))
+        }
+    }
+}
@@ -1,12 +1,13 @@
 use crate::{
     AppState,
     db::{Database, games},
+    discovery,
     handlers::{
         HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
         deleted_response, internal_error, not_found_error, success_response,
     },
     models::{
-        CreateGameRequest, Game, GameId, GameSummary, PaginatedResponse, PaginationParams,
+        CreateGameRequest, Game, GameId, GameListQuery, GameSummary, PaginatedResponse,
         UpdateGameRequest,
     },
 };
@@ -19,20 +20,42 @@ pub struct GamePathParam {
     pub id: GameId,
 }
 
-/// List all games with pagination
+/// List games with optional filters and sorting, paginated
 #[endpoint {
     method = GET,
     path = "/api/games"
 }]
 pub async fn list_games(
     rqctx: RequestContext<AppState>,
-    query: Query<PaginationParams>,
+    query: Query<GameListQuery>,
 ) -> Result<HttpOk<PaginatedResponse<GameSummary>>, HttpError> {
     let app_state = rqctx.context();
-    let pagination = query.into_inner();
+    let list_query = query.into_inner();
     let db = app_state.db();
 
-    match games::list_games(&db, pagination.page, pagination.limit).await {
+    // Validate the filters
+    if list_query.limit == 0 {
+        return Err(bad_request_error("limit must be at least 1".to_string()));
+    }
+    if list_query.players.is_some_and(|players| players < 1) {
+        return Err(bad_request_error("players must be at least 1".to_string()));
+    }
+    if let (Some(min), Some(max)) = (list_query.min_complexity, list_query.max_complexity)
+        && min > max
+    {
+        return Err(bad_request_error(
+            "min_complexity cannot be greater than max_complexity".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (list_query.min_year, list_query.max_year)
+        && min > max
+    {
+        return Err(bad_request_error(
+            "min_year cannot be greater than max_year".to_string(),
+        ));
+    }
+
+    match games::list_games(&db, &list_query).await {
         Ok(result) => success_response(result),
         Err(e) => {
             tracing::error!("Failed to list games: {}", e);
@@ -67,6 +90,32 @@ pub async fn get_game(
     }
 }
 
+/// List the expansions of a base game
+#[endpoint {
+    method = GET,
+    path = "/api/games/{id}/expansions"
+}]
+pub async fn list_game_expansions(
+    rqctx: RequestContext<AppState>,
+    path: Path<GamePathParam>,
+) -> Result<HttpOk<Vec<Game>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match games::list_expansions(&db, game_id).await {
+        Ok(Some(expansions)) => success_response(expansions),
+        Ok(None) => Err(not_found_error(format!(
+            "Game with id {} not found",
+            game_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to list expansions of game {}: {}", game_id, e);
+            Err(internal_error("Failed to list expansions".to_string()))
+        }
+    }
+}
+
 /// Create a new game
 #[endpoint {
     method = POST,
@@ -94,7 +143,15 @@ pub async fn create_game(
     }
 
     match games::create_game(&db, create_request).await {
-        Ok(game) => created_response(game),
+        Ok(game) => {
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            created_response(game)
+        }
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message.starts_with("Base game") =>
+        {
+            Err(bad_request_error(message))
+        }
         Err(e) => {
             tracing::error!("Failed to create game: {}", e);
             Err(internal_error("Failed to create game".to_string()))
@@ -133,11 +190,19 @@ pub async fn update_game(
     }
 
     match games::update_game(&db, game_id, update_request).await {
-        Ok(Some(game)) => success_response(game),
+        Ok(Some(game)) => {
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            success_response(game)
+        }
         Ok(None) => Err(not_found_error(format!(
             "Game with id {} not found",
             game_id
         ))),
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message.starts_with("Base game") =>
+        {
+            Err(bad_request_error(message))
+        }
         Err(e) => {
             tracing::error!("Failed to update game {}: {}", game_id, e);
             Err(internal_error("Failed to update game".to_string()))
@@ -4,17 +4,21 @@ use serde::Deserialize;
 
 use crate::{
     AppState,
-    db::house_rules,
+    db::{self, house_rules},
     handlers::{
         HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
         deleted_response, internal_error, not_found_error, success_response,
     },
+    house_rule_analysis,
     models::{
-        CreateHouseRuleRequest, GameId, HouseRule, HouseRuleId, PaginatedResponse,
-        PaginationParams, UpdateHouseRuleRequest,
+        CreateHouseRuleRequest, GameId, HouseRule, HouseRuleAnalysis, HouseRuleId,
+        PaginatedResponse, PaginationParams, QueryStrategy, UpdateHouseRuleRequest,
     },
+    semantic_cache,
 };
 
+use super::chat::retrieve_rules;
+
 #[derive(Deserialize, JsonSchema)]
 pub struct HouseRulePathParam {
     pub id: HouseRuleId,
@@ -107,8 +111,24 @@ pub async fn create_house_rule(
         ));
     }
 
+    let analyze = create_request.analyze;
+
     match house_rules::create_house_rule(&db, create_request).await {
-        Ok(house_rule) => created_response(house_rule),
+        Ok(mut house_rule) => {
+            semantic_cache::invalidate(&db, Some(house_rule.game_id)).await;
+            // A failed analysis should not fail the create; it can be rerun later
+            if analyze {
+                match run_analysis(app_state, &house_rule).await {
+                    Ok(analysis) => house_rule.analysis = Some(analysis),
+                    Err(e) => tracing::warn!(
+                        "Failed to analyze new house rule {}: {}",
+                        house_rule.id,
+                        e.external_message
+                    ),
+                }
+            }
+            created_response(house_rule)
+        }
         Err(e) => {
             tracing::error!("Failed to create house rule: {}", e);
             Err(internal_error("Failed to create house rule".to_string()))
@@ -148,7 +168,10 @@ pub async fn update_house_rule(
     }
 
     match house_rules::update_house_rule(&db, house_rule_id, update_request).await {
-        Ok(Some(house_rule)) => success_response(house_rule),
+        Ok(Some(house_rule)) => {
+            semantic_cache::invalidate(&db, Some(house_rule.game_id)).await;
+            success_response(house_rule)
+        }
         Ok(None) => Err(not_found_error(format!(
             "House rule with id {} not found",
             house_rule_id
@@ -173,8 +196,20 @@ pub async fn delete_house_rule(
     let house_rule_id = path.into_inner().id;
     let db = app_state.db();
 
+    // Remember the game so its cached answers can be dropped after the delete
+    let game_id = house_rules::get_house_rule(&db, house_rule_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|house_rule| house_rule.game_id);
+
     match house_rules::delete_house_rule(&db, house_rule_id).await {
-        Ok(true) => deleted_response(),
+        Ok(true) => {
+            if let Some(game_id) = game_id {
+                semantic_cache::invalidate(&db, Some(game_id)).await;
+            }
+            deleted_response()
+        }
         Ok(false) => Err(not_found_error(format!(
             "House rule with id {} not found",
             house_rule_id
@@ -185,3 +220,79 @@ pub async fn delete_house_rule(
         }
     }
 }
+
+/// Compare a house rule with the rulebook and store how it relates
+#[endpoint {
+    method = POST,
+    path = "/api/house-rules/{id}/analysis"
+}]
+pub async fn analyze_house_rule(
+    rqctx: RequestContext<AppState>,
+    path: Path<HouseRulePathParam>,
+) -> Result<HttpOk<HouseRuleAnalysis>, HttpError> {
+    let app_state = rqctx.context();
+    let house_rule_id = path.into_inner().id;
+    let db = app_state.db();
+
+    let house_rule = match house_rules::get_house_rule(&db, house_rule_id).await {
+        Ok(Some(house_rule)) => house_rule,
+        Ok(None) => {
+            return Err(not_found_error(format!(
+                "House rule with id {} not found",
+                house_rule_id
+            )));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get house rule {}: {}", house_rule_id, e);
+            return Err(internal_error("Failed to get house rule".to_string()));
+        }
+    };
+
+    success_response(run_analysis(app_state, &house_rule).await?)
+}
+
+/// Retrieve the rulebook passages a house rule touches, classify it and store the result
+async fn run_analysis(
+    app_state: &AppState,
+    house_rule: &HouseRule,
+) -> Result<HouseRuleAnalysis, HttpError> {
+    let db = app_state.db();
+
+    let game = db::games::get_game(&db, house_rule.game_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get game {}: {}", house_rule.game_id, e);
+            internal_error("Failed to load game".to_string())
+        })?
+        .ok_or_else(|| not_found_error(format!("Game with id {} not found", house_rule.game_id)))?;
+
+    let passages = retrieve_rules(
+        app_state,
+        house_rule.game_id,
+        &house_rule_analysis::analysis_query(house_rule),
+        QueryStrategy::Direct,
+    )
+    .await?
+    .results;
+
+    let analysis =
+        house_rule_analysis::analyze_house_rule(app_state.llm(), &game.name, house_rule, &passages)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to analyze house rule {}: {:#}", house_rule.id, e);
+                internal_error("Failed to analyze house rule".to_string())
+            })?;
+
+    house_rules::set_house_rule_analysis(&db, house_rule.id, &analysis)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to store analysis of house rule {}: {}",
+                house_rule.id,
+                e
+            );
+            internal_error("Failed to store house rule analysis".to_string())
+        })?;
+
+    Ok(analysis)
+}
@@ -0,0 +1,114 @@
+use dropshot::{Query, RequestContext, TypedBody, UntypedBody, endpoint};
+
+use crate::{
+    AppState, bgg,
+    db::games,
+    discovery,
+    handlers::{HttpError, HttpOk, bad_request_error, internal_error, success_response},
+    models::{BggImportSummary, ImportBggRanksQuery, ImportBggThingsRequest},
+};
+
+/// Import games from a BoardGameGeek ranks CSV sent as the request body,
+/// creating or updating them by BGG id
+#[endpoint {
+    method = POST,
+    path = "/api/import/bgg/ranks"
+}]
+pub async fn import_bgg_ranks(
+    rqctx: RequestContext<AppState>,
+    query: Query<ImportBggRanksQuery>,
+    body: UntypedBody,
+) -> Result<HttpOk<BggImportSummary>, HttpError> {
+    let app_state = rqctx.context();
+    let import_query = query.into_inner();
+    let db = app_state.db();
+
+    if body.as_bytes().is_empty() {
+        return Err(bad_request_error("No CSV data provided".to_string()));
+    }
+    let ranks = bgg::parse_ranks_csv(body.as_bytes())
+        .map_err(|e| bad_request_error(format!("Invalid ranks CSV: {:#}", e)))?;
+
+    let total = ranks.len();
+    let mut ranks: Vec<_> = ranks
+        .into_iter()
+        .filter(|rank| import_query.include_expansions || !rank.is_expansion)
+        .collect();
+    if let Some(limit) = import_query.limit {
+        // Best-ranked first; unranked games go last
+        ranks.sort_by_key(|rank| rank.rank.unwrap_or(i32::MAX));
+        ranks.truncate(limit);
+    }
+
+    match games::import_bgg_ranks(&db, &ranks).await {
+        Ok(mut summary) => {
+            summary.skipped = total - ranks.len();
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            success_response(summary)
+        }
+        Err(e) => {
+            tracing::error!("Failed to import BGG ranks: {}", e);
+            Err(internal_error("Failed to import BGG ranks".to_string()))
+        }
+    }
+}
+
+/// Import games from BoardGameGeek `thing` documents, fetched by id from the
+/// configured XML API2 server or provided directly, creating or updating them by BGG id
+#[endpoint {
+    method = POST,
+    path = "/api/import/bgg/things"
+}]
+pub async fn import_bgg_things(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<ImportBggThingsRequest>,
+) -> Result<HttpOk<BggImportSummary>, HttpError> {
+    let app_state = rqctx.context();
+    let import_request = body.into_inner();
+    let db = app_state.db();
+
+    if import_request.bgg_ids.is_empty() && import_request.xml.is_none() {
+        return Err(bad_request_error(
+            "Provide BGG ids to fetch or a thing document to import".to_string(),
+        ));
+    }
+
+    let mut things = Vec::new();
+    if let Some(xml) = &import_request.xml {
+        things.extend(
+            bgg::parse_things_xml(xml)
+                .map_err(|e| bad_request_error(format!("Invalid thing document: {:#}", e)))?,
+        );
+    }
+    for batch in import_request.bgg_ids.chunks(bgg::THING_BATCH_SIZE) {
+        let xml = app_state.bgg().fetch_things(batch).await.map_err(|e| {
+            tracing::error!("Failed to fetch BGG things: {:#}", e);
+            internal_error(format!("Failed to fetch BGG things: {:#}", e))
+        })?;
+        things.extend(bgg::parse_things_xml(&xml).map_err(|e| {
+            tracing::error!("BGG returned an unreadable thing document: {:#}", e);
+            internal_error(format!(
+                "BGG returned an unreadable thing document: {:#}",
+                e
+            ))
+        })?);
+    }
+
+    let total = things.len();
+    let things: Vec<_> = things
+        .into_iter()
+        .filter(|thing| import_request.include_expansions || !thing.is_expansion)
+        .collect();
+
+    match games::import_bgg_things(&db, &things).await {
+        Ok(mut summary) => {
+            summary.skipped = total - things.len();
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            success_response(summary)
+        }
+        Err(e) => {
+            tracing::error!("Failed to import BGG things: {}", e);
+            Err(internal_error("Failed to import BGG things".to_string()))
+        }
+    }
+}
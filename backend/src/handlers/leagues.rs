@@ -0,0 +1,277 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::leagues,
+    handlers::{
+        HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        CreateLeagueRequest, GameId, League, LeagueId, LeagueRatingsQuery, Play, RatingChange,
+        RatingHistoryQuery, Standing, UpdateLeagueRequest,
+    },
+    ratings,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LeaguePathParam {
+    pub id: LeagueId,
+}
+
+/// Duplicate names and missing players or games are reported by the database
+fn invalid_league(error: &rusqlite::Error) -> Option<HttpError> {
+    match error {
+        rusqlite::Error::SqliteFailure(e, Some(message))
+            if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            Some(bad_request_error(if message.starts_with("UNIQUE") {
+                "A league with that name already exists".to_string()
+            } else {
+                message.clone()
+            }))
+        }
+        _ => None,
+    }
+}
+
+fn validate_season(
+    starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    ends_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(), HttpError> {
+    if let (Some(starts_at), Some(ends_at)) = (starts_at, ends_at)
+        && starts_at > ends_at
+    {
+        return Err(bad_request_error(
+            "starts_at cannot be later than ends_at".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Load a league and the plays that count towards it, oldest first, with
+/// `game_id` narrowing them to one of its games
+async fn league_with_plays(
+    app_state: &AppState,
+    league_id: LeagueId,
+    game_id: Option<GameId>,
+) -> Result<(League, Vec<Play>), HttpError> {
+    let db = app_state.db();
+
+    let league = match leagues::get_league(&db, league_id).await {
+        Ok(Some(league)) => league,
+        Ok(None) => {
+            return Err(not_found_error(format!(
+                "League with id {} not found",
+                league_id
+            )));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get league {}: {}", league_id, e);
+            return Err(internal_error("Failed to get league".to_string()));
+        }
+    };
+    if let Some(game_id) = game_id
+        && !league.game_ids.is_empty()
+        && !league.game_ids.contains(&game_id)
+    {
+        return Err(bad_request_error(format!(
+            "Game {} is not part of league {}",
+            game_id, league_id
+        )));
+    }
+
+    match leagues::league_plays(&db, &league, game_id).await {
+        Ok(plays) => Ok((league, plays)),
+        Err(e) => {
+            tracing::error!("Failed to load plays for league {}: {}", league_id, e);
+            Err(internal_error("Failed to load league plays".to_string()))
+        }
+    }
+}
+
+/// List leagues
+#[endpoint {
+    method = GET,
+    path = "/api/leagues"
+}]
+pub async fn list_leagues(
+    rqctx: RequestContext<AppState>,
+) -> Result<HttpOk<Vec<League>>, HttpError> {
+    let app_state = rqctx.context();
+    let db = app_state.db();
+
+    match leagues::list_leagues(&db).await {
+        Ok(leagues) => success_response(leagues),
+        Err(e) => {
+            tracing::error!("Failed to list leagues: {}", e);
+            Err(internal_error("Failed to list leagues".to_string()))
+        }
+    }
+}
+
+/// Get a league with its members and games
+#[endpoint {
+    method = GET,
+    path = "/api/leagues/{id}"
+}]
+pub async fn get_league(
+    rqctx: RequestContext<AppState>,
+    path: Path<LeaguePathParam>,
+) -> Result<HttpOk<League>, HttpError> {
+    let app_state = rqctx.context();
+    let league_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match leagues::get_league(&db, league_id).await {
+        Ok(Some(league)) => success_response(league),
+        Ok(None) => Err(not_found_error(format!(
+            "League with id {} not found",
+            league_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get league {}: {}", league_id, e);
+            Err(internal_error("Failed to get league".to_string()))
+        }
+    }
+}
+
+/// Create a league; names are unique, ignoring case
+#[endpoint {
+    method = POST,
+    path = "/api/leagues"
+}]
+pub async fn create_league(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreateLeagueRequest>,
+) -> Result<HttpCreated<League>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if create_request.name.trim().is_empty() {
+        return Err(bad_request_error("League name cannot be empty".to_string()));
+    }
+    validate_season(create_request.starts_at, create_request.ends_at)?;
+
+    match leagues::create_league(&db, create_request).await {
+        Ok(league) => created_response(league),
+        Err(e) => Err(invalid_league(&e).unwrap_or_else(|| {
+            tracing::error!("Failed to create league: {}", e);
+            internal_error("Failed to create league".to_string())
+        })),
+    }
+}
+
+/// Update a league; member and game lists are replaced when given
+#[endpoint {
+    method = PUT,
+    path = "/api/leagues/{id}"
+}]
+pub async fn update_league(
+    rqctx: RequestContext<AppState>,
+    path: Path<LeaguePathParam>,
+    body: TypedBody<UpdateLeagueRequest>,
+) -> Result<HttpOk<League>, HttpError> {
+    let app_state = rqctx.context();
+    let league_id = path.into_inner().id;
+    let update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(name) = &update_request.name
+        && name.trim().is_empty()
+    {
+        return Err(bad_request_error("League name cannot be empty".to_string()));
+    }
+    validate_season(update_request.starts_at, update_request.ends_at)?;
+
+    match leagues::update_league(&db, league_id, update_request).await {
+        Ok(Some(league)) => success_response(league),
+        Ok(None) => Err(not_found_error(format!(
+            "League with id {} not found",
+            league_id
+        ))),
+        Err(e) => Err(invalid_league(&e).unwrap_or_else(|| {
+            tracing::error!("Failed to update league {}: {}", league_id, e);
+            internal_error("Failed to update league".to_string())
+        })),
+    }
+}
+
+/// Delete a league; its players and plays are kept
+#[endpoint {
+    method = DELETE,
+    path = "/api/leagues/{id}"
+}]
+pub async fn delete_league(
+    rqctx: RequestContext<AppState>,
+    path: Path<LeaguePathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let league_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match leagues::delete_league(&db, league_id).await {
+        Ok(true) => deleted_response(),
+        Ok(false) => Err(not_found_error(format!(
+            "League with id {} not found",
+            league_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to delete league {}: {}", league_id, e);
+            Err(internal_error("Failed to delete league".to_string()))
+        }
+    }
+}
+
+/// League table of members by Elo rating, recomputed from the play log;
+/// overall across the league's games, or for one game with `game_id`
+#[endpoint {
+    method = GET,
+    path = "/api/leagues/{id}/standings"
+}]
+pub async fn get_league_standings(
+    rqctx: RequestContext<AppState>,
+    path: Path<LeaguePathParam>,
+    query: Query<LeagueRatingsQuery>,
+) -> Result<HttpOk<Vec<Standing>>, HttpError> {
+    let app_state = rqctx.context();
+    let league_id = path.into_inner().id;
+    let query = query.into_inner();
+
+    let (league, plays) = league_with_plays(app_state, league_id, query.game_id).await?;
+    let run = ratings::rate_plays(&plays, |player_id| league.player_ids.contains(&player_id));
+    success_response(ratings::standings(&run))
+}
+
+/// How each rated play moved members' ratings, oldest first
+#[endpoint {
+    method = GET,
+    path = "/api/leagues/{id}/ratings/history"
+}]
+pub async fn get_league_rating_history(
+    rqctx: RequestContext<AppState>,
+    path: Path<LeaguePathParam>,
+    query: Query<RatingHistoryQuery>,
+) -> Result<HttpOk<Vec<RatingChange>>, HttpError> {
+    let app_state = rqctx.context();
+    let league_id = path.into_inner().id;
+    let query = query.into_inner();
+
+    let (league, plays) = league_with_plays(app_state, league_id, query.game_id).await?;
+    let run = ratings::rate_plays(&plays, |player_id| league.player_ids.contains(&player_id));
+    let history = run
+        .history
+        .into_iter()
+        .filter(|change| {
+            query
+                .player_id
+                .is_none_or(|player_id| change.player_id == player_id)
+        })
+        .collect();
+    success_response(history)
+}
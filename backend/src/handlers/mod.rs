@@ -4,10 +4,26 @@ use dropshot::{
 use schemars::JsonSchema;
 use serde::Serialize;
 
+pub mod answer_cache;
+pub mod backups;
 pub mod chat;
+pub mod collection;
+pub mod embedding_cache;
+pub mod evaluations;
+pub mod faq;
 pub mod games;
 pub mod house_rules;
+pub mod imports;
+pub mod leagues;
+pub mod players;
+pub mod plays;
+pub mod prompt_templates;
+pub mod quick_references;
+pub mod recommendations;
+pub mod similar_games;
 pub mod static_files;
+pub mod stats;
+pub mod tags;
 pub mod upload;
 
 // Re-exports are available but not used globally to avoid namespace pollution
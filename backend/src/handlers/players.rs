@@ -0,0 +1,165 @@
+use dropshot::{Path, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::{players, stats},
+    handlers::{
+        HttpCreated, HttpError, HttpOk, bad_request_error, created_response, internal_error,
+        not_found_error, success_response,
+    },
+    models::{CreatePlayerRequest, Player, PlayerId, PlayerStats, UpdatePlayerRequest},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PlayerPathParam {
+    pub id: PlayerId,
+}
+
+fn is_duplicate_player(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _) if e.code == rusqlite::ErrorCode::ConstraintViolation
+    )
+}
+
+/// List players with their play and win counts
+#[endpoint {
+    method = GET,
+    path = "/api/players"
+}]
+pub async fn list_players(
+    rqctx: RequestContext<AppState>,
+) -> Result<HttpOk<Vec<Player>>, HttpError> {
+    let app_state = rqctx.context();
+    let db = app_state.db();
+
+    match players::list_players(&db).await {
+        Ok(players) => success_response(players),
+        Err(e) => {
+            tracing::error!("Failed to list players: {}", e);
+            Err(internal_error("Failed to list players".to_string()))
+        }
+    }
+}
+
+/// Get a player's profile
+#[endpoint {
+    method = GET,
+    path = "/api/players/{id}"
+}]
+pub async fn get_player(
+    rqctx: RequestContext<AppState>,
+    path: Path<PlayerPathParam>,
+) -> Result<HttpOk<Player>, HttpError> {
+    let app_state = rqctx.context();
+    let player_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match players::get_player(&db, player_id).await {
+        Ok(Some(player)) => success_response(player),
+        Ok(None) => Err(not_found_error(format!(
+            "Player with id {} not found",
+            player_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get player {}: {}", player_id, e);
+            Err(internal_error("Failed to get player".to_string()))
+        }
+    }
+}
+
+/// Create a player; names are unique, ignoring case
+#[endpoint {
+    method = POST,
+    path = "/api/players"
+}]
+pub async fn create_player(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreatePlayerRequest>,
+) -> Result<HttpCreated<Player>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if create_request.name.trim().is_empty() {
+        return Err(bad_request_error("Player name cannot be empty".to_string()));
+    }
+
+    match players::create_player(&db, create_request).await {
+        Ok(player) => created_response(player),
+        Err(e) if is_duplicate_player(&e) => Err(bad_request_error(
+            "A player with that name already exists".to_string(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to create player: {}", e);
+            Err(internal_error("Failed to create player".to_string()))
+        }
+    }
+}
+
+/// Rename a player
+#[endpoint {
+    method = PUT,
+    path = "/api/players/{id}"
+}]
+pub async fn update_player(
+    rqctx: RequestContext<AppState>,
+    path: Path<PlayerPathParam>,
+    body: TypedBody<UpdatePlayerRequest>,
+) -> Result<HttpOk<Player>, HttpError> {
+    let app_state = rqctx.context();
+    let player_id = path.into_inner().id;
+    let update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(name) = &update_request.name
+        && name.trim().is_empty()
+    {
+        return Err(bad_request_error("Player name cannot be empty".to_string()));
+    }
+
+    match players::update_player(&db, player_id, update_request).await {
+        Ok(Some(player)) => success_response(player),
+        Ok(None) => Err(not_found_error(format!(
+            "Player with id {} not found",
+            player_id
+        ))),
+        Err(e) if is_duplicate_player(&e) => Err(bad_request_error(
+            "A player with that name already exists".to_string(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to update player {}: {}", player_id, e);
+            Err(internal_error("Failed to update player".to_string()))
+        }
+    }
+}
+
+/// A player's win rate, h-index and record in every game they have played
+#[endpoint {
+    method = GET,
+    path = "/api/players/{id}/stats"
+}]
+pub async fn get_player_stats(
+    rqctx: RequestContext<AppState>,
+    path: Path<PlayerPathParam>,
+) -> Result<HttpOk<PlayerStats>, HttpError> {
+    let app_state = rqctx.context();
+    let player_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match stats::player_stats(&db, player_id).await {
+        Ok(Some(stats)) => success_response(stats),
+        Ok(None) => Err(not_found_error(format!(
+            "Player with id {} not found",
+            player_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to compute stats for player {}: {}", player_id, e);
+            Err(internal_error("Failed to compute player stats".to_string()))
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::plays,
+    handlers::{
+        HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        CreatePlayRequest, PaginatedResponse, Play, PlayId, PlayListQuery, PlayPlayerRequest,
+        UpdatePlayRequest,
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PlayPathParam {
+    pub id: PlayId,
+}
+
+fn validate_players(players: &[PlayPlayerRequest]) -> Result<(), HttpError> {
+    if players.is_empty() {
+        return Err(bad_request_error(
+            "A play needs at least one player".to_string(),
+        ));
+    }
+    let mut seen: Vec<String> = Vec::with_capacity(players.len());
+    for player in players {
+        let name = player.name.trim().to_lowercase();
+        if name.is_empty() {
+            return Err(bad_request_error("Player name cannot be empty".to_string()));
+        }
+        if seen.contains(&name) {
+            return Err(bad_request_error(format!(
+                "Player {} is listed more than once",
+                player.name.trim()
+            )));
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+fn validate_duration(duration_minutes: Option<i32>) -> Result<(), HttpError> {
+    if duration_minutes.is_some_and(|minutes| minutes < 1) {
+        return Err(bad_request_error(
+            "duration_minutes must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Games, expansions and chat sessions a play points at are checked in the database
+fn invalid_reference(error: &rusqlite::Error) -> Option<HttpError> {
+    match error {
+        rusqlite::Error::SqliteFailure(e, Some(message))
+            if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            Some(bad_request_error(message.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// List logged plays, most recent first, paginated
+#[endpoint {
+    method = GET,
+    path = "/api/plays"
+}]
+pub async fn list_plays(
+    rqctx: RequestContext<AppState>,
+    query: Query<PlayListQuery>,
+) -> Result<HttpOk<PaginatedResponse<Play>>, HttpError> {
+    let app_state = rqctx.context();
+    let list_query = query.into_inner();
+    let db = app_state.db();
+
+    if list_query.limit == 0 {
+        return Err(bad_request_error("limit must be at least 1".to_string()));
+    }
+
+    match plays::list_plays(&db, &list_query).await {
+        Ok(result) => success_response(result),
+        Err(e) => {
+            tracing::error!("Failed to list plays: {}", e);
+            Err(internal_error("Failed to list plays".to_string()))
+        }
+    }
+}
+
+/// Get a specific play by ID
+#[endpoint {
+    method = GET,
+    path = "/api/plays/{id}"
+}]
+pub async fn get_play(
+    rqctx: RequestContext<AppState>,
+    path: Path<PlayPathParam>,
+) -> Result<HttpOk<Play>, HttpError> {
+    let app_state = rqctx.context();
+    let play_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match plays::get_play(&db, play_id).await {
+        Ok(Some(play)) => success_response(play),
+        Ok(None) => Err(not_found_error(format!(
+            "Play with id {} not found",
+            play_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get play {}: {}", play_id, e);
+            Err(internal_error("Failed to get play".to_string()))
+        }
+    }
+}
+
+/// Log a play of a game; players are created on first use
+#[endpoint {
+    method = POST,
+    path = "/api/plays"
+}]
+pub async fn create_play(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreatePlayRequest>,
+) -> Result<HttpCreated<Play>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    validate_players(&create_request.players)?;
+    validate_duration(create_request.duration_minutes)?;
+
+    match plays::create_play(&db, create_request).await {
+        Ok(play) => created_response(play),
+        Err(e) => Err(invalid_reference(&e).unwrap_or_else(|| {
+            tracing::error!("Failed to create play: {}", e);
+            internal_error("Failed to create play".to_string())
+        })),
+    }
+}
+
+/// Update a play; player, expansion and chat session lists are replaced when given
+#[endpoint {
+    method = PUT,
+    path = "/api/plays/{id}"
+}]
+pub async fn update_play(
+    rqctx: RequestContext<AppState>,
+    path: Path<PlayPathParam>,
+    body: TypedBody<UpdatePlayRequest>,
+) -> Result<HttpOk<Play>, HttpError> {
+    let app_state = rqctx.context();
+    let play_id = path.into_inner().id;
+    let update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(players) = &update_request.players {
+        validate_players(players)?;
+    }
+    validate_duration(update_request.duration_minutes)?;
+
+    match plays::update_play(&db, play_id, update_request).await {
+        Ok(Some(play)) => success_response(play),
+        Ok(None) => Err(not_found_error(format!(
+            "Play with id {} not found",
+            play_id
+        ))),
+        Err(e) => Err(invalid_reference(&e).unwrap_or_else(|| {
+            tracing::error!("Failed to update play {}: {}", play_id, e);
+            internal_error("Failed to update play".to_string())
+        })),
+    }
+}
+
+/// Delete a play
+#[endpoint {
+    method = DELETE,
+    path = "/api/plays/{id}"
+}]
+pub async fn delete_play(
+    rqctx: RequestContext<AppState>,
+    path: Path<PlayPathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let play_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match plays::delete_play(&db, play_id).await {
+        Ok(true) => deleted_response(),
+        Ok(false) => Err(not_found_error(format!(
+            "Play with id {} not found",
+            play_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to delete play {}: {}", play_id, e);
+            Err(internal_error("Failed to delete play".to_string()))
+        }
+    }
+}
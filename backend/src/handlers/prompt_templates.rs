@@ -0,0 +1,303 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::{chat, prompt_templates},
+    handlers::{
+        HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        CreatePromptTemplateRequest, GameId, PromptPreviewRequest, PromptPreviewResponse,
+        PromptTemplate, PromptTemplateId, PromptTemplateVariable, UpdatePromptTemplateRequest,
+    },
+    semantic_cache, templates,
+};
+
+use super::chat::{RulesPrompt, RulesPromptRequest, build_rules_prompt, retrieve_rules};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PromptTemplatePathParam {
+    pub id: PromptTemplateId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PromptTemplatesQuery {
+    /// Only list this game's templates and the global defaults
+    pub game_id: Option<GameId>,
+}
+
+fn validate_name(name: &str) -> Result<(), HttpError> {
+    if name.trim().is_empty() {
+        return Err(bad_request_error(
+            "Prompt template name cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_template_text(template: &str) -> Result<(), HttpError> {
+    templates::validate_template(template).map_err(bad_request_error)
+}
+
+/// List prompt templates
+#[endpoint {
+    method = GET,
+    path = "/api/prompt-templates"
+}]
+pub async fn list_prompt_templates(
+    rqctx: RequestContext<AppState>,
+    query: Query<PromptTemplatesQuery>,
+) -> Result<HttpOk<Vec<PromptTemplate>>, HttpError> {
+    let app_state = rqctx.context();
+    let query = query.into_inner();
+    let db = app_state.db();
+
+    match prompt_templates::list_prompt_templates(&db, query.game_id).await {
+        Ok(templates) => success_response(templates),
+        Err(e) => {
+            tracing::error!("Failed to list prompt templates: {}", e);
+            Err(internal_error(
+                "Failed to list prompt templates".to_string(),
+            ))
+        }
+    }
+}
+
+/// List the variables prompt templates may use
+#[endpoint {
+    method = GET,
+    path = "/api/prompt-variables"
+}]
+pub async fn list_prompt_template_variables(
+    _rqctx: RequestContext<AppState>,
+) -> Result<HttpOk<Vec<PromptTemplateVariable>>, HttpError> {
+    success_response(templates::template_variables())
+}
+
+/// Get a specific prompt template by ID
+#[endpoint {
+    method = GET,
+    path = "/api/prompt-templates/{id}"
+}]
+pub async fn get_prompt_template(
+    rqctx: RequestContext<AppState>,
+    path: Path<PromptTemplatePathParam>,
+) -> Result<HttpOk<PromptTemplate>, HttpError> {
+    let app_state = rqctx.context();
+    let template_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match prompt_templates::get_prompt_template(&db, template_id).await {
+        Ok(Some(template)) => success_response(template),
+        Ok(None) => Err(not_found_error(format!(
+            "Prompt template with id {} not found",
+            template_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to get prompt template {}: {}", template_id, e);
+            Err(internal_error("Failed to get prompt template".to_string()))
+        }
+    }
+}
+
+/// Create a prompt template; an active template replaces the active one in its scope
+#[endpoint {
+    method = POST,
+    path = "/api/prompt-templates"
+}]
+pub async fn create_prompt_template(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreatePromptTemplateRequest>,
+) -> Result<HttpCreated<PromptTemplate>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    validate_name(&create_request.name)?;
+    validate_template_text(&create_request.template)?;
+
+    match prompt_templates::create_prompt_template(&db, create_request).await {
+        Ok(template) => {
+            semantic_cache::invalidate(&db, template.game_id).await;
+            created_response(template)
+        }
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message == "Game does not exist" =>
+        {
+            Err(bad_request_error(message))
+        }
+        Err(e) => {
+            tracing::error!("Failed to create prompt template: {}", e);
+            Err(internal_error(
+                "Failed to create prompt template".to_string(),
+            ))
+        }
+    }
+}
+
+/// Update an existing prompt template
+#[endpoint {
+    method = PUT,
+    path = "/api/prompt-templates/{id}"
+}]
+pub async fn update_prompt_template(
+    rqctx: RequestContext<AppState>,
+    path: Path<PromptTemplatePathParam>,
+    body: TypedBody<UpdatePromptTemplateRequest>,
+) -> Result<HttpOk<PromptTemplate>, HttpError> {
+    let app_state = rqctx.context();
+    let template_id = path.into_inner().id;
+    let update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(ref name) = update_request.name {
+        validate_name(name)?;
+    }
+    if let Some(ref template) = update_request.template {
+        validate_template_text(template)?;
+    }
+
+    match prompt_templates::update_prompt_template(&db, template_id, update_request).await {
+        Ok(Some(template)) => {
+            semantic_cache::invalidate(&db, template.game_id).await;
+            success_response(template)
+        }
+        Ok(None) => Err(not_found_error(format!(
+            "Prompt template with id {} not found",
+            template_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to update prompt template {}: {}", template_id, e);
+            Err(internal_error(
+                "Failed to update prompt template".to_string(),
+            ))
+        }
+    }
+}
+
+/// Delete a prompt template
+#[endpoint {
+    method = DELETE,
+    path = "/api/prompt-templates/{id}"
+}]
+pub async fn delete_prompt_template(
+    rqctx: RequestContext<AppState>,
+    path: Path<PromptTemplatePathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let template_id = path.into_inner().id;
+    let db = app_state.db();
+
+    let existing = prompt_templates::get_prompt_template(&db, template_id)
+        .await
+        .ok()
+        .flatten();
+
+    match prompt_templates::delete_prompt_template(&db, template_id).await {
+        Ok(true) => {
+            if let Some(template) = existing {
+                semantic_cache::invalidate(&db, template.game_id).await;
+            }
+            deleted_response()
+        }
+        Ok(false) => Err(not_found_error(format!(
+            "Prompt template with id {} not found",
+            template_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to delete prompt template {}: {}", template_id, e);
+            Err(internal_error(
+                "Failed to delete prompt template".to_string(),
+            ))
+        }
+    }
+}
+
+/// Render the final system prompt for a sample question without calling the model
+#[endpoint {
+    method = POST,
+    path = "/api/prompt-preview"
+}]
+pub async fn preview_prompt(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<PromptPreviewRequest>,
+) -> Result<HttpOk<PromptPreviewResponse>, HttpError> {
+    let app_state = rqctx.context();
+    let preview_request = body.into_inner();
+    let db = app_state.db();
+
+    if preview_request.question.trim().is_empty() {
+        return Err(bad_request_error("Question cannot be empty".to_string()));
+    }
+    if let Some(ref template) = preview_request.template {
+        validate_template_text(template)?;
+    }
+
+    let history = match preview_request.session_id {
+        Some(session_id) => {
+            let history = chat::get_chat_history(&db, session_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to get chat session {}: {}", session_id, e);
+                    internal_error("Failed to access chat session".to_string())
+                })?
+                .ok_or_else(|| {
+                    not_found_error(format!("Chat session with id {} not found", session_id))
+                })?;
+            if history.session.game_id != preview_request.game_id {
+                return Err(bad_request_error(format!(
+                    "Chat session {} belongs to a different game",
+                    session_id
+                )));
+            }
+            history.messages
+        }
+        None => Vec::new(),
+    };
+
+    let search_results = retrieve_rules(
+        app_state,
+        preview_request.game_id,
+        &preview_request.question,
+        preview_request.query_strategy.unwrap_or_default(),
+    )
+    .await?
+    .results;
+
+    let RulesPrompt {
+        system_prompt,
+        sections,
+        template_source,
+        template_id,
+    } = build_rules_prompt(
+        app_state,
+        RulesPromptRequest {
+            game_id: preview_request.game_id,
+            compare_game_ids: &[],
+            expansion_ids: &[],
+            question: &preview_request.question,
+            search_results: &search_results,
+            history: &history,
+            use_tools: true,
+            template_override: preview_request.template.as_deref(),
+            persona_override: preview_request.persona.as_deref(),
+        },
+    )
+    .await?;
+
+    success_response(PromptPreviewResponse {
+        system_prompt,
+        template_source,
+        template_id,
+        prompt_tokens: sections.usage.prompt_tokens(),
+        context_window: sections.usage.context_window,
+        dropped_chunks: sections.usage.dropped_chunks,
+        dropped_history_messages: sections.usage.dropped_history_messages,
+        included_chunk_ids: sections.included_chunk_ids,
+    })
+}
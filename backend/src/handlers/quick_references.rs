@@ -0,0 +1,158 @@
+use chrono::Utc;
+use dropshot::{Path, Query, RequestContext, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::{self, quick_references},
+    handlers::{
+        HttpError, HttpOk, bad_request_error, chat::retrieve_rules, internal_error,
+        not_found_error, success_response,
+    },
+    models::{
+        EmbeddingSearchResult, EmbeddingSourceType, GameId, QueryStrategy, QuickReference,
+        QuickReferenceQuery,
+    },
+    quick_reference, semantic_cache,
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct QuickReferenceGamePathParam {
+    pub id: GameId,
+}
+
+/// Get a game's quick-reference sheet, generating it when there is none or the rules changed
+#[endpoint {
+    method = GET,
+    path = "/api/games/{id}/quick-reference"
+}]
+pub async fn get_quick_reference(
+    rqctx: RequestContext<AppState>,
+    path: Path<QuickReferenceGamePathParam>,
+    query: Query<QuickReferenceQuery>,
+) -> Result<HttpOk<QuickReference>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let QuickReferenceQuery {
+        player_count,
+        refresh,
+    } = query.into_inner();
+    let db = app_state.db();
+
+    let game = db::games::get_game(&db, game_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get game {}: {}", game_id, e);
+            internal_error("Failed to load game".to_string())
+        })?
+        .ok_or_else(|| not_found_error(format!("Game with id {} not found", game_id)))?;
+
+    if let Some(count) = player_count {
+        let min = game.min_players.unwrap_or(1).max(1);
+        let max = game.max_players.unwrap_or(i32::MAX);
+        if count < min || count > max {
+            return Err(bad_request_error(format!(
+                "{} does not support {} players",
+                game.name, count
+            )));
+        }
+    }
+
+    // The sheet is current as long as the rulebook chunks are unchanged
+    let chunks = db::embeddings::list_chunks_for_game(&db, game_id, EmbeddingSourceType::RulesPdf)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load rule chunks for game {}: {}", game_id, e);
+            internal_error("Failed to load rules".to_string())
+        })?;
+    if chunks.is_empty() {
+        return Err(bad_request_error(format!(
+            "Game {} has no uploaded rules to build a quick reference from",
+            game_id
+        )));
+    }
+    let fingerprint = semantic_cache::source_fingerprint(
+        &chunks
+            .iter()
+            .map(|chunk| (chunk.id, chunk.chunk_text.clone()))
+            .collect::<Vec<_>>(),
+    );
+
+    if !refresh {
+        match quick_references::get_quick_reference(&db, game_id, player_count).await {
+            Ok(Some((stored, stored_fingerprint))) if stored_fingerprint == fingerprint => {
+                return success_response(stored);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                // A broken cache entry is replaced below
+                tracing::warn!("Failed to read quick reference for game {}: {}", game_id, e);
+            }
+        }
+    }
+
+    let mut passages: Vec<EmbeddingSearchResult> = Vec::new();
+    for (_, section_query) in quick_reference::SECTIONS {
+        let results = retrieve_rules(
+            app_state,
+            game_id,
+            &quick_reference::section_query(section_query, player_count),
+            QueryStrategy::Direct,
+        )
+        .await?
+        .results;
+        for result in results {
+            if result.source_type == EmbeddingSourceType::RulesPdf
+                && !passages.iter().any(|p| p.id == result.id)
+            {
+                passages.push(result);
+            }
+        }
+    }
+    // Present the passages in rulebook order
+    passages.sort_by_key(|passage| {
+        chunks
+            .iter()
+            .position(|chunk| chunk.id == passage.id)
+            .unwrap_or(usize::MAX)
+    });
+
+    let sections = quick_reference::generate_quick_reference(
+        app_state.llm(),
+        &game.name,
+        player_count,
+        &passages,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(
+            "Failed to generate quick reference for game {}: {:#}",
+            game_id,
+            e
+        );
+        internal_error("Failed to generate quick reference".to_string())
+    })?;
+
+    let quick_reference = QuickReference {
+        game_id,
+        player_count,
+        sections,
+        model: app_state.llm().get_model().to_string(),
+        generated_at: Utc::now(),
+        cached: false,
+    };
+
+    if let Err(e) =
+        quick_references::save_quick_reference(&db, &quick_reference, &fingerprint).await
+    {
+        // The sheet is still worth returning; it is regenerated next time
+        tracing::warn!(
+            "Failed to store quick reference for game {}: {}",
+            game_id,
+            e
+        );
+    }
+
+    success_response(quick_reference)
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use dropshot::{RequestContext, TypedBody, endpoint};
+
+use crate::{
+    AppState,
+    db::{collection, game_descriptions, games, stats},
+    handlers::{HttpError, HttpOk, bad_request_error, internal_error, success_response},
+    models::{Game, GameId, RecommendationRequest, RecommendationResponse},
+    recommender,
+};
+
+/// Closest liked game for every candidate in the description index. Index failures
+/// leave games to be ranked on their other signals
+async fn description_similarities(
+    app_state: &AppState,
+    candidates: &[Game],
+    liked: &[Game],
+) -> HashMap<GameId, (GameId, f64)> {
+    if candidates.is_empty() || liked.is_empty() {
+        return HashMap::new();
+    }
+
+    let db = app_state.db();
+    let game_ids: Vec<GameId> = liked.iter().chain(candidates).map(|game| game.id).collect();
+    let embeddings = match game_descriptions::get_description_embeddings(&db, &game_ids).await {
+        Ok(embeddings) => embeddings,
+        Err(e) => {
+            tracing::warn!(
+                "Ranking recommendations without description similarity: {:#}",
+                e
+            );
+            return HashMap::new();
+        }
+    };
+
+    let liked_embeddings: Vec<(GameId, Vec<f32>)> = liked
+        .iter()
+        .filter_map(|game| Some((game.id, embeddings.get(&game.id)?.clone())))
+        .collect();
+    candidates
+        .iter()
+        .filter_map(|game| {
+            let closest =
+                recommender::closest_liked(game.id, embeddings.get(&game.id)?, &liked_embeddings)?;
+            Some((game.id, closest))
+        })
+        .collect()
+}
+
+/// Recommend owned games for a game night: filtered by player count, play time and
+/// complexity, ranked by time since last play, personal rating and description
+/// similarity to games the group liked
+#[endpoint {
+    method = POST,
+    path = "/api/recommendations"
+}]
+pub async fn recommend_games(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<RecommendationRequest>,
+) -> Result<HttpOk<RecommendationResponse>, HttpError> {
+    let app_state = rqctx.context();
+    let request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if request.players < 1 {
+        return Err(bad_request_error(
+            "At least one player is needed".to_string(),
+        ));
+    }
+    if request.max_play_time.is_some_and(|minutes| minutes < 1) {
+        return Err(bad_request_error(
+            "max_play_time must be positive".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (request.min_complexity, request.max_complexity)
+        && min > max
+    {
+        return Err(bad_request_error(
+            "min_complexity cannot be greater than max_complexity".to_string(),
+        ));
+    }
+    if request.limit == 0 || request.limit > recommender::MAX_RECOMMENDATIONS {
+        return Err(bad_request_error(format!(
+            "limit must be between 1 and {}",
+            recommender::MAX_RECOMMENDATIONS
+        )));
+    }
+
+    let candidates = games::list_playable_games(
+        &db,
+        request.players,
+        request.max_play_time,
+        request.min_complexity,
+        request.max_complexity,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to list games for recommendations: {}", e);
+        internal_error("Failed to list games".to_string())
+    })?;
+    let ratings = collection::game_ratings(&db).await.map_err(|e| {
+        tracing::error!("Failed to load game ratings: {}", e);
+        internal_error("Failed to load game ratings".to_string())
+    })?;
+    let most_played =
+        stats::most_played_by(&db, &request.player_names, recommender::LIKED_FROM_PLAYS)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load the group's most played games: {}", e);
+                internal_error("Failed to load play history".to_string())
+            })?;
+
+    // Liked games: those named in the request, the group's most played, then the best rated
+    let mut highly_rated: Vec<(GameId, f64)> = ratings
+        .iter()
+        .filter(|(_, rating)| **rating >= recommender::LIKED_RATING)
+        .map(|(game_id, rating)| (*game_id, *rating))
+        .collect();
+    highly_rated.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut liked_game_ids: Vec<GameId> = Vec::new();
+    for game_id in request
+        .liked_game_ids
+        .iter()
+        .copied()
+        .chain(most_played)
+        .chain(highly_rated.into_iter().map(|(game_id, _)| game_id))
+    {
+        if !liked_game_ids.contains(&game_id) {
+            liked_game_ids.push(game_id);
+        }
+    }
+
+    let mut liked: Vec<Game> = Vec::new();
+    for &game_id in &liked_game_ids {
+        match games::get_game(&db, game_id).await {
+            Ok(Some(game)) => liked.push(game),
+            Ok(None) => {
+                return Err(bad_request_error(format!(
+                    "Game {} does not exist",
+                    game_id
+                )));
+            }
+            Err(e) => {
+                tracing::error!("Failed to get liked game {}: {}", game_id, e);
+                return Err(internal_error("Failed to get liked games".to_string()));
+            }
+        }
+    }
+
+    let similarities = description_similarities(app_state, &candidates, &liked).await;
+    let mut recommendations = recommender::rank(
+        &candidates,
+        &ratings,
+        &similarities,
+        Utc::now(),
+        request.limit,
+    );
+
+    if request.explain && !recommendations.is_empty() {
+        let picks: Vec<&Game> = recommendations
+            .iter()
+            .filter_map(|pick| candidates.iter().find(|game| game.id == pick.game_id))
+            .collect();
+        let liked: Vec<&Game> = liked.iter().collect();
+        match recommender::justify(
+            app_state.llm(),
+            request.players,
+            request.max_play_time,
+            &liked,
+            &picks,
+        )
+        .await
+        {
+            Ok(mut reasons) => {
+                for pick in &mut recommendations {
+                    pick.justification = reasons.remove(&pick.game_id);
+                }
+            }
+            Err(e) => tracing::warn!("Returning recommendations without justifications: {:#}", e),
+        }
+    }
+
+    success_response(RecommendationResponse {
+        recommendations,
+        liked_game_ids,
+    })
+}
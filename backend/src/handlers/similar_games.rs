@@ -0,0 +1,157 @@
+use dropshot::{Path, Query, RequestContext, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::{game_descriptions, games},
+    discovery,
+    handlers::{
+        HttpError, HttpOk, bad_request_error, internal_error, not_found_error, success_response,
+    },
+    models::{GameId, LibrarySearchQuery, SimilarGame, SimilarGamesQuery},
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SimilarGamePathParam {
+    pub id: GameId,
+}
+
+fn validate_limit(limit: u32) -> Result<(), HttpError> {
+    if limit == 0 || limit > discovery::MAX_SIMILAR_GAMES {
+        return Err(bad_request_error(format!(
+            "limit must be between 1 and {}",
+            discovery::MAX_SIMILAR_GAMES
+        )));
+    }
+    Ok(())
+}
+
+/// Look up the matched games, skipping `exclude` and any deleted since they were found
+async fn similar_games(
+    app_state: &AppState,
+    neighbors: Vec<(GameId, f64)>,
+    exclude: Option<GameId>,
+    limit: u32,
+) -> Result<Vec<SimilarGame>, HttpError> {
+    let db = app_state.db();
+    let mut results = Vec::new();
+    for (game_id, similarity) in neighbors {
+        if Some(game_id) == exclude {
+            continue;
+        }
+        let game = games::get_game(&db, game_id).await.map_err(|e| {
+            tracing::error!("Failed to get similar game {}: {}", game_id, e);
+            internal_error("Failed to get similar games".to_string())
+        })?;
+        if let Some(game) = game {
+            results.push(SimilarGame {
+                game_id: game.id,
+                game_name: game.name,
+                description: game.description,
+                min_players: game.min_players,
+                max_players: game.max_players,
+                play_time_minutes: game.play_time_minutes,
+                complexity_rating: game.complexity_rating,
+                similarity,
+            });
+        }
+    }
+    results.truncate(limit as usize);
+    Ok(results)
+}
+
+/// Games whose description and tags are most like this game's
+#[endpoint {
+    method = GET,
+    path = "/api/games/{id}/similar"
+}]
+pub async fn list_similar_games(
+    rqctx: RequestContext<AppState>,
+    path: Path<SimilarGamePathParam>,
+    query: Query<SimilarGamesQuery>,
+) -> Result<HttpOk<Vec<SimilarGame>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let limit = query.into_inner().limit;
+    let db = app_state.db();
+
+    validate_limit(limit)?;
+    match games::get_game(&db, game_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(not_found_error(format!(
+                "Game with id {} not found",
+                game_id
+            )));
+        }
+        Err(e) => {
+            tracing::error!("Failed to get game {}: {}", game_id, e);
+            return Err(internal_error("Failed to get game".to_string()));
+        }
+    }
+
+    let mut embeddings = game_descriptions::get_description_embeddings(&db, &[game_id])
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to get the description vector of game {}: {}",
+                game_id,
+                e
+            );
+            internal_error("Failed to get similar games".to_string())
+        })?;
+    let Some(embedding) = embeddings.remove(&game_id) else {
+        return Err(bad_request_error(format!(
+            "Game {} has no description or tags to compare",
+            game_id
+        )));
+    };
+
+    // One extra neighbor, since the game itself is the nearest
+    let neighbors = game_descriptions::find_similar_games(&db, &embedding, limit + 1)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to find games similar to {}: {}", game_id, e);
+            internal_error("Failed to get similar games".to_string())
+        })?;
+    success_response(similar_games(app_state, neighbors, Some(game_id), limit).await?)
+}
+
+/// Search the library by what a game is like, e.g. "cooperative dungeon crawler with campaign"
+#[endpoint {
+    method = GET,
+    path = "/api/library/search"
+}]
+pub async fn search_library(
+    rqctx: RequestContext<AppState>,
+    query: Query<LibrarySearchQuery>,
+) -> Result<HttpOk<Vec<SimilarGame>>, HttpError> {
+    let app_state = rqctx.context();
+    let search = query.into_inner();
+    let db = app_state.db();
+
+    // Validate the query
+    let text = search.q.trim();
+    if text.is_empty() {
+        return Err(bad_request_error("q cannot be empty".to_string()));
+    }
+    validate_limit(search.limit)?;
+
+    let embedding = app_state
+        .embedder()
+        .generate_embedding(text)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to embed library search: {}", e);
+            internal_error("Failed to process search".to_string())
+        })?;
+
+    let neighbors = game_descriptions::find_similar_games(&db, &embedding, search.limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to search the library: {}", e);
+            internal_error("Failed to search the library".to_string())
+        })?;
+    success_response(similar_games(app_state, neighbors, None, search.limit).await?)
+}
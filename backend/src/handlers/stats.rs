@@ -0,0 +1,133 @@
+use dropshot::{Path, Query, RequestContext, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::stats,
+    handlers::{
+        HttpError, HttpOk, bad_request_error, internal_error, not_found_error, success_response,
+    },
+    models::{
+        GameId, GamePlayCount, GameStats, MostPlayedQuery, PlayStatsSummary, StatsPeriodQuery,
+        UnplayedGame,
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GameStatsPathParam {
+    pub id: GameId,
+}
+
+fn validate_period(
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(), HttpError> {
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        return Err(bad_request_error(
+            "from cannot be later than to".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Totals over the play log: plays, games, players, minutes played and h-index
+#[endpoint {
+    method = GET,
+    path = "/api/stats"
+}]
+pub async fn get_play_summary(
+    rqctx: RequestContext<AppState>,
+    query: Query<StatsPeriodQuery>,
+) -> Result<HttpOk<PlayStatsSummary>, HttpError> {
+    let app_state = rqctx.context();
+    let period = query.into_inner();
+    let db = app_state.db();
+
+    validate_period(period.from, period.to)?;
+
+    match stats::play_summary(&db, &period).await {
+        Ok(summary) => success_response(summary),
+        Err(e) => {
+            tracing::error!("Failed to compute play summary: {}", e);
+            Err(internal_error("Failed to compute play summary".to_string()))
+        }
+    }
+}
+
+/// The games played most often over a period
+#[endpoint {
+    method = GET,
+    path = "/api/stats/most-played"
+}]
+pub async fn get_most_played_games(
+    rqctx: RequestContext<AppState>,
+    query: Query<MostPlayedQuery>,
+) -> Result<HttpOk<Vec<GamePlayCount>>, HttpError> {
+    let app_state = rqctx.context();
+    let most_played_query = query.into_inner();
+    let db = app_state.db();
+
+    validate_period(most_played_query.from, most_played_query.to)?;
+    if most_played_query.limit == 0 {
+        return Err(bad_request_error("limit must be at least 1".to_string()));
+    }
+
+    match stats::most_played_games(&db, &most_played_query).await {
+        Ok(games) => success_response(games),
+        Err(e) => {
+            tracing::error!("Failed to list most played games: {}", e);
+            Err(internal_error(
+                "Failed to list most played games".to_string(),
+            ))
+        }
+    }
+}
+
+/// Games in the library that have never been played
+#[endpoint {
+    method = GET,
+    path = "/api/stats/shelf-of-shame"
+}]
+pub async fn get_shelf_of_shame(
+    rqctx: RequestContext<AppState>,
+) -> Result<HttpOk<Vec<UnplayedGame>>, HttpError> {
+    let app_state = rqctx.context();
+    let db = app_state.db();
+
+    match stats::shelf_of_shame(&db).await {
+        Ok(games) => success_response(games),
+        Err(e) => {
+            tracing::error!("Failed to list unplayed games: {}", e);
+            Err(internal_error("Failed to list unplayed games".to_string()))
+        }
+    }
+}
+
+/// Plays, scores and per-player win rates for a game
+#[endpoint {
+    method = GET,
+    path = "/api/games/{id}/stats"
+}]
+pub async fn get_game_stats(
+    rqctx: RequestContext<AppState>,
+    path: Path<GameStatsPathParam>,
+) -> Result<HttpOk<GameStats>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match stats::game_stats(&db, game_id).await {
+        Ok(Some(stats)) => success_response(stats),
+        Ok(None) => Err(not_found_error(format!(
+            "Game with id {} not found",
+            game_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to compute stats for game {}: {}", game_id, e);
+            Err(internal_error("Failed to compute game stats".to_string()))
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use dropshot::{Path, Query, RequestContext, TypedBody, endpoint};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    db::tags,
+    discovery,
+    handlers::{
+        HttpCreated, HttpDeleted, HttpError, HttpOk, bad_request_error, created_response,
+        deleted_response, internal_error, not_found_error, success_response,
+    },
+    models::{
+        CreateTagRequest, GameId, SetGameTagsRequest, Tag, TagId, TagListQuery, TagSummary,
+        UpdateTagRequest,
+    },
+};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TagPathParam {
+    pub id: TagId,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GameTagsPathParam {
+    pub id: GameId,
+}
+
+fn is_duplicate_tag(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _) if e.code == rusqlite::ErrorCode::ConstraintViolation
+    )
+}
+
+/// List tags with the number of games carrying each
+#[endpoint {
+    method = GET,
+    path = "/api/tags"
+}]
+pub async fn list_tags(
+    rqctx: RequestContext<AppState>,
+    query: Query<TagListQuery>,
+) -> Result<HttpOk<Vec<Tag>>, HttpError> {
+    let app_state = rqctx.context();
+    let tag_type = query.into_inner().tag_type;
+    let db = app_state.db();
+
+    match tags::list_tags(&db, tag_type).await {
+        Ok(tags) => success_response(tags),
+        Err(e) => {
+            tracing::error!("Failed to list tags: {}", e);
+            Err(internal_error("Failed to list tags".to_string()))
+        }
+    }
+}
+
+/// Get a specific tag by ID
+#[endpoint {
+    method = GET,
+    path = "/api/tags/{id}"
+}]
+pub async fn get_tag(
+    rqctx: RequestContext<AppState>,
+    path: Path<TagPathParam>,
+) -> Result<HttpOk<Tag>, HttpError> {
+    let app_state = rqctx.context();
+    let tag_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match tags::get_tag(&db, tag_id).await {
+        Ok(Some(tag)) => success_response(tag),
+        Ok(None) => Err(not_found_error(format!("Tag with id {} not found", tag_id))),
+        Err(e) => {
+            tracing::error!("Failed to get tag {}: {}", tag_id, e);
+            Err(internal_error("Failed to get tag".to_string()))
+        }
+    }
+}
+
+/// Create a tag; names are unique within a tag type, ignoring case
+#[endpoint {
+    method = POST,
+    path = "/api/tags"
+}]
+pub async fn create_tag(
+    rqctx: RequestContext<AppState>,
+    body: TypedBody<CreateTagRequest>,
+) -> Result<HttpCreated<Tag>, HttpError> {
+    let app_state = rqctx.context();
+    let create_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if create_request.name.trim().is_empty() {
+        return Err(bad_request_error("Tag name cannot be empty".to_string()));
+    }
+
+    let tag_type = create_request.tag_type;
+    match tags::create_tag(&db, create_request).await {
+        Ok(tag) => created_response(tag),
+        Err(e) if is_duplicate_tag(&e) => Err(bad_request_error(format!(
+            "A {} tag with that name already exists",
+            tag_type.as_str()
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to create tag: {}", e);
+            Err(internal_error("Failed to create tag".to_string()))
+        }
+    }
+}
+
+/// Rename a tag or change its type
+#[endpoint {
+    method = PUT,
+    path = "/api/tags/{id}"
+}]
+pub async fn update_tag(
+    rqctx: RequestContext<AppState>,
+    path: Path<TagPathParam>,
+    body: TypedBody<UpdateTagRequest>,
+) -> Result<HttpOk<Tag>, HttpError> {
+    let app_state = rqctx.context();
+    let tag_id = path.into_inner().id;
+    let update_request = body.into_inner();
+    let db = app_state.db();
+
+    // Validate the request
+    if let Some(name) = &update_request.name
+        && name.trim().is_empty()
+    {
+        return Err(bad_request_error("Tag name cannot be empty".to_string()));
+    }
+
+    match tags::update_tag(&db, tag_id, update_request).await {
+        Ok(Some(tag)) => {
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            success_response(tag)
+        }
+        Ok(None) => Err(not_found_error(format!("Tag with id {} not found", tag_id))),
+        Err(e) if is_duplicate_tag(&e) => Err(bad_request_error(
+            "A tag with that name and type already exists".to_string(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to update tag {}: {}", tag_id, e);
+            Err(internal_error("Failed to update tag".to_string()))
+        }
+    }
+}
+
+/// Delete a tag, removing it from every game
+#[endpoint {
+    method = DELETE,
+    path = "/api/tags/{id}"
+}]
+pub async fn delete_tag(
+    rqctx: RequestContext<AppState>,
+    path: Path<TagPathParam>,
+) -> Result<HttpDeleted, HttpError> {
+    let app_state = rqctx.context();
+    let tag_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match tags::delete_tag(&db, tag_id).await {
+        Ok(true) => {
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            deleted_response()
+        }
+        Ok(false) => Err(not_found_error(format!("Tag with id {} not found", tag_id))),
+        Err(e) => {
+            tracing::error!("Failed to delete tag {}: {}", tag_id, e);
+            Err(internal_error("Failed to delete tag".to_string()))
+        }
+    }
+}
+
+/// List a game's tags
+#[endpoint {
+    method = GET,
+    path = "/api/games/{id}/tags"
+}]
+pub async fn list_game_tags(
+    rqctx: RequestContext<AppState>,
+    path: Path<GameTagsPathParam>,
+) -> Result<HttpOk<Vec<TagSummary>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let db = app_state.db();
+
+    match tags::list_game_tags(&db, game_id).await {
+        Ok(Some(tags)) => success_response(tags),
+        Ok(None) => Err(not_found_error(format!(
+            "Game with id {} not found",
+            game_id
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to list tags for game {}: {}", game_id, e);
+            Err(internal_error("Failed to list game tags".to_string()))
+        }
+    }
+}
+
+/// Replace the set of tags a game carries
+#[endpoint {
+    method = PUT,
+    path = "/api/games/{id}/tags"
+}]
+pub async fn set_game_tags(
+    rqctx: RequestContext<AppState>,
+    path: Path<GameTagsPathParam>,
+    body: TypedBody<SetGameTagsRequest>,
+) -> Result<HttpOk<Vec<TagSummary>>, HttpError> {
+    let app_state = rqctx.context();
+    let game_id = path.into_inner().id;
+    let tag_ids = body.into_inner().tag_ids;
+    let db = app_state.db();
+
+    match tags::set_game_tags(&db, game_id, &tag_ids).await {
+        Ok(Some(tags)) => {
+            discovery::index_descriptions(&db, app_state.embedder()).await;
+            success_response(tags)
+        }
+        Ok(None) => Err(not_found_error(format!(
+            "Game with id {} not found",
+            game_id
+        ))),
+        Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+            if message.ends_with("does not exist") =>
+        {
+            Err(bad_request_error(message))
+        }
+        Err(e) => {
+            tracing::error!("Failed to set tags for game {}: {}", game_id, e);
+            Err(internal_error("Failed to set game tags".to_string()))
+        }
+    }
+}
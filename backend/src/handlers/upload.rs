@@ -7,10 +7,11 @@ use serde::{Deserialize, Serialize};
 
 use super::{bad_request_error, internal_error, not_found_error, success_response};
 use crate::{
-    AppState, db,
+    AppState, db, faq_generation,
     handlers::{HttpError, HttpOk},
     models::{CreateEmbeddingRequest, EmbeddingSourceType, GameId, RulesInfoResponse},
     pdf::{Processor, generate_pdf_filename, validate_pdf_file},
+    semantic_cache,
 };
 
 #[derive(Deserialize, JsonSchema)]
@@ -61,9 +62,9 @@ pub async fn upload_rules_pdf(
         )))?;
 
     // Create uploads directory if it doesn't exist
-    let uploads_dir = PathBuf::from("uploads");
+    let uploads_dir = app_state.uploads_dir();
     if !uploads_dir.exists() {
-        fs::create_dir_all(&uploads_dir)
+        fs::create_dir_all(uploads_dir)
             .map_err(|e| internal_error(format!("Failed to create uploads directory: {}", e)))?;
     }
 
@@ -103,6 +104,7 @@ pub async fn upload_rules_pdf(
                 "file_name": &filename,
                 "chunk_size": chunk.len(),
                 "total_chunks": processed_pdf.chunks.len(),
+                "page": processed_pdf.chunk_pages.get(chunk_index),
                 "processing_timestamp": chrono::Utc::now().to_rfc3339(),
                 "embedding_model": app_state.embedder().get_model()
             });
@@ -140,6 +142,10 @@ pub async fn upload_rules_pdf(
             internal_error(format!("Failed to store embeddings: {}", e))
         })?;
 
+    // Answers built from the previous rulebook may no longer be right
+    semantic_cache::invalidate(&db, Some(game.id)).await;
+    faq_generation::invalidate(&db, game.id).await;
+
     let response = UploadResponse {
         message: format!(
             "Successfully uploaded and processed PDF for game {}. Extracted {} characters and created {} text chunks.",
@@ -218,6 +224,8 @@ pub async fn delete_rules(
     )
     .await
     .map_err(|e| internal_error(format!("Failed to delete embeddings: {}", e)))?;
+    semantic_cache::invalidate(&db, Some(game_id)).await;
+    faq_generation::invalidate(&db, game_id).await;
 
     // Clear the PDF path and rules text from the game record
     db.with_connection(|conn| {
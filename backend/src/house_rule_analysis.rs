@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::{
+    answers,
+    llm::{ChatMessage, LLMClient},
+    models::{
+        CitedPassage, EmbeddingSearchResult, EmbeddingSourceType, HouseRule, HouseRuleAnalysis,
+        HouseRuleRelation,
+    },
+};
+
+const ANALYSIS_MAX_TOKENS: u16 = 600;
+
+/// Text searched to find the rulebook passages a house rule touches
+pub fn analysis_query(house_rule: &HouseRule) -> String {
+    format!("{}: {}", house_rule.title, house_rule.description)
+}
+
+/// Classify a house rule against the rulebook passages related to it
+pub async fn analyze_house_rule(
+    llm: &LLMClient,
+    game_name: &str,
+    house_rule: &HouseRule,
+    passages: &[EmbeddingSearchResult],
+) -> Result<HouseRuleAnalysis> {
+    // Other house rules are not the rulebook
+    let passages: Vec<&EmbeddingSearchResult> = passages
+        .iter()
+        .filter(|passage| passage.source_type == EmbeddingSourceType::RulesPdf)
+        .collect();
+
+    if passages.is_empty() {
+        return Ok(HouseRuleAnalysis {
+            relation: HouseRuleRelation::Addition,
+            explanation: "No rulebook passage relates to this house rule.".to_string(),
+            cited_passages: Vec::new(),
+            confidence: None,
+            model: llm.get_model().to_string(),
+            analyzed_at: Utc::now(),
+        });
+    }
+
+    let raw = llm
+        .chat_completion_json(
+            vec![ChatMessage::user(format!(
+                "House rule \"{}\": {}",
+                house_rule.title, house_rule.description
+            ))],
+            Some(analysis_prompt(game_name, &passages)),
+            Some(ANALYSIS_MAX_TOKENS),
+            Some(0.0),
+        )
+        .await
+        .context("Failed to get house rule analysis from the LLM")?;
+
+    parse_analysis(&raw, &passages, llm.get_model())
+}
+
+fn analysis_prompt(game_name: &str, passages: &[&EmbeddingSearchResult]) -> String {
+    let passages = passages
+        .iter()
+        .map(|passage| format!("[chunk {}] {}", passage.id, passage.chunk_text))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!(
+        "You compare a house rule for the board game {} with the official rulebook passages below.
+
+Rulebook passages:
+{}
+
+Classify the house rule as exactly one of:
+- \"override\": it replaces an official rule with a different one
+- \"addition\": it covers something the rulebook does not address
+- \"duplicate\": it restates what the rulebook already says
+- \"contradiction\": it conflicts with the rulebook without clearly replacing a rule
+
+Respond with a single JSON object and nothing else, in the form
+{{\"relation\": \"<classification>\", \"explanation\": \"<one or two sentences>\", \"cited_passage_ids\": [<ids of the [chunk N] passages you relied on>], \"confidence\": <0 to 1>}}",
+        game_name, passages
+    )
+}
+
+#[derive(Deserialize)]
+struct RawAnalysis {
+    relation: String,
+    #[serde(default)]
+    explanation: String,
+    #[serde(default, alias = "cited_chunk_ids")]
+    cited_passage_ids: Vec<serde_json::Value>,
+    #[serde(default)]
+    confidence: Option<serde_json::Value>,
+}
+
+/// Parse the model's classification, keeping only citations of passages it was shown
+fn parse_analysis(
+    raw: &str,
+    passages: &[&EmbeddingSearchResult],
+    model: &str,
+) -> Result<HouseRuleAnalysis> {
+    let json = answers::extract_json_object(raw).context("Analysis is not a JSON object")?;
+    let parsed: RawAnalysis =
+        serde_json::from_str(json).context("Analysis does not match the requested format")?;
+
+    let relation = HouseRuleRelation::from_str(&parsed.relation.trim().to_lowercase())
+        .with_context(|| format!("Unknown house rule relation '{}'", parsed.relation))?;
+
+    let mut cited_passages: Vec<CitedPassage> = Vec::new();
+    for value in &parsed.cited_passage_ids {
+        let id = value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(answers::parse_chunk_reference));
+        let Some(passage) = id.and_then(|id| passages.iter().find(|p| p.id == id)) else {
+            tracing::debug!("Dropping citation of unknown passage {}", value);
+            continue;
+        };
+        if !cited_passages.iter().any(|c| c.embedding_id == passage.id) {
+            cited_passages.push(CitedPassage {
+                embedding_id: passage.id,
+                chunk_text: passage.chunk_text.clone(),
+            });
+        }
+    }
+
+    Ok(HouseRuleAnalysis {
+        relation,
+        explanation: parsed.explanation.trim().to_string(),
+        cited_passages,
+        confidence: parsed
+            .confidence
+            .as_ref()
+            .and_then(answers::parse_confidence),
+        model: model.to_string(),
+        analyzed_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockReply;
+
+    fn house_rule() -> HouseRule {
+        HouseRule {
+            id: 1,
+            game_id: 1,
+            title: "Free parking".to_string(),
+            description: "Rolling a seven does not move the robber.".to_string(),
+            category: None,
+            is_active: true,
+            analysis: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn passage(id: i64, source_type: EmbeddingSourceType, text: &str) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id: 1,
+            chunk_text: text.to_string(),
+            similarity_score: 0.8,
+            source_type,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_house_rule_keeps_known_citations() {
+        let llm = LLMClient::scripted(vec![MockReply::Text(
+            r#"{"relation": "Override", "explanation": "Replaces the robber rule.", "cited_passage_ids": [7, "chunk 9", 42], "confidence": "high"}"#
+                .to_string(),
+        )]);
+        let passages = vec![
+            passage(
+                7,
+                EmbeddingSourceType::RulesPdf,
+                "On a seven, move the robber.",
+            ),
+            passage(
+                9,
+                EmbeddingSourceType::HouseRule,
+                "Robber only moves on doubles.",
+            ),
+        ];
+
+        let analysis = analyze_house_rule(&llm, "Harbor Traders", &house_rule(), &passages)
+            .await
+            .unwrap();
+
+        assert_eq!(analysis.relation, HouseRuleRelation::Override);
+        assert_eq!(analysis.explanation, "Replaces the robber rule.");
+        assert_eq!(analysis.cited_passages.len(), 1);
+        assert_eq!(analysis.cited_passages[0].embedding_id, 7);
+        assert_eq!(analysis.confidence, Some(0.9));
+        assert_eq!(analysis.model, crate::llm::MOCK_MODEL);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_house_rule_without_related_passages() {
+        let llm = LLMClient::scripted(Vec::new());
+        let passages = vec![passage(
+            9,
+            EmbeddingSourceType::HouseRule,
+            "Something else.",
+        )];
+
+        let analysis = analyze_house_rule(&llm, "Harbor Traders", &house_rule(), &passages)
+            .await
+            .unwrap();
+
+        assert_eq!(analysis.relation, HouseRuleRelation::Addition);
+        assert!(analysis.cited_passages.is_empty());
+        // Nothing to compare against, so the model is not asked
+        assert_eq!(llm.mock_calls(), 0);
+    }
+
+    #[test]
+    fn test_parse_analysis_rejects_unknown_relation() {
+        let raw = r#"{"relation": "tweak", "explanation": "?"}"#;
+        assert!(parse_analysis(raw, &[], "model").is_err());
+        assert!(parse_analysis("not json", &[], "model").is_err());
+    }
+}
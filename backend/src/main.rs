@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::{Arg, Command};
@@ -9,27 +9,59 @@ use rusqlite::{Connection, ffi::sqlite3_auto_extension};
 use rusqlite_migration::{M, Migrations};
 use sqlite_vec::sqlite3_vec_init;
 
+mod answers;
+#[cfg(test)]
+mod api_tests;
+mod backup;
+mod bgg;
+mod comparison;
 mod db;
+mod discovery;
 mod embeddings;
+mod evaluation;
+mod expansions;
+mod faq_generation;
+mod grounding;
 mod handlers;
+mod house_rule_analysis;
 mod llm;
 mod models;
 mod pdf;
-
+mod prompt;
+mod query_transform;
+mod quick_reference;
+mod ratings;
+mod recommender;
+mod semantic_cache;
+mod templates;
+mod tools;
+
+use bgg::BggClient;
 use db::Database;
-use embeddings::Embedder;
+use embeddings::{DEFAULT_CACHE_MAX_ENTRIES, Embedder, EmbeddingCache};
 use handlers::static_files;
 use handlers::*;
 use llm::LLMClient;
 
+/// Which backends serve embeddings and completions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provider {
+    /// Ollama's OpenAI-compatible API
+    Ollama,
+    /// Deterministic offline backends for development and tests
+    Mock,
+}
+
 pub struct AppState {
     db: Database,
     embeddings: Embedder,
     llm: LLMClient,
+    uploads_dir: PathBuf,
+    bgg: BggClient,
 }
 
 impl AppState {
-    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+    pub fn new(path: impl AsRef<Path>, provider: Provider) -> Result<Self> {
         // Initialize sqlite-vec extension
         unsafe {
             sqlite3_auto_extension(Some(std::mem::transmute(sqlite3_vec_init as *const ())));
@@ -49,17 +81,100 @@ impl AppState {
                 "../../migrations/V003__create_embeddings_table.sql"
             )),
             M::up(include_str!("../../migrations/V004__seed_games_data.sql")),
+            M::up(include_str!(
+                "../../migrations/V005__create_embedding_cache_table.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V006__add_chat_message_tool_trace.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V007__add_chat_message_citations.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V008__create_prompt_templates_table.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V009__create_answer_cache_tables.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V010__create_chat_session_games_table.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V011__add_house_rule_analysis.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V012__create_faq_entries_table.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V013__create_quick_references_table.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V014__create_evaluation_tables.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V015__add_game_library_search.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V016__create_tags_tables.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V017__add_game_expansions.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V018__create_plays_tables.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V019__create_leagues_tables.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V020__create_collection_tables.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V021__add_collection_rating.sql"
+            )),
+            M::up(include_str!(
+                "../../migrations/V022__create_game_description_index.sql"
+            )),
         ]);
 
         migrations.to_latest(&mut db)?;
 
+        let db = Database::new(db);
+        let (embedder, llm) = match provider {
+            Provider::Ollama => (Embedder::new(), LLMClient::new()),
+            Provider::Mock => (Embedder::mock(), LLMClient::mock()),
+        };
+        let embeddings =
+            embedder.with_cache(EmbeddingCache::new(db.clone(), DEFAULT_CACHE_MAX_ENTRIES));
+
         Ok(Self {
-            db: Database::new(db),
-            embeddings: Embedder::new(),
-            llm: LLMClient::new(),
+            db,
+            embeddings,
+            llm,
+            uploads_dir: PathBuf::from("uploads"),
+            bgg: BggClient::default(),
         })
     }
 
+    /// Store uploaded files somewhere other than `./uploads`
+    pub fn with_uploads_dir(mut self, uploads_dir: impl Into<PathBuf>) -> Self {
+        self.uploads_dir = uploads_dir.into();
+        self
+    }
+
+    /// Fetch BGG data from another XML API2 server, such as a local stand-in
+    pub fn with_bgg_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.bgg = BggClient::new(base_url);
+        self
+    }
+
+    /// Answer with another model client, such as a scripted mock
+    #[cfg(test)]
+    pub fn with_llm(mut self, llm: LLMClient) -> Self {
+        self.llm = llm;
+        self
+    }
+
     pub fn db(&self) -> Database {
         self.db.clone()
     }
@@ -71,6 +186,14 @@ impl AppState {
     pub fn llm(&self) -> &LLMClient {
         &self.llm
     }
+
+    pub fn uploads_dir(&self) -> &Path {
+        &self.uploads_dir
+    }
+
+    pub fn bgg(&self) -> &BggClient {
+        &self.bgg
+    }
 }
 
 #[tokio::main]
@@ -93,6 +216,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_name("ADDRESS")
                 .default_value("127.0.0.1:8080"),
         )
+        .arg(
+            Arg::new("provider")
+                .short('p')
+                .long("provider")
+                .help("Embedding and completion backend")
+                .value_name("PROVIDER")
+                .value_parser(["ollama", "mock"])
+                .default_value("ollama"),
+        )
+        .arg(
+            Arg::new("bgg-base-url")
+                .long("bgg-base-url")
+                .help("BoardGameGeek XML API2 base URL for game imports")
+                .value_name("URL")
+                .default_value(bgg::DEFAULT_BGG_BASE_URL),
+        )
         .get_matches();
 
     // Check if --openapi flag is provided
@@ -102,6 +241,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let bind_address = matches.get_one::<String>("bind-address").unwrap();
+    let provider = match matches.get_one::<String>("provider").map(String::as_str) {
+        Some("mock") => Provider::Mock,
+        _ => Provider::Ollama,
+    };
 
     // Set up logging
     let config_logging = ConfigLogging::StderrTerminal {
@@ -122,7 +265,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create API description
     let api = create_api_description()?;
 
-    let app_state = AppState::new("atlas.db")?;
+    let bgg_base_url = matches.get_one::<String>("bgg-base-url").unwrap();
+    let app_state = AppState::new("atlas.db", provider)?.with_bgg_base_url(bgg_base_url);
+    // Index games added by migrations or indexed with another embedding model
+    discovery::index_descriptions(&app_state.db(), app_state.embedder()).await;
     let server = HttpServerStarter::new(&config_dropshot, api, app_state, &log)
         .map_err(|error| format!("failed to create server: {}", error))?
         .start();
@@ -138,15 +284,63 @@ fn create_api_description() -> Result<ApiDescription<AppState>, Box<dyn std::err
     // Register API endpoints first (these have higher priority)
     api.register(games::list_games)?;
     api.register(games::get_game)?;
+    api.register(games::list_game_expansions)?;
     api.register(games::create_game)?;
     api.register(games::update_game)?;
     api.register(games::delete_game)?;
 
+    api.register(tags::list_tags)?;
+    api.register(tags::get_tag)?;
+    api.register(tags::create_tag)?;
+    api.register(tags::update_tag)?;
+    api.register(tags::delete_tag)?;
+    api.register(tags::list_game_tags)?;
+    api.register(tags::set_game_tags)?;
+
+    api.register(plays::list_plays)?;
+    api.register(plays::get_play)?;
+    api.register(plays::create_play)?;
+    api.register(plays::update_play)?;
+    api.register(plays::delete_play)?;
+
+    api.register(players::list_players)?;
+    api.register(players::get_player)?;
+    api.register(players::create_player)?;
+    api.register(players::update_player)?;
+    api.register(players::get_player_stats)?;
+
+    api.register(stats::get_play_summary)?;
+    api.register(stats::get_most_played_games)?;
+    api.register(stats::get_shelf_of_shame)?;
+    api.register(stats::get_game_stats)?;
+
+    api.register(collection::get_game_collection)?;
+    api.register(collection::update_game_collection)?;
+    api.register(collection::list_loans)?;
+    api.register(collection::get_loan)?;
+    api.register(collection::create_loan)?;
+    api.register(collection::return_loan)?;
+    api.register(collection::delete_loan)?;
+
+    api.register(recommendations::recommend_games)?;
+
+    api.register(similar_games::list_similar_games)?;
+    api.register(similar_games::search_library)?;
+
+    api.register(leagues::list_leagues)?;
+    api.register(leagues::get_league)?;
+    api.register(leagues::create_league)?;
+    api.register(leagues::update_league)?;
+    api.register(leagues::delete_league)?;
+    api.register(leagues::get_league_standings)?;
+    api.register(leagues::get_league_rating_history)?;
+
     api.register(house_rules::list_house_rules)?;
     api.register(house_rules::get_house_rule)?;
     api.register(house_rules::create_house_rule)?;
     api.register(house_rules::update_house_rule)?;
     api.register(house_rules::delete_house_rule)?;
+    api.register(house_rules::analyze_house_rule)?;
 
     api.register(upload::upload_rules_pdf)?;
     api.register(upload::get_rules_info)?;
@@ -156,6 +350,42 @@ fn create_api_description() -> Result<ApiDescription<AppState>, Box<dyn std::err
     api.register(chat::get_chat_session)?;
     api.register(chat::create_chat_session)?;
     api.register(chat::search_rules)?;
+    api.register(chat::compare_query_strategies)?;
+
+    api.register(faq::list_faq_entries)?;
+    api.register(faq::get_faq_entry)?;
+    api.register(faq::create_faq_entry)?;
+    api.register(faq::update_faq_entry)?;
+    api.register(faq::delete_faq_entry)?;
+    api.register(faq::generate_faq)?;
+
+    api.register(quick_references::get_quick_reference)?;
+
+    api.register(imports::import_bgg_ranks)?;
+    api.register(imports::import_bgg_things)?;
+
+    api.register(backups::export_library)?;
+    api.register(backups::restore_library)?;
+
+    api.register(evaluations::list_eval_questions)?;
+    api.register(evaluations::import_eval_questions)?;
+    api.register(evaluations::delete_eval_question)?;
+    api.register(evaluations::run_evaluation)?;
+    api.register(evaluations::list_eval_runs)?;
+    api.register(evaluations::get_eval_run)?;
+
+    api.register(prompt_templates::list_prompt_templates)?;
+    api.register(prompt_templates::get_prompt_template)?;
+    api.register(prompt_templates::create_prompt_template)?;
+    api.register(prompt_templates::update_prompt_template)?;
+    api.register(prompt_templates::delete_prompt_template)?;
+    api.register(prompt_templates::list_prompt_template_variables)?;
+    api.register(prompt_templates::preview_prompt)?;
+
+    api.register(embedding_cache::get_embedding_cache_stats)?;
+    api.register(embedding_cache::clear_embedding_cache)?;
+    api.register(answer_cache::get_answer_cache_stats)?;
+    api.register(answer_cache::clear_answer_cache)?;
 
     // Register health check
     api.register(static_files::health_check)?;
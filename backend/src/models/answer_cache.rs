@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{CachedAnswerId, ContextSource, EmbeddingId, GameId};
+
+/// A previous answer that can be replayed for semantically identical questions
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CachedAnswer {
+    pub id: CachedAnswerId,
+    pub game_id: GameId,
+    pub question: String,
+    pub answer: String,
+    pub context_sources: Vec<ContextSource>,
+    pub cited_chunks: Vec<EmbeddingId>,
+    pub confidence: Option<f32>,
+    pub not_covered_by_rules: bool,
+    pub structured: bool,
+    pub source_fingerprint: String,
+    pub hit_count: u64,
+    pub created_at: DateTime<Utc>,
+    pub last_hit_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct CreateCachedAnswerRequest {
+    pub game_id: GameId,
+    pub question: String,
+    pub question_embedding: Vec<f32>,
+    pub answer: String,
+    pub context_sources: Vec<ContextSource>,
+    pub cited_chunks: Vec<EmbeddingId>,
+    pub confidence: Option<f32>,
+    pub not_covered_by_rules: bool,
+    pub structured: bool,
+    pub source_fingerprint: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AnswerCacheStats {
+    pub game_id: Option<GameId>,
+    pub entries: u64,
+    pub total_hits: u64,
+    pub similarity_threshold: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClearAnswerCacheResponse {
+    pub entries_removed: u64,
+}
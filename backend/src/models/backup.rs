@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ChatHistory, Embedding, Game, GameCollection, GameId, HouseRule, League, Loan, Play, TagSummary,
+};
+
+/// A portable copy of the whole library. Ids inside it only link its own records
+/// together; they are reassigned on restore
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LibraryArchive {
+    /// Archive layout version; newer versions than this build understands are rejected
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Model that produced the chunk embeddings
+    pub embedding_model: String,
+    pub games: Vec<ArchivedGame>,
+    #[serde(default)]
+    pub leagues: Vec<ArchivedLeague>,
+}
+
+/// A game with everything that belongs to it
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ArchivedGame {
+    pub game: Game,
+    #[serde(default)]
+    pub tags: Vec<TagSummary>,
+    pub house_rules: Vec<HouseRule>,
+    pub rules_document: Option<ArchivedDocument>,
+    /// Rulebook and house rule chunks with their embeddings
+    pub chunks: Vec<Embedding>,
+    pub chat_sessions: Vec<ChatHistory>,
+    /// Logged plays with their players, expansions and consulted chat sessions
+    #[serde(default)]
+    pub plays: Vec<Play>,
+    #[serde(default)]
+    pub collection: Option<GameCollection>,
+    #[serde(default)]
+    pub loans: Vec<Loan>,
+}
+
+/// A league with its members named, since players are matched by name on restore
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ArchivedLeague {
+    pub league: League,
+    pub player_names: Vec<String>,
+}
+
+/// An uploaded file, stored inline
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArchivedDocument {
+    pub file_name: String,
+    /// Base64-encoded file contents
+    pub content: String,
+}
+
+/// What to do with an archived game or league that is already in the library.
+/// Games are matched by BGG id, or by name for games without one; leagues by name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Keep the library's game untouched
+    #[default]
+    Skip,
+    /// Replace the library's game and everything attached to it
+    Overwrite,
+    /// Fill in missing details, add new house rules, chat sessions and plays,
+    /// and take the archived rulebook only if the game has none
+    Merge,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RestoreLibraryQuery {
+    #[serde(default)]
+    pub conflict: ConflictStrategy,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RestoreSummary {
+    pub created: usize,
+    pub overwritten: usize,
+    pub merged: usize,
+    pub skipped: usize,
+    /// Chunks embedded again because the archive came from another embedding model
+    pub reembedded_chunks: usize,
+    /// Library ids of the archived games, in archive order
+    pub game_ids: Vec<GameId>,
+}
@@ -0,0 +1,63 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::GameId;
+
+/// One row of the BoardGameGeek ranks CSV
+#[derive(Debug, Clone, PartialEq)]
+pub struct BggRank {
+    pub bgg_id: i32,
+    pub name: String,
+    pub year_published: Option<i32>,
+    pub rank: Option<i32>,
+    pub is_expansion: bool,
+}
+
+/// A game as described by a BoardGameGeek XML API2 `thing` item
+#[derive(Debug, Clone, PartialEq)]
+pub struct BggThing {
+    pub bgg_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub publisher: Option<String>,
+    pub year_published: Option<i32>,
+    pub min_players: Option<i32>,
+    pub max_players: Option<i32>,
+    pub play_time_minutes: Option<i32>,
+    /// Average community weight, 1 to 5
+    pub weight: Option<f64>,
+    pub mechanics: Vec<String>,
+    pub categories: Vec<String>,
+    pub is_expansion: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportBggRanksQuery {
+    /// Import expansions too; they are skipped by default
+    #[serde(default)]
+    pub include_expansions: bool,
+    /// Import only the best-ranked games, up to this many
+    pub limit: Option<usize>,
+}
+
+/// Import games from BGG `thing` documents, fetched by id or provided directly
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportBggThingsRequest {
+    /// BGG ids to fetch from the configured XML API2 base URL
+    #[serde(default)]
+    pub bgg_ids: Vec<i32>,
+    /// A `thing` document to import as is, e.g. read from a file
+    pub xml: Option<String>,
+    #[serde(default)]
+    pub include_expansions: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BggImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    /// Entries left out, such as expansions when they are not included
+    pub skipped: usize,
+    /// Games created or updated, in import order
+    pub game_ids: Vec<GameId>,
+}
@@ -1,4 +1,7 @@
-use super::{ChatMessageId, ChatSessionId, EmbeddingId, GameId};
+use super::{
+    ChatMessageId, ChatSessionId, EmbeddingId, EmbeddingSearchResult, FaqMatch, GameId,
+    QueryStrategy,
+};
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -7,6 +10,11 @@ use serde::{Deserialize, Serialize};
 pub struct ChatSession {
     pub id: ChatSessionId,
     pub game_id: GameId,
+    /// Other games this session compares against; empty for single-game sessions
+    pub compare_game_ids: Vec<GameId>,
+    /// Expansions of the game in play, whose rules amend the base rules
+    #[serde(default)]
+    pub expansion_ids: Vec<GameId>,
     pub title: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -19,9 +27,50 @@ pub struct ChatMessage {
     pub role: MessageRole,
     pub content: String,
     pub context_chunks: Option<Vec<EmbeddingId>>, // IDs of embeddings used for context
+    pub tool_trace: Option<Vec<ToolInvocation>>,  // Tools called while generating this message
+    pub cited_chunks: Option<Vec<EmbeddingId>>,   // IDs of embeddings the answer actually cited
+    pub confidence: Option<f32>,
+    pub not_covered_by_rules: Option<bool>,
     pub created_at: DateTime<Utc>,
 }
 
+/// Fields for a new chat message; everything beyond role and content is optional
+#[derive(Debug)]
+pub struct CreateChatMessageRequest {
+    pub session_id: ChatSessionId,
+    pub role: MessageRole,
+    pub content: String,
+    pub context_chunks: Option<Vec<EmbeddingId>>,
+    pub tool_trace: Option<Vec<ToolInvocation>>,
+    pub cited_chunks: Option<Vec<EmbeddingId>>,
+    pub confidence: Option<f32>,
+    pub not_covered_by_rules: Option<bool>,
+}
+
+impl CreateChatMessageRequest {
+    pub fn new(session_id: ChatSessionId, role: MessageRole, content: String) -> Self {
+        Self {
+            session_id,
+            role,
+            content,
+            context_chunks: None,
+            tool_trace: None,
+            cited_chunks: None,
+            confidence: None,
+            not_covered_by_rules: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ToolInvocation {
+    pub iteration: u32,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub output: String,
+    pub is_error: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum MessageRole {
     #[serde(rename = "user")]
@@ -55,33 +104,117 @@ impl MessageRole {
 pub struct CreateChatSessionRequest {
     pub game_id: GameId,
     pub title: Option<String>,
+    /// Additional games to compare against in the same session
+    #[serde(default)]
+    pub compare_game_ids: Vec<GameId>,
+    /// Expansions of the game that are in play
+    #[serde(default)]
+    pub expansion_ids: Vec<GameId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ChatRequest {
     pub session_id: ChatSessionId,
     pub message: String,
+    /// Let the assistant call lookup tools (defaults to true)
+    pub use_tools: Option<bool>,
+    /// Reuse a cached answer to a near-identical earlier question (defaults to true)
+    pub use_cache: Option<bool>,
+    /// Answer from the game's FAQ when a FAQ question matches (defaults to true)
+    pub use_faq: Option<bool>,
+    /// How to turn the question into search text (defaults to direct)
+    pub query_strategy: Option<QueryStrategy>,
+    /// Check the answer's sentences against the retrieved rules (defaults to true)
+    pub verify_grounding: Option<bool>,
+    /// Regenerate with a stricter prompt when sentences are unsupported (defaults to false)
+    pub retry_ungrounded: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ChatResponse {
     pub message: ChatMessage,
     pub context_sources: Vec<ContextSource>,
+    /// Model-reported confidence in the answer, from 0 to 1
+    pub confidence: Option<f32>,
+    /// The model found nothing in the rules that answers the question
+    pub not_covered_by_rules: bool,
+    /// False when the model ignored the structured answer format
+    pub structured: bool,
+    /// The answer was replayed from the semantic answer cache
+    pub cached: bool,
+    /// Similarity between this question and the cached one
+    pub cache_similarity: Option<f32>,
+    /// How well the answer is backed by the rules; absent when verification did not run
+    pub grounding: Option<GroundingReport>,
+    /// The FAQ entry the answer came from
+    pub faq_match: Option<FaqMatch>,
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+/// How an answer's sentences were checked against the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GroundingMethod {
+    /// The model judged each sentence against the passages
+    LlmJudge,
+    /// Word overlap with the passages, used when the judge gives no usable verdict
+    Lexical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UnsupportedSentence {
+    pub sentence: String,
+    pub reason: Option<String>,
+}
+
+/// Result of verifying an answer against the passages it was generated from
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GroundingReport {
+    pub method: GroundingMethod,
+    pub sentences_checked: usize,
+    pub unsupported_sentences: Vec<UnsupportedSentence>,
+    /// The answer was regenerated with a stricter prompt after failing verification
+    pub retried: bool,
+}
+
+impl GroundingReport {
+    pub fn is_grounded(&self) -> bool {
+        self.unsupported_sentences.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContextSource {
     pub embedding_id: EmbeddingId,
+    /// Game whose rules the chunk comes from
+    pub game_id: GameId,
     pub chunk_text: String,
     pub source_type: String,
     pub similarity_score: f32,
     pub metadata: Option<String>,
+    /// Whether the answer cited this chunk
+    pub cited: bool,
+}
+
+impl From<&EmbeddingSearchResult> for ContextSource {
+    fn from(result: &EmbeddingSearchResult) -> Self {
+        ContextSource {
+            embedding_id: result.id,
+            game_id: result.game_id,
+            chunk_text: result.chunk_text.clone(),
+            source_type: result.source_type.as_str().to_string(),
+            similarity_score: result.similarity_score,
+            metadata: result.metadata.clone(),
+            cited: false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ChatSessionSummary {
     pub id: ChatSessionId,
     pub game_id: GameId,
+    pub compare_game_ids: Vec<GameId>,
+    pub expansion_ids: Vec<GameId>,
     pub title: Option<String>,
     pub message_count: i32,
     pub last_message_at: Option<DateTime<Utc>>,
@@ -95,6 +228,17 @@ pub struct ChatHistory {
 }
 
 impl ChatSession {
+    /// Every game the session covers, the primary game first
+    pub fn game_ids(&self) -> Vec<GameId> {
+        std::iter::once(self.game_id)
+            .chain(self.compare_game_ids.iter().copied())
+            .collect()
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        !self.compare_game_ids.is_empty()
+    }
+
     pub fn to_summary(
         &self,
         message_count: i32,
@@ -103,6 +247,8 @@ impl ChatSession {
         ChatSessionSummary {
             id: self.id,
             game_id: self.game_id,
+            compare_game_ids: self.compare_game_ids.clone(),
+            expansion_ids: self.expansion_ids.clone(),
             title: self.title.clone(),
             message_count,
             last_message_at,
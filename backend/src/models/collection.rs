@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{GameId, LoanId};
+
+/// Where a game stands in the collection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionStatus {
+    #[default]
+    Owned,
+    PreviouslyOwned,
+    Wishlist,
+    WantToPlay,
+    /// Owned, but up for trade
+    ForTrade,
+}
+
+impl CollectionStatus {
+    pub const ALL: [CollectionStatus; 5] = [
+        CollectionStatus::Owned,
+        CollectionStatus::PreviouslyOwned,
+        CollectionStatus::Wishlist,
+        CollectionStatus::WantToPlay,
+        CollectionStatus::ForTrade,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CollectionStatus::Owned => "owned",
+            CollectionStatus::PreviouslyOwned => "previously_owned",
+            CollectionStatus::Wishlist => "wishlist",
+            CollectionStatus::WantToPlay => "want_to_play",
+            CollectionStatus::ForTrade => "for_trade",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == s)
+    }
+
+    /// Whether the game is on the shelf and can be played or lent out
+    pub fn is_owned(&self) -> bool {
+        matches!(self, CollectionStatus::Owned | CollectionStatus::ForTrade)
+    }
+}
+
+/// A game's collection details; games never given any are owned
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GameCollection {
+    pub game_id: GameId,
+    pub status: CollectionStatus,
+    /// 1 (most wanted) to 5, for wishlisted games
+    pub wishlist_priority: Option<i32>,
+    pub acquired_at: Option<DateTime<Utc>>,
+    pub acquisition_price: Option<f64>,
+    pub shelf_location: Option<String>,
+    /// Personal rating, 1 to 10
+    pub rating: Option<f64>,
+    pub notes: Option<String>,
+    /// The loan the game is out on, if any
+    pub current_loan: Option<Loan>,
+    /// `None` until the details are first set
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Fields left out are kept; moving off the wishlist clears the priority
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateGameCollectionRequest {
+    pub status: Option<CollectionStatus>,
+    pub wishlist_priority: Option<i32>,
+    pub acquired_at: Option<DateTime<Utc>>,
+    pub acquisition_price: Option<f64>,
+    pub shelf_location: Option<String>,
+    pub rating: Option<f64>,
+    pub notes: Option<String>,
+}
+
+/// A game lent to someone
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Loan {
+    pub id: LoanId,
+    pub game_id: GameId,
+    pub game_name: String,
+    pub borrower: String,
+    pub loaned_at: DateTime<Utc>,
+    pub due_at: Option<DateTime<Utc>>,
+    /// `None` while the game is still out
+    pub returned_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    /// Still out past its due date
+    pub overdue: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateLoanRequest {
+    pub game_id: GameId,
+    pub borrower: String,
+    /// Defaults to now
+    pub loaned_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReturnLoanRequest {
+    /// Defaults to now
+    pub returned_at: Option<DateTime<Utc>>,
+}
+
+/// Filters for the loan list, most recent loan first
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LoanListQuery {
+    pub game_id: Option<GameId>,
+    /// Only loans still out, or only returned ones
+    pub active: Option<bool>,
+    /// Only loans still out past their due date, longest overdue first
+    #[serde(default)]
+    pub overdue: bool,
+    /// Exact borrower, ignoring case
+    pub borrower: Option<String>,
+}
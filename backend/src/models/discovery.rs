@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::GameId;
+
+/// What a game's description vector is embedded from
+#[derive(Debug)]
+pub struct GameDescriptionSource {
+    pub game_id: GameId,
+    pub name: String,
+    pub description: Option<String>,
+    /// Tag names, sorted
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SimilarGamesQuery {
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+/// Free-text description of the kind of game wanted
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LibrarySearchQuery {
+    pub q: String,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+fn default_limit() -> u32 {
+    5
+}
+
+/// A library game matched by description and tags
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimilarGame {
+    pub game_id: GameId,
+    pub game_name: String,
+    pub description: Option<String>,
+    pub min_players: Option<i32>,
+    pub max_players: Option<i32>,
+    pub play_time_minutes: Option<i32>,
+    pub complexity_rating: Option<f64>,
+    /// Cosine similarity of the description vectors, most similar first
+    pub similarity: f64,
+}
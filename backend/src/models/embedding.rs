@@ -55,6 +55,7 @@ pub struct CreateEmbeddingRequest {
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct EmbeddingSearchResult {
     pub id: EmbeddingId,
+    pub game_id: GameId,
     pub chunk_text: String,
     pub similarity_score: f32,
     pub source_type: EmbeddingSourceType,
@@ -62,6 +63,18 @@ pub struct EmbeddingSearchResult {
     pub metadata: Option<String>,
 }
 
+/// A stored chunk without its vector, used when reading chunks back in document order
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RuleChunk {
+    pub id: EmbeddingId,
+    pub game_id: GameId,
+    pub chunk_text: String,
+    pub chunk_index: i32,
+    pub source_type: EmbeddingSourceType,
+    pub source_id: Option<HouseRuleId>,
+    pub metadata: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SimilaritySearchRequest {
     pub game_id: GameId,
@@ -79,3 +92,21 @@ fn default_search_limit() -> u32 {
 fn default_similarity_threshold() -> f32 {
     0.5
 }
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EmbeddingCacheStats {
+    pub model: String,
+    pub enabled: bool,
+    pub entries: u64,
+    pub max_entries: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub lifetime_hits: u64,
+    pub oldest_entry: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClearEmbeddingCacheResponse {
+    pub entries_removed: u64,
+}
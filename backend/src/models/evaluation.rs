@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{EmbeddingId, EvalQuestionId, EvalRunId, GameId, QueryStrategy};
+
+/// A golden question with what a good retrieval and answer should contain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EvalQuestion {
+    pub id: EvalQuestionId,
+    pub game_id: GameId,
+    pub question: String,
+    /// Chunks a good retrieval finds; these go stale when the rules are re-uploaded
+    pub expected_chunk_ids: Vec<EmbeddingId>,
+    /// Phrases the relevant rules and a correct answer contain; they survive re-chunking
+    pub expected_keywords: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One golden question as written in a fixture file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GoldenQuestion {
+    pub question: String,
+    #[serde(default)]
+    pub expected_chunk_ids: Vec<EmbeddingId>,
+    #[serde(default)]
+    pub expected_keywords: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ImportEvalQuestionsRequest {
+    pub game_id: GameId,
+    pub questions: Vec<GoldenQuestion>,
+    /// Replace the game's existing golden set instead of adding to it
+    #[serde(default)]
+    pub replace: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EvalRunRequest {
+    pub game_id: GameId,
+    #[serde(default)]
+    pub strategy: QueryStrategy,
+    /// Chunks retrieved per question (defaults to 5)
+    pub k: Option<u32>,
+    /// Also generate answers and check them for the expected keywords
+    #[serde(default)]
+    pub generate_answers: bool,
+    /// Run to diff against; the game's previous run when omitted
+    pub compare_to: Option<EvalRunId>,
+}
+
+/// A stored evaluation run
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EvalRun {
+    pub id: EvalRunId,
+    pub game_id: GameId,
+    pub strategy: QueryStrategy,
+    pub k: u32,
+    pub generate_answers: bool,
+    pub embedding_model: String,
+    pub llm_model: Option<String>,
+    pub summary: EvalSummary,
+    pub results: Vec<EvalQuestionResult>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An evaluation run ready to store
+#[derive(Debug)]
+pub struct NewEvalRun {
+    pub game_id: GameId,
+    pub strategy: QueryStrategy,
+    pub k: u32,
+    pub generate_answers: bool,
+    pub embedding_model: String,
+    pub llm_model: Option<String>,
+    pub summary: EvalSummary,
+    pub results: Vec<EvalQuestionResult>,
+}
+
+/// Metrics averaged over the questions they apply to; `None` when none do
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EvalSummary {
+    pub questions: usize,
+    /// Share of expected chunks found in the top k
+    pub recall_at_k: Option<f32>,
+    /// Mean reciprocal rank of the first relevant chunk
+    pub mrr: Option<f32>,
+    /// Share of expected keywords found in the top k chunks
+    pub keyword_recall: Option<f32>,
+    /// Share of generated answers containing every expected keyword
+    pub answer_match_rate: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EvalQuestionResult {
+    pub question_id: EvalQuestionId,
+    pub question: String,
+    pub retrieved_chunk_ids: Vec<EmbeddingId>,
+    pub recall: Option<f32>,
+    pub reciprocal_rank: Option<f32>,
+    pub keyword_recall: Option<f32>,
+    pub answer: Option<String>,
+    pub answer_match: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EvalRunResponse {
+    pub run: EvalRun,
+    /// Changes since the compared run, if there was one
+    pub diff: Option<EvalDiff>,
+}
+
+/// How a run moved relative to an earlier one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EvalDiff {
+    pub baseline_run_id: EvalRunId,
+    pub recall_at_k_delta: Option<f32>,
+    pub mrr_delta: Option<f32>,
+    pub keyword_recall_delta: Option<f32>,
+    pub answer_match_rate_delta: Option<f32>,
+    pub improved: Vec<EvalQuestionChange>,
+    pub regressed: Vec<EvalQuestionChange>,
+}
+
+/// A question whose score changed between runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EvalQuestionChange {
+    pub question_id: EvalQuestionId,
+    pub question: String,
+    pub before: f32,
+    pub after: f32,
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{EmbeddingId, FaqEntryId, GameId};
+
+/// A curated question and answer about a game's rules
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaqEntry {
+    pub id: FaqEntryId,
+    pub game_id: GameId,
+    pub question: String,
+    pub answer: String,
+    /// Rule chunks the answer is based on
+    pub cited_chunks: Vec<EmbeddingId>,
+    /// Written or changed by a person; regenerating the FAQ keeps edited entries
+    pub edited: bool,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateFaqEntryRequest {
+    pub game_id: GameId,
+    pub question: String,
+    pub answer: String,
+    #[serde(default)]
+    pub cited_chunks: Vec<EmbeddingId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateFaqEntryRequest {
+    pub question: Option<String>,
+    pub answer: Option<String>,
+    pub cited_chunks: Option<Vec<EmbeddingId>>,
+}
+
+/// A FAQ entry ready to store, with its question vector
+#[derive(Debug)]
+pub struct NewFaqEntry {
+    pub game_id: GameId,
+    pub question: String,
+    pub question_embedding: Vec<f32>,
+    pub answer: String,
+    pub cited_chunks: Vec<EmbeddingId>,
+    pub edited: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateFaqRequest {
+    /// Most questions to generate (defaults to 20, at most 50)
+    pub max_questions: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateFaqResponse {
+    pub game_id: GameId,
+    /// The game's whole FAQ after generation, edited entries first
+    pub entries: Vec<FaqEntry>,
+    pub generated: usize,
+    /// Previously generated, unedited entries that were replaced
+    pub replaced: u64,
+}
+
+/// The FAQ entry a chat question was answered from
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaqMatch {
+    pub entry_id: FaqEntryId,
+    pub question: String,
+    /// Similarity between the chat question and the FAQ question
+    pub similarity: f32,
+}
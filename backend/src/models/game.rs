@@ -1,4 +1,4 @@
-use super::GameId;
+use super::{CollectionStatus, GameId, TagSummary};
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -19,6 +19,9 @@ pub struct Game {
     pub rules_text: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub last_played_at: Option<DateTime<Utc>>,
+    /// The base game this expansion extends; `None` for base games
+    pub base_game_id: Option<GameId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -32,6 +35,8 @@ pub struct CreateGameRequest {
     pub play_time_minutes: Option<i32>,
     pub complexity_rating: Option<f64>,
     pub bgg_id: Option<i32>,
+    /// Makes the game an expansion of this base game
+    pub base_game_id: Option<GameId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -45,6 +50,11 @@ pub struct UpdateGameRequest {
     pub play_time_minutes: Option<i32>,
     pub complexity_rating: Option<f64>,
     pub bgg_id: Option<i32>,
+    /// Makes the game an expansion of this base game
+    pub base_game_id: Option<GameId>,
+    /// Makes the game a standalone base game again
+    #[serde(default)]
+    pub clear_base_game: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -55,9 +65,83 @@ pub struct GameSummary {
     pub year_published: Option<i32>,
     pub min_players: Option<i32>,
     pub max_players: Option<i32>,
+    pub play_time_minutes: Option<i32>,
     pub complexity_rating: Option<f64>,
     pub has_rules_pdf: bool,
     pub house_rules_count: i32,
+    pub updated_at: DateTime<Utc>,
+    pub last_played_at: Option<DateTime<Utc>>,
+    pub base_game_id: Option<GameId>,
+    pub tags: Vec<TagSummary>,
+}
+
+/// Filters, sorting and pagination for the game library
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GameListQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    /// Case-insensitive substring of the name
+    pub name: Option<String>,
+    /// Full-text search over name, description and publisher
+    pub q: Option<String>,
+    /// Only games that can be played with this many players
+    pub players: Option<i32>,
+    pub max_play_time: Option<i32>,
+    pub min_complexity: Option<f64>,
+    pub max_complexity: Option<f64>,
+    pub min_year: Option<i32>,
+    pub max_year: Option<i32>,
+    /// Exact publisher, ignoring case
+    pub publisher: Option<String>,
+    pub has_rules: Option<bool>,
+    pub has_house_rules: Option<bool>,
+    /// Comma-separated tag names, ignoring case; games must carry all of them
+    pub tags: Option<String>,
+    /// Games without collection details count as owned
+    pub collection_status: Option<CollectionStatus>,
+    #[serde(default)]
+    pub sort: GameSort,
+    /// Overrides the sort's natural direction
+    pub order: Option<SortOrder>,
+}
+
+fn default_page() -> u32 {
+    1
+}
+fn default_limit() -> u32 {
+    20
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GameSort {
+    #[default]
+    Name,
+    Year,
+    Complexity,
+    RecentlyUpdated,
+    RecentlyPlayed,
+}
+
+impl GameSort {
+    /// Direction used when none is requested: alphabetical, lightest first, newest first
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            GameSort::Name | GameSort::Complexity => SortOrder::Asc,
+            GameSort::Year | GameSort::RecentlyUpdated | GameSort::RecentlyPlayed => {
+                SortOrder::Desc
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -72,7 +156,7 @@ pub struct RulesInfoResponse {
 }
 
 impl Game {
-    pub fn to_summary(&self, house_rules_count: i32) -> GameSummary {
+    pub fn to_summary(&self, house_rules_count: i32, tags: Vec<TagSummary>) -> GameSummary {
         GameSummary {
             id: self.id,
             name: self.name.clone(),
@@ -80,9 +164,14 @@ impl Game {
             year_published: self.year_published,
             min_players: self.min_players,
             max_players: self.max_players,
+            play_time_minutes: self.play_time_minutes,
             complexity_rating: self.complexity_rating,
             has_rules_pdf: self.rules_pdf_path.is_some(),
             house_rules_count,
+            updated_at: self.updated_at,
+            last_played_at: self.last_played_at,
+            base_game_id: self.base_game_id,
+            tags,
         }
     }
 }
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use super::{GameId, HouseRuleId};
+use super::{EmbeddingId, GameId, HouseRuleId};
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HouseRule {
@@ -11,6 +11,8 @@ pub struct HouseRule {
     pub description: String,
     pub category: Option<String>,
     pub is_active: bool,
+    /// How the rule relates to the rulebook, if it has been analyzed since its last edit
+    pub analysis: Option<HouseRuleAnalysis>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +25,9 @@ pub struct CreateHouseRuleRequest {
     pub category: Option<String>,
     #[serde(default = "default_true")]
     pub is_active: bool,
+    /// Compare the rule against the rulebook right after creating it
+    #[serde(default)]
+    pub analyze: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -33,6 +38,60 @@ pub struct UpdateHouseRuleRequest {
     pub is_active: Option<bool>,
 }
 
+/// How a house rule relates to the official rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HouseRuleRelation {
+    /// Replaces an official rule with a different one
+    Override,
+    /// Covers something the rulebook does not address
+    Addition,
+    /// Restates what the rulebook already says
+    Duplicate,
+    /// Conflicts with the rulebook without clearly replacing a rule
+    Contradiction,
+}
+
+impl HouseRuleRelation {
+    pub const ALL: [HouseRuleRelation; 4] = [
+        HouseRuleRelation::Override,
+        HouseRuleRelation::Addition,
+        HouseRuleRelation::Duplicate,
+        HouseRuleRelation::Contradiction,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HouseRuleRelation::Override => "override",
+            HouseRuleRelation::Addition => "addition",
+            HouseRuleRelation::Duplicate => "duplicate",
+            HouseRuleRelation::Contradiction => "contradiction",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|relation| relation.as_str() == s)
+    }
+}
+
+/// A rulebook passage the analysis relied on
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CitedPassage {
+    pub embedding_id: EmbeddingId,
+    pub chunk_text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HouseRuleAnalysis {
+    pub relation: HouseRuleRelation,
+    pub explanation: String,
+    pub cited_passages: Vec<CitedPassage>,
+    /// Model-reported confidence in the classification, from 0 to 1
+    pub confidence: Option<f32>,
+    pub model: String,
+    pub analyzed_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct HouseRuleSummary {
     pub id: HouseRuleId,
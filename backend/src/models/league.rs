@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{GameId, LeagueId, PlayId, PlayerId};
+
+/// Players competing over a season; plays of the included games between
+/// members during the season count towards the league
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct League {
+    pub id: LeagueId,
+    pub name: String,
+    pub description: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub player_ids: Vec<PlayerId>,
+    /// Empty when every game counts
+    pub game_ids: Vec<GameId>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateLeagueRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub player_ids: Vec<PlayerId>,
+    #[serde(default)]
+    pub game_ids: Vec<GameId>,
+}
+
+/// Fields left out are kept; lists that are given replace the current ones
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateLeagueRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub player_ids: Option<Vec<PlayerId>>,
+    pub game_ids: Option<Vec<GameId>>,
+}
+
+/// Restricts ratings to one of the league's games; overall ratings otherwise
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LeagueRatingsQuery {
+    pub game_id: Option<GameId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RatingHistoryQuery {
+    pub game_id: Option<GameId>,
+    pub player_id: Option<PlayerId>,
+}
+
+/// A member's place in the league table
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Standing {
+    /// 1 for the highest rating; equal ratings share a rank
+    pub rank: u32,
+    pub player_id: PlayerId,
+    pub name: String,
+    pub rating: f64,
+    pub plays: i64,
+    pub wins: i64,
+}
+
+/// How one rated play moved a player's rating
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RatingChange {
+    pub play_id: PlayId,
+    pub game_id: GameId,
+    pub played_at: DateTime<Utc>,
+    pub player_id: PlayerId,
+    pub rating_before: f64,
+    pub rating_after: f64,
+}
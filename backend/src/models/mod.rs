@@ -2,15 +2,47 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+pub mod answer_cache;
+pub mod backup;
+pub mod bgg;
 pub mod chat;
+pub mod collection;
+pub mod discovery;
 pub mod embedding;
+pub mod evaluation;
+pub mod faq;
 pub mod game;
 pub mod house_rule;
+pub mod league;
+pub mod play;
+pub mod player;
+pub mod prompt_template;
+pub mod quick_reference;
+pub mod recommendation;
+pub mod retrieval;
+pub mod stats;
+pub mod tag;
 
+pub use answer_cache::*;
+pub use backup::*;
+pub use bgg::*;
 pub use chat::*;
+pub use collection::*;
+pub use discovery::*;
 pub use embedding::*;
+pub use evaluation::*;
+pub use faq::*;
 pub use game::*;
 pub use house_rule::*;
+pub use league::*;
+pub use play::*;
+pub use player::*;
+pub use prompt_template::*;
+pub use quick_reference::*;
+pub use recommendation::*;
+pub use retrieval::*;
+pub use stats::*;
+pub use tag::*;
 
 // Common types used across models
 pub type GameId = i64;
@@ -18,6 +50,16 @@ pub type HouseRuleId = i64;
 pub type EmbeddingId = i64;
 pub type ChatSessionId = i64;
 pub type ChatMessageId = i64;
+pub type PromptTemplateId = i64;
+pub type CachedAnswerId = i64;
+pub type FaqEntryId = i64;
+pub type EvalQuestionId = i64;
+pub type EvalRunId = i64;
+pub type TagId = i64;
+pub type PlayId = i64;
+pub type PlayerId = i64;
+pub type LeagueId = i64;
+pub type LoanId = i64;
 
 
 
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{ChatSessionId, GameId, PlayId, PlayerId};
+
+/// One logged play of a game
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Play {
+    pub id: PlayId,
+    pub game_id: GameId,
+    pub game_name: String,
+    pub played_at: DateTime<Utc>,
+    pub duration_minutes: Option<i32>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    /// Expansions of the game that were used
+    pub expansion_ids: Vec<GameId>,
+    /// Participants in the order they were listed
+    pub players: Vec<PlayPlayer>,
+    /// Chat sessions consulted during the play
+    pub chat_session_ids: Vec<ChatSessionId>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A participant in a play
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PlayPlayer {
+    pub player_id: PlayerId,
+    pub name: String,
+    pub score: Option<i64>,
+    pub winner: bool,
+    /// Players on the same team share their placement
+    pub team: Option<String>,
+}
+
+/// A participant as given when logging a play; players are matched by name, ignoring case
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlayPlayerRequest {
+    pub name: String,
+    pub score: Option<i64>,
+    #[serde(default)]
+    pub winner: bool,
+    pub team: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreatePlayRequest {
+    pub game_id: GameId,
+    /// Defaults to now
+    pub played_at: Option<DateTime<Utc>>,
+    pub duration_minutes: Option<i32>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub expansion_ids: Vec<GameId>,
+    pub players: Vec<PlayPlayerRequest>,
+    #[serde(default)]
+    pub chat_session_ids: Vec<ChatSessionId>,
+}
+
+/// Fields left out are kept; lists that are given replace the current ones
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdatePlayRequest {
+    pub played_at: Option<DateTime<Utc>>,
+    pub duration_minutes: Option<i32>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    pub expansion_ids: Option<Vec<GameId>>,
+    pub players: Option<Vec<PlayPlayerRequest>>,
+    pub chat_session_ids: Option<Vec<ChatSessionId>>,
+}
+
+/// Filters and pagination for the play log, most recent play first
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlayListQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    pub game_id: Option<GameId>,
+    /// Only plays this player took part in, ignoring case
+    pub player: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn default_page() -> u32 {
+    1
+}
+fn default_limit() -> u32 {
+    20
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{GameId, PlayerId};
+
+/// Someone who takes part in plays
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Player {
+    pub id: PlayerId,
+    pub name: String,
+    pub play_count: i64,
+    pub win_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreatePlayerRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdatePlayerRequest {
+    pub name: Option<String>,
+}
+
+/// A player's record across every game they have played
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlayerStats {
+    pub player: Player,
+    /// Share of plays won; `None` before the first play
+    pub win_rate: Option<f64>,
+    /// Largest h such that the player has played h games at least h times each
+    pub h_index: i64,
+    /// Most played first
+    pub games: Vec<PlayerGameStats>,
+}
+
+/// A player's record in one game
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlayerGameStats {
+    pub game_id: GameId,
+    pub game_name: String,
+    pub plays: i64,
+    pub wins: i64,
+    pub win_rate: f64,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i64>,
+    pub last_played_at: DateTime<Utc>,
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{ChatSessionId, EmbeddingId, GameId, PromptTemplateId, QueryStrategy};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PromptTemplate {
+    pub id: PromptTemplateId,
+    pub game_id: Option<GameId>, // None for the global default
+    pub name: String,
+    pub persona: Option<String>,
+    pub template: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreatePromptTemplateRequest {
+    pub game_id: Option<GameId>,
+    pub name: String,
+    pub persona: Option<String>,
+    pub template: String,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdatePromptTemplateRequest {
+    pub name: Option<String>,
+    pub persona: Option<String>,
+    pub template: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// A variable that templates may reference as `{{name}}`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PromptTemplateVariable {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// Where the template used for a prompt came from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptTemplateSource {
+    Game,
+    Global,
+    BuiltIn,
+    Draft,
+}
+
+/// Render the system prompt for a sample question without calling the model
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PromptPreviewRequest {
+    pub game_id: GameId,
+    pub question: String,
+    /// Unsaved template text to preview instead of the game's active template
+    pub template: Option<String>,
+    pub persona: Option<String>,
+    /// Include this session's conversation history
+    pub session_id: Option<ChatSessionId>,
+    /// How to turn the question into search text (defaults to direct)
+    pub query_strategy: Option<QueryStrategy>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PromptPreviewResponse {
+    pub system_prompt: String,
+    pub template_source: PromptTemplateSource,
+    pub template_id: Option<PromptTemplateId>,
+    pub included_chunk_ids: Vec<EmbeddingId>,
+    pub prompt_tokens: usize,
+    pub context_window: usize,
+    pub dropped_chunks: usize,
+    pub dropped_history_messages: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{EmbeddingId, GameId};
+
+/// A one-page rules summary for game night
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QuickReference {
+    pub game_id: GameId,
+    /// Player count the sheet was written for, if any
+    pub player_count: Option<i32>,
+    pub sections: Vec<QuickReferenceSection>,
+    pub model: String,
+    pub generated_at: DateTime<Utc>,
+    /// Served from the stored sheet rather than generated for this request
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct QuickReferenceSection {
+    pub title: String,
+    pub bullets: Vec<QuickReferenceBullet>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct QuickReferenceBullet {
+    pub text: String,
+    /// Rulebook pages the point comes from
+    pub pages: Vec<u32>,
+    /// Rule chunks the point is based on
+    pub cited_chunks: Vec<EmbeddingId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct QuickReferenceQuery {
+    /// Tailor setup and rules to this many players
+    pub player_count: Option<i32>,
+    /// Generate a new sheet even if the stored one is current
+    #[serde(default)]
+    pub refresh: bool,
+}
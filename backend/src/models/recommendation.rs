@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::GameId;
+
+/// Who is at the table and what they are in the mood for
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecommendationRequest {
+    /// Number of people playing
+    pub players: i32,
+    /// Longest game the group has time for, in minutes
+    pub max_play_time: Option<i32>,
+    pub min_complexity: Option<f64>,
+    pub max_complexity: Option<f64>,
+    /// The people playing; the games they have played most count as liked
+    #[serde(default)]
+    pub player_names: Vec<String>,
+    /// Games the group liked, on top of those found from plays and ratings
+    #[serde(default)]
+    pub liked_game_ids: Vec<GameId>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Ask the LLM for a sentence on why each pick suits the group
+    #[serde(default)]
+    pub explain: bool,
+}
+
+fn default_limit() -> usize {
+    5
+}
+
+/// A game picked for the table and why it ranked where it did
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Recommendation {
+    pub game_id: GameId,
+    pub game_name: String,
+    pub min_players: Option<i32>,
+    pub max_players: Option<i32>,
+    pub play_time_minutes: Option<i32>,
+    pub complexity_rating: Option<f64>,
+    pub last_played_at: Option<DateTime<Utc>>,
+    /// Personal rating, 1 to 10
+    pub rating: Option<f64>,
+    /// Description similarity to the closest liked game, 0 to 1
+    pub similarity: Option<f64>,
+    /// The liked game the description is closest to
+    pub similar_to: Option<GameId>,
+    /// Overall score, 0 to 1; picks are sorted by it
+    pub score: f64,
+    pub justification: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecommendationResponse {
+    pub recommendations: Vec<Recommendation>,
+    /// Liked games the picks were compared with
+    pub liked_game_ids: Vec<GameId>,
+}
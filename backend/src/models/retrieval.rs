@@ -0,0 +1,95 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{EmbeddingId, GameId};
+
+/// How a question is turned into the text that gets embedded for rules retrieval
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryStrategy {
+    /// Embed the question as asked
+    #[default]
+    Direct,
+    /// Append rulebook synonyms from a fixed English keyword table
+    Keywords,
+    /// Have the model rephrase the question in rulebook terms
+    Rewrite,
+    /// Embed a hypothetical rulebook passage that answers the question
+    Hyde,
+    /// Search with several model-written variants and fuse the rankings
+    MultiQuery,
+}
+
+impl QueryStrategy {
+    pub const ALL: [QueryStrategy; 5] = [
+        QueryStrategy::Direct,
+        QueryStrategy::Keywords,
+        QueryStrategy::Rewrite,
+        QueryStrategy::Hyde,
+        QueryStrategy::MultiQuery,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryStrategy::Direct => "direct",
+            QueryStrategy::Keywords => "keywords",
+            QueryStrategy::Rewrite => "rewrite",
+            QueryStrategy::Hyde => "hyde",
+            QueryStrategy::MultiQuery => "multi_query",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|strategy| strategy.as_str() == s)
+    }
+
+    /// Whether the strategy calls the LLM before retrieval
+    pub fn uses_llm(&self) -> bool {
+        matches!(
+            self,
+            QueryStrategy::Rewrite | QueryStrategy::Hyde | QueryStrategy::MultiQuery
+        )
+    }
+}
+
+/// Run one question through several query strategies side by side
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct QueryStrategyComparisonRequest {
+    pub game_id: GameId,
+    pub question: String,
+    /// Strategies to compare; all of them when omitted
+    pub strategies: Option<Vec<QueryStrategy>>,
+    pub limit: Option<u32>,
+    /// Chunks a good retrieval should find, used to score each strategy
+    pub expected_chunk_ids: Option<Vec<EmbeddingId>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct QueryStrategyComparison {
+    pub game_id: GameId,
+    pub question: String,
+    pub runs: Vec<QueryStrategyRun>,
+}
+
+/// Retrieval results for one strategy
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct QueryStrategyRun {
+    pub strategy: QueryStrategy,
+    /// Texts that were embedded and searched
+    pub queries: Vec<String>,
+    pub results: Vec<RetrievedChunk>,
+    pub latency_ms: u64,
+    /// Share of the expected chunks that were retrieved
+    pub recall: Option<f32>,
+    /// 1 / rank of the first expected chunk, 0 when none was retrieved
+    pub reciprocal_rank: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RetrievedChunk {
+    pub embedding_id: EmbeddingId,
+    pub similarity_score: f32,
+    pub chunk_text: String,
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{GameId, PlayerId};
+
+/// Restricts statistics to plays within a period; both ends are inclusive
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct StatsPeriodQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MostPlayedQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default = "default_most_played_limit")]
+    pub limit: u32,
+}
+
+fn default_most_played_limit() -> u32 {
+    10
+}
+
+/// Totals over the whole play log
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlayStatsSummary {
+    pub total_plays: i64,
+    pub games_played: i64,
+    pub players: i64,
+    pub total_minutes: i64,
+    /// Largest h such that h games have been played at least h times each
+    pub h_index: i64,
+}
+
+/// How often a game was played
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GamePlayCount {
+    pub game_id: GameId,
+    pub game_name: String,
+    pub plays: i64,
+    /// Different players who took part
+    pub players: i64,
+    pub total_minutes: i64,
+    pub last_played_at: DateTime<Utc>,
+}
+
+/// A game in the library that has never been played
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UnplayedGame {
+    pub game_id: GameId,
+    pub game_name: String,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Play statistics for one game
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GameStats {
+    pub game_id: GameId,
+    pub game_name: String,
+    pub plays: i64,
+    pub average_duration_minutes: Option<f64>,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i64>,
+    pub last_played_at: Option<DateTime<Utc>>,
+    /// Most wins first
+    pub players: Vec<GamePlayerStats>,
+}
+
+/// A player's record in the game the statistics are for
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GamePlayerStats {
+    pub player_id: PlayerId,
+    pub name: String,
+    pub plays: i64,
+    pub wins: i64,
+    pub win_rate: f64,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i64>,
+}
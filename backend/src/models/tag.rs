@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::TagId;
+
+/// What kind of classification a tag is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TagType {
+    /// How the game plays, e.g. worker placement
+    Mechanic,
+    /// What kind of game it is, e.g. economic
+    Category,
+    Theme,
+    Custom,
+}
+
+impl TagType {
+    pub const ALL: [TagType; 4] = [
+        TagType::Mechanic,
+        TagType::Category,
+        TagType::Theme,
+        TagType::Custom,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TagType::Mechanic => "mechanic",
+            TagType::Category => "category",
+            TagType::Theme => "theme",
+            TagType::Custom => "custom",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tag_type| tag_type.as_str() == s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Tag {
+    pub id: TagId,
+    pub name: String,
+    pub tag_type: TagType,
+    /// Games carrying the tag
+    pub game_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A tag as listed on a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TagSummary {
+    pub id: TagId,
+    pub name: String,
+    pub tag_type: TagType,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateTagRequest {
+    pub name: String,
+    pub tag_type: TagType,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateTagRequest {
+    pub name: Option<String>,
+    pub tag_type: Option<TagType>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TagListQuery {
+    pub tag_type: Option<TagType>,
+}
+
+/// The complete set of tags a game should carry
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SetGameTagsRequest {
+    pub tag_ids: Vec<TagId>,
+}
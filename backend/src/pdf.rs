@@ -1,5 +1,5 @@
 use anyhow::{Result, anyhow};
-use pdf_extract::extract_text;
+use pdf_extract::extract_text_by_pages;
 use std::path::Path;
 
 /// Configuration for text chunking
@@ -7,6 +7,7 @@ const CHUNK_SIZE: usize = 1000; // characters per chunk
 const CHUNK_OVERLAP: usize = 300; // overlap between chunks
 const MIN_CHUNK_SIZE: usize = 100; // minimum characters for a valid chunk
 const MAX_CHUNK_SIZE: usize = 1500; // maximum characters before forced split
+const PAGE_MATCH_PREFIX: usize = 40; // leading characters used to find a chunk's page
 
 /// Simple PDF service that only handles PDF text extraction and chunking
 /// Database and embedding operations are handled separately
@@ -17,12 +18,12 @@ impl Processor {
         Self
     }
 
-    /// Extract text from a PDF file
-    pub async fn extract_text_from_pdf(&self, pdf_path: &Path) -> Result<String> {
-        let text = extract_text(pdf_path)
+    /// Extract the text of each page of a PDF file, in page order
+    pub async fn extract_pages_from_pdf(&self, pdf_path: &Path) -> Result<Vec<String>> {
+        let pages = extract_text_by_pages(pdf_path)
             .map_err(|e| anyhow!("Failed to extract text from PDF: {}", e))?;
 
-        Ok(text)
+        Ok(pages)
     }
 
     /// Split text into chunks for embedding with intelligent sentence boundary detection
@@ -263,18 +264,57 @@ impl Processor {
         overlap
     }
 
+    /// Find the 1-based page each chunk starts on by locating its opening words in the
+    /// cleaned page texts. Chunks are searched in order, so a repeated passage resolves to
+    /// the page after the previous chunk rather than to its first occurrence
+    pub fn chunk_start_pages(&self, pages: &[String], chunks: &[String]) -> Vec<u32> {
+        let mut page_starts = Vec::with_capacity(pages.len());
+        let mut cleaned = String::new();
+        for page in pages {
+            let page_text = self.clean_text(page);
+            if !cleaned.is_empty() && !page_text.is_empty() {
+                cleaned.push(' ');
+            }
+            page_starts.push(cleaned.len());
+            cleaned.push_str(&page_text);
+        }
+
+        let page_at = |offset: usize| page_starts.partition_point(|&start| start <= offset).max(1);
+
+        let mut search_from = 0;
+        let mut last_page = 1;
+        chunks
+            .iter()
+            .map(|chunk| {
+                let opening: String = chunk.chars().take(PAGE_MATCH_PREFIX).collect();
+                if let Some(found) = cleaned
+                    .get(search_from..)
+                    .and_then(|rest| rest.find(&opening))
+                {
+                    let offset = search_from + found;
+                    search_from = offset;
+                    last_page = page_at(offset) as u32;
+                }
+                last_page
+            })
+            .collect()
+    }
+
     /// Process a PDF file and return extracted text and chunks
     /// This is a pure processing function that doesn't touch the database or embeddings
     pub async fn process_pdf(&self, pdf_path: &Path) -> Result<ProcessedPdf> {
-        // Extract text from PDF
-        let text = self.extract_text_from_pdf(pdf_path).await?;
+        // Extract text page by page so chunks can be traced back to their pages
+        let pages = self.extract_pages_from_pdf(pdf_path).await?;
+        let text = pages.join("\n");
 
         // Chunk the text
         let chunks = self.chunk_text(&text);
+        let chunk_pages = self.chunk_start_pages(&pages, &chunks);
 
         Ok(ProcessedPdf {
             full_text: text,
             chunks,
+            chunk_pages,
         })
     }
 }
@@ -290,6 +330,8 @@ impl Default for Processor {
 pub struct ProcessedPdf {
     pub full_text: String,
     pub chunks: Vec<String>,
+    /// 1-based page each chunk starts on, parallel to `chunks`
+    pub chunk_pages: Vec<u32>,
 }
 
 /// Validate that a file is a PDF
@@ -332,6 +374,22 @@ mod tests {
         assert!(chunks[0].len() >= MIN_CHUNK_SIZE);
     }
 
+    #[test]
+    fn test_chunk_start_pages() {
+        let service = Processor::new();
+        let pages = vec![
+            "Setup\nEach player takes a ship and five coins. ".repeat(30),
+            "Turn order\nPlayers take turns clockwise, starting with the youngest. ".repeat(30),
+        ];
+        let chunks = service.chunk_text(&pages.join("\n"));
+        let chunk_pages = service.chunk_start_pages(&pages, &chunks);
+
+        assert_eq!(chunk_pages.len(), chunks.len());
+        assert_eq!(chunk_pages.first(), Some(&1));
+        assert_eq!(chunk_pages.last(), Some(&2));
+        assert!(chunk_pages.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
     #[test]
     fn test_validate_pdf_file() {
         let pdf_bytes = b"%PDF-1.4 fake pdf content";
@@ -0,0 +1,527 @@
+use std::collections::HashMap;
+
+use tiktoken_rs::{
+    CoreBPE, cl100k_base_singleton, o200k_base_singleton,
+    tokenizer::{Tokenizer as BpeEncoding, get_tokenizer},
+};
+
+use crate::models::{ChatMessage, EmbeddingId, EmbeddingSearchResult, GameId, HouseRule};
+
+/// Context window assumed for models missing from `CONTEXT_WINDOWS`
+pub const DEFAULT_CONTEXT_WINDOW: usize = 8_192;
+
+/// Known context windows, matched by model name prefix (longest prefix first).
+/// Ollama only honours these when the model's `num_ctx` is at least as large.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("mistral-small3", 32_768),
+    ("mistral-nemo", 128_000),
+    ("mistral", 32_768),
+    ("llama3.1", 128_000),
+    ("llama3.2", 128_000),
+    ("llama3", 8_192),
+    ("qwen2.5", 32_768),
+    ("gemma2", 8_192),
+    ("gemma3", 128_000),
+];
+
+/// Open-weight models use SentencePiece vocabularies that split English into
+/// noticeably more tokens than cl100k, so their counts are scaled up to stay safe
+const OPEN_MODEL_TOKEN_SCALE: f32 = 1.2;
+
+/// Tokens kept free for chat-template framing that we cannot count exactly
+const SAFETY_MARGIN_TOKENS: usize = 128;
+
+/// Per-item overhead for separators between formatted entries
+const SEPARATOR_TOKENS: usize = 2;
+
+/// Partial entries smaller than this are dropped rather than truncated
+const MIN_TRUNCATED_TOKENS: usize = 48;
+
+const TRUNCATION_MARKER: &str = " [...]";
+
+/// Share of the section budget each section may claim before leftovers are redistributed
+const HOUSE_RULES_SHARE: f32 = 0.15;
+const HISTORY_SHARE: f32 = 0.30;
+
+/// Look up the context window for a model name
+pub fn context_window_for_model(model: &str) -> usize {
+    let model = model.to_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, window)| *window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+/// Counts tokens with the tokenizer that best matches a model
+#[derive(Clone, Copy)]
+pub struct Tokenizer {
+    bpe: &'static CoreBPE,
+    scale: f32,
+}
+
+impl Tokenizer {
+    /// OpenAI models get their exact encoding; other models are approximated with a scaled cl100k count
+    pub fn for_model(model: &str) -> Self {
+        match get_tokenizer(model) {
+            Some(BpeEncoding::O200kBase) => Self {
+                bpe: o200k_base_singleton(),
+                scale: 1.0,
+            },
+            Some(_) => Self {
+                bpe: cl100k_base_singleton(),
+                scale: 1.0,
+            },
+            None => Self {
+                bpe: cl100k_base_singleton(),
+                scale: OPEN_MODEL_TOKEN_SCALE,
+            },
+        }
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        let tokens = self.bpe.encode_ordinary(text).len();
+        (tokens as f32 * self.scale).ceil() as usize
+    }
+
+    /// Shorten text to at most `max_tokens`, cutting at a sentence or word boundary
+    pub fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        if self.count(text) <= max_tokens {
+            return text.to_string();
+        }
+
+        let marker_tokens = self.count(TRUNCATION_MARKER);
+        let budget = ((max_tokens.saturating_sub(marker_tokens)) as f32 / self.scale) as usize;
+        let tokens = self.bpe.encode_ordinary(text);
+
+        // A cut can land inside a multi-byte character; back off until the prefix decodes
+        let mut keep = budget.min(tokens.len());
+        let prefix = loop {
+            if keep == 0 {
+                return String::new();
+            }
+            match self.bpe.decode(tokens[..keep].to_vec()) {
+                Ok(prefix) => break prefix,
+                Err(_) => keep -= 1,
+            }
+        };
+
+        let cut = prefix
+            .rfind(['.', '!', '?', '\n'])
+            .map(|i| i + 1)
+            .filter(|&i| i >= prefix.len() * 2 / 3)
+            .or_else(|| prefix.rfind(char::is_whitespace))
+            .unwrap_or(prefix.len());
+
+        format!("{}{}", prefix[..cut].trim_end(), TRUNCATION_MARKER)
+    }
+}
+
+/// Everything that competes for room in the system prompt
+pub struct PromptInputs<'a> {
+    pub question: &'a str,
+    pub house_rules: &'a [HouseRule],
+    /// Retrieved chunks, best match first
+    pub chunks: &'a [EmbeddingSearchResult],
+    /// Earlier messages in the session, oldest first
+    pub history: &'a [ChatMessage],
+    /// Label chunks and house rules with their game, for prompts covering several games
+    pub game_names: Option<&'a HashMap<GameId, String>>,
+}
+
+/// Token accounting for a built prompt
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptUsage {
+    pub context_window: usize,
+    pub reserved_for_output: usize,
+    pub instructions: usize,
+    pub question: usize,
+    pub house_rules: usize,
+    pub rules_context: usize,
+    pub history: usize,
+    pub dropped_house_rules: usize,
+    pub dropped_chunks: usize,
+    pub dropped_history_messages: usize,
+    pub truncated: bool,
+}
+
+impl PromptUsage {
+    pub fn prompt_tokens(&self) -> usize {
+        self.instructions + self.question + self.house_rules + self.rules_context + self.history
+    }
+}
+
+/// Prompt sections trimmed to fit the budget, ready to be placed into the instructions
+pub struct PromptSections {
+    pub rules_context: String,
+    pub house_rules: String,
+    pub history: String,
+    /// Chunks that made it into `rules_context`, in rank order
+    pub included_chunk_ids: Vec<EmbeddingId>,
+    pub usage: PromptUsage,
+}
+
+/// Splits a model's context window between instructions, house rules, retrieved rules and history
+pub struct PromptBudget {
+    tokenizer: Tokenizer,
+    context_window: usize,
+    max_output_tokens: usize,
+    reserved_tokens: usize,
+}
+
+impl PromptBudget {
+    pub fn new(tokenizer: Tokenizer, context_window: usize, max_output_tokens: usize) -> Self {
+        Self {
+            tokenizer,
+            context_window,
+            max_output_tokens,
+            reserved_tokens: 0,
+        }
+    }
+
+    /// Hold back tokens for content added after the prompt is built, such as tool results
+    pub fn with_reserved_tokens(mut self, reserved_tokens: usize) -> Self {
+        self.reserved_tokens = reserved_tokens;
+        self
+    }
+
+    /// Fit the inputs around fixed `instructions`.
+    ///
+    /// Retrieved chunks are dropped lowest-ranked first and history oldest first;
+    /// the last entry that only partly fits is truncated instead of dropped.
+    pub fn allocate(&self, instructions: &str, inputs: &PromptInputs) -> PromptSections {
+        let mut usage = PromptUsage {
+            context_window: self.context_window,
+            reserved_for_output: self.max_output_tokens,
+            instructions: self.tokenizer.count(instructions),
+            question: self.tokenizer.count(inputs.question) + SEPARATOR_TOKENS,
+            ..Default::default()
+        };
+
+        let available = self.context_window.saturating_sub(
+            self.max_output_tokens
+                + self.reserved_tokens
+                + SAFETY_MARGIN_TOKENS
+                + usage.instructions
+                + usage.question,
+        );
+        if available == 0 {
+            tracing::warn!(
+                "Instructions and question use the whole {} token context window",
+                self.context_window
+            );
+        }
+
+        let game_name = |game_id: GameId| {
+            inputs
+                .game_names
+                .and_then(|names| names.get(&game_id))
+                .map(String::as_str)
+        };
+        let house_rules: Vec<String> = inputs
+            .house_rules
+            .iter()
+            .map(|rule| format_house_rule(rule, game_name(rule.game_id)))
+            .collect();
+        let chunks: Vec<String> = inputs
+            .chunks
+            .iter()
+            .map(|chunk| format_chunk(chunk, game_name(chunk.game_id)))
+            .collect();
+        let history: Vec<String> = inputs
+            .history
+            .iter()
+            .rev()
+            .map(format_history_message)
+            .collect();
+
+        // House rules and history get a fixed share; retrieved rules take everything else,
+        // and history may then grow into whatever the rules did not need
+        let house_rules_fit = self.fill(&house_rules, share(available, HOUSE_RULES_SHARE));
+        let history_fit = self.fill(&history, share(available, HISTORY_SHARE));
+        let chunks_fit = self.fill(
+            &chunks,
+            available.saturating_sub(house_rules_fit.tokens + history_fit.tokens),
+        );
+        let history_fit = self.fill(
+            &history,
+            available.saturating_sub(house_rules_fit.tokens + chunks_fit.tokens),
+        );
+
+        usage.house_rules = house_rules_fit.tokens;
+        usage.rules_context = chunks_fit.tokens;
+        usage.history = history_fit.tokens;
+        usage.dropped_house_rules = house_rules.len() - house_rules_fit.entries.len();
+        usage.dropped_chunks = chunks.len() - chunks_fit.entries.len();
+        usage.dropped_history_messages = history.len() - history_fit.entries.len();
+        usage.truncated =
+            house_rules_fit.truncated || chunks_fit.truncated || history_fit.truncated;
+
+        let included_chunk_ids = inputs
+            .chunks
+            .iter()
+            .take(chunks_fit.entries.len())
+            .map(|chunk| chunk.id)
+            .collect();
+
+        let rules_context = if chunks_fit.entries.is_empty() {
+            "No specific rules found for this question.".to_string()
+        } else {
+            chunks_fit.entries.join("\n\n")
+        };
+        let house_rules = if house_rules_fit.entries.is_empty() {
+            "No house rules for this game.".to_string()
+        } else {
+            house_rules_fit.entries.join("\n")
+        };
+        let mut history_entries = history_fit.entries;
+        history_entries.reverse();
+
+        PromptSections {
+            rules_context,
+            house_rules,
+            history: history_entries.join("\n"),
+            included_chunk_ids,
+            usage,
+        }
+    }
+
+    /// Take entries in order until the budget runs out, truncating the first one that does not fit
+    fn fill(&self, entries: &[String], budget: usize) -> Fitted {
+        let mut fitted = Fitted::default();
+
+        for entry in entries {
+            let remaining = budget.saturating_sub(fitted.tokens);
+            let tokens = self.tokenizer.count(entry) + SEPARATOR_TOKENS;
+
+            if tokens <= remaining {
+                fitted.entries.push(entry.clone());
+                fitted.tokens += tokens;
+                continue;
+            }
+
+            if remaining >= MIN_TRUNCATED_TOKENS {
+                let truncated = self.tokenizer.truncate(entry, remaining - SEPARATOR_TOKENS);
+                if !truncated.is_empty() {
+                    fitted.tokens += self.tokenizer.count(&truncated) + SEPARATOR_TOKENS;
+                    fitted.entries.push(truncated);
+                    fitted.truncated = true;
+                }
+            }
+            break;
+        }
+
+        fitted
+    }
+}
+
+#[derive(Default)]
+struct Fitted {
+    entries: Vec<String>,
+    tokens: usize,
+    truncated: bool,
+}
+
+fn share(available: usize, fraction: f32) -> usize {
+    (available as f32 * fraction) as usize
+}
+
+fn format_chunk(chunk: &EmbeddingSearchResult, game_name: Option<&str>) -> String {
+    match game_name {
+        Some(game_name) => format!(
+            "Rule [chunk {}, {}]: {}",
+            chunk.id, game_name, chunk.chunk_text
+        ),
+        None => format!("Rule [chunk {}]: {}", chunk.id, chunk.chunk_text),
+    }
+}
+
+fn format_house_rule(rule: &HouseRule, game_name: Option<&str>) -> String {
+    match game_name {
+        Some(game_name) => format!("- [{}] {}: {}", game_name, rule.title, rule.description),
+        None => format!("- {}: {}", rule.title, rule.description),
+    }
+}
+
+fn format_history_message(message: &ChatMessage) -> String {
+    format!("{}: {}", message.role.as_str(), message.content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EmbeddingSourceType, MessageRole};
+    use chrono::Utc;
+
+    fn chunk(id: EmbeddingId, words: usize) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id: 1,
+            chunk_text: "Players draw two cards at the start of their turn. ".repeat(words / 10),
+            similarity_score: 1.0 - id as f32 / 100.0,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    fn message(id: i64, content: &str) -> ChatMessage {
+        ChatMessage {
+            id,
+            session_id: 1,
+            role: if id % 2 == 0 {
+                MessageRole::Assistant
+            } else {
+                MessageRole::User
+            },
+            content: content.to_string(),
+            context_chunks: None,
+            tool_trace: None,
+            cited_chunks: None,
+            confidence: None,
+            not_covered_by_rules: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn inputs<'a>(
+        chunks: &'a [EmbeddingSearchResult],
+        history: &'a [ChatMessage],
+    ) -> PromptInputs<'a> {
+        PromptInputs {
+            question: "How many cards do I draw?",
+            house_rules: &[],
+            chunks,
+            history,
+            game_names: None,
+        }
+    }
+
+    #[test]
+    fn test_context_window_lookup() {
+        assert_eq!(context_window_for_model("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window_for_model("gpt-4-0613"), 8_192);
+        assert_eq!(context_window_for_model("mistral-small3.2:24b"), 32_768);
+        assert_eq!(context_window_for_model("llama3.1:8b"), 128_000);
+        assert_eq!(
+            context_window_for_model("unknown-model"),
+            DEFAULT_CONTEXT_WINDOW
+        );
+    }
+
+    #[test]
+    fn test_open_models_count_conservatively() {
+        let text = "The active player rolls both dice and collects resources.";
+        let openai = Tokenizer::for_model("gpt-4").count(text);
+        let open = Tokenizer::for_model("mistral-small3.2:24b").count(text);
+
+        assert!(openai > 0);
+        assert!(open > openai);
+    }
+
+    #[test]
+    fn test_truncate_respects_limit() {
+        let tokenizer = Tokenizer::for_model("gpt-4");
+        let text = "Each player starts with five coins. Coins are spent to build. ".repeat(50);
+
+        let truncated = tokenizer.truncate(&text, 40);
+        assert!(tokenizer.count(&truncated) <= 40);
+        assert!(truncated.ends_with(TRUNCATION_MARKER));
+        assert_eq!(tokenizer.truncate("Short rule.", 40), "Short rule.");
+    }
+
+    #[test]
+    fn test_everything_fits_in_large_window() {
+        let chunks = vec![chunk(1, 50), chunk(2, 50)];
+        let history = vec![message(1, "Hi"), message(2, "Hello!")];
+        let budget = PromptBudget::new(Tokenizer::for_model("gpt-4o"), 128_000, 512);
+
+        let sections = budget.allocate("You explain rules.", &inputs(&chunks, &history));
+
+        assert_eq!(sections.included_chunk_ids, vec![1, 2]);
+        assert_eq!(sections.history, "user: Hi\nassistant: Hello!");
+        assert!(!sections.usage.truncated);
+        assert_eq!(sections.usage.dropped_chunks, 0);
+    }
+
+    #[test]
+    fn test_game_names_label_chunks() {
+        let mut chunks = vec![chunk(1, 20), chunk(2, 20)];
+        chunks[1].game_id = 2;
+        let game_names =
+            HashMap::from([(1, "Gloomhaven".to_string()), (2, "Frosthaven".to_string())]);
+        let budget = PromptBudget::new(Tokenizer::for_model("gpt-4o"), 128_000, 512);
+
+        let mut inputs = inputs(&chunks, &[]);
+        inputs.game_names = Some(&game_names);
+        let sections = budget.allocate("You compare rules.", &inputs);
+
+        assert!(
+            sections
+                .rules_context
+                .starts_with("Rule [chunk 1, Gloomhaven]: ")
+        );
+        assert!(
+            sections
+                .rules_context
+                .contains("Rule [chunk 2, Frosthaven]: ")
+        );
+    }
+
+    #[test]
+    fn test_small_window_drops_lowest_ranked_chunks_and_oldest_history() {
+        let chunks: Vec<_> = (1..=6).map(|id| chunk(id, 300)).collect();
+        let history: Vec<_> = (1..=10)
+            .map(|id| message(id, &format!("message {} {}", id, "word ".repeat(80))))
+            .collect();
+        let budget = PromptBudget::new(Tokenizer::for_model("gpt-4"), 2_048, 512);
+
+        let sections = budget.allocate("You explain rules.", &inputs(&chunks, &history));
+        let usage = &sections.usage;
+
+        assert!(usage.prompt_tokens() + usage.reserved_for_output <= usage.context_window);
+        assert!(usage.dropped_chunks > 0);
+        assert!(usage.dropped_history_messages > 0);
+        assert_eq!(sections.included_chunk_ids[0], 1);
+        assert!(
+            sections
+                .included_chunk_ids
+                .windows(2)
+                .all(|pair| pair[0] < pair[1])
+        );
+        assert!(sections.history.ends_with(&history[9].content));
+        assert!(!sections.history.contains("message 1 "));
+    }
+
+    #[test]
+    fn test_unused_history_budget_goes_to_rules() {
+        let chunks: Vec<_> = (1..=6).map(|id| chunk(id, 300)).collect();
+        let budget = PromptBudget::new(Tokenizer::for_model("gpt-4"), 2_048, 512);
+
+        let with_history: Vec<_> = (1..=10)
+            .map(|id| message(id, &"word ".repeat(80)))
+            .collect();
+        let crowded = budget.allocate("You explain rules.", &inputs(&chunks, &with_history));
+        let roomy = budget.allocate("You explain rules.", &inputs(&chunks, &[]));
+
+        assert!(roomy.usage.rules_context > crowded.usage.rules_context);
+    }
+
+    #[test]
+    fn test_reserved_tokens_shrink_sections() {
+        let chunks: Vec<_> = (1..=12).map(|id| chunk(id, 300)).collect();
+        let budget = PromptBudget::new(Tokenizer::for_model("gpt-4"), 4_096, 512);
+        let full = budget.allocate("You explain rules.", &inputs(&chunks, &[]));
+        let reserved = PromptBudget::new(Tokenizer::for_model("gpt-4"), 4_096, 512)
+            .with_reserved_tokens(1_500)
+            .allocate("You explain rules.", &inputs(&chunks, &[]));
+
+        assert!(reserved.usage.rules_context + 1_000 < full.usage.rules_context);
+    }
+}
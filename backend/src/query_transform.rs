@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+
+use crate::llm::{ChatMessage, LLMClient};
+use crate::models::{EmbeddingId, EmbeddingSearchResult, QueryStrategy};
+
+/// Model-written variants searched by the multi-query strategy, besides the question itself
+const MULTI_QUERY_VARIANTS: usize = 3;
+
+/// Rank offset for reciprocal rank fusion; 60 is the value from the original paper
+const RRF_K: f32 = 60.0;
+
+const TRANSFORM_MAX_TOKENS: u16 = 256;
+
+/// Texts to embed for a question, always at least one.
+///
+/// LLM strategies fall back to the plain question when the model fails, so a
+/// flaky rewrite never blocks retrieval.
+pub async fn transform_query(
+    llm: &LLMClient,
+    strategy: QueryStrategy,
+    question: &str,
+    game_name: &str,
+) -> Vec<String> {
+    let transformed = match strategy {
+        QueryStrategy::Direct => return vec![question.to_string()],
+        QueryStrategy::Keywords => return vec![keyword_expansion(question)],
+        QueryStrategy::Rewrite => complete(
+            llm,
+            format!(
+                "You turn players' questions about the board game {} into search queries for its rulebook. \
+                 Use the rulebook's terminology, keep the key nouns and drop filler words. \
+                 Reply with only the query.",
+                game_name
+            ),
+            question,
+        )
+        .await
+        .map(|query| vec![query]),
+        QueryStrategy::Hyde => complete(
+            llm,
+            format!(
+                "You write passages from the rulebook of the board game {}. \
+                 Write a short passage of two to four sentences, in the style of a rulebook, \
+                 that answers the player's question. Reply with only the passage.",
+                game_name
+            ),
+            question,
+        )
+        .await
+        .map(|passage| vec![passage]),
+        QueryStrategy::MultiQuery => complete(
+            llm,
+            format!(
+                "Write {} different search queries for the rulebook of the board game {} \
+                 that together cover the player's question. \
+                 Reply with one query per line and nothing else.",
+                MULTI_QUERY_VARIANTS, game_name
+            ),
+            question,
+        )
+        .await
+        .map(|reply| {
+            let mut queries = vec![question.to_string()];
+            for query in parse_query_lines(&reply) {
+                if queries.len() > MULTI_QUERY_VARIANTS {
+                    break;
+                }
+                if !queries.iter().any(|q| q.eq_ignore_ascii_case(&query)) {
+                    queries.push(query);
+                }
+            }
+            queries
+        }),
+    };
+
+    match transformed {
+        Some(queries) => queries,
+        None => vec![question.to_string()],
+    }
+}
+
+async fn complete(llm: &LLMClient, system_prompt: String, question: &str) -> Option<String> {
+    match llm
+        .chat_completion(
+            vec![ChatMessage::user(question)],
+            Some(system_prompt),
+            Some(TRANSFORM_MAX_TOKENS),
+            Some(0.0),
+        )
+        .await
+    {
+        Ok(reply) => {
+            let reply = clean_line(&reply);
+            (!reply.is_empty()).then_some(reply)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Query transformation failed, using the question as is: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Split a one-query-per-line reply, dropping numbering, bullets and quotes
+pub fn parse_query_lines(reply: &str) -> Vec<String> {
+    reply
+        .lines()
+        .map(clean_line)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Drop list numbering such as "2." or "3)", keeping numbers that start the query itself
+fn strip_numbering(line: &str) -> &str {
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == line.len() {
+        return line;
+    }
+    match rest.strip_prefix(['.', ')']) {
+        Some(text) if text.is_empty() || text.starts_with(char::is_whitespace) => text,
+        _ => line,
+    }
+}
+
+fn clean_line(line: &str) -> String {
+    strip_numbering(line.trim())
+        .trim_start_matches(['-', '*', '•'])
+        .trim()
+        .trim_matches(['"', '\'', '`'])
+        .trim()
+        .to_string()
+}
+
+/// Merge rankings from several queries with reciprocal rank fusion.
+///
+/// Each chunk keeps its best similarity score, so thresholds still read as cosine similarity.
+pub fn fuse_results(
+    rankings: Vec<Vec<EmbeddingSearchResult>>,
+    limit: usize,
+) -> Vec<EmbeddingSearchResult> {
+    if rankings.len() == 1 {
+        let mut ranking = rankings.into_iter().next().unwrap_or_default();
+        ranking.truncate(limit);
+        return ranking;
+    }
+
+    let mut fused: HashMap<EmbeddingId, (f32, EmbeddingSearchResult)> = HashMap::new();
+    for ranking in rankings {
+        for (rank, result) in ranking.into_iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f32 + 1.0);
+            fused
+                .entry(result.id)
+                .and_modify(|(total, best)| {
+                    *total += score;
+                    if result.similarity_score > best.similarity_score {
+                        best.similarity_score = result.similarity_score;
+                    }
+                })
+                .or_insert((score, result));
+        }
+    }
+
+    let mut fused: Vec<(f32, EmbeddingSearchResult)> = fused.into_values().collect();
+    fused.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .total_cmp(a_score)
+            .then(b.similarity_score.total_cmp(&a.similarity_score))
+            .then(a.id.cmp(&b.id))
+    });
+    fused
+        .into_iter()
+        .take(limit)
+        .map(|(_, result)| result)
+        .collect()
+}
+
+/// Recall and reciprocal rank of a retrieval against the chunks it should have found
+pub fn retrieval_scores(retrieved: &[EmbeddingId], expected: &[EmbeddingId]) -> (f32, f32) {
+    if expected.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let found = expected.iter().filter(|id| retrieved.contains(id)).count();
+    let reciprocal_rank = retrieved
+        .iter()
+        .position(|id| expected.contains(id))
+        .map_or(0.0, |rank| 1.0 / (rank as f32 + 1.0));
+
+    (found as f32 / expected.len() as f32, reciprocal_rank)
+}
+
+/// Expand a query with rulebook phrasings from a fixed English keyword table
+pub fn keyword_expansion(query: &str) -> String {
+    let query_lower = query.to_lowercase();
+    let mut enhanced_parts = Vec::new();
+
+    // Convert questions to statement form for better embedding matching
+    if query_lower.starts_with("how do i") || query_lower.starts_with("how to") {
+        let without_prefix = query_lower
+            .strip_prefix("how do i ")
+            .or_else(|| query_lower.strip_prefix("how to "))
+            .unwrap_or(&query_lower);
+        enhanced_parts.push(without_prefix.to_string());
+        enhanced_parts.push(format!("rules for {}", without_prefix));
+        enhanced_parts.push(format!("instructions {}", without_prefix));
+    } else if query_lower.starts_with("what") {
+        if query_lower.contains("happens") {
+            enhanced_parts.push(query_lower.replace("what happens", "when"));
+            enhanced_parts.push(query_lower.replace("what happens", "rules"));
+        } else if query_lower.contains("can i") || query_lower.contains("may i") {
+            enhanced_parts.push(query_lower.replace("what can i", "player may"));
+            enhanced_parts.push(query_lower.replace("what may i", "player may"));
+            enhanced_parts.push("allowed actions".to_string());
+        } else {
+            enhanced_parts.push(query_lower.clone());
+        }
+    } else if query_lower.starts_with("when") {
+        enhanced_parts.push(query_lower.clone());
+        enhanced_parts.push(query_lower.replace("when", "if"));
+        enhanced_parts.push("timing rules".to_string());
+    } else if query_lower.starts_with("can i") || query_lower.starts_with("may i") {
+        let without_prefix = query_lower
+            .strip_prefix("can i ")
+            .or_else(|| query_lower.strip_prefix("may i "))
+            .unwrap_or(&query_lower);
+        enhanced_parts.push(format!("player may {}", without_prefix));
+        enhanced_parts.push(format!("allowed to {}", without_prefix));
+        enhanced_parts.push(without_prefix.to_string());
+    } else {
+        enhanced_parts.push(query_lower.clone());
+    }
+
+    // Add domain-specific game terms
+    let game_terms = extract_game_terms(&query_lower);
+    enhanced_parts.extend(game_terms);
+
+    // Join with the original query for comprehensive matching
+    let mut final_query = query.to_string();
+    if !enhanced_parts.is_empty() {
+        final_query.push(' ');
+        final_query.push_str(&enhanced_parts.join(" "));
+    }
+
+    final_query
+}
+
+/// Extract and enhance game-specific terms from the query
+fn extract_game_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+
+    // Common game concepts and their rule document equivalents
+    let concept_mappings = [
+        ("win", vec!["victory", "winning condition", "game end"]),
+        ("lose", vec!["defeat", "elimination", "losing condition"]),
+        ("turn", vec!["round", "phase", "player turn"]),
+        ("move", vec!["movement", "moving pieces", "relocate"]),
+        ("attack", vec!["combat", "battle", "fight"]),
+        ("defend", vec!["defense", "block", "protection"]),
+        ("points", vec!["score", "scoring", "victory points"]),
+        ("cards", vec!["hand", "deck", "draw"]),
+        ("dice", vec!["roll", "rolling", "die"]),
+        ("setup", vec!["preparation", "initial setup", "game setup"]),
+        ("end", vec!["finish", "conclusion", "game over"]),
+    ];
+
+    for (concept, equivalents) in &concept_mappings {
+        if query.contains(concept) {
+            terms.extend(equivalents.iter().map(|s| s.to_string()));
+        }
+    }
+
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EmbeddingSourceType;
+
+    fn result(id: EmbeddingId, similarity_score: f32) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id: 1,
+            chunk_text: format!("chunk {}", id),
+            similarity_score,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offline_strategies_skip_the_model() {
+        let llm = LLMClient::with_config("http://127.0.0.1:9/v1", "unused", "unused");
+
+        assert_eq!(
+            transform_query(&llm, QueryStrategy::Direct, "Can I trade?", "Catan").await,
+            vec!["Can I trade?".to_string()]
+        );
+        let expanded =
+            transform_query(&llm, QueryStrategy::Keywords, "How do I win?", "Catan").await;
+        assert!(expanded[0].contains("victory"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_failure_falls_back_to_question() {
+        let llm = LLMClient::with_config("http://127.0.0.1:9/v1", "unused", "unused");
+
+        for strategy in [
+            QueryStrategy::Rewrite,
+            QueryStrategy::Hyde,
+            QueryStrategy::MultiQuery,
+        ] {
+            assert_eq!(
+                transform_query(&llm, strategy, "Can I trade?", "Catan").await,
+                vec!["Can I trade?".to_string()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_query_lines() {
+        let reply = "1. robber movement rules\n\n- \"rolling a seven\"\n* knight card effects\n";
+        assert_eq!(
+            parse_query_lines(reply),
+            vec![
+                "robber movement rules".to_string(),
+                "rolling a seven".to_string(),
+                "knight card effects".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_query_lines_keeps_leading_numbers() {
+        let reply = "5 resource cards hand limit\n2) 7 rolled with 8 cards\n3.5 victory points";
+        assert_eq!(
+            parse_query_lines(reply),
+            vec![
+                "5 resource cards hand limit".to_string(),
+                "7 rolled with 8 cards".to_string(),
+                "3.5 victory points".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fusion_rewards_agreement_across_queries() {
+        let fused = fuse_results(
+            vec![
+                vec![result(1, 0.9), result(2, 0.8), result(3, 0.7)],
+                vec![result(3, 0.85), result(4, 0.8), result(2, 0.6)],
+            ],
+            3,
+        );
+
+        let ids: Vec<EmbeddingId> = fused.iter().map(|r| r.id).collect();
+        // 2 and 3 appear in both rankings; 1 tops only one
+        assert_eq!(ids, vec![3, 2, 1]);
+        assert_eq!(fused[0].similarity_score, 0.85);
+    }
+
+    #[test]
+    fn test_single_ranking_is_passed_through() {
+        let fused = fuse_results(vec![vec![result(5, 0.5), result(6, 0.9)]], 1);
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].id, 5);
+    }
+
+    #[test]
+    fn test_retrieval_scores() {
+        assert_eq!(retrieval_scores(&[4, 2, 9], &[2, 7]), (0.5, 0.5));
+        assert_eq!(retrieval_scores(&[4, 9], &[2]), (0.0, 0.0));
+        assert_eq!(retrieval_scores(&[2], &[]), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,304 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::{
+    answers,
+    llm::{ChatMessage, LLMClient},
+    models::{EmbeddingId, EmbeddingSearchResult, QuickReferenceBullet, QuickReferenceSection},
+};
+
+/// Sections every sheet covers, with the text searched to find their rules
+pub const SECTIONS: [(&str, &str); 4] = [
+    (
+        "Setup",
+        "game setup: components each player receives, board preparation, starting positions",
+    ),
+    (
+        "Turn Structure",
+        "turn order and the phases or steps of a player's turn",
+    ),
+    (
+        "Actions",
+        "actions a player can take on their turn and what each one does",
+    ),
+    (
+        "End Game & Scoring",
+        "when the game ends, how points are scored and who wins",
+    ),
+];
+
+const QUICK_REFERENCE_MAX_TOKENS: u16 = 2048;
+
+/// Text searched for one section's rules, mentioning the player count when there is one
+pub fn section_query(query: &str, player_count: Option<i32>) -> String {
+    match player_count {
+        Some(count) => format!("{} with {} players", query, count),
+        None => query.to_string(),
+    }
+}
+
+/// Rulebook page a chunk starts on, recorded in its metadata at upload
+pub fn chunk_page(metadata: Option<&str>) -> Option<u32> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata?).ok()?;
+    metadata.get("page")?.as_u64().map(|page| page as u32)
+}
+
+/// Summarize the rulebook passages into the quick-reference sections
+pub async fn generate_quick_reference(
+    llm: &LLMClient,
+    game_name: &str,
+    player_count: Option<i32>,
+    passages: &[EmbeddingSearchResult],
+) -> Result<Vec<QuickReferenceSection>> {
+    if passages.is_empty() {
+        bail!("No rulebook passages to summarize");
+    }
+
+    let raw = llm
+        .chat_completion_json(
+            vec![ChatMessage::user(match player_count {
+                Some(count) => format!("Write the quick reference for {} players.", count),
+                None => "Write the quick reference.".to_string(),
+            })],
+            Some(quick_reference_prompt(game_name, player_count, passages)),
+            Some(QUICK_REFERENCE_MAX_TOKENS),
+            Some(0.2),
+        )
+        .await
+        .context("Failed to get quick reference from the LLM")?;
+
+    let sections = parse_quick_reference(&raw, passages);
+    if sections.is_empty() {
+        bail!("Quick reference response has no cited bullet points");
+    }
+    Ok(sections)
+}
+
+fn quick_reference_prompt(
+    game_name: &str,
+    player_count: Option<i32>,
+    passages: &[EmbeddingSearchResult],
+) -> String {
+    let passages = passages
+        .iter()
+        .map(|passage| match chunk_page(passage.metadata.as_deref()) {
+            Some(page) => format!(
+                "[chunk {}, page {}] {}",
+                passage.id, page, passage.chunk_text
+            ),
+            None => format!("[chunk {}] {}", passage.id, passage.chunk_text),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let titles = SECTIONS
+        .iter()
+        .map(|(title, _)| format!("\"{}\"", title))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let players = match player_count {
+        Some(count) => format!(
+            "The sheet is for a {}-player game: include only the setup and rules that apply \
+with {} players, and call out anything that changes at that player count.\n",
+            count, count
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        "You write a one-page quick reference for the board game {} to keep on the table \
+during play. Below are passages from its rulebook.
+
+Rulebook passages:
+{}
+
+Write the sections {} in that order, each as short, imperative bullet points a player can \
+scan mid-game. Use only the passages and cite the passages each bullet relies on.
+{}Respond with a single JSON object and nothing else, in the form
+{{\"sections\": [{{\"title\": \"...\", \"bullets\": [{{\"text\": \"...\", \"cited_chunk_ids\": [<ids of [chunk N] passages>]}}]}}]}}",
+        game_name, passages, titles, players
+    )
+}
+
+#[derive(Deserialize)]
+struct RawQuickReference {
+    #[serde(default)]
+    sections: Vec<RawSection>,
+}
+
+#[derive(Deserialize)]
+struct RawSection {
+    #[serde(default)]
+    title: String,
+    #[serde(default, alias = "points")]
+    bullets: Vec<RawBullet>,
+}
+
+#[derive(Deserialize)]
+struct RawBullet {
+    #[serde(default)]
+    text: String,
+    #[serde(default, alias = "citations", alias = "cited_chunks")]
+    cited_chunk_ids: Vec<serde_json::Value>,
+}
+
+/// Keep bullets citing at least one provided passage, and sections left with any bullets
+fn parse_quick_reference(
+    raw: &str,
+    passages: &[EmbeddingSearchResult],
+) -> Vec<QuickReferenceSection> {
+    let Some(parsed) = answers::extract_json_object(raw)
+        .and_then(|json| serde_json::from_str::<RawQuickReference>(json).ok())
+    else {
+        tracing::warn!("Quick reference response is not in the requested format");
+        return Vec::new();
+    };
+
+    parsed
+        .sections
+        .into_iter()
+        .filter_map(|section| {
+            let bullets: Vec<QuickReferenceBullet> = section
+                .bullets
+                .into_iter()
+                .filter_map(|bullet| parse_bullet(bullet, passages))
+                .collect();
+            let title = section.title.trim();
+            (!title.is_empty() && !bullets.is_empty()).then(|| QuickReferenceSection {
+                title: title.to_string(),
+                bullets,
+            })
+        })
+        .collect()
+}
+
+fn parse_bullet(
+    bullet: RawBullet,
+    passages: &[EmbeddingSearchResult],
+) -> Option<QuickReferenceBullet> {
+    let text = bullet
+        .text
+        .trim()
+        .trim_start_matches(['-', '*', '•'])
+        .trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut cited_chunks: Vec<EmbeddingId> = Vec::new();
+    let mut pages: Vec<u32> = Vec::new();
+    for value in &bullet.cited_chunk_ids {
+        let id = value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(answers::parse_chunk_reference));
+        let Some(passage) = id.and_then(|id| passages.iter().find(|p| p.id == id)) else {
+            continue;
+        };
+        if !cited_chunks.contains(&passage.id) {
+            cited_chunks.push(passage.id);
+        }
+        if let Some(page) = chunk_page(passage.metadata.as_deref()) {
+            pages.push(page);
+        }
+    }
+    if cited_chunks.is_empty() {
+        return None;
+    }
+
+    pages.sort_unstable();
+    pages.dedup();
+    Some(QuickReferenceBullet {
+        text: text.to_string(),
+        pages,
+        cited_chunks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{llm::MockReply, models::EmbeddingSourceType};
+
+    fn passage(id: EmbeddingId, page: Option<u32>, text: &str) -> EmbeddingSearchResult {
+        EmbeddingSearchResult {
+            id,
+            game_id: 1,
+            chunk_text: text.to_string(),
+            similarity_score: 0.9,
+            source_type: EmbeddingSourceType::RulesPdf,
+            source_id: None,
+            metadata: page.map(|page| serde_json::json!({ "page": page }).to_string()),
+        }
+    }
+
+    #[test]
+    fn test_chunk_page() {
+        assert_eq!(
+            chunk_page(Some(r#"{"file_name": "a.pdf", "page": 4}"#)),
+            Some(4)
+        );
+        assert_eq!(chunk_page(Some(r#"{"file_name": "a.pdf"}"#)), None);
+        assert_eq!(chunk_page(None), None);
+    }
+
+    #[test]
+    fn test_parse_quick_reference_keeps_cited_bullets() {
+        let passages = vec![
+            passage(1, Some(2), "Each player takes a ship and five coins."),
+            passage(2, Some(5), "The game ends when the market deck runs out."),
+            passage(3, None, "Players score one point per coin."),
+        ];
+        let raw = r#"{"sections": [
+            {"title": "Setup", "bullets": [
+                {"text": "- Take a ship and 5 coins", "cited_chunk_ids": [1, "chunk 1"]},
+                {"text": "Shuffle everything", "cited_chunk_ids": []}
+            ]},
+            {"title": "End Game & Scoring", "bullets": [
+                {"text": "Ends when the market deck is empty; 1 point per coin", "cited_chunk_ids": [3, 2, 99]}
+            ]},
+            {"title": "Actions", "bullets": [{"text": "Invented", "cited_chunk_ids": [42]}]}
+        ]}"#;
+
+        let sections = parse_quick_reference(raw, &passages);
+
+        assert_eq!(
+            sections,
+            vec![
+                QuickReferenceSection {
+                    title: "Setup".to_string(),
+                    bullets: vec![QuickReferenceBullet {
+                        text: "Take a ship and 5 coins".to_string(),
+                        pages: vec![2],
+                        cited_chunks: vec![1],
+                    }],
+                },
+                QuickReferenceSection {
+                    title: "End Game & Scoring".to_string(),
+                    bullets: vec![QuickReferenceBullet {
+                        text: "Ends when the market deck is empty; 1 point per coin".to_string(),
+                        pages: vec![5],
+                        cited_chunks: vec![3, 2],
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_quick_reference_rejects_unusable_responses() {
+        let passages = vec![passage(1, Some(1), "Each player takes a ship.")];
+
+        let llm = LLMClient::scripted(vec![MockReply::Text("Here you go!".to_string())]);
+        assert!(
+            generate_quick_reference(&llm, "Harbor Traders", Some(3), &passages)
+                .await
+                .is_err()
+        );
+
+        let llm = LLMClient::scripted(Vec::new());
+        assert!(
+            generate_quick_reference(&llm, "Harbor Traders", None, &[])
+                .await
+                .is_err()
+        );
+    }
+}
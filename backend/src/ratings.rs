@@ -0,0 +1,310 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::models::{Play, PlayPlayer, PlayerId, RatingChange, Standing};
+
+/// Rating every player starts from
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Most a rating can move in one play
+const K_FACTOR: f64 = 32.0;
+
+/// Rating difference at which the stronger side is expected to win ten times as often
+const RATING_SCALE: f64 = 400.0;
+
+/// A player's rating and record after a run of plays
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerRating {
+    pub player_id: PlayerId,
+    pub name: String,
+    pub rating: f64,
+    pub plays: i64,
+    pub wins: i64,
+}
+
+/// Ratings after every play, and how each play changed them
+#[derive(Debug, Default)]
+pub struct RatingRun {
+    pub ratings: HashMap<PlayerId, PlayerRating>,
+    pub history: Vec<RatingChange>,
+}
+
+/// A team, or a player without one, competing in a play
+struct Side<'a> {
+    members: Vec<&'a PlayPlayer>,
+    winner: bool,
+    score: Option<i64>,
+}
+
+/// Group a play's participants into sides, keeping the order they were listed in
+fn sides<'a>(players: impl Iterator<Item = &'a PlayPlayer>) -> Vec<Side<'a>> {
+    let mut sides: Vec<Side> = Vec::new();
+    let mut teams: HashMap<String, usize> = HashMap::new();
+
+    for player in players {
+        let index = match player.team.as_ref().map(|team| team.to_lowercase()) {
+            Some(team) if teams.contains_key(&team) => teams[&team],
+            team => {
+                if let Some(team) = team {
+                    teams.insert(team, sides.len());
+                }
+                sides.push(Side {
+                    members: Vec::new(),
+                    winner: false,
+                    score: None,
+                });
+                sides.len() - 1
+            }
+        };
+
+        let side = &mut sides[index];
+        side.members.push(player);
+        side.winner |= player.winner;
+        side.score = side.score.max(player.score);
+    }
+    sides
+}
+
+/// How side `a` fared against side `b`: 1 for a win, 0.5 for a draw, 0 for a loss.
+/// Winners beat everyone else; otherwise the higher score wins when both have one
+fn outcome(a: &Side, b: &Side) -> f64 {
+    let ordering = a.winner.cmp(&b.winner).then(match (a.score, b.score) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => Ordering::Equal,
+    });
+    match ordering {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    }
+}
+
+fn expected(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / RATING_SCALE))
+}
+
+fn rounded(rating: f64) -> f64 {
+    (rating * 10.0).round() / 10.0
+}
+
+/// Rate players over plays in the order they happened.
+///
+/// Each play is scored as a set of head-to-head results between its sides, with
+/// K spread across a side's opponents so big games move ratings no more than duels.
+/// A team is rated at its members' average and every member gets the team's change.
+/// Only participants accepted by `counts` take part; plays left with fewer than
+/// two sides are skipped.
+pub fn rate_plays(plays: &[Play], counts: impl Fn(PlayerId) -> bool) -> RatingRun {
+    let mut run = RatingRun::default();
+
+    for play in plays {
+        let sides = sides(
+            play.players
+                .iter()
+                .filter(|player| counts(player.player_id)),
+        );
+        if sides.len() < 2 {
+            continue;
+        }
+
+        let rating_of = |player_id: PlayerId, ratings: &HashMap<PlayerId, PlayerRating>| {
+            ratings
+                .get(&player_id)
+                .map_or(INITIAL_RATING, |rating| rating.rating)
+        };
+        let side_ratings: Vec<f64> = sides
+            .iter()
+            .map(|side| {
+                side.members
+                    .iter()
+                    .map(|player| rating_of(player.player_id, &run.ratings))
+                    .sum::<f64>()
+                    / side.members.len() as f64
+            })
+            .collect();
+
+        let opponents = (sides.len() - 1) as f64;
+        for (index, side) in sides.iter().enumerate() {
+            let change: f64 = sides
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != index)
+                .map(|(other, opponent)| {
+                    outcome(side, opponent) - expected(side_ratings[index], side_ratings[other])
+                })
+                .sum::<f64>()
+                * K_FACTOR
+                / opponents;
+
+            for player in &side.members {
+                let rating_before = rating_of(player.player_id, &run.ratings);
+                let entry = run
+                    .ratings
+                    .entry(player.player_id)
+                    .or_insert_with(|| PlayerRating {
+                        player_id: player.player_id,
+                        name: player.name.clone(),
+                        rating: INITIAL_RATING,
+                        plays: 0,
+                        wins: 0,
+                    });
+                entry.rating = rating_before + change;
+                entry.plays += 1;
+                entry.wins += i64::from(side.winner);
+
+                run.history.push(RatingChange {
+                    play_id: play.id,
+                    game_id: play.game_id,
+                    played_at: play.played_at,
+                    player_id: player.player_id,
+                    rating_before: rounded(rating_before),
+                    rating_after: rounded(rating_before + change),
+                });
+            }
+        }
+    }
+
+    run
+}
+
+/// The league table: highest rating first, equal ratings sharing a rank
+pub fn standings(run: &RatingRun) -> Vec<Standing> {
+    let mut ratings: Vec<&PlayerRating> = run.ratings.values().collect();
+    ratings.sort_by(|a, b| {
+        b.rating
+            .total_cmp(&a.rating)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    let mut standings: Vec<Standing> = Vec::with_capacity(ratings.len());
+    for (position, rating) in ratings.into_iter().enumerate() {
+        let rating_value = rounded(rating.rating);
+        let rank = match standings.last() {
+            Some(previous) if previous.rating == rating_value => previous.rank,
+            _ => position as u32 + 1,
+        };
+        standings.push(Standing {
+            rank,
+            player_id: rating.player_id,
+            name: rating.name.clone(),
+            rating: rating_value,
+            plays: rating.plays,
+            wins: rating.wins,
+        });
+    }
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn player(
+        player_id: PlayerId,
+        score: Option<i64>,
+        winner: bool,
+        team: Option<&str>,
+    ) -> PlayPlayer {
+        PlayPlayer {
+            player_id,
+            name: format!("Player {}", player_id),
+            score,
+            winner,
+            team: team.map(str::to_string),
+        }
+    }
+
+    fn play(id: i64, players: Vec<PlayPlayer>) -> Play {
+        Play {
+            id,
+            game_id: 1,
+            game_name: "Harbor Traders".to_string(),
+            played_at: Utc::now(),
+            duration_minutes: None,
+            location: None,
+            notes: None,
+            expansion_ids: Vec::new(),
+            players,
+            chat_session_ids: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_duel_moves_ratings_symmetrically() {
+        let run = rate_plays(
+            &[play(
+                1,
+                vec![player(1, None, true, None), player(2, None, false, None)],
+            )],
+            |_| true,
+        );
+
+        assert_eq!(run.ratings[&1].rating, INITIAL_RATING + K_FACTOR / 2.0);
+        assert_eq!(run.ratings[&2].rating, INITIAL_RATING - K_FACTOR / 2.0);
+        assert_eq!(run.ratings[&1].wins, 1);
+        assert_eq!(run.history.len(), 2);
+    }
+
+    #[test]
+    fn test_free_for_all_ranks_by_winner_then_score() {
+        let run = rate_plays(
+            &[play(
+                1,
+                vec![
+                    player(1, Some(20), false, None),
+                    player(2, Some(30), true, None),
+                    player(3, Some(25), false, None),
+                ],
+            )],
+            |_| true,
+        );
+
+        let table = standings(&run);
+        let order: Vec<PlayerId> = table.iter().map(|s| s.player_id).collect();
+        assert_eq!(order, vec![2, 3, 1]);
+        assert_eq!(table[1].rating, INITIAL_RATING);
+        let total: f64 = run.ratings.values().map(|r| r.rating).sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_teammates_share_the_team_result() {
+        let run = rate_plays(
+            &[play(
+                1,
+                vec![
+                    player(1, None, true, Some("Red")),
+                    player(2, None, false, Some("red")),
+                    player(3, None, false, Some("Blue")),
+                    player(4, None, false, Some("Blue")),
+                ],
+            )],
+            |_| true,
+        );
+
+        assert_eq!(run.ratings[&1].rating, run.ratings[&2].rating);
+        assert_eq!(run.ratings[&2].wins, 1);
+        assert_eq!(run.ratings[&3].rating, INITIAL_RATING - K_FACTOR / 2.0);
+    }
+
+    #[test]
+    fn test_non_members_and_solo_plays_are_skipped() {
+        let plays = [
+            play(
+                1,
+                vec![player(1, None, true, None), player(9, None, false, None)],
+            ),
+            play(
+                2,
+                vec![player(1, None, true, None), player(2, None, false, None)],
+            ),
+        ];
+        let run = rate_plays(&plays, |player_id| player_id != 9);
+
+        assert!(!run.ratings.contains_key(&9));
+        assert_eq!(run.ratings[&1].plays, 1);
+        assert!(run.history.iter().all(|change| change.play_id == 2));
+    }
+}
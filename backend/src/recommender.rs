@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    answers,
+    llm::{ChatMessage, LLMClient},
+    models::{Game, GameId, Recommendation},
+};
+
+/// Most picks one request can ask for
+pub const MAX_RECOMMENDATIONS: usize = 20;
+
+/// Games rated at least this highly count as liked
+pub const LIKED_RATING: f64 = 8.0;
+
+/// How many of the group's most played games count as liked
+pub const LIKED_FROM_PLAYS: u32 = 5;
+
+/// A game last played this many days ago is as fresh as one never played
+const FRESHNESS_HORIZON_DAYS: f64 = 180.0;
+
+const FRESHNESS_WEIGHT: f64 = 0.3;
+const RATING_WEIGHT: f64 = 0.3;
+const SIMILARITY_WEIGHT: f64 = 0.4;
+
+const JUSTIFICATION_MAX_TOKENS: u16 = 800;
+
+/// Description characters shown to the LLM per game
+const DESCRIPTION_EXCERPT_CHARS: usize = 300;
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    (dot / (norm_a * norm_b)) as f64
+}
+
+/// The liked game whose description is closest to the candidate's, other than
+/// the candidate itself, with the similarity clamped to 0 to 1
+pub fn closest_liked(
+    candidate_id: GameId,
+    embedding: &[f32],
+    liked: &[(GameId, Vec<f32>)],
+) -> Option<(GameId, f64)> {
+    liked
+        .iter()
+        .filter(|(game_id, _)| *game_id != candidate_id)
+        .map(|(game_id, liked_embedding)| {
+            (
+                *game_id,
+                cosine_similarity(embedding, liked_embedding).clamp(0.0, 1.0),
+            )
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// 0 for a game played just now, rising to 1 for one not played in
+/// `FRESHNESS_HORIZON_DAYS` or never played
+pub fn freshness(last_played_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> f64 {
+    match last_played_at {
+        Some(played_at) => {
+            let days = (now - played_at).num_seconds() as f64 / 86_400.0;
+            (days / FRESHNESS_HORIZON_DAYS).clamp(0.0, 1.0)
+        }
+        None => 1.0,
+    }
+}
+
+/// Weighted mean of the signals a game has; unrated games and games with no
+/// description to compare are judged on the rest
+pub fn score(freshness: f64, rating: Option<f64>, similarity: Option<f64>) -> f64 {
+    let signals = [
+        Some((freshness, FRESHNESS_WEIGHT)),
+        rating.map(|rating| ((rating - 1.0) / 9.0, RATING_WEIGHT)),
+        similarity.map(|similarity| (similarity, SIMILARITY_WEIGHT)),
+    ];
+    let (total, weights) = signals
+        .into_iter()
+        .flatten()
+        .fold((0.0, 0.0), |(total, weights), (value, weight)| {
+            (total + value * weight, weights + weight)
+        });
+    total / weights
+}
+
+/// Score the candidates and keep the best `limit`, ties going to the name
+pub fn rank(
+    candidates: &[Game],
+    ratings: &HashMap<GameId, f64>,
+    similarities: &HashMap<GameId, (GameId, f64)>,
+    now: DateTime<Utc>,
+    limit: usize,
+) -> Vec<Recommendation> {
+    let mut recommendations: Vec<Recommendation> = candidates
+        .iter()
+        .map(|game| {
+            let rating = ratings.get(&game.id).copied();
+            let similar = similarities.get(&game.id).copied();
+            Recommendation {
+                game_id: game.id,
+                game_name: game.name.clone(),
+                min_players: game.min_players,
+                max_players: game.max_players,
+                play_time_minutes: game.play_time_minutes,
+                complexity_rating: game.complexity_rating,
+                last_played_at: game.last_played_at,
+                rating,
+                similarity: similar.map(|(_, similarity)| similarity),
+                similar_to: similar.map(|(game_id, _)| game_id),
+                score: score(
+                    freshness(game.last_played_at, now),
+                    rating,
+                    similar.map(|(_, similarity)| similarity),
+                ),
+                justification: None,
+            }
+        })
+        .collect();
+
+    recommendations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.game_name.to_lowercase().cmp(&b.game_name.to_lowercase()))
+    });
+    recommendations.truncate(limit);
+    recommendations
+}
+
+/// Ask the LLM for a sentence per pick on why it suits the group, keyed by game
+pub async fn justify(
+    llm: &LLMClient,
+    players: i32,
+    max_play_time: Option<i32>,
+    liked: &[&Game],
+    picks: &[&Game],
+) -> Result<HashMap<GameId, String>> {
+    let raw = llm
+        .chat_completion_json(
+            vec![ChatMessage::user(match max_play_time {
+                Some(minutes) => format!(
+                    "We are {} players with {} minutes. Why should we play each of these?",
+                    players, minutes
+                ),
+                None => format!(
+                    "We are {} players. Why should we play each of these?",
+                    players
+                ),
+            })],
+            Some(justification_prompt(liked, picks)),
+            Some(JUSTIFICATION_MAX_TOKENS),
+            Some(0.3),
+        )
+        .await
+        .context("Failed to get recommendation justifications from the LLM")?;
+
+    parse_justifications(&raw, picks)
+}
+
+fn game_line(game: &Game) -> String {
+    let mut line = format!("[game {}] {}", game.id, game.name);
+    if let (Some(min), Some(max)) = (game.min_players, game.max_players) {
+        line.push_str(&format!(", {}-{} players", min, max));
+    }
+    if let Some(minutes) = game.play_time_minutes {
+        line.push_str(&format!(", {} minutes", minutes));
+    }
+    if let Some(complexity) = game.complexity_rating {
+        line.push_str(&format!(", complexity {:.1}/5", complexity));
+    }
+    if let Some(description) = game.description.as_deref() {
+        let excerpt: String = description
+            .chars()
+            .take(DESCRIPTION_EXCERPT_CHARS)
+            .collect();
+        line.push_str(&format!(": {}", excerpt.trim()));
+    }
+    line
+}
+
+fn justification_prompt(liked: &[&Game], picks: &[&Game]) -> String {
+    let liked = if liked.is_empty() {
+        "(none known)".to_string()
+    } else {
+        liked
+            .iter()
+            .map(|game| game.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let picks = picks
+        .iter()
+        .map(|game| game_line(game))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "You help a group of friends pick a board game for tonight.
+
+Games the group liked before: {}
+
+Games picked for them:
+{}
+
+For every picked game write one short sentence on why it suits this group tonight, drawing on the player count, time and the games they liked.
+Respond with a single JSON object and nothing else, in the form
+{{\"justifications\": [{{\"game_id\": <id from [game N]>, \"reason\": \"<one sentence>\"}}]}}",
+        liked, picks
+    )
+}
+
+#[derive(Deserialize)]
+struct RawJustifications {
+    #[serde(default)]
+    justifications: Vec<RawJustification>,
+}
+
+#[derive(Deserialize)]
+struct RawJustification {
+    game_id: serde_json::Value,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Keep the reasons given for games that were picked
+fn parse_justifications(raw: &str, picks: &[&Game]) -> Result<HashMap<GameId, String>> {
+    let json = answers::extract_json_object(raw).context("Justifications are not a JSON object")?;
+    let parsed: RawJustifications =
+        serde_json::from_str(json).context("Justifications do not match the requested format")?;
+
+    Ok(parsed
+        .justifications
+        .into_iter()
+        .filter_map(|justification| {
+            let game_id = justification.game_id.as_i64().or_else(|| {
+                justification
+                    .game_id
+                    .as_str()
+                    .and_then(|id| id.trim_start_matches("game").trim().parse().ok())
+            })?;
+            let reason = justification.reason.trim().to_string();
+            (picks.iter().any(|game| game.id == game_id) && !reason.is_empty())
+                .then_some((game_id, reason))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn game(id: GameId, name: &str, last_played_at: Option<DateTime<Utc>>) -> Game {
+        Game {
+            id,
+            name: name.to_string(),
+            description: Some(format!("{} is a game about trading.", name)),
+            publisher: None,
+            year_published: None,
+            min_players: Some(2),
+            max_players: Some(5),
+            play_time_minutes: Some(60),
+            complexity_rating: Some(2.5),
+            bgg_id: None,
+            rules_pdf_path: None,
+            rules_text: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_played_at,
+            base_game_id: None,
+        }
+    }
+
+    #[test]
+    fn test_freshness_grows_with_time_since_last_play() {
+        let now = Utc::now();
+        assert_eq!(freshness(None, now), 1.0);
+        assert_eq!(freshness(Some(now), now), 0.0);
+        assert!((freshness(Some(now - Duration::days(90)), now) - 0.5).abs() < 1e-9);
+        assert_eq!(freshness(Some(now - Duration::days(400)), now), 1.0);
+    }
+
+    #[test]
+    fn test_score_uses_only_the_signals_a_game_has() {
+        assert_eq!(score(0.5, None, None), 0.5);
+        assert!((score(1.0, Some(10.0), None) - 1.0).abs() < 1e-9);
+        let all = score(0.0, Some(1.0), Some(1.0));
+        assert!((all - SIMILARITY_WEIGHT).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rank_prefers_fresh_well_rated_similar_games() {
+        let now = Utc::now();
+        let candidates = vec![
+            game(1, "Harbor Traders", Some(now - Duration::days(2))),
+            game(2, "Lighthouse Keepers", None),
+            game(3, "Orchard Rush", None),
+        ];
+        let ratings = HashMap::from([(1, 9.0), (2, 8.0)]);
+        let similarities = HashMap::from([(2, (1, 0.9)), (3, (1, 0.2))]);
+
+        let picks = rank(&candidates, &ratings, &similarities, now, 2);
+        let order: Vec<GameId> = picks.iter().map(|pick| pick.game_id).collect();
+        assert_eq!(order, vec![2, 3]);
+        assert_eq!(picks[0].similar_to, Some(1));
+    }
+
+    #[test]
+    fn test_closest_liked_skips_the_game_itself() {
+        let liked = vec![(1, vec![1.0, 0.0]), (2, vec![0.6, 0.8])];
+        assert_eq!(closest_liked(1, &[1.0, 0.0], &liked).unwrap().0, 2);
+        assert_eq!(closest_liked(3, &[-1.0, 0.0], &liked).unwrap().1, 0.0);
+    }
+
+    #[test]
+    fn test_parse_justifications_keeps_picked_games() {
+        let picks = [game(4, "Harbor Traders", None)];
+        let picks: Vec<&Game> = picks.iter().collect();
+        let raw = r#"Here: {"justifications": [
+            {"game_id": 4, "reason": " Quick trading for five. "},
+            {"game_id": "game 9", "reason": "Not picked."}
+        ]}"#;
+
+        let reasons = parse_justifications(raw, &picks).unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[&4], "Quick trading for five.");
+    }
+}
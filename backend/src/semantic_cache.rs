@@ -0,0 +1,286 @@
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+
+use crate::db::{self, Database};
+use crate::models::{CachedAnswer, CreateCachedAnswerRequest, EmbeddingId, GameId};
+
+/// Cosine similarity a previous question needs before its answer is reused
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.92;
+
+/// Cached questions compared against each incoming question
+const CANDIDATE_LIMIT: u32 = 3;
+
+/// A cached answer that is still valid for the question being asked
+pub struct CacheHit {
+    pub answer: CachedAnswer,
+    pub similarity: f32,
+}
+
+/// Hash the chunks an answer was built from, so edited or removed rules text is detected
+pub fn source_fingerprint(chunks: &[(EmbeddingId, String)]) -> String {
+    let mut chunks: Vec<&(EmbeddingId, String)> = chunks.iter().collect();
+    chunks.sort_by_key(|(id, _)| *id);
+
+    let mut hasher = Sha256::new();
+    for (id, text) in chunks {
+        hasher.update(id.to_le_bytes());
+        hasher.update(text.as_bytes());
+        hasher.update([0u8]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Fingerprint of the chunks as they are stored right now
+pub async fn current_fingerprint(db: &Database, chunk_ids: &[EmbeddingId]) -> Result<String> {
+    let chunks = db::embeddings::get_chunk_texts(db, chunk_ids).await?;
+    if chunks.len() != chunk_ids.len() {
+        // A source chunk was deleted; no stored fingerprint can match this marker
+        return Ok("missing-chunks".to_string());
+    }
+    Ok(source_fingerprint(&chunks))
+}
+
+/// Find a previous answer to a near-identical question whose sources are unchanged.
+///
+/// Entries whose source chunks changed are deleted as they are found.
+pub async fn lookup(
+    db: &Database,
+    game_id: GameId,
+    question_embedding: &[f32],
+    threshold: f32,
+) -> Result<Option<CacheHit>> {
+    let candidates =
+        db::answer_cache::find_similar_answers(db, game_id, question_embedding, CANDIDATE_LIMIT)
+            .await?;
+
+    for (answer, similarity) in candidates {
+        if similarity < threshold {
+            break;
+        }
+
+        let chunk_ids: Vec<EmbeddingId> = answer
+            .context_sources
+            .iter()
+            .map(|source| source.embedding_id)
+            .collect();
+        if current_fingerprint(db, &chunk_ids).await? != answer.source_fingerprint {
+            tracing::debug!("Dropping stale cached answer {}", answer.id);
+            db::answer_cache::delete_cached_answer(db, answer.id).await?;
+            continue;
+        }
+
+        db::answer_cache::record_answer_cache_hit(db, answer.id).await?;
+        return Ok(Some(CacheHit { answer, similarity }));
+    }
+
+    Ok(None)
+}
+
+/// Remember an answer, fingerprinting its sources as they are now
+pub async fn store(db: &Database, mut request: CreateCachedAnswerRequest) -> Result<()> {
+    let chunk_ids: Vec<EmbeddingId> = request
+        .context_sources
+        .iter()
+        .map(|source| source.embedding_id)
+        .collect();
+    request.source_fingerprint = current_fingerprint(db, &chunk_ids).await?;
+
+    db::answer_cache::store_cached_answer(db, request).await?;
+    Ok(())
+}
+
+/// Forget cached answers after a game's rules, house rules or prompt change
+pub async fn invalidate(db: &Database, game_id: Option<GameId>) {
+    match db::answer_cache::invalidate_answer_cache(db, game_id).await {
+        Ok(0) => {}
+        Ok(removed) => tracing::info!(
+            "Invalidated {} cached answers for {}",
+            removed,
+            game_id.map_or("all games".to_string(), |id| format!("game {}", id))
+        ),
+        Err(e) => tracing::error!("Failed to invalidate answer cache: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ContextSource, CreateEmbeddingRequest, EmbeddingSourceType};
+    use rusqlite::{Connection, ffi::sqlite3_auto_extension};
+    use sqlite_vec::sqlite3_vec_init;
+
+    fn test_db() -> Database {
+        unsafe {
+            sqlite3_auto_extension(Some(std::mem::transmute::<
+                *const (),
+                unsafe extern "C" fn(
+                    *mut rusqlite::ffi::sqlite3,
+                    *mut *mut std::os::raw::c_char,
+                    *const rusqlite::ffi::sqlite3_api_routines,
+                ) -> std::os::raw::c_int,
+            >(sqlite3_vec_init as *const ())));
+        }
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!(
+            "../../migrations/V001__create_games_table.sql"
+        ))
+        .unwrap();
+        conn.execute_batch(include_str!(
+            "../../migrations/V002__create_house_rules_table.sql"
+        ))
+        .unwrap();
+        conn.execute_batch(include_str!(
+            "../../migrations/V003__create_embeddings_table.sql"
+        ))
+        .unwrap();
+        conn.execute_batch(include_str!(
+            "../../migrations/V009__create_answer_cache_tables.sql"
+        ))
+        .unwrap();
+        conn.execute(
+            "INSERT INTO games (id, name) VALUES (1, 'Catan'), (2, 'Carcassonne')",
+            [],
+        )
+        .unwrap();
+        Database::new(conn)
+    }
+
+    fn unit_vector(hot: usize) -> Vec<f32> {
+        let mut vector = vec![0.0; 768];
+        vector[hot] = 1.0;
+        vector
+    }
+
+    async fn add_chunk(db: &Database, text: &str) -> EmbeddingId {
+        db::embeddings::create_embeddings_batch(
+            db,
+            vec![CreateEmbeddingRequest {
+                game_id: 1,
+                chunk_text: text.to_string(),
+                embedding: unit_vector(700),
+                chunk_index: 0,
+                source_type: EmbeddingSourceType::RulesPdf,
+                source_id: None,
+                metadata: None,
+            }],
+        )
+        .await
+        .unwrap()[0]
+    }
+
+    fn request(chunk_id: EmbeddingId, embedding: Vec<f32>) -> CreateCachedAnswerRequest {
+        CreateCachedAnswerRequest {
+            game_id: 1,
+            question: "How does the robber work?".to_string(),
+            question_embedding: embedding,
+            answer: "Move it on a 7.".to_string(),
+            context_sources: vec![ContextSource {
+                embedding_id: chunk_id,
+                game_id: 1,
+                chunk_text: "When a 7 is rolled, move the robber.".to_string(),
+                source_type: "rules_pdf".to_string(),
+                similarity_score: 0.8,
+                metadata: None,
+                cited: true,
+            }],
+            cited_chunks: vec![chunk_id],
+            confidence: Some(0.9),
+            not_covered_by_rules: false,
+            structured: true,
+            source_fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_order_but_not_text() {
+        let a = vec![(1, "one".to_string()), (2, "two".to_string())];
+        let b = vec![(2, "two".to_string()), (1, "one".to_string())];
+        let c = vec![(1, "one".to_string()), (2, "two!".to_string())];
+
+        assert_eq!(source_fingerprint(&a), source_fingerprint(&b));
+        assert_ne!(source_fingerprint(&a), source_fingerprint(&c));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_hits_similar_question_for_same_game() {
+        let db = test_db();
+        let chunk_id = add_chunk(&db, "When a 7 is rolled, move the robber.").await;
+        store(&db, request(chunk_id, unit_vector(0))).await.unwrap();
+
+        let hit = lookup(&db, 1, &unit_vector(0), DEFAULT_SIMILARITY_THRESHOLD)
+            .await
+            .unwrap()
+            .expect("expected a cache hit");
+        assert_eq!(hit.answer.answer, "Move it on a 7.");
+        assert_eq!(hit.answer.cited_chunks, vec![chunk_id]);
+        assert!(hit.similarity > 0.99);
+
+        // Unrelated question and other game miss
+        assert!(
+            lookup(&db, 1, &unit_vector(5), DEFAULT_SIMILARITY_THRESHOLD)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            lookup(&db, 2, &unit_vector(0), DEFAULT_SIMILARITY_THRESHOLD)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let (entries, hits) = db::answer_cache::get_answer_cache_totals(&db, Some(1))
+            .await
+            .unwrap();
+        assert_eq!((entries, hits), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_changed_source_chunk_invalidates_entry() {
+        let db = test_db();
+        let chunk_id = add_chunk(&db, "When a 7 is rolled, move the robber.").await;
+        store(&db, request(chunk_id, unit_vector(0))).await.unwrap();
+
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE embeddings SET chunk_text = 'The robber moves on a 7 or a knight.' WHERE id = ?",
+                [chunk_id],
+            )
+        })
+        .unwrap();
+
+        assert!(
+            lookup(&db, 1, &unit_vector(0), DEFAULT_SIMILARITY_THRESHOLD)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let (entries, _) = db::answer_cache::get_answer_cache_totals(&db, None)
+            .await
+            .unwrap();
+        assert_eq!(entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_game() {
+        let db = test_db();
+        let chunk_id = add_chunk(&db, "When a 7 is rolled, move the robber.").await;
+        store(&db, request(chunk_id, unit_vector(0))).await.unwrap();
+
+        invalidate(&db, Some(2)).await;
+        assert!(
+            lookup(&db, 1, &unit_vector(0), DEFAULT_SIMILARITY_THRESHOLD)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        invalidate(&db, Some(1)).await;
+        assert!(
+            lookup(&db, 1, &unit_vector(0), DEFAULT_SIMILARITY_THRESHOLD)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
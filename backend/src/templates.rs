@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::models::{Game, PromptTemplateVariable};
+
+/// Longest template text we accept
+pub const MAX_TEMPLATE_CHARS: usize = 20_000;
+
+/// Persona used when a template does not set its own
+pub const DEFAULT_PERSONA: &str = "a helpful assistant that explains board game rules";
+
+/// System prompt used when neither the game nor the global scope has an active template
+pub const DEFAULT_TEMPLATE: &str = "You are {{persona}}. Use the following rules for {{game_name}} to answer questions accurately and clearly. If the rules don't contain enough information to answer the question, say so honestly.
+
+Game Rules Context:
+{{rules_context}}
+
+House Rules (these override the rulebook where they conflict):
+{{house_rules}}
+
+Conversation History:
+{{history}}
+
+Instructions:
+- Answer based on the provided rules context
+- Be concise but thorough
+- If rules are unclear or missing, acknowledge this
+- Use examples when helpful
+- Focus on practical gameplay guidance
+- If the context above is not enough, use the available tools to look up game details, house rules or more rules text";
+
+/// System prompt for sessions comparing several games; passages are labelled with their game
+pub const COMPARISON_TEMPLATE: &str = "You are {{persona}}. Players want to compare the rules of {{game_name}}. Each rules passage below is labelled with the game it comes from. Answer using only those passages.
+
+Game Rules Context:
+{{rules_context}}
+
+House Rules (these override the rulebook of the game they are labelled with):
+{{house_rules}}
+
+Conversation History:
+{{history}}
+
+Instructions:
+- Explain how each game handles the topic, then point out the differences and similarities
+- Always say which game a rule belongs to; never carry a rule over from one game to another
+- Cite passages from every game your answer covers
+- If one game's rules do not cover the question, say so for that game";
+
+/// Variables a template may use: (name, description, required)
+const VARIABLES: &[(&str, &str, bool)] = &[
+    (
+        "persona",
+        "Who the assistant should be; defaults to a helpful rules explainer",
+        false,
+    ),
+    ("game_name", "Name of the game", false),
+    ("game_description", "Catalog description of the game", false),
+    ("publisher", "Publisher of the game", false),
+    ("year_published", "Year the game was published", false),
+    (
+        "player_count",
+        "Supported player count, e.g. \"2-4\"",
+        false,
+    ),
+    ("play_time", "Typical play time, e.g. \"60 minutes\"", false),
+    ("complexity", "Complexity rating out of 5", false),
+    (
+        "rules_context",
+        "Rulebook passages retrieved for the question, trimmed to the token budget",
+        true,
+    ),
+    ("house_rules", "Active house rules for the game", false),
+    (
+        "history",
+        "Earlier messages in the conversation, oldest first",
+        false,
+    ),
+];
+
+fn variable_pattern() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z0-9_]*)\s*\}\}").unwrap()
+}
+
+/// Variables available to templates, for the editor
+pub fn template_variables() -> Vec<PromptTemplateVariable> {
+    VARIABLES
+        .iter()
+        .map(|(name, description, required)| PromptTemplateVariable {
+            name: name.to_string(),
+            description: description.to_string(),
+            required: *required,
+        })
+        .collect()
+}
+
+/// Check that a template only uses known variables and includes the required ones
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Template cannot be empty".to_string());
+    }
+    if template.chars().count() > MAX_TEMPLATE_CHARS {
+        return Err(format!(
+            "Template is longer than {} characters",
+            MAX_TEMPLATE_CHARS
+        ));
+    }
+
+    let pattern = variable_pattern();
+    let mut used = Vec::new();
+    for capture in pattern.captures_iter(template) {
+        let name = &capture[1];
+        if !VARIABLES.iter().any(|(known, _, _)| *known == name) {
+            return Err(format!("Unknown template variable {{{{{}}}}}", name));
+        }
+        used.push(name.to_string());
+    }
+
+    // Anything brace-like left over is a malformed placeholder
+    let remainder = pattern.replace_all(template, "");
+    if remainder.contains("{{") || remainder.contains("}}") {
+        return Err("Template has an unclosed or malformed {{variable}}".to_string());
+    }
+
+    for (name, _, required) in VARIABLES {
+        if *required && !used.iter().any(|used| used == name) {
+            return Err(format!("Template must include {{{{{}}}}}", name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Substitute variables in one pass, so values containing braces are never expanded
+pub fn render_template(template: &str, variables: &HashMap<&str, String>) -> String {
+    variable_pattern()
+        .replace_all(template, |capture: &regex::Captures| {
+            variables.get(&capture[1]).cloned().unwrap_or_default()
+        })
+        .into_owned()
+}
+
+fn persona_or_default(persona: Option<&str>) -> String {
+    persona
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or(DEFAULT_PERSONA)
+        .to_string()
+}
+
+/// Template variables describing the game and persona; retrieval sections are added per question
+pub fn game_variables(game: &Game, persona: Option<&str>) -> HashMap<&'static str, String> {
+    let player_count = match (game.min_players, game.max_players) {
+        (Some(min), Some(max)) if min == max => min.to_string(),
+        (Some(min), Some(max)) => format!("{}-{}", min, max),
+        (Some(min), None) => format!("{}+", min),
+        (None, Some(max)) => format!("up to {}", max),
+        (None, None) => "unknown".to_string(),
+    };
+
+    HashMap::from([
+        ("persona", persona_or_default(persona)),
+        ("game_name", game.name.clone()),
+        (
+            "game_description",
+            game.description.clone().unwrap_or_default(),
+        ),
+        ("publisher", game.publisher.clone().unwrap_or_default()),
+        (
+            "year_published",
+            game.year_published
+                .map(|y| y.to_string())
+                .unwrap_or_default(),
+        ),
+        ("player_count", player_count),
+        (
+            "play_time",
+            game.play_time_minutes
+                .map(|m| format!("{} minutes", m))
+                .unwrap_or_default(),
+        ),
+        (
+            "complexity",
+            game.complexity_rating
+                .map(|c| format!("{:.1}/5", c))
+                .unwrap_or_default(),
+        ),
+    ])
+}
+
+/// Template variables for a comparison; `game_name` lists every game
+pub fn comparison_variables(
+    games: &[Game],
+    persona: Option<&str>,
+) -> HashMap<&'static str, String> {
+    let names: Vec<&str> = games.iter().map(|game| game.name.as_str()).collect();
+    let game_name = match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => names.concat(),
+    };
+
+    HashMap::from([
+        ("persona", persona_or_default(persona)),
+        ("game_name", game_name),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn game() -> Game {
+        Game {
+            id: 1,
+            name: "Catan".to_string(),
+            description: None,
+            publisher: Some("Kosmos".to_string()),
+            year_published: Some(1995),
+            min_players: Some(3),
+            max_players: Some(4),
+            play_time_minutes: Some(90),
+            complexity_rating: Some(2.3),
+            bgg_id: None,
+            rules_pdf_path: None,
+            rules_text: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_played_at: None,
+            base_game_id: None,
+        }
+    }
+
+    #[test]
+    fn test_default_template_is_valid() {
+        assert_eq!(validate_template(DEFAULT_TEMPLATE), Ok(()));
+    }
+
+    #[test]
+    fn test_comparison_template_is_valid() {
+        assert_eq!(validate_template(COMPARISON_TEMPLATE), Ok(()));
+    }
+
+    #[test]
+    fn test_comparison_lists_every_game() {
+        let mut frosthaven = game();
+        frosthaven.name = "Frosthaven".to_string();
+        let mut jaws = game();
+        jaws.name = "Jaws of the Lion".to_string();
+
+        let variables = comparison_variables(&[game(), frosthaven, jaws], None);
+        assert_eq!(
+            variables["game_name"],
+            "Catan, Frosthaven and Jaws of the Lion"
+        );
+        assert_eq!(variables["persona"], DEFAULT_PERSONA);
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        assert!(validate_template("   ").is_err());
+        assert!(
+            validate_template("{{rules_context}} {{secret}}")
+                .unwrap_err()
+                .contains("{{secret}}")
+        );
+        assert!(
+            validate_template("Rules: {{game_name}}")
+                .unwrap_err()
+                .contains("{{rules_context}}")
+        );
+        assert!(validate_template("{{rules_context}} {{game_name").is_err());
+        assert!(validate_template("{{ rules_context }}").is_ok());
+    }
+
+    #[test]
+    fn test_render_substitutes_once() {
+        let mut variables = game_variables(&game(), Some("a pirate"));
+        variables.insert("rules_context", "Roll {{game_name}}".to_string());
+
+        let rendered = render_template(
+            "You are {{persona}} playing {{ game_name }} ({{player_count}}, {{complexity}}): {{rules_context}}",
+            &variables,
+        );
+
+        assert_eq!(
+            rendered,
+            "You are a pirate playing Catan (3-4, 2.3/5): Roll {{game_name}}"
+        );
+    }
+
+    #[test]
+    fn test_blank_persona_uses_default() {
+        let variables = game_variables(&game(), Some("  "));
+        assert_eq!(variables["persona"], DEFAULT_PERSONA);
+    }
+}
@@ -0,0 +1,390 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::db::{self, Database};
+use crate::embeddings::Embedder;
+use crate::llm::{ChatMessage, LLMClient, ToolCall, ToolDefinition};
+use crate::models::{ContextSource, EmbeddingId, GameId, SimilaritySearchRequest, ToolInvocation};
+use crate::prompt::Tokenizer;
+
+/// Maximum number of model turns that may request tools before we force an answer
+pub const MAX_TOOL_ITERATIONS: u32 = 4;
+
+/// Tool output longer than this is truncated before it is sent back to the model
+const MAX_TOOL_OUTPUT_CHARS: usize = 6000;
+
+/// Tool results the prompt budget makes room for; later lookups rely on the model's own window
+const RESERVED_TOOL_RESULTS: usize = 2;
+
+const DEFAULT_SEARCH_LIMIT: u32 = 5;
+const MAX_SEARCH_LIMIT: u32 = 10;
+const DEFAULT_NEIGHBOR_WINDOW: u32 = 1;
+const MAX_NEIGHBOR_WINDOW: u32 = 3;
+
+/// Everything a tool needs to answer lookups for one chat session
+pub struct ToolContext<'a> {
+    pub db: &'a Database,
+    pub embedder: &'a Embedder,
+    pub game_id: GameId,
+}
+
+/// Result of running the tool-calling loop
+pub struct ToolLoopOutcome {
+    pub answer: String,
+    pub trace: Vec<ToolInvocation>,
+    /// Chunks surfaced by tools, in the order they were first seen
+    pub sources: Vec<ContextSource>,
+}
+
+#[derive(Deserialize)]
+struct SearchRulesArgs {
+    query: String,
+    limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ListHouseRulesArgs {
+    #[serde(default)]
+    include_inactive: bool,
+}
+
+#[derive(Deserialize)]
+struct GetChunkNeighborsArgs {
+    chunk_id: EmbeddingId,
+    window: Option<u32>,
+}
+
+/// Tools offered to the model while answering rules questions
+pub fn rule_tools() -> Vec<ToolDefinition> {
+    vec![
+        ToolDefinition {
+            name: "search_rules".to_string(),
+            description: "Search this game's rulebook and house rules for passages relevant to a query. Returns chunk ids and text.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to look for, phrased like rulebook text" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_SEARCH_LIMIT }
+                },
+                "required": ["query"]
+            }),
+        },
+        ToolDefinition {
+            name: "list_house_rules".to_string(),
+            description: "List the house rules this group plays with for the game.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "include_inactive": { "type": "boolean", "description": "Also list disabled house rules" }
+                }
+            }),
+        },
+        ToolDefinition {
+            name: "get_game_info".to_string(),
+            description: "Get catalog details for the game: publisher, year, player count, play time and complexity.".to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+        },
+        ToolDefinition {
+            name: "get_chunk_neighbors".to_string(),
+            description: "Read the rulebook text immediately before and after a chunk returned by search_rules.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "chunk_id": { "type": "integer" },
+                    "window": { "type": "integer", "minimum": 1, "maximum": MAX_NEIGHBOR_WINDOW }
+                },
+                "required": ["chunk_id"]
+            }),
+        },
+    ]
+}
+
+/// Prompt tokens to hold back for the tool schemas and the results fed back to the model
+pub fn prompt_reserve_tokens(tokenizer: &Tokenizer) -> usize {
+    let schemas: usize = rule_tools()
+        .iter()
+        .map(|tool| {
+            tokenizer.count(&tool.name)
+                + tokenizer.count(&tool.description)
+                + tokenizer.count(&tool.parameters.to_string())
+        })
+        .sum();
+    // Tool output is mostly JSON-wrapped rules text, roughly four characters per token
+    let result = MAX_TOOL_OUTPUT_CHARS / 4;
+
+    schemas + RESERVED_TOOL_RESULTS * result
+}
+
+/// Let the model call tools until it produces an answer or the iteration cap is reached
+pub async fn run_tool_loop(
+    llm: &LLMClient,
+    ctx: &ToolContext<'_>,
+    mut messages: Vec<ChatMessage>,
+    system_prompt: String,
+    max_tokens: Option<u16>,
+    temperature: Option<f32>,
+) -> Result<ToolLoopOutcome> {
+    let tools = rule_tools();
+    let mut trace = Vec::new();
+    let mut sources: Vec<ContextSource> = Vec::new();
+
+    for iteration in 1..=MAX_TOOL_ITERATIONS {
+        let turn = llm
+            .chat_completion_with_tools(
+                messages.clone(),
+                Some(system_prompt.clone()),
+                &tools,
+                max_tokens,
+                temperature,
+            )
+            .await?;
+
+        if turn.tool_calls.is_empty() {
+            return Ok(ToolLoopOutcome {
+                answer: turn.content.unwrap_or_default(),
+                trace,
+                sources,
+            });
+        }
+
+        messages.push(ChatMessage::assistant_tool_calls(
+            turn.content,
+            turn.tool_calls.clone(),
+        ));
+
+        for call in turn.tool_calls {
+            let arguments = serde_json::from_str(&call.arguments).unwrap_or(json!({}));
+            let (output, is_error) = match execute_tool(ctx, &call).await {
+                Ok((output, found)) => {
+                    for source in found {
+                        if !sources
+                            .iter()
+                            .any(|s| s.embedding_id == source.embedding_id)
+                        {
+                            sources.push(source);
+                        }
+                    }
+                    (truncate_output(output.to_string()), false)
+                }
+                Err(e) => (json!({ "error": e.to_string() }).to_string(), true),
+            };
+
+            messages.push(ChatMessage::tool_result(call.id.clone(), output.clone()));
+            trace.push(ToolInvocation {
+                iteration,
+                tool: call.name,
+                arguments,
+                output,
+                is_error,
+            });
+        }
+    }
+
+    // The model kept asking for tools; make it answer with what it has gathered
+    tracing::warn!(
+        "Tool loop hit the {} iteration cap for game {}",
+        MAX_TOOL_ITERATIONS,
+        ctx.game_id
+    );
+    messages.push(ChatMessage::system(
+        "Tool call limit reached. Answer the question now using the information gathered so far.",
+    ));
+    let answer = llm
+        .chat_completion(messages, Some(system_prompt), max_tokens, temperature)
+        .await?;
+
+    Ok(ToolLoopOutcome {
+        answer,
+        trace,
+        sources,
+    })
+}
+
+/// Run a single tool call, returning its JSON output and any rule chunks it surfaced
+async fn execute_tool(
+    ctx: &ToolContext<'_>,
+    call: &ToolCall,
+) -> Result<(serde_json::Value, Vec<ContextSource>)> {
+    let arguments = if call.arguments.trim().is_empty() {
+        "{}"
+    } else {
+        call.arguments.as_str()
+    };
+
+    match call.name.as_str() {
+        "search_rules" => {
+            let args: SearchRulesArgs = serde_json::from_str(arguments)
+                .map_err(|e| anyhow!("Invalid arguments for search_rules: {}", e))?;
+            let limit = args
+                .limit
+                .unwrap_or(DEFAULT_SEARCH_LIMIT)
+                .clamp(1, MAX_SEARCH_LIMIT);
+
+            let query_embedding = ctx.embedder.generate_embedding(&args.query).await?;
+            let results = db::embeddings::similarity_search(
+                ctx.db,
+                SimilaritySearchRequest {
+                    game_id: ctx.game_id,
+                    query_embedding,
+                    similarity_threshold: 0.0,
+                    limit,
+                },
+            )
+            .await?;
+
+            let output = json!(
+                results
+                    .iter()
+                    .map(|r| json!({
+                        "chunk_id": r.id,
+                        "source_type": r.source_type.as_str(),
+                        "similarity": r.similarity_score,
+                        "text": r.chunk_text,
+                    }))
+                    .collect::<Vec<_>>()
+            );
+            let sources = results.iter().map(ContextSource::from).collect();
+
+            Ok((output, sources))
+        }
+        "list_house_rules" => {
+            let args: ListHouseRulesArgs = serde_json::from_str(arguments)
+                .map_err(|e| anyhow!("Invalid arguments for list_house_rules: {}", e))?;
+            let rules = db::house_rules::list_house_rules_by_game(
+                ctx.db,
+                ctx.game_id,
+                !args.include_inactive,
+            )
+            .await?;
+
+            let output = json!(
+                rules
+                    .iter()
+                    .map(|r| json!({
+                        "title": r.title,
+                        "description": r.description,
+                        "category": r.category,
+                        "is_active": r.is_active,
+                    }))
+                    .collect::<Vec<_>>()
+            );
+
+            Ok((output, Vec::new()))
+        }
+        "get_game_info" => {
+            let game = db::games::get_game(ctx.db, ctx.game_id)
+                .await?
+                .ok_or_else(|| anyhow!("Game {} not found", ctx.game_id))?;
+
+            let output = json!({
+                "name": game.name,
+                "description": game.description,
+                "publisher": game.publisher,
+                "year_published": game.year_published,
+                "min_players": game.min_players,
+                "max_players": game.max_players,
+                "play_time_minutes": game.play_time_minutes,
+                "complexity_rating": game.complexity_rating,
+                "has_rules_pdf": game.rules_pdf_path.is_some(),
+            });
+
+            Ok((output, Vec::new()))
+        }
+        "get_chunk_neighbors" => {
+            let args: GetChunkNeighborsArgs = serde_json::from_str(arguments)
+                .map_err(|e| anyhow!("Invalid arguments for get_chunk_neighbors: {}", e))?;
+            let window = args
+                .window
+                .unwrap_or(DEFAULT_NEIGHBOR_WINDOW)
+                .clamp(1, MAX_NEIGHBOR_WINDOW);
+
+            let chunks = db::embeddings::get_chunk_neighbors(ctx.db, args.chunk_id, window).await?;
+
+            // Never leak chunks from another game's rulebook
+            if chunks.iter().any(|c| c.game_id != ctx.game_id) || chunks.is_empty() {
+                return Err(anyhow!("Chunk {} not found for this game", args.chunk_id));
+            }
+
+            let output = json!(
+                chunks
+                    .iter()
+                    .map(|c| json!({
+                        "chunk_id": c.id,
+                        "chunk_index": c.chunk_index,
+                        "text": c.chunk_text,
+                    }))
+                    .collect::<Vec<_>>()
+            );
+            let sources = chunks
+                .into_iter()
+                .map(|c| ContextSource {
+                    embedding_id: c.id,
+                    game_id: c.game_id,
+                    chunk_text: c.chunk_text,
+                    source_type: c.source_type.as_str().to_string(),
+                    similarity_score: 0.0,
+                    metadata: c.metadata,
+                    cited: false,
+                })
+                .collect();
+
+            Ok((output, sources))
+        }
+        other => Err(anyhow!("Unknown tool: {}", other)),
+    }
+}
+
+fn truncate_output(output: String) -> String {
+    if output.chars().count() <= MAX_TOOL_OUTPUT_CHARS {
+        return output;
+    }
+
+    let truncated: String = output.chars().take(MAX_TOOL_OUTPUT_CHARS).collect();
+    format!("{}... [truncated]", truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_tools_have_object_schemas() {
+        let tools = rule_tools();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "search_rules",
+                "list_house_rules",
+                "get_game_info",
+                "get_chunk_neighbors"
+            ]
+        );
+        for tool in &tools {
+            assert_eq!(tool.parameters["type"], "object");
+        }
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        let short = "short output".to_string();
+        assert_eq!(truncate_output(short.clone()), short);
+
+        let long = "x".repeat(MAX_TOOL_OUTPUT_CHARS + 10);
+        let truncated = truncate_output(long);
+        assert!(truncated.ends_with("... [truncated]"));
+        assert_eq!(
+            truncated.chars().count(),
+            MAX_TOOL_OUTPUT_CHARS + "... [truncated]".len()
+        );
+    }
+
+    #[test]
+    fn test_prompt_reserve_covers_tool_results() {
+        let reserve = prompt_reserve_tokens(&Tokenizer::for_model("gpt-4"));
+        assert!(reserve > RESERVED_TOOL_RESULTS * MAX_TOOL_OUTPUT_CHARS / 4);
+        assert!(reserve < 8_192);
+    }
+}
@@ -0,0 +1,14 @@
+-- Create embedding cache table so identical text is only embedded once per model
+CREATE TABLE embedding_cache (
+    text_hash TEXT NOT NULL, -- SHA-256 of the normalized text
+    model TEXT NOT NULL, -- Embedding model that produced the vector
+    embedding TEXT NOT NULL, -- JSON-encoded vector
+    dimensions INTEGER NOT NULL,
+    hit_count INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    last_used_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (text_hash, model)
+);
+
+-- Index for least-recently-used eviction
+CREATE INDEX idx_embedding_cache_last_used ON embedding_cache(last_used_at);
@@ -0,0 +1,2 @@
+-- Record the tools the assistant called while producing a message
+ALTER TABLE chat_messages ADD COLUMN tool_trace TEXT; -- JSON array of tool invocations
@@ -0,0 +1,4 @@
+-- Store what a structured assistant answer actually cited and how sure it was
+ALTER TABLE chat_messages ADD COLUMN cited_chunks TEXT; -- JSON array of embedding IDs cited by the answer
+ALTER TABLE chat_messages ADD COLUMN confidence REAL CHECK (confidence >= 0.0 AND confidence <= 1.0);
+ALTER TABLE chat_messages ADD COLUMN not_covered_by_rules BOOLEAN;
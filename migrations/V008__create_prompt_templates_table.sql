@@ -0,0 +1,20 @@
+-- Create prompt templates table for editable system prompts and per-game personas
+CREATE TABLE prompt_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER, -- NULL for the global default used by every game without its own template
+    name TEXT NOT NULL,
+    persona TEXT, -- Who the assistant should be, available to the template as {{persona}}
+    template TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Index for finding templates by game
+CREATE INDEX idx_prompt_templates_game_id ON prompt_templates(game_id);
+
+-- At most one active template per game, and one active global default
+CREATE UNIQUE INDEX idx_prompt_templates_active_scope
+    ON prompt_templates(COALESCE(game_id, 0))
+    WHERE is_active = 1;
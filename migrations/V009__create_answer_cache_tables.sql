@@ -0,0 +1,26 @@
+-- Create semantic answer cache so repeated rules questions skip retrieval and generation
+CREATE TABLE answer_cache (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    question TEXT NOT NULL,
+    answer TEXT NOT NULL,
+    context_sources TEXT NOT NULL, -- JSON array of the context sources shown with the answer
+    cited_chunks TEXT, -- JSON array of embedding IDs the answer cited
+    confidence REAL,
+    not_covered_by_rules BOOLEAN,
+    structured BOOLEAN NOT NULL DEFAULT TRUE,
+    source_fingerprint TEXT NOT NULL, -- SHA-256 over the source chunks, to detect edited rules
+    hit_count INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    last_hit_at DATETIME,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Index for invalidating a game's cached answers
+CREATE INDEX idx_answer_cache_game_id ON answer_cache(game_id);
+
+-- Question vectors for KNN lookup, partitioned by game and linked to answer_cache via rowid
+CREATE VIRTUAL TABLE vec_answer_cache USING vec0(
+    game_id INTEGER PARTITION KEY,
+    question_vector float[768] distance_metric=cosine
+);
@@ -0,0 +1,12 @@
+-- Games a comparison chat session covers besides its primary game
+CREATE TABLE chat_session_games (
+    session_id INTEGER NOT NULL,
+    game_id INTEGER NOT NULL,
+    position INTEGER NOT NULL, -- Order the games were listed in when the session was created
+    PRIMARY KEY (session_id, game_id),
+    FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Index for listing the comparison sessions a game takes part in
+CREATE INDEX idx_chat_session_games_game_id ON chat_session_games(game_id);
@@ -0,0 +1,2 @@
+-- How a house rule relates to the official rulebook, as judged by the LLM
+ALTER TABLE house_rules ADD COLUMN analysis TEXT; -- JSON HouseRuleAnalysis, cleared when the rule text changes
@@ -0,0 +1,22 @@
+-- Curated questions and answers per game, generated from the rules and editable afterwards
+CREATE TABLE faq_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    question TEXT NOT NULL,
+    answer TEXT NOT NULL,
+    cited_chunks TEXT NOT NULL DEFAULT '[]', -- JSON array of embedding IDs backing the answer
+    edited BOOLEAN NOT NULL DEFAULT FALSE, -- written or changed by a person; kept when regenerating
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Index for listing a game's FAQ in order
+CREATE INDEX idx_faq_entries_game_id ON faq_entries(game_id, position);
+
+-- Question vectors so chat can match incoming questions, linked to faq_entries via rowid
+CREATE VIRTUAL TABLE vec_faq_entries USING vec0(
+    game_id INTEGER PARTITION KEY,
+    question_vector float[768] distance_metric=cosine
+);
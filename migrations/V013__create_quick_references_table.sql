@@ -0,0 +1,12 @@
+-- Generated one-page rules summaries, one per game and player count
+CREATE TABLE quick_references (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    player_count INTEGER NOT NULL DEFAULT 0, -- 0 when the sheet is not for a specific player count
+    sections TEXT NOT NULL, -- JSON array of sections with cited bullet points
+    source_fingerprint TEXT NOT NULL, -- hash of the rule chunks the sheet was generated from
+    model TEXT NOT NULL,
+    generated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE,
+    UNIQUE (game_id, player_count)
+);
@@ -0,0 +1,29 @@
+-- Golden questions per game for measuring retrieval and answer quality
+CREATE TABLE eval_questions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    question TEXT NOT NULL,
+    expected_chunk_ids TEXT NOT NULL DEFAULT '[]', -- JSON array of embedding IDs a good retrieval finds
+    expected_keywords TEXT NOT NULL DEFAULT '[]', -- JSON array of phrases the relevant rules and answer contain
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_eval_questions_game_id ON eval_questions(game_id);
+
+-- Results of running a game's golden questions, kept to compare later runs against
+CREATE TABLE eval_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    strategy TEXT NOT NULL,
+    k INTEGER NOT NULL,
+    generate_answers BOOLEAN NOT NULL DEFAULT FALSE,
+    embedding_model TEXT NOT NULL,
+    llm_model TEXT, -- set when answers were generated
+    summary TEXT NOT NULL, -- JSON aggregate metrics
+    results TEXT NOT NULL, -- JSON per-question results
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_eval_runs_game_id ON eval_runs(game_id, id);
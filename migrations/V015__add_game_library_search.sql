@@ -0,0 +1,37 @@
+-- When the game was last played; kept up to date as plays are logged
+ALTER TABLE games ADD COLUMN last_played_at DATETIME;
+
+-- Indexes for filtering and sorting the game library
+CREATE INDEX idx_games_players ON games(min_players, max_players);
+CREATE INDEX idx_games_play_time ON games(play_time_minutes);
+CREATE INDEX idx_games_complexity ON games(complexity_rating);
+CREATE INDEX idx_games_year ON games(year_published);
+CREATE INDEX idx_games_publisher ON games(publisher COLLATE NOCASE);
+CREATE INDEX idx_games_updated_at ON games(updated_at);
+CREATE INDEX idx_games_last_played_at ON games(last_played_at);
+CREATE INDEX idx_house_rules_active_game ON house_rules(game_id, is_active);
+
+-- Full-text index over the descriptive fields, kept in sync with games by triggers
+CREATE VIRTUAL TABLE games_fts USING fts5(
+    name, description, publisher,
+    content='games', content_rowid='id'
+);
+
+INSERT INTO games_fts(games_fts) VALUES ('rebuild');
+
+CREATE TRIGGER games_fts_insert AFTER INSERT ON games BEGIN
+    INSERT INTO games_fts(rowid, name, description, publisher)
+    VALUES (new.id, new.name, new.description, new.publisher);
+END;
+
+CREATE TRIGGER games_fts_delete AFTER DELETE ON games BEGIN
+    INSERT INTO games_fts(games_fts, rowid, name, description, publisher)
+    VALUES ('delete', old.id, old.name, old.description, old.publisher);
+END;
+
+CREATE TRIGGER games_fts_update AFTER UPDATE OF name, description, publisher ON games BEGIN
+    INSERT INTO games_fts(games_fts, rowid, name, description, publisher)
+    VALUES ('delete', old.id, old.name, old.description, old.publisher);
+    INSERT INTO games_fts(rowid, name, description, publisher)
+    VALUES (new.id, new.name, new.description, new.publisher);
+END;
@@ -0,0 +1,20 @@
+-- Classification labels for games: BGG mechanics and categories, themes and custom tags
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    tag_type TEXT NOT NULL CHECK (tag_type IN ('mechanic', 'category', 'theme', 'custom')),
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tag_type, name COLLATE NOCASE)
+);
+
+-- Which games carry which tags
+CREATE TABLE game_tags (
+    game_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (game_id, tag_id),
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+-- Index for finding the games with a tag
+CREATE INDEX idx_game_tags_tag_id ON game_tags(tag_id);
@@ -0,0 +1,15 @@
+-- Expansions point at the base game they extend; base games leave this NULL
+ALTER TABLE games ADD COLUMN base_game_id INTEGER REFERENCES games(id);
+
+-- Index for listing a base game's expansions
+CREATE INDEX idx_games_base_game_id ON games(base_game_id);
+
+-- Expansions in play during a chat session about their base game
+CREATE TABLE chat_session_expansions (
+    session_id INTEGER NOT NULL,
+    game_id INTEGER NOT NULL,
+    position INTEGER NOT NULL, -- Order the expansions were listed in when the session was created
+    PRIMARY KEY (session_id, game_id),
+    FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
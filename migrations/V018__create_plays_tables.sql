@@ -0,0 +1,56 @@
+-- People who take part in plays, identified by name
+CREATE TABLE players (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One logged play of a game
+CREATE TABLE plays (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    played_at DATETIME NOT NULL,
+    duration_minutes INTEGER,
+    location TEXT,
+    notes TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Index for listing a game's plays, most recent first
+CREATE INDEX idx_plays_game_id ON plays(game_id, played_at);
+
+-- Who took part in a play, with their score and whether they won
+CREATE TABLE play_players (
+    play_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    position INTEGER NOT NULL, -- Order the players were listed in
+    score INTEGER,
+    is_winner BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (play_id, player_id),
+    FOREIGN KEY (play_id) REFERENCES plays(id) ON DELETE CASCADE,
+    FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+);
+
+-- Index for listing a player's plays
+CREATE INDEX idx_play_players_player_id ON play_players(player_id);
+
+-- Expansions of the game used in a play
+CREATE TABLE play_expansions (
+    play_id INTEGER NOT NULL,
+    game_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (play_id, game_id),
+    FOREIGN KEY (play_id) REFERENCES plays(id) ON DELETE CASCADE,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Chat sessions consulted during a play
+CREATE TABLE play_chat_sessions (
+    play_id INTEGER NOT NULL,
+    session_id INTEGER NOT NULL,
+    PRIMARY KEY (play_id, session_id),
+    FOREIGN KEY (play_id) REFERENCES plays(id) ON DELETE CASCADE,
+    FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE
+);
@@ -0,0 +1,31 @@
+-- Players on the same team share their placement in a play; NULL plays alone
+ALTER TABLE play_players ADD COLUMN team TEXT;
+
+-- A league: a group of players competing over a season
+CREATE TABLE leagues (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    description TEXT,
+    starts_at DATETIME, -- NULL for no start
+    ends_at DATETIME, -- NULL for no end
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Players competing in a league
+CREATE TABLE league_players (
+    league_id INTEGER NOT NULL,
+    player_id INTEGER NOT NULL,
+    PRIMARY KEY (league_id, player_id),
+    FOREIGN KEY (league_id) REFERENCES leagues(id) ON DELETE CASCADE,
+    FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+);
+
+-- Games whose plays count towards a league; none means every game
+CREATE TABLE league_games (
+    league_id INTEGER NOT NULL,
+    game_id INTEGER NOT NULL,
+    PRIMARY KEY (league_id, game_id),
+    FOREIGN KEY (league_id) REFERENCES leagues(id) ON DELETE CASCADE,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
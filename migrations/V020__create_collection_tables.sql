@@ -0,0 +1,31 @@
+-- Where a game stands in the collection; games without a row are owned
+CREATE TABLE game_collection (
+    game_id INTEGER PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'owned'
+        CHECK (status IN ('owned', 'previously_owned', 'wishlist', 'want_to_play', 'for_trade')),
+    wishlist_priority INTEGER CHECK (wishlist_priority BETWEEN 1 AND 5), -- 1 is the most wanted
+    acquired_at DATETIME,
+    acquisition_price REAL,
+    shelf_location TEXT,
+    notes TEXT,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_game_collection_status ON game_collection(status);
+
+-- Games lent out; a loan is active until it is returned
+CREATE TABLE game_loans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL,
+    borrower TEXT NOT NULL,
+    loaned_at DATETIME NOT NULL,
+    due_at DATETIME,
+    returned_at DATETIME,
+    notes TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_game_loans_game_id ON game_loans(game_id);
+CREATE INDEX idx_game_loans_active ON game_loans(returned_at, due_at);
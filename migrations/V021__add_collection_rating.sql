@@ -0,0 +1,2 @@
+-- Personal rating of a game, 1 to 10
+ALTER TABLE game_collection ADD COLUMN rating REAL CHECK (rating BETWEEN 1 AND 10);
@@ -0,0 +1,12 @@
+-- Which text each game's description vector was embedded from, to spot stale vectors
+CREATE TABLE game_description_embeddings (
+    game_id INTEGER PRIMARY KEY,
+    text_hash TEXT NOT NULL, -- SHA-256 over the embedding model and the embedded text
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+);
+
+-- Description and tag vectors for finding similar games, linked to games via rowid
+CREATE VIRTUAL TABLE vec_game_descriptions USING vec0(
+    description_vector float[768] distance_metric=cosine
+);